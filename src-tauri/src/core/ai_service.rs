@@ -1,16 +1,82 @@
 use serde_json::json;
+use std::time::Duration;
 
 const GROQ_API_URL: &str = "https://api.groq.com/openai/v1/chat/completions";
+pub const GROQ_MODEL: &str = "llama-3.3-70b-versatile";
 
-/// Calls Groq API to convert natural language to SQL.
-/// Returns the raw SQL string on success.
-pub async fn generate_sql(
+/// How rate-limited (HTTP 429) requests are retried before the quota error is surfaced
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first request
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on every further retry
+    pub base_delay: Duration,
+    /// Waits longer than this (including a server `Retry-After`) give up instead
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after the given 1-based attempt, or `None` to stop retrying
+    fn delay_after(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = retry_after
+            .unwrap_or_else(|| self.base_delay.saturating_mul(1 << (attempt - 1).min(16)));
+        (delay <= self.max_delay).then_some(delay)
+    }
+}
+
+/// Parses a `Retry-After` header given in seconds; HTTP-date values are ignored
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?;
+    let seconds: f64 = value.to_str().ok()?.trim().parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Posts a chat completion request, retrying rate-limited responses with exponential backoff.
+/// The last response is returned as-is once retries are exhausted.
+async fn send_with_retry(
+    url: &str,
     api_key: &str,
-    prompt: &str,
-    schema_context: &str,
-    db_type: &str,
-) -> Result<String, String> {
-    let system_prompt = format!(
+    request_body: &serde_json::Value,
+    retry: &RetryPolicy,
+) -> Result<reqwest::Response, String> {
+    let client = reqwest::Client::new();
+    let mut attempt = 1;
+
+    loop {
+        let response = client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .json(request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to call Groq API: {}", e))?;
+
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        match retry.delay_after(attempt, retry_after(&response)) {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Ok(response),
+        }
+        attempt += 1;
+    }
+}
+
+fn system_prompt(schema_context: &str, db_type: &str) -> String {
+    format!(
         "You are an expert {} SQL query generator. \
          Given the database schema below, convert the user's natural language request into a valid SQL query.\n\n\
          RULES:\n\
@@ -20,10 +86,28 @@ pub async fn generate_sql(
          - Write syntactically correct {} SQL\n\n\
          DATABASE SCHEMA:\n{}",
         db_type, db_type, schema_context
-    );
+    )
+}
+
+/// Approximate token count of the request, assuming ~4 characters per token
+pub fn estimate_prompt_tokens(prompt: &str, schema_context: &str, db_type: &str) -> u64 {
+    let chars = system_prompt(schema_context, db_type).chars().count() + prompt.chars().count();
+    chars.div_ceil(4) as u64
+}
+
+/// Calls Groq API to convert natural language to SQL.
+/// Returns the raw SQL string on success.
+pub async fn generate_sql(
+    api_key: &str,
+    prompt: &str,
+    schema_context: &str,
+    db_type: &str,
+    retry: &RetryPolicy,
+) -> Result<String, String> {
+    let system_prompt = system_prompt(schema_context, db_type);
 
     let request_body = json!({
-        "model": "llama-3.3-70b-versatile",
+        "model": GROQ_MODEL,
         "messages": [
             {
                 "role": "system",
@@ -38,14 +122,7 @@ pub async fn generate_sql(
         "max_tokens": 1024
     });
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(GROQ_API_URL)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to call Groq API: {}", e))?;
+    let response = send_with_retry(GROQ_API_URL, api_key, &request_body, retry).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -93,3 +170,102 @@ pub async fn generate_sql(
 
     Ok(cleaned)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves `responses` in order, one per connection, and counts the requests it answered
+    async fn mock_server(responses: Vec<&'static str>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/chat/completions", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                // Read the headers and the body they announce before answering
+                let mut request = Vec::new();
+                let mut chunk = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    request.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().to_string())
+                            })
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                served.fetch_add(1, Ordering::SeqCst);
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    const RATE_LIMITED: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\n\
+                                Content-Length: 0\r\nConnection: close\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                      Content-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+    fn fast_retries(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried_until_they_succeed() {
+        let (url, requests) = mock_server(vec![RATE_LIMITED, RATE_LIMITED, OK]).await;
+        let response = send_with_retry(&url, "key", &json!({}), &fast_retries(3))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn the_last_429_is_returned_once_attempts_run_out() {
+        let (url, requests) = mock_server(vec![RATE_LIMITED, RATE_LIMITED]).await;
+        let response = send_with_retry(&url, "key", &json!({}), &fast_retries(2))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn backoff_doubles_and_honors_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_after(1, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay_after(2, None), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay_after(3, None), None);
+        assert_eq!(
+            policy.delay_after(1, Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+        // A server asking for more than max_delay is not waited for
+        assert_eq!(policy.delay_after(1, Some(Duration::from_secs(60))), None);
+    }
+}
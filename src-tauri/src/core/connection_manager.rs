@@ -1,21 +1,658 @@
-use crate::core::{ConnectionConfig, DatabaseType};
+use crate::core::{
+    ConnectionConfig, ConnectionStageStatus, ConnectionTestResult, ConnectionTestStage,
+    DatabaseType, ResultCursor, ServerInfo, SshHop, TunnelInfo, UserTransaction,
+};
 use anyhow::{anyhow, Result};
-use sqlx::mysql::{MySqlConnectOptions, MySqlSslMode};
-use sqlx::postgres::{PgConnectOptions, PgSslMode};
-use sqlx::{Connection, MySql, MySqlPool, PgPool, Pool, Postgres, Sqlite, SqlitePool};
-use ssh2::Session;
-use std::collections::HashMap;
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection, MySqlSslMode};
+use sqlx::postgres::{PgConnectOptions, PgConnection, PgSslMode};
+use sqlx::{Connection, MySql, MySqlPool, PgPool, Pool, Postgres, Row, Sqlite, SqlitePool};
+use ssh2::{CheckResult, HashType, KnownHostFileKind, Session};
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+const MAX_FORWARDED_CONNECTIONS: usize = 32;
+/// Upper bound for each network stage of `test_connection`, matching the pool acquire timeout
+const TEST_STAGE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Each open cursor pins one of the pool's 5 connections, so only a few may stay open at once
+const MAX_CURSORS_PER_CONNECTION: usize = 2;
+/// Cursors not fetched from for this long are closed, releasing their connection
+const CURSOR_IDLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// An open result cursor, with the connection it pins and when it was last fetched from
+struct OpenCursor {
+    connection_id: Uuid,
+    last_used: Instant,
+    cursor: Arc<Mutex<ResultCursor>>,
+}
+
+/// Checks the server's host key against an OpenSSH known_hosts file.
+///
+/// A mismatching key is always rejected. Unknown hosts are only rejected in strict mode.
+fn verify_host_key(
+    sess: &Session,
+    host: &str,
+    port: u16,
+    known_hosts_path: &Path,
+    strict: bool,
+) -> Result<()> {
+    let (key, _) = sess
+        .host_key()
+        .ok_or_else(|| anyhow!("SSH server did not provide a host key"))?;
+    let fingerprint = sess
+        .host_key_hash(HashType::Sha256)
+        .map(|hash| {
+            hash.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(":")
+        })
+        .unwrap_or_default();
+    check_known_host(
+        sess,
+        host,
+        port,
+        key,
+        &fingerprint,
+        known_hosts_path,
+        strict,
+    )
+}
+
+/// Looks `key` up in `known_hosts_path`; the session only supplies libssh2's context.
+fn check_known_host(
+    sess: &Session,
+    host: &str,
+    port: u16,
+    key: &[u8],
+    fingerprint: &str,
+    known_hosts_path: &Path,
+    strict: bool,
+) -> Result<()> {
+    let mut known_hosts = sess.known_hosts()?;
+    if known_hosts_path.exists() {
+        known_hosts.read_file(known_hosts_path, KnownHostFileKind::OpenSSH)?;
+    }
+
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(anyhow!(
+            "SSH host key for {} does not match known_hosts (SHA256 {}). The server key may have changed or the connection may be intercepted.",
+            host,
+            fingerprint
+        )),
+        CheckResult::NotFound if strict => Err(anyhow!(
+            "SSH host key unknown for {} (SHA256 {}). Add it to {} to trust this host.",
+            host,
+            fingerprint,
+            known_hosts_path.display()
+        )),
+        CheckResult::NotFound => Ok(()),
+        CheckResult::Failure => Err(anyhow!("Failed to check SSH host key for {}", host)),
+    }
+}
+
+/// Connects, verifies and authenticates an SSH session over an established TCP stream.
+fn open_ssh_session(tcp: TcpStream, hop: &SshHop, strict_host_key: bool) -> Result<Session> {
+    let ssh_host = &hop.host;
+    let ssh_port = hop.port();
+    let ssh_user = &hop.username;
+
+    let mut sess = Session::new()?;
+    sess.set_tcp_stream(tcp);
+    sess.handshake()?;
+
+    let known_hosts_path = dirs::home_dir()
+        .map(|home| home.join(".ssh").join("known_hosts"))
+        .ok_or_else(|| anyhow!("Could not determine home directory for known_hosts"))?;
+    verify_host_key(
+        &sess,
+        ssh_host,
+        ssh_port,
+        &known_hosts_path,
+        strict_host_key,
+    )?;
+
+    // Auth
+    if hop.auth_method.as_deref() == Some("password") {
+        let pass = hop
+            .password
+            .as_ref()
+            .ok_or_else(|| anyhow!("SSH password missing"))?;
+        sess.userauth_password(ssh_user, pass)?;
+    } else if let Some(key_path_str) = &hop.private_key_path {
+        let mut resolved_path = std::path::PathBuf::from(key_path_str);
+
+        // If the path is just a file name like "id_rsa" or starts with "~", resolve it against the home directory
+        if !resolved_path.is_absolute() {
+            if let Some(home) = dirs::home_dir() {
+                if key_path_str.starts_with("~/") {
+                    resolved_path = home.join(key_path_str.trim_start_matches("~/"));
+                } else if !key_path_str.contains('/') && !key_path_str.contains('\\') {
+                    // Just a filename like "id_rsa", look in ~/.ssh/
+                    resolved_path = home.join(".ssh").join(key_path_str);
+                }
+            }
+        }
+
+        if !resolved_path.exists() {
+            return Err(anyhow!(
+                "SSH key file not found at: {}",
+                resolved_path.display()
+            ));
+        }
+
+        // Attempt to find the corresponding .pub file
+        // libssh2 often requires the explicit public key file to successfully authenticate with modern keys
+        let mut pub_path_opt = None;
+        let pubkey_path =
+            resolved_path.with_extension(if let Some(ext) = resolved_path.extension() {
+                format!("{}.pub", ext.to_string_lossy())
+            } else {
+                "pub".to_string()
+            });
+
+        // If it was just "id_rsa", with_extension("pub") makes "id_rsa.pub"
+        if pubkey_path.exists() {
+            pub_path_opt = Some(pubkey_path);
+        } else {
+            // Fallback for names without extensions that might not work with with_extension right
+            let mut alt_pubkey = resolved_path.clone();
+            if let Some(name) = alt_pubkey.file_name() {
+                let mut new_name = name.to_os_string();
+                new_name.push(".pub");
+                alt_pubkey.set_file_name(new_name);
+                if alt_pubkey.exists() {
+                    pub_path_opt = Some(alt_pubkey);
+                }
+            }
+        }
+
+        match sess.userauth_pubkey_file(ssh_user, pub_path_opt.as_deref(), &resolved_path, None) {
+            Ok(_) => {}
+            Err(e) => return Err(anyhow!("SSH pubkey auth failed: {}", e)),
+        }
+    } else {
+        return Err(anyhow!("Unsupported or missing SSH auth method"));
+    }
+
+    if !sess.authenticated() {
+        return Err(anyhow!("SSH authentication failed on {}", ssh_host));
+    }
+
+    Ok(sess)
+}
+
+/// Forwards connections accepted on a random local port to `remote_host:remote_port` through the session.
+fn spawn_forwarder(
+    sess: Session,
+    remote_host: String,
+    remote_port: u16,
+    shutdown: Arc<AtomicBool>,
+) -> Result<(u16, JoinHandle<()>)> {
+    // Bind local listener to a random port
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let local_port = listener.local_addr()?.port();
+
+    let sess_arc = Arc::new(std::sync::Mutex::new(sess));
+
+    let task_handle = tokio::task::spawn_blocking(move || {
+        let active_connections = Arc::new(AtomicUsize::new(0));
+
+        // Poll instead of blocking in accept() so the shutdown flag is noticed promptly
+        if let Err(e) = listener.set_nonblocking(true) {
+            eprintln!("Local listener error: {}", e);
+            return;
+        }
+
+        while !shutdown.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((mut local_stream, _)) => {
+                    if active_connections.load(Ordering::Relaxed) >= MAX_FORWARDED_CONNECTIONS {
+                        eprintln!(
+                            "SSH tunnel connection limit ({}) reached, refusing connection",
+                            MAX_FORWARDED_CONNECTIONS
+                        );
+                        continue;
+                    }
+
+                    let sess_locked = sess_arc.lock().unwrap();
+                    let _ = sess_locked.set_blocking(true); // Ensure blocking for channel creation
+                    match sess_locked.channel_direct_tcpip(&remote_host, remote_port, None) {
+                        Ok(mut channel) => {
+                            let sess_for_thread = sess_arc.clone();
+                            let shutdown = shutdown.clone();
+                            let active_connections = active_connections.clone();
+                            active_connections.fetch_add(1, Ordering::Relaxed);
+                            std::thread::spawn(move || {
+                                let mut local_stream_clone = local_stream
+                                    .try_clone()
+                                    .expect("Failed to clone local stream");
+
+                                // Set non-blocking on the session for this thread's channel
+                                let sess_locked = sess_for_thread.lock().unwrap();
+                                sess_locked.set_blocking(false);
+                                drop(sess_locked);
+
+                                let mut buf_local = [0; 4096];
+                                let mut buf_remote = [0; 4096];
+
+                                local_stream.set_nonblocking(true).unwrap();
+
+                                loop {
+                                    if shutdown.load(Ordering::Relaxed) {
+                                        break;
+                                    }
+                                    let mut active = false;
+
+                                    // Local -> Remote
+                                    match local_stream.read(&mut buf_local) {
+                                        Ok(0) => break,
+                                        Ok(n) => {
+                                            let mut written = 0;
+                                            while written < n {
+                                                let sess_locked = sess_for_thread.lock().unwrap();
+                                                let _ = sess_locked.set_blocking(false);
+                                                match channel.write(&buf_local[written..n]) {
+                                                    Ok(0) => break,
+                                                    Ok(m) => {
+                                                        written += m;
+                                                        active = true;
+                                                    }
+                                                    Err(ref e)
+                                                        if e.kind()
+                                                            == std::io::ErrorKind::WouldBlock =>
+                                                    {
+                                                        drop(sess_locked);
+                                                        std::thread::sleep(
+                                                            std::time::Duration::from_millis(1),
+                                                        );
+                                                        continue;
+                                                    }
+                                                    Err(_) => break,
+                                                }
+                                            }
+                                        }
+                                        Err(ref e)
+                                            if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                                        Err(_) => break,
+                                    }
+
+                                    // Remote -> Local
+                                    {
+                                        let sess_locked = sess_for_thread.lock().unwrap();
+                                        let _ = sess_locked.set_blocking(false);
+                                        match channel.read(&mut buf_remote) {
+                                            Ok(0) => break,
+                                            Ok(n) => {
+                                                if local_stream_clone
+                                                    .write_all(&buf_remote[..n])
+                                                    .is_err()
+                                                {
+                                                    break;
+                                                }
+                                                active = true;
+                                            }
+                                            Err(ref e)
+                                                if e.kind() == std::io::ErrorKind::WouldBlock => {} // EAGAIN
+                                            Err(e) => {
+                                                eprintln!("SSH Remote Read Error: {:?}", e);
+                                                break;
+                                            }
+                                        }
+                                    }
+
+                                    if !active {
+                                        std::thread::sleep(std::time::Duration::from_millis(5));
+                                    }
+                                }
+
+                                active_connections.fetch_sub(1, Ordering::Relaxed);
+                            });
+                        }
+                        Err(e) => eprintln!("Failed to open SSH channel: {}", e),
+                    }
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                }
+                Err(e) => eprintln!("Local listener error: {}", e),
+            }
+        }
+    });
+
+    Ok((local_port, task_handle))
+}
+
 pub struct SshTunnel {
     pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
     pub task_handle: JoinHandle<()>,
+    pub hop_handles: Vec<JoinHandle<()>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl SshTunnel {
+    /// Stops accepting new connections and ends every forwarding thread of the tunnel
+    pub fn close(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        self.task_handle.abort();
+        for handle in &self.hop_handles {
+            handle.abort();
+        }
+    }
+}
+
+/// Tunnels are shared through `Arc`s, so the last holder to let go closes it
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Connects through every SSH hop and starts forwarding to the database. Blocks.
+fn establish_ssh_tunnel_blocking(config: &ConnectionConfig) -> Result<Arc<SshTunnel>> {
+    let hops = config.ssh_hops()?;
+    let remote_db_host = config
+        .host
+        .clone()
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+    let remote_db_port = config.port_or_default();
+
+    // Each bastion forwards a local port to the next hop; the next session connects through it
+    let first = hops.first().ok_or_else(|| anyhow!("SSH host missing"))?;
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let mut hop_handles = Vec::new();
+    let mut tcp = TcpStream::connect(format!("{}:{}", first.host, first.port()))?;
+
+    for (i, hop) in hops.iter().enumerate() {
+        let sess = open_ssh_session(tcp, hop, config.ssh_strict_host_key)?;
+
+        if let Some(next) = hops.get(i + 1) {
+            let (local_port, handle) =
+                spawn_forwarder(sess, next.host.clone(), next.port(), shutdown.clone())?;
+            hop_handles.push(handle);
+            tcp = TcpStream::connect(("127.0.0.1", local_port))?;
+        } else {
+            let (local_port, task_handle) = spawn_forwarder(
+                sess,
+                remote_db_host.clone(),
+                remote_db_port,
+                shutdown.clone(),
+            )?;
+            return Ok(Arc::new(SshTunnel {
+                local_port,
+                remote_host: remote_db_host,
+                remote_port: remote_db_port,
+                task_handle,
+                hop_handles,
+                shutdown,
+            }));
+        }
+    }
+
+    Err(anyhow!("SSH host missing"))
+}
+
+fn postgres_connect_options(config: &ConnectionConfig, password: &str) -> PgConnectOptions {
+    let host = config.host.as_deref().unwrap_or("localhost");
+    let port = config.port_or_default();
+    let user = config.username.as_deref().unwrap_or("postgres");
+    let db = config.database.as_deref().unwrap_or("postgres");
+
+    let mut opts = PgConnectOptions::new()
+        .host(host)
+        .port(port)
+        .username(user)
+        .password(password)
+        .database(db)
+        .application_name(config.application_name.as_deref().unwrap_or("sqlMate"));
+
+    if let Some(timeout_ms) = config.statement_timeout_ms {
+        opts = opts.options([("statement_timeout", timeout_ms.to_string())]);
+    }
+
+    // Apply SSL settings
+    if config.ssl_enabled {
+        let mode = match config.ssl_mode.as_deref() {
+            Some("require") => PgSslMode::Require,
+            Some("verify-ca") => PgSslMode::VerifyCa,
+            Some("verify-full") => PgSslMode::VerifyFull,
+            Some("prefer") => PgSslMode::Prefer,
+            _ => PgSslMode::Disable,
+        };
+        opts = opts.ssl_mode(mode);
+
+        if let Some(ca) = &config.ssl_ca_path {
+            opts = opts.ssl_root_cert(ca);
+        }
+        if let Some(cert) = &config.ssl_cert_path {
+            opts = opts.ssl_client_cert(cert);
+        }
+        if let Some(key) = &config.ssl_key_path {
+            opts = opts.ssl_client_key(key);
+        }
+    }
+    opts
+}
+
+fn mysql_connect_options(config: &ConnectionConfig, password: &str) -> MySqlConnectOptions {
+    let host = config.host.as_deref().unwrap_or("localhost");
+    let port = config.port_or_default();
+    let user = config.username.as_deref().unwrap_or("root");
+    let db = config.database.as_deref().unwrap_or("");
+
+    // Default to utf8mb4 so servers defaulting to latin1 don't mangle multibyte text
+    let mut opts = MySqlConnectOptions::new()
+        .host(host)
+        .port(port)
+        .username(user)
+        .password(password)
+        .database(db)
+        .charset(config.charset.as_deref().unwrap_or("utf8mb4"));
+
+    if let Some(collation) = &config.collation {
+        opts = opts.collation(collation);
+    }
+
+    if config.ssl_enabled {
+        let mode = match config.ssl_mode.as_deref() {
+            Some("require") | Some("verify-ca") | Some("verify-full") => MySqlSslMode::Required,
+            _ => MySqlSslMode::Disabled,
+        };
+        opts = opts.ssl_mode(mode);
+
+        if let Some(ca) = &config.ssl_ca_path {
+            opts = opts.ssl_ca(ca);
+        }
+    }
+    opts
+}
+
+async fn with_stage_timeout<T>(stage: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(TEST_STAGE_TIMEOUT, stage)
+        .await
+        .map_err(|_| anyhow!("Timed out after {}s", TEST_STAGE_TIMEOUT.as_secs()))?
+}
+
+/// TLS version negotiated on a Postgres connection, None when it is unencrypted
+async fn pg_negotiated_tls(conn: &mut PgConnection) -> Result<Option<String>> {
+    let sql = "SELECT version FROM pg_stat_ssl WHERE pid = pg_backend_pid() AND ssl";
+    Ok(sqlx::query_scalar(sql).fetch_optional(conn).await?)
+}
+
+/// TLS version negotiated on a MySQL connection, None when it is unencrypted
+async fn mysql_negotiated_tls(conn: &mut MySqlConnection) -> Result<Option<String>> {
+    let row = sqlx::query("SHOW SESSION STATUS LIKE 'Ssl_version'")
+        .fetch_optional(conn)
+        .await?;
+    Ok(row
+        .and_then(|row| row.try_get_unchecked::<String, _>(1).ok())
+        .filter(|version| !version.is_empty()))
+}
+
+/// Stages recorded by `test_connection`
+#[derive(Default)]
+struct StageLog {
+    stages: Vec<ConnectionTestStage>,
+}
+
+impl StageLog {
+    fn push(
+        &mut self,
+        name: &str,
+        status: ConnectionStageStatus,
+        started: Option<Instant>,
+        message: Option<String>,
+    ) {
+        self.stages.push(ConnectionTestStage {
+            name: name.to_string(),
+            status,
+            duration_ms: started.map(|s| s.elapsed().as_millis() as u64),
+            message,
+        });
+    }
+
+    /// Records the stage's outcome, returning its value when it succeeded
+    fn record<T>(&mut self, name: &str, started: Instant, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.push(name, ConnectionStageStatus::Ok, Some(started), None);
+                Some(value)
+            }
+            Err(e) => {
+                self.push(
+                    name,
+                    ConnectionStageStatus::Failed,
+                    Some(started),
+                    Some(e.to_string()),
+                );
+                None
+            }
+        }
+    }
+
+    /// Splits the sqlx handshake into its TLS and auth stages. sqlx reports TLS
+    /// problems as `Error::Tls`; anything else after TCP connected is put down to auth.
+    /// A successful TLS stage is recorded by `tls` once the connection can be asked.
+    fn handshake<C>(
+        &mut self,
+        started: Instant,
+        result: Result<std::result::Result<C, sqlx::Error>>,
+    ) -> Option<C> {
+        match result {
+            Ok(Err(e @ sqlx::Error::Tls(_))) => {
+                self.push(
+                    "tls",
+                    ConnectionStageStatus::Failed,
+                    Some(started),
+                    Some(e.to_string()),
+                );
+                None
+            }
+            Ok(Ok(conn)) => {
+                self.push("auth", ConnectionStageStatus::Ok, Some(started), None);
+                Some(conn)
+            }
+            Ok(Err(e)) => self.record("auth", started, Err(e.into())),
+            Err(e) => self.record("auth", started, Err(e)),
+        }
+    }
+
+    /// Records the TLS stage from what the server actually negotiated, ahead of the auth
+    /// stage it shares a handshake with. `prefer` may fall back to plaintext, so only an
+    /// encrypted session counts as Ok.
+    fn tls(&mut self, config: &ConnectionConfig, negotiated: Result<Option<String>>) {
+        let (status, message) = match negotiated {
+            Ok(Some(version)) => (ConnectionStageStatus::Ok, version),
+            Ok(None) if config.ssl_enabled => (
+                ConnectionStageStatus::Skipped,
+                "Server did not negotiate TLS; the connection is unencrypted".to_string(),
+            ),
+            Ok(None) => (ConnectionStageStatus::Skipped, "SSL disabled".to_string()),
+            Err(e) => (
+                ConnectionStageStatus::Skipped,
+                format!("Could not determine the TLS state: {}", e),
+            ),
+        };
+        let at = self
+            .stages
+            .iter()
+            .position(|stage| stage.name == "auth")
+            .unwrap_or(self.stages.len());
+        self.stages.insert(
+            at,
+            ConnectionTestStage {
+                name: "tls".to_string(),
+                status,
+                duration_ms: None,
+                message: Some(message),
+            },
+        );
+    }
+
+    fn finish(self) -> ConnectionTestResult {
+        let error = self
+            .stages
+            .iter()
+            .find(|stage| stage.status == ConnectionStageStatus::Failed)
+            .map(|stage| {
+                format!(
+                    "{} failed: {}",
+                    stage.name,
+                    stage.message.as_deref().unwrap_or("")
+                )
+            });
+        ConnectionTestResult {
+            success: error.is_none(),
+            stages: self.stages,
+            error,
+        }
+    }
+}
+
+fn tunnel_info(id: Uuid, tunnel: &SshTunnel, standalone: bool) -> TunnelInfo {
+    TunnelInfo {
+        id,
+        local_port: tunnel.local_port,
+        remote_host: tunnel.remote_host.clone(),
+        remote_port: tunnel.remote_port,
+        standalone,
+    }
+}
+
+/// Identifies the route and database endpoint of a tunnel, so connections to the same
+/// target can share one
+fn tunnel_key(config: &ConnectionConfig) -> Result<String> {
+    let route = config
+        .ssh_hops()?
+        .iter()
+        .map(|hop| format!("{}@{}:{}", hop.username, hop.host, hop.port()))
+        .collect::<Vec<_>>()
+        .join(" > ");
+    Ok(format!(
+        "{} > {}:{}",
+        route,
+        config.host.as_deref().unwrap_or("127.0.0.1"),
+        config.port_or_default()
+    ))
+}
+
+/// One SSH target's shared tunnel. Connects to that target queue on the slot's lock while
+/// the first of them establishes the tunnel, so they all end up sharing it.
+type TunnelSlot = Arc<Mutex<Weak<SshTunnel>>>;
+
+/// MariaDB reports versions like `10.11.6-MariaDB-1:10.11.6+maria~ubu2204`
+pub fn is_mariadb_version(version: &str) -> bool {
+    version.to_ascii_lowercase().contains("mariadb")
 }
 
 pub struct ConnectionManager {
@@ -25,6 +662,16 @@ pub struct ConnectionManager {
     configs: Arc<Mutex<HashMap<Uuid, ConnectionConfig>>>,
     passwords: Arc<Mutex<HashMap<Uuid, Option<String>>>>,
     tunnels: Arc<Mutex<HashMap<Uuid, Arc<SshTunnel>>>>,
+    /// Entries of `tunnels` opened with `open_tunnel`, which no connection owns
+    standalone_tunnels: Arc<Mutex<HashSet<Uuid>>>,
+    /// Live tunnels by `tunnel_key`; connections in `tunnels` (and connection tests) own them
+    shared_tunnels: Arc<Mutex<HashMap<String, TunnelSlot>>>,
+    /// MySQL-protocol connections whose server identified itself as MariaDB
+    mariadb: Arc<Mutex<HashSet<Uuid>>>,
+    /// Open result cursors by cursor id, with the connection each one pins
+    cursors: Arc<Mutex<HashMap<Uuid, OpenCursor>>>,
+    /// Explicit transactions by id; the slot is emptied once committed or rolled back
+    transactions: Arc<Mutex<HashMap<Uuid, (Uuid, Arc<Mutex<Option<UserTransaction>>>)>>>,
 }
 
 impl ConnectionManager {
@@ -36,9 +683,18 @@ impl ConnectionManager {
             configs: Arc::new(Mutex::new(HashMap::new())),
             passwords: Arc::new(Mutex::new(HashMap::new())),
             tunnels: Arc::new(Mutex::new(HashMap::new())),
+            standalone_tunnels: Arc::new(Mutex::new(HashSet::new())),
+            shared_tunnels: Arc::new(Mutex::new(HashMap::new())),
+            mariadb: Arc::new(Mutex::new(HashSet::new())),
+            cursors: Arc::new(Mutex::new(HashMap::new())),
+            transactions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    pub async fn is_mariadb(&self, id: &Uuid) -> bool {
+        self.mariadb.lock().await.contains(id)
+    }
+
     pub async fn get_tunnels(&self) -> tokio::sync::MutexGuard<'_, HashMap<Uuid, Arc<SshTunnel>>> {
         self.tunnels.lock().await
     }
@@ -61,69 +717,102 @@ impl ConnectionManager {
         }
     }
 
+    /// Connects step by step (SSH, DNS, TCP, TLS/auth, ping), stopping at the first failure.
+    /// Missing settings are still an `Err`; network and server failures land in the result.
     pub async fn test_connection(
         &self,
         config: ConnectionConfig,
         password: Option<String>,
-    ) -> Result<()> {
+    ) -> Result<ConnectionTestResult> {
+        let mut log = StageLog::default();
         let mut final_config = config.clone();
         let mut tunnel_opt: Option<Arc<SshTunnel>> = None;
 
         if config.ssh_enabled {
-            let tunnel = self.establish_ssh_tunnel(&config).await?;
+            let started = Instant::now();
+            let Some(tunnel) = log.record("ssh", started, self.acquire_ssh_tunnel(&config).await)
+            else {
+                return Ok(log.finish());
+            };
             final_config.host = Some("127.0.0.1".to_string());
             final_config.port = Some(tunnel.local_port);
             tunnel_opt = Some(tunnel);
         }
 
-        let result = match final_config.db_type {
-            DatabaseType::Postgres => {
+        let pass = password.unwrap_or_default();
+        match final_config.db_type {
+            DatabaseType::Postgres | DatabaseType::MySql => {
+                let label = match final_config.db_type {
+                    DatabaseType::Postgres => "Postgres",
+                    _ => "MySQL",
+                };
                 let host = final_config
                     .host
                     .clone()
-                    .ok_or_else(|| anyhow!("Host required for Postgres"))?;
-                let port = final_config.port.unwrap_or(5432);
-                let user = final_config
+                    .ok_or_else(|| anyhow!("Host required for {}", label))?;
+                let port = final_config.port_or_default();
+                final_config
                     .username
-                    .clone()
-                    .ok_or_else(|| anyhow!("Username required for Postgres"))?;
-                let db = final_config
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Username required for {}", label))?;
+                final_config
                     .database
-                    .clone()
-                    .ok_or_else(|| anyhow!("Database name required for Postgres"))?;
-                let pass = password.unwrap_or_default();
-                let url = format!("postgres://{}:{}@{}:{}/{}", user, pass, host, port, db);
-
-                async {
-                    let mut conn = sqlx::postgres::PgConnection::connect(&url).await?;
-                    conn.ping().await
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Database name required for {}", label))?;
+
+                let started = Instant::now();
+                let lookup = with_stage_timeout(async {
+                    let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), port))
+                        .await?
+                        .collect();
+                    addrs
+                        .first()
+                        .copied()
+                        .ok_or_else(|| anyhow!("{} did not resolve to any address", host))
+                })
+                .await;
+                let Some(addr) = log.record("dns", started, lookup) else {
+                    return Ok(log.finish());
+                };
+
+                let started = Instant::now();
+                let tcp = with_stage_timeout(async {
+                    tokio::net::TcpStream::connect(addr)
+                        .await
+                        .map_err(|e| anyhow!("{}: {}", addr, e))
+                })
+                .await;
+                if log.record("tcp", started, tcp).is_none() {
+                    return Ok(log.finish());
                 }
-                .await
-                .map_err(|e| anyhow!("Connection/Ping failed: {}", e))
-            }
-            DatabaseType::MySql => {
-                let host = final_config
-                    .host
-                    .clone()
-                    .ok_or_else(|| anyhow!("Host required for MySQL"))?;
-                let port = final_config.port.unwrap_or(3306);
-                let user = final_config
-                    .username
-                    .clone()
-                    .ok_or_else(|| anyhow!("Username required for MySQL"))?;
-                let db = final_config
-                    .database
-                    .clone()
-                    .ok_or_else(|| anyhow!("Database name required for MySQL"))?;
-                let pass = password.unwrap_or_default();
-                let url = format!("mysql://{}:{}@{}:{}/{}", user, pass, host, port, db);
 
-                async {
-                    let mut conn = sqlx::mysql::MySqlConnection::connect(&url).await?;
-                    conn.ping().await
+                let started = Instant::now();
+                if let DatabaseType::Postgres = final_config.db_type {
+                    let opts = postgres_connect_options(&final_config, &pass);
+                    let connected =
+                        with_stage_timeout(async { Ok(PgConnection::connect_with(&opts).await) })
+                            .await;
+                    if let Some(mut conn) = log.handshake(started, connected) {
+                        let negotiated = with_stage_timeout(pg_negotiated_tls(&mut conn)).await;
+                        log.tls(&final_config, negotiated);
+                        let started = Instant::now();
+                        let ping = with_stage_timeout(async { Ok(conn.ping().await?) }).await;
+                        log.record("ping", started, ping);
+                    }
+                } else {
+                    let opts = mysql_connect_options(&final_config, &pass);
+                    let connected = with_stage_timeout(async {
+                        Ok(MySqlConnection::connect_with(&opts).await)
+                    })
+                    .await;
+                    if let Some(mut conn) = log.handshake(started, connected) {
+                        let negotiated = with_stage_timeout(mysql_negotiated_tls(&mut conn)).await;
+                        log.tls(&final_config, negotiated);
+                        let started = Instant::now();
+                        let ping = with_stage_timeout(async { Ok(conn.ping().await?) }).await;
+                        log.record("ping", started, ping);
+                    }
                 }
-                .await
-                .map_err(|e| anyhow!("Connection/Ping failed: {}", e))
             }
             DatabaseType::Sqlite => {
                 let db_path = final_config
@@ -132,224 +821,93 @@ impl ConnectionManager {
                     .ok_or_else(|| anyhow!("Path required for SQLite"))?;
                 let url = format!("sqlite:{}", db_path);
 
-                async {
-                    let mut conn = sqlx::sqlite::SqliteConnection::connect(&url).await?;
-                    conn.ping().await
+                let started = Instant::now();
+                let opened = sqlx::sqlite::SqliteConnection::connect(&url).await;
+                if let Some(mut conn) = log.record("open", started, opened.map_err(Into::into)) {
+                    let started = Instant::now();
+                    let ping = with_stage_timeout(async { Ok(conn.ping().await?) }).await;
+                    log.record("ping", started, ping);
                 }
-                .await
-                .map_err(|e| anyhow!("Connection/Ping failed: {}", e))
             }
-        };
-
-        // Clean up the temporary test tunnel
-        if let Some(tunnel) = tunnel_opt {
-            tunnel.task_handle.abort();
         }
 
-        let _ = result?;
-        Ok(())
-    }
+        // Release the test's hold; the tunnel closes unless a live connection shares it
+        drop(tunnel_opt);
 
-    async fn establish_ssh_tunnel(&self, config: &ConnectionConfig) -> Result<Arc<SshTunnel>> {
-        let ssh_host = config
-            .ssh_host
-            .as_ref()
-            .ok_or_else(|| anyhow!("SSH host missing"))?;
-        let ssh_port = config.ssh_port.unwrap_or(22);
-        let ssh_user = config
-            .ssh_username
-            .as_ref()
-            .ok_or_else(|| anyhow!("SSH username missing"))?;
-
-        // Connect to SSH server
-        let tcp = TcpStream::connect(format!("{}:{}", ssh_host, ssh_port))?;
-        let mut sess = Session::new()?;
-        sess.set_tcp_stream(tcp);
-        sess.handshake()?;
-
-        // Auth
-        if config.ssh_auth_method.as_deref() == Some("password") {
-            let pass = config
-                .ssh_password
-                .as_ref()
-                .ok_or_else(|| anyhow!("SSH password missing"))?;
-            sess.userauth_password(ssh_user, pass)?;
-        } else if let Some(key_path_str) = &config.ssh_private_key_path {
-            let mut resolved_path = std::path::PathBuf::from(key_path_str);
-
-            // If the path is just a file name like "id_rsa" or starts with "~", resolve it against the home directory
-            if !resolved_path.is_absolute() {
-                if let Some(home) = dirs::home_dir() {
-                    if key_path_str.starts_with("~/") {
-                        resolved_path = home.join(key_path_str.trim_start_matches("~/"));
-                    } else if !key_path_str.contains('/') && !key_path_str.contains('\\') {
-                        // Just a filename like "id_rsa", look in ~/.ssh/
-                        resolved_path = home.join(".ssh").join(key_path_str);
-                    }
-                }
-            }
+        Ok(log.finish())
+    }
 
-            if !resolved_path.exists() {
-                return Err(anyhow!(
-                    "SSH key file not found at: {}",
-                    resolved_path.display()
-                ));
-            }
+    /// Opens a tunnel that isn't tied to a database pool, for use by external tools.
+    /// It is never shared with connections and stays open until `close_tunnel`.
+    pub async fn open_tunnel(&self, config: &ConnectionConfig) -> Result<TunnelInfo> {
+        let tunnel = self.establish_ssh_tunnel(config).await?;
+        Ok(self.track_standalone_tunnel(tunnel).await)
+    }
 
-            // Attempt to find the corresponding .pub file
-            // libssh2 often requires the explicit public key file to successfully authenticate with modern keys
-            let mut pub_path_opt = None;
-            let pubkey_path =
-                resolved_path.with_extension(if let Some(ext) = resolved_path.extension() {
-                    format!("{}.pub", ext.to_string_lossy())
-                } else {
-                    "pub".to_string()
-                });
+    async fn track_standalone_tunnel(&self, tunnel: Arc<SshTunnel>) -> TunnelInfo {
+        let id = Uuid::new_v4();
+        let info = tunnel_info(id, &tunnel, true);
+        self.tunnels.lock().await.insert(id, tunnel);
+        self.standalone_tunnels.lock().await.insert(id);
+        info
+    }
 
-            // If it was just "id_rsa", with_extension("pub") makes "id_rsa.pub"
-            if pubkey_path.exists() {
-                pub_path_opt = Some(pubkey_path);
-            } else {
-                // Fallback for names without extensions that might not work with with_extension right
-                let mut alt_pubkey = resolved_path.clone();
-                if let Some(name) = alt_pubkey.file_name() {
-                    let mut new_name = name.to_os_string();
-                    new_name.push(".pub");
-                    alt_pubkey.set_file_name(new_name);
-                    if alt_pubkey.exists() {
-                        pub_path_opt = Some(alt_pubkey);
-                    }
-                }
-            }
+    pub async fn list_tunnels(&self) -> Vec<TunnelInfo> {
+        let tunnels = self.tunnels.lock().await;
+        let standalone = self.standalone_tunnels.lock().await;
+        let mut infos: Vec<TunnelInfo> = tunnels
+            .iter()
+            .map(|(id, tunnel)| tunnel_info(*id, tunnel, standalone.contains(id)))
+            .collect();
+        infos.sort_by_key(|info| info.local_port);
+        infos
+    }
 
-            match sess.userauth_pubkey_file(ssh_user, pub_path_opt.as_deref(), &resolved_path, None)
-            {
-                Ok(_) => {}
-                Err(e) => return Err(anyhow!("SSH pubkey auth failed: {}", e)),
-            }
-        } else {
-            return Err(anyhow!("Unsupported or missing SSH auth method"));
+    /// Closes a tunnel from `open_tunnel`; connection tunnels close on disconnect instead
+    pub async fn close_tunnel(&self, id: &Uuid) -> Result<()> {
+        if !self.standalone_tunnels.lock().await.remove(id) {
+            return Err(anyhow!(
+                "Tunnel not found, or it belongs to an open connection"
+            ));
         }
-
-        if !sess.authenticated() {
-            return Err(anyhow!("SSH authentication failed"));
+        if let Some(tunnel) = self.tunnels.lock().await.remove(id) {
+            tunnel.close();
         }
+        Ok(())
+    }
 
-        // Bind local listener to a random port
-        let listener = TcpListener::bind("127.0.0.1:0")?;
-        let local_port = listener.local_addr()?.port();
-
-        let remote_db_host = config
-            .host
-            .clone()
-            .unwrap_or_else(|| "127.0.0.1".to_string());
-        let remote_db_port = config.port.unwrap_or(5432); // Default for PG, but we should use actual config port
-
-        let sess_arc = Arc::new(std::sync::Mutex::new(sess));
-
-        let task_handle = tokio::task::spawn_blocking(move || {
-            for stream in listener.incoming() {
-                match stream {
-                    Ok(mut local_stream) => {
-                        let sess_locked = sess_arc.lock().unwrap();
-                        let _ = sess_locked.set_blocking(true); // Ensure blocking for channel creation
-                        match sess_locked.channel_direct_tcpip(
-                            &remote_db_host,
-                            remote_db_port,
-                            None,
-                        ) {
-                            Ok(mut channel) => {
-                                let sess_for_thread = sess_arc.clone();
-                                std::thread::spawn(move || {
-                                    let mut local_stream_clone = local_stream
-                                        .try_clone()
-                                        .expect("Failed to clone local stream");
-
-                                    // Set non-blocking on the session for this thread's channel
-                                    let sess_locked = sess_for_thread.lock().unwrap();
-                                    sess_locked.set_blocking(false);
-                                    drop(sess_locked);
-
-                                    let mut buf_local = [0; 4096];
-                                    let mut buf_remote = [0; 4096];
-
-                                    local_stream.set_nonblocking(true).unwrap();
-
-                                    loop {
-                                        let mut active = false;
-
-                                        // Local -> Remote
-                                        match local_stream.read(&mut buf_local) {
-                                            Ok(0) => break,
-                                            Ok(n) => {
-                                                let mut written = 0;
-                                                while written < n {
-                                                    let sess_locked =
-                                                        sess_for_thread.lock().unwrap();
-                                                    let _ = sess_locked.set_blocking(false);
-                                                    match channel.write(&buf_local[written..n]) {
-                                                            Ok(0) => break,
-                                                            Ok(m) => {
-                                                                written += m;
-                                                                active = true;
-                                                            }
-                                                            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                                                                drop(sess_locked);
-                                                                std::thread::sleep(std::time::Duration::from_millis(1));
-                                                                continue;
-                                                            }
-                                                            Err(_) => break,
-                                                        }
-                                                }
-                                            }
-                                            Err(ref e)
-                                                if e.kind() == std::io::ErrorKind::WouldBlock => {}
-                                            Err(_) => break,
-                                        }
-
-                                        // Remote -> Local
-                                        {
-                                            let sess_locked = sess_for_thread.lock().unwrap();
-                                            let _ = sess_locked.set_blocking(false);
-                                            match channel.read(&mut buf_remote) {
-                                                Ok(0) => break,
-                                                Ok(n) => {
-                                                    if local_stream_clone
-                                                        .write_all(&buf_remote[..n])
-                                                        .is_err()
-                                                    {
-                                                        break;
-                                                    }
-                                                    active = true;
-                                                }
-                                                Err(ref e)
-                                                    if e.kind()
-                                                        == std::io::ErrorKind::WouldBlock => {} // EAGAIN
-                                                Err(e) => {
-                                                    eprintln!("SSH Remote Read Error: {:?}", e);
-                                                    break;
-                                                }
-                                            }
-                                        }
+    /// Returns a live tunnel to the config's SSH target, reusing one that another connection
+    /// (or this one, when switching databases) already holds
+    async fn acquire_ssh_tunnel(&self, config: &ConnectionConfig) -> Result<Arc<SshTunnel>> {
+        let key = tunnel_key(config)?;
+        // The map lock only covers finding the slot, so a slow bastion holds up connects to
+        // its own target and nobody else's
+        let slot = {
+            let mut shared = self.shared_tunnels.lock().await;
+            shared.retain(|_, slot| {
+                Arc::strong_count(slot) > 1
+                    || slot
+                        .try_lock()
+                        .map_or(true, |tunnel| tunnel.strong_count() > 0)
+            });
+            shared.entry(key).or_default().clone()
+        };
 
-                                        if !active {
-                                            std::thread::sleep(std::time::Duration::from_millis(5));
-                                        }
-                                    }
-                                });
-                            }
-                            Err(e) => eprintln!("Failed to open SSH channel: {}", e),
-                        }
-                    }
-                    Err(e) => eprintln!("Local listener error: {}", e),
-                }
+        let mut slot = slot.lock().await;
+        if let Some(tunnel) = slot.upgrade() {
+            if !tunnel.task_handle.is_finished() {
+                return Ok(tunnel);
             }
-        });
+        }
+        let tunnel = self.establish_ssh_tunnel(config).await?;
+        *slot = Arc::downgrade(&tunnel);
+        Ok(tunnel)
+    }
 
-        Ok(Arc::new(SshTunnel {
-            local_port,
-            task_handle,
-        }))
+    /// TCP connects and SSH handshakes block, so they run on the blocking pool
+    async fn establish_ssh_tunnel(&self, config: &ConnectionConfig) -> Result<Arc<SshTunnel>> {
+        let config = config.clone();
+        tokio::task::spawn_blocking(move || establish_ssh_tunnel_blocking(&config)).await?
     }
 
     async fn connect_postgres(
@@ -360,7 +918,7 @@ impl ConnectionManager {
         let mut final_config = config.clone();
 
         if config.ssh_enabled {
-            let tunnel = self.establish_ssh_tunnel(&config).await?;
+            let tunnel = self.acquire_ssh_tunnel(&config).await?;
             final_config.host = Some("127.0.0.1".to_string());
             final_config.port = Some(tunnel.local_port);
 
@@ -368,40 +926,7 @@ impl ConnectionManager {
             tunnels.insert(config.id, tunnel);
         }
 
-        let host = final_config.host.as_deref().unwrap_or("localhost");
-        let port = final_config.port.unwrap_or(5432);
-        let user = final_config.username.as_deref().unwrap_or("postgres");
-        let db = final_config.database.as_deref().unwrap_or("postgres");
-        let pass = password.unwrap_or_default();
-
-        let mut opts = PgConnectOptions::new()
-            .host(host)
-            .port(port)
-            .username(user)
-            .password(&pass)
-            .database(db);
-
-        // Apply SSL settings
-        if final_config.ssl_enabled {
-            let mode = match final_config.ssl_mode.as_deref() {
-                Some("require") => PgSslMode::Require,
-                Some("verify-ca") => PgSslMode::VerifyCa,
-                Some("verify-full") => PgSslMode::VerifyFull,
-                Some("prefer") => PgSslMode::Prefer,
-                _ => PgSslMode::Disable,
-            };
-            opts = opts.ssl_mode(mode);
-
-            if let Some(ca) = &final_config.ssl_ca_path {
-                opts = opts.ssl_root_cert(ca);
-            }
-            if let Some(cert) = &final_config.ssl_cert_path {
-                opts = opts.ssl_client_cert(cert);
-            }
-            if let Some(key) = &final_config.ssl_key_path {
-                opts = opts.ssl_client_key(key);
-            }
-        }
+        let opts = postgres_connect_options(&final_config, &password.unwrap_or_default());
 
         let pool = sqlx::postgres::PgPoolOptions::new()
             .max_connections(5)
@@ -409,8 +934,13 @@ impl ConnectionManager {
             .connect_with(opts)
             .await?;
 
-        let mut pools = self.postgres_pools.lock().await;
-        pools.insert(config.id, pool);
+        // switch_database replaces a live pool; close it instead of letting it idle out.
+        // Closing waits for in-flight queries, so it runs in the background.
+        let old_pool = self.postgres_pools.lock().await.insert(config.id, pool);
+        if let Some(old_pool) = old_pool {
+            self.release_pinned_connections(&config.id).await;
+            tokio::spawn(async move { old_pool.close().await });
+        }
 
         Ok(())
     }
@@ -423,7 +953,7 @@ impl ConnectionManager {
         let mut final_config = config.clone();
 
         if config.ssh_enabled {
-            let tunnel = self.establish_ssh_tunnel(&config).await?;
+            let tunnel = self.acquire_ssh_tunnel(&config).await?;
             final_config.host = Some("127.0.0.1".to_string());
             final_config.port = Some(tunnel.local_port);
 
@@ -431,30 +961,7 @@ impl ConnectionManager {
             tunnels.insert(config.id, tunnel);
         }
 
-        let host = final_config.host.as_deref().unwrap_or("localhost");
-        let port = final_config.port.unwrap_or(3306);
-        let user = final_config.username.as_deref().unwrap_or("root");
-        let db = final_config.database.as_deref().unwrap_or("");
-        let pass = password.unwrap_or_default();
-
-        let mut opts = MySqlConnectOptions::new()
-            .host(host)
-            .port(port)
-            .username(user)
-            .password(&pass)
-            .database(db);
-
-        if final_config.ssl_enabled {
-            let mode = match final_config.ssl_mode.as_deref() {
-                Some("require") | Some("verify-ca") | Some("verify-full") => MySqlSslMode::Required,
-                _ => MySqlSslMode::Disabled,
-            };
-            opts = opts.ssl_mode(mode);
-
-            if let Some(ca) = &final_config.ssl_ca_path {
-                opts = opts.ssl_ca(ca);
-            }
-        }
+        let opts = mysql_connect_options(&final_config, &password.unwrap_or_default());
 
         let pool = sqlx::mysql::MySqlPoolOptions::new()
             .max_connections(5)
@@ -462,8 +969,17 @@ impl ConnectionManager {
             .connect_with(opts)
             .await?;
 
-        let mut pools = self.mysql_pools.lock().await;
-        pools.insert(config.id, pool);
+        let old_pool = self.mysql_pools.lock().await.insert(config.id, pool);
+        if let Some(old_pool) = old_pool {
+            self.release_pinned_connections(&config.id).await;
+            tokio::spawn(async move { old_pool.close().await });
+        }
+
+        if self.get_server_info(&config.id).await?.is_mariadb {
+            self.mariadb.lock().await.insert(config.id);
+        } else {
+            self.mariadb.lock().await.remove(&config.id);
+        }
 
         Ok(())
     }
@@ -474,8 +990,12 @@ impl ConnectionManager {
             .clone()
             .ok_or_else(|| anyhow!("Path required for SQLite"))?;
         let url = format!("sqlite:{}", db_path);
+        // Keep the single connection alive so ATTACHed databases persist
         let pool = sqlx::sqlite::SqlitePoolOptions::new()
             .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
             .acquire_timeout(Duration::from_secs(5))
             .connect(&url)
             .await
@@ -532,6 +1052,58 @@ impl ConnectionManager {
         result
     }
 
+    /// Rebuilds the pool for a connection from its stored config and password
+    pub async fn reconnect(&self, id: &Uuid) -> Result<()> {
+        let config = {
+            let configs = self.configs.lock().await;
+            configs
+                .get(id)
+                .cloned()
+                .ok_or_else(|| anyhow!("Connection config not found"))?
+        };
+        let password = {
+            let passwords = self.passwords.lock().await;
+            passwords.get(id).cloned().flatten()
+        };
+
+        self.close_pools(id).await;
+        self.connect(config, password).await
+    }
+
+    /// Drops the cursors and transactions of a connection.
+    /// They hold pooled connections, which would keep the pool's `close()` waiting.
+    async fn release_pinned_connections(&self, id: &Uuid) {
+        self.cursors
+            .lock()
+            .await
+            .retain(|_, open| open.connection_id != *id);
+        // Dropping an uncommitted transaction rolls it back
+        self.transactions
+            .lock()
+            .await
+            .retain(|_, (connection_id, _)| connection_id != id);
+    }
+
+    /// Closes and forgets any pool and tunnel held for a connection
+    async fn close_pools(&self, id: &Uuid) {
+        self.release_pinned_connections(id).await;
+        let pool = self.postgres_pools.lock().await.remove(id);
+        if let Some(pool) = pool {
+            pool.close().await;
+        }
+        let pool = self.mysql_pools.lock().await.remove(id);
+        if let Some(pool) = pool {
+            pool.close().await;
+        }
+        self.mariadb.lock().await.remove(id);
+        let pool = self.sqlite_pools.lock().await.remove(id);
+        if let Some(pool) = pool {
+            pool.close().await;
+        }
+        // Closes the tunnel unless another connection still shares it
+        self.tunnels.lock().await.remove(id);
+    }
+
     pub async fn disconnect(&self, id: &Uuid) -> Result<()> {
         {
             let mut configs = self.configs.lock().await;
@@ -541,31 +1113,226 @@ impl ConnectionManager {
             let mut passwords = self.passwords.lock().await;
             passwords.remove(id);
         }
-        {
-            let mut pools = self.postgres_pools.lock().await;
-            if pools.remove(id).is_some() {
-                return Ok(());
-            }
+        self.close_pools(id).await;
+        Ok(())
+    }
+
+    /// Checks a live pool connection and returns the round-trip latency in milliseconds
+    pub async fn ping(&self, id: &Uuid) -> Result<u64> {
+        let start = Instant::now();
+
+        let pool = self.postgres_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let mut conn = pool.acquire().await?;
+            conn.ping().await?;
+            return Ok(start.elapsed().as_millis() as u64);
         }
-        {
-            let mut pools = self.mysql_pools.lock().await;
-            if pools.remove(id).is_some() {
-                return Ok(());
+        let pool = self.mysql_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let mut conn = pool.acquire().await?;
+            conn.ping().await?;
+            return Ok(start.elapsed().as_millis() as u64);
+        }
+        let pool = self.sqlite_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let mut conn = pool.acquire().await?;
+            conn.ping().await?;
+            return Ok(start.elapsed().as_millis() as u64);
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Asks the server for its version; MySQL-protocol servers are checked for MariaDB
+    pub async fn get_server_info(&self, id: &Uuid) -> Result<ServerInfo> {
+        let pool = self.postgres_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let version: String = sqlx::query_scalar("SHOW server_version")
+                .fetch_one(&pool)
+                .await?;
+            return Ok(ServerInfo {
+                db_type: "postgres".to_string(),
+                version,
+                is_mariadb: false,
+            });
+        }
+        let pool = self.mysql_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let version: String = sqlx::query_scalar("SELECT VERSION()")
+                .fetch_one(&pool)
+                .await?;
+            return Ok(ServerInfo {
+                db_type: "mysql".to_string(),
+                is_mariadb: is_mariadb_version(&version),
+                version,
+            });
+        }
+        let pool = self.sqlite_pools.lock().await.get(id).cloned();
+        if let Some(pool) = pool {
+            let version: String = sqlx::query_scalar("SELECT sqlite_version()")
+                .fetch_one(&pool)
+                .await?;
+            return Ok(ServerInfo {
+                db_type: "sqlite".to_string(),
+                version,
+                is_mariadb: false,
+            });
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Rejects destructive operations on connections marked read-only
+    pub async fn ensure_writable(&self, id: &Uuid) -> Result<()> {
+        let configs = self.configs.lock().await;
+        match configs.get(id) {
+            Some(config) if config.read_only => {
+                Err(anyhow!("Connection '{}' is read-only", config.name))
             }
+            _ => Ok(()),
         }
-        {
-            let mut pools = self.sqlite_pools.lock().await;
-            pools.remove(id);
+    }
+
+    /// Prefixes `sql` with `/* sqlMate conn=<name> user=<os user> */` when the connection has
+    /// `tag_queries` on, so pg_stat_activity and slow logs can be traced back to sqlMate.
+    /// A leading block comment keeps WITH/EXPLAIN statements and keyword detection intact.
+    pub async fn tag_query(&self, id: &Uuid, sql: &str) -> String {
+        let configs = self.configs.lock().await;
+        match configs.get(id) {
+            Some(config) if config.tag_queries => {
+                let os_user = std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .unwrap_or_default();
+                // Neither value may close the comment early
+                format!(
+                    "/* sqlMate conn={} user={} */ {}",
+                    config.name.replace("*/", "* /"),
+                    os_user.replace("*/", "* /"),
+                    sql
+                )
+            }
+            _ => sql.to_string(),
         }
-        {
-            let mut tunnels = self.tunnels.lock().await;
-            if let Some(tunnel) = tunnels.remove(id) {
-                let _ = tunnel.task_handle.abort();
+    }
+
+    /// Memory guard for `execute_query`: rows past this are dropped and the result is
+    /// flagged as truncated
+    pub async fn max_result_rows(&self, id: &Uuid) -> Option<usize> {
+        let configs = self.configs.lock().await;
+        configs
+            .get(id)
+            .and_then(|config| config.max_rows)
+            .map(|max| max as usize)
+    }
+
+    /// Row cap for table browsing when the caller asks for every row
+    pub async fn default_browse_limit(&self, id: &Uuid) -> Option<u32> {
+        let configs = self.configs.lock().await;
+        configs
+            .get(id)
+            .and_then(|config| config.default_browse_limit)
+    }
+
+    /// Rejects destructive operations on production connections unless the caller confirmed them
+    pub async fn ensure_production_confirmed(&self, id: &Uuid, confirmed: bool) -> Result<()> {
+        let configs = self.configs.lock().await;
+        match configs.get(id) {
+            Some(config) if !confirmed && config.environment.as_deref() == Some("production") => {
+                Err(anyhow!(
+                    "Connection '{}' is marked as production; destructive operations require confirm_production",
+                    config.name
+                ))
             }
+            _ => Ok(()),
+        }
+    }
+
+    /// Drops cursors idle for longer than `CURSOR_IDLE_TTL`; dropping rolls their transaction back
+    fn expire_idle_cursors(cursors: &mut HashMap<Uuid, OpenCursor>) {
+        cursors.retain(|_, open| open.last_used.elapsed() < CURSOR_IDLE_TTL);
+    }
+
+    /// Fails when a connection already has `MAX_CURSORS_PER_CONNECTION` cursors open
+    pub async fn ensure_cursor_capacity(&self, connection_id: &Uuid) -> Result<()> {
+        let mut cursors = self.cursors.lock().await;
+        Self::expire_idle_cursors(&mut cursors);
+        let open = cursors
+            .values()
+            .filter(|open| open.connection_id == *connection_id)
+            .count();
+        if open >= MAX_CURSORS_PER_CONNECTION {
+            return Err(anyhow!(
+                "This connection already has {} result cursors open; close one first",
+                open
+            ));
         }
         Ok(())
     }
 
+    pub async fn add_cursor(&self, connection_id: Uuid, cursor: ResultCursor) -> Result<Uuid> {
+        self.ensure_cursor_capacity(&connection_id).await?;
+        let cursor_id = Uuid::new_v4();
+        self.cursors.lock().await.insert(
+            cursor_id,
+            OpenCursor {
+                connection_id,
+                last_used: Instant::now(),
+                cursor: Arc::new(Mutex::new(cursor)),
+            },
+        );
+        Ok(cursor_id)
+    }
+
+    pub async fn get_cursor(&self, cursor_id: &Uuid) -> Option<Arc<Mutex<ResultCursor>>> {
+        let mut cursors = self.cursors.lock().await;
+        Self::expire_idle_cursors(&mut cursors);
+        cursors.get_mut(cursor_id).map(|open| {
+            open.last_used = Instant::now();
+            open.cursor.clone()
+        })
+    }
+
+    pub async fn remove_cursor(&self, cursor_id: &Uuid) -> Option<Arc<Mutex<ResultCursor>>> {
+        self.cursors
+            .lock()
+            .await
+            .remove(cursor_id)
+            .map(|open| open.cursor)
+    }
+
+    pub async fn add_transaction(&self, connection_id: Uuid, tx: UserTransaction) -> Uuid {
+        let tx_id = Uuid::new_v4();
+        self.transactions
+            .lock()
+            .await
+            .insert(tx_id, (connection_id, Arc::new(Mutex::new(Some(tx)))));
+        tx_id
+    }
+
+    pub async fn get_transaction(
+        &self,
+        tx_id: &Uuid,
+    ) -> Option<Arc<Mutex<Option<UserTransaction>>>> {
+        self.transactions
+            .lock()
+            .await
+            .get(tx_id)
+            .map(|(_, tx)| tx.clone())
+    }
+
+    /// The connection a transaction was opened on
+    pub async fn get_transaction_connection(&self, tx_id: &Uuid) -> Option<Uuid> {
+        self.transactions
+            .lock()
+            .await
+            .get(tx_id)
+            .map(|(connection_id, _)| *connection_id)
+    }
+
+    pub async fn remove_transaction(&self, tx_id: &Uuid) {
+        self.transactions.lock().await.remove(tx_id);
+    }
+
     pub async fn get_postgres_pools(
         &self,
     ) -> tokio::sync::OwnedMutexGuard<HashMap<Uuid, Pool<Postgres>>> {
@@ -584,3 +1351,530 @@ impl ConnectionManager {
         self.sqlite_pools.clone().lock_owned().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support;
+
+    #[tokio::test]
+    async fn ping_succeeds_until_disconnect() {
+        let (manager, id) = test_support::sqlite().await;
+        manager.ping(&id).await.unwrap();
+
+        manager.disconnect(&id).await.unwrap();
+        assert!(manager.ping(&id).await.is_err());
+    }
+
+    fn fixture_known_hosts() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/known_hosts")
+    }
+
+    /// The raw key blob the fixture lists for bastion.example.com:2222
+    fn fixture_key() -> Vec<u8> {
+        use base64::prelude::{Engine, BASE64_STANDARD};
+        let line = std::fs::read_to_string(fixture_known_hosts()).unwrap();
+        let encoded = line
+            .lines()
+            .find(|l| !l.starts_with('#'))
+            .and_then(|l| l.split_whitespace().nth(2))
+            .unwrap();
+        BASE64_STANDARD.decode(encoded).unwrap()
+    }
+
+    #[test]
+    fn known_hosts_rejects_a_mismatched_key() {
+        let sess = Session::new().unwrap();
+        let path = fixture_known_hosts();
+        let key = fixture_key();
+        check_known_host(&sess, "bastion.example.com", 2222, &key, "", &path, true).unwrap();
+
+        let mut other = key.clone();
+        *other.last_mut().unwrap() ^= 0xff;
+        let err = check_known_host(&sess, "bastion.example.com", 2222, &other, "", &path, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match known_hosts"));
+    }
+
+    #[test]
+    fn unknown_hosts_are_only_rejected_in_strict_mode() {
+        let sess = Session::new().unwrap();
+        let path = fixture_known_hosts();
+        let key = fixture_key();
+        check_known_host(&sess, "new.example.com", 22, &key, "", &path, false).unwrap();
+        assert!(check_known_host(&sess, "new.example.com", 22, &key, "", &path, true).is_err());
+    }
+
+    /// Two hops through real hosts, run with `cargo test --features ssh-integration-tests`:
+    ///
+    /// - `SQLMATE_TEST_SSH_JUMP`: the bastion reachable from here, as `user@host:port`
+    /// - `SQLMATE_TEST_SSH_TARGET`: the SSH host only the bastion can reach, same form
+    /// - `SQLMATE_TEST_SSH_KEY`: a private key path both hosts accept
+    /// - `SQLMATE_TEST_SSH_DB`: the `host:port` the target forwards to
+    #[cfg(feature = "ssh-integration-tests")]
+    #[tokio::test]
+    async fn tunnels_through_a_jump_host() {
+        let var = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} not set", name));
+        let endpoint = |value: String| -> (String, String, u16) {
+            let (user, address) = value.split_once('@').expect("user@host:port");
+            let (host, port) = address.rsplit_once(':').expect("host:port");
+            (
+                user.to_string(),
+                host.to_string(),
+                port.parse().expect("port"),
+            )
+        };
+        let key = var("SQLMATE_TEST_SSH_KEY");
+        let (jump_user, jump_host, jump_port) = endpoint(var("SQLMATE_TEST_SSH_JUMP"));
+        let (user, host, port) = endpoint(var("SQLMATE_TEST_SSH_TARGET"));
+        let db = var("SQLMATE_TEST_SSH_DB");
+        let (db_host, db_port) = db.rsplit_once(':').expect("host:port");
+
+        let config: ConnectionConfig = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "name": "two hops",
+            "db_type": "Postgres",
+            "host": db_host,
+            "port": db_port.parse::<u16>().unwrap(),
+            "ssl_enabled": false,
+            "ssh_enabled": true,
+            "ssh_host": host,
+            "ssh_port": port,
+            "ssh_username": user,
+            "ssh_auth_method": "key",
+            "ssh_private_key_path": key,
+            "ssh_jump_hosts": [{
+                "host": jump_host,
+                "port": jump_port,
+                "username": jump_user,
+                "auth_method": "key",
+                "private_key_path": key,
+            }],
+        }))
+        .unwrap();
+
+        let manager = ConnectionManager::new();
+        let tunnel = manager.open_tunnel(&config).await.unwrap();
+        TcpStream::connect(("127.0.0.1", tunnel.local_port)).unwrap();
+        manager.close_tunnel(&tunnel.id).await.unwrap();
+    }
+
+    /// A listening tunnel whose session is never used: nothing connects through it
+    fn idle_tunnel() -> SshTunnel {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (local_port, task_handle) = spawn_forwarder(
+            Session::new().unwrap(),
+            "127.0.0.1".into(),
+            9,
+            shutdown.clone(),
+        )
+        .unwrap();
+        SshTunnel {
+            local_port,
+            remote_host: "127.0.0.1".into(),
+            remote_port: 9,
+            task_handle,
+            hop_handles: Vec::new(),
+            shutdown,
+        }
+    }
+
+    /// Waits up to two seconds for `local_port` to stop accepting connections
+    async fn assert_stops_listening(local_port: u16) {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while TcpStream::connect(("127.0.0.1", local_port)).is_ok() {
+            assert!(
+                Instant::now() < deadline,
+                "listener still accepting after close"
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn closing_a_tunnel_stops_its_listener() {
+        let tunnel = idle_tunnel();
+        let local_port = tunnel.local_port;
+
+        tunnel.close();
+        assert_stops_listening(local_port).await;
+    }
+
+    #[tokio::test]
+    async fn standalone_tunnels_are_listed_until_closed() {
+        let manager = ConnectionManager::new();
+        let info = manager
+            .track_standalone_tunnel(Arc::new(idle_tunnel()))
+            .await;
+        assert!(TcpStream::connect(("127.0.0.1", info.local_port)).is_ok());
+
+        let listed = manager.list_tunnels().await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, info.id);
+        assert_eq!(listed[0].local_port, info.local_port);
+        assert!(listed[0].standalone);
+
+        manager.close_tunnel(&info.id).await.unwrap();
+        assert!(manager.list_tunnels().await.is_empty());
+        assert_stops_listening(info.local_port).await;
+        assert!(manager.close_tunnel(&info.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn connection_tunnels_cannot_be_closed_directly() {
+        let manager = ConnectionManager::new();
+        let owner = Uuid::new_v4();
+        manager
+            .tunnels
+            .lock()
+            .await
+            .insert(owner, Arc::new(idle_tunnel()));
+
+        let err = manager.close_tunnel(&owner).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tunnel not found, or it belongs to an open connection"
+        );
+        assert!(!manager.list_tunnels().await[0].standalone);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_round_trips_emoji_over_utf8mb4() {
+        let (manager, id) =
+            test_support::mysql_with(serde_json::json!({ "charset": "utf8mb4" })).await;
+        test_support::run(
+            &manager,
+            &id,
+            "DROP TABLE IF EXISTS sqlmate_emoji;
+             CREATE TABLE sqlmate_emoji (body VARCHAR(32)) CHARACTER SET utf8mb4;
+             INSERT INTO sqlmate_emoji VALUES ('ship it 🚀 ✓');",
+        )
+        .await;
+
+        let result = test_support::query(&manager, &id, "SELECT body FROM sqlmate_emoji").await;
+        test_support::run(&manager, &id, "DROP TABLE sqlmate_emoji").await;
+        assert_eq!(result.rows, vec![vec![serde_json::json!("ship it 🚀 ✓")]]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_sessions_use_the_configured_statement_timeout() {
+        let (manager, id) = test_support::postgres_with(serde_json::json!({
+            "statement_timeout_ms": 1500,
+            "application_name": "sqlMate tests",
+        }))
+        .await;
+
+        let timeout = test_support::query(&manager, &id, "SHOW statement_timeout").await;
+        assert_eq!(timeout.rows, vec![vec![serde_json::json!("1500ms")]]);
+        let name = test_support::query(&manager, &id, "SHOW application_name").await;
+        assert_eq!(name.rows, vec![vec![serde_json::json!("sqlMate tests")]]);
+    }
+
+    #[test]
+    fn mariadb_is_detected_from_the_version_string() {
+        assert!(is_mariadb_version(
+            "10.11.6-MariaDB-1:10.11.6+maria~ubu2204"
+        ));
+        assert!(is_mariadb_version("5.5.5-10.6.16-MariaDB"));
+        assert!(!is_mariadb_version("8.0.36"));
+        assert!(!is_mariadb_version("8.0.36-0ubuntu0.22.04.1"));
+    }
+
+    /// Run with `cargo test --features mariadb-integration-tests` and the server in
+    /// `SQLMATE_TEST_MARIADB_{HOST,PORT,USER,PASSWORD,DATABASE}`
+    #[cfg(feature = "mariadb-integration-tests")]
+    #[tokio::test]
+    async fn mariadb_metadata_and_structure_load() {
+        use crate::core::query_engine::QueryEngine;
+        let (manager, id) = test_support::mariadb().await;
+        assert!(manager.is_mariadb(&id).await);
+        test_support::run(
+            &manager,
+            &id,
+            "DROP TABLE IF EXISTS sqlmate_maria;
+             CREATE TABLE sqlmate_maria (
+               id INT PRIMARY KEY AUTO_INCREMENT,
+               name VARCHAR(20) DEFAULT 'x' COMMENT 'label',
+               seen TIMESTAMP DEFAULT current_timestamp() ON UPDATE current_timestamp()
+             ) COMMENT 'maria table';",
+        )
+        .await;
+
+        let metadata = QueryEngine::get_table_metadata(&manager, &id, "sqlmate_maria")
+            .await
+            .unwrap();
+        let structure = QueryEngine::get_table_structure(&manager, &id, "sqlmate_maria")
+            .await
+            .unwrap();
+        test_support::run(&manager, &id, "DROP TABLE sqlmate_maria").await;
+
+        assert_eq!(metadata.comment.as_deref(), Some("maria table"));
+        assert!(metadata.total_size.is_some());
+        let columns: Vec<&str> = structure.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(columns, ["id", "name", "seen"]);
+    }
+
+    #[tokio::test]
+    async fn production_deletes_need_confirmation() {
+        let (manager, id) =
+            test_support::sqlite_with(serde_json::json!({ "environment": "production" })).await;
+
+        let err = manager
+            .ensure_production_confirmed(&id, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Connection 'test' is marked as production; destructive operations require confirm_production"
+        );
+        manager
+            .ensure_production_confirmed(&id, true)
+            .await
+            .unwrap();
+
+        let (staging, staging_id) =
+            test_support::sqlite_with(serde_json::json!({ "environment": "staging" })).await;
+        staging
+            .ensure_production_confirmed(&staging_id, false)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn tunnels_default_to_the_backend_port() {
+        let tunneled = |db_type: &str, port: Option<u16>| {
+            test_support::config(
+                db_type,
+                serde_json::json!({
+                    "host": "db.internal",
+                    "port": port,
+                    "ssh_enabled": true,
+                    "ssh_host": "bastion",
+                    "ssh_username": "tester",
+                }),
+            )
+        };
+        let key = |config: &ConnectionConfig| tunnel_key(config).unwrap();
+
+        assert!(key(&tunneled("MySql", None)).ends_with(" > db.internal:3306"));
+        assert!(key(&tunneled("Postgres", None)).ends_with(" > db.internal:5432"));
+        assert!(key(&tunneled("MySql", Some(3307))).ends_with(" > db.internal:3307"));
+        assert_eq!(tunneled("MySql", None).port_or_default(), 3306);
+    }
+
+    #[tokio::test]
+    async fn tunnel_setup_only_holds_up_its_own_target() {
+        // Nothing listens on the SSH port, so establishing a new tunnel fails
+        let config = |host: &str| {
+            test_support::config(
+                "Postgres",
+                serde_json::json!({
+                    "host": host,
+                    "ssh_enabled": true,
+                    "ssh_host": "127.0.0.1",
+                    "ssh_port": 1,
+                    "ssh_username": "tester",
+                }),
+            )
+        };
+        let (busy, other) = (config("busy.internal"), config("other.internal"));
+        let manager = ConnectionManager::new();
+        let slot = TunnelSlot::default();
+        manager
+            .shared_tunnels
+            .lock()
+            .await
+            .insert(tunnel_key(&busy).unwrap(), slot.clone());
+
+        // While a connect to `busy` is establishing its tunnel, others to it wait on it...
+        let mut in_flight = slot.lock().await;
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(200),
+            manager.acquire_ssh_tunnel(&busy),
+        )
+        .await;
+        assert!(waiting.is_err(), "did not wait for the tunnel in flight");
+        // ...but connects to other targets go ahead
+        let elsewhere =
+            tokio::time::timeout(Duration::from_secs(5), manager.acquire_ssh_tunnel(&other))
+                .await
+                .expect("blocked behind another target's tunnel");
+        assert!(elsewhere.is_err());
+
+        // Once established, the waiting connects share the tunnel
+        let live = Arc::new(idle_tunnel());
+        *in_flight = Arc::downgrade(&live);
+        drop(in_flight);
+        let shared = manager.acquire_ssh_tunnel(&busy).await.unwrap();
+        assert!(Arc::ptr_eq(&shared, &live));
+    }
+
+    /// Reaches the `SQLMATE_TEST_POSTGRES_*` server through `SQLMATE_TEST_SSH_TARGET`
+    /// (`user@host:port`) with `SQLMATE_TEST_SSH_KEY`; the Postgres host is as the SSH host sees it
+    #[tokio::test]
+    #[ignore = "needs an SSH server in front of a Postgres server"]
+    async fn switching_databases_reuses_the_live_tunnel() {
+        let var = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} not set", name));
+        let target = var("SQLMATE_TEST_SSH_TARGET");
+        let (user, address) = target.split_once('@').expect("user@host:port");
+        let (host, port) = address.rsplit_once(':').expect("host:port");
+        let (mut config, password) = test_support::postgres_config();
+        config.ssh_enabled = true;
+        config.ssh_host = Some(host.to_string());
+        config.ssh_port = Some(port.parse().expect("port"));
+        config.ssh_username = Some(user.to_string());
+        config.ssh_auth_method = Some("key".into());
+        config.ssh_private_key_path = Some(var("SQLMATE_TEST_SSH_KEY"));
+        let (id, home) = (config.id, config.database.clone().unwrap());
+
+        let manager = ConnectionManager::new();
+        manager.connect(config, Some(password)).await.unwrap();
+        let first = manager.get_tunnels().await[&id].clone();
+        manager.switch_database(&id, "postgres").await.unwrap();
+        manager.switch_database(&id, &home).await.unwrap();
+        test_support::query(&manager, &id, "SELECT 1").await;
+
+        let current = manager.get_tunnels().await[&id].clone();
+        assert!(Arc::ptr_eq(&first, &current));
+        assert_eq!(manager.shared_tunnels.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn switching_databases_closes_the_previous_pool() {
+        let app_name = format!("sqlMate switch {}", Uuid::new_v4().simple());
+        let (manager, id) =
+            test_support::postgres_with(serde_json::json!({ "application_name": app_name })).await;
+        let home = test_support::query(&manager, &id, "SELECT current_database()").await;
+        let home = home.rows[0][0].as_str().unwrap().to_string();
+
+        for i in 0..10 {
+            let database = if i % 2 == 0 {
+                "postgres"
+            } else {
+                home.as_str()
+            };
+            manager.switch_database(&id, database).await.unwrap();
+            test_support::query(&manager, &id, "SELECT 1").await;
+        }
+
+        // Old pools close in the background; only the live pool's connections may remain
+        let sql = format!(
+            "SELECT COUNT(*) FROM pg_stat_activity WHERE application_name = '{}'",
+            app_name
+        );
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let count = test_support::query(&manager, &id, &sql).await.rows[0][0]
+                .as_i64()
+                .unwrap();
+            if count <= 5 {
+                break;
+            }
+            assert!(Instant::now() < deadline, "{} sessions still open", count);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    fn stage_statuses(result: &ConnectionTestResult) -> Vec<(&str, ConnectionStageStatus)> {
+        result
+            .stages
+            .iter()
+            .map(|stage| (stage.name.as_str(), stage.status.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn handshake_errors_fail_the_tls_or_auth_stage() {
+        let mut log = StageLog::default();
+        log.record("tcp", Instant::now(), Ok(()));
+        let auth = Err(sqlx::Error::Protocol(
+            "password authentication failed".into(),
+        ));
+        assert!(log.handshake::<()>(Instant::now(), Ok(auth)).is_none());
+        let result = log.finish();
+        assert!(!result.success);
+        assert_eq!(
+            stage_statuses(&result),
+            [
+                ("tcp", ConnectionStageStatus::Ok),
+                ("auth", ConnectionStageStatus::Failed)
+            ]
+        );
+        assert_eq!(
+            result.error.as_deref(),
+            Some("auth failed: encountered unexpected or invalid data: password authentication failed")
+        );
+
+        let mut log = StageLog::default();
+        let tls = Err(sqlx::Error::Tls("certificate has expired".into()));
+        assert!(log.handshake::<()>(Instant::now(), Ok(tls)).is_none());
+        assert_eq!(
+            stage_statuses(&log.finish()),
+            [("tls", ConnectionStageStatus::Failed)]
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_hosts_fail_at_tcp() {
+        // A port that was free a moment ago refuses connections
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = test_support::config(
+            "Postgres",
+            serde_json::json!({
+                "host": "127.0.0.1",
+                "port": port,
+                "username": "postgres",
+                "database": "postgres",
+            }),
+        );
+
+        let result = ConnectionManager::new()
+            .test_connection(config, None)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            stage_statuses(&result),
+            [
+                ("dns", ConnectionStageStatus::Ok),
+                ("tcp", ConnectionStageStatus::Failed)
+            ]
+        );
+        assert!(result.error.unwrap().starts_with("tcp failed: 127.0.0.1:"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn wrong_passwords_fail_at_auth() {
+        let (config, password) = test_support::postgres_config();
+        let manager = ConnectionManager::new();
+
+        let result = manager
+            .test_connection(config.clone(), Some(password.clone()))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.stages.last().unwrap().name, "ping");
+
+        let result = manager
+            .test_connection(config, Some(format!("{}-wrong", password)))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            stage_statuses(&result),
+            [
+                ("dns", ConnectionStageStatus::Ok),
+                ("tcp", ConnectionStageStatus::Ok),
+                ("auth", ConnectionStageStatus::Failed)
+            ]
+        );
+    }
+}
@@ -1,7 +1,13 @@
 pub mod ai_service;
 pub mod connection_manager;
 pub mod query_engine;
+#[cfg(test)]
+pub(crate) mod test_support;
 
+use crate::db::connection_configs::ConnectionConfigStore;
+use crate::db::history::QueryHistoryStore;
+use crate::db::saved_queries::SavedQueryStore;
+use crate::db::sidebar_preferences::SidebarPreferenceStore;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Instant;
@@ -39,8 +45,107 @@ pub struct ConnectionConfig {
     pub ssh_auth_method: Option<String>, // "password" | "key"
     pub ssh_password: Option<String>,
     pub ssh_private_key_path: Option<String>,
+    #[serde(default)]
+    pub ssh_strict_host_key: bool, // reject hosts missing from known_hosts, not just mismatches
+    #[serde(default)]
+    pub ssh_jump_hosts: Vec<SshHop>, // bastions traversed in order before ssh_host
     pub environment: Option<String>, // "local", "test", "dev", "staging", "production"
     pub color_tag: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    pub charset: Option<String>,   // MySQL only, defaults to utf8mb4
+    pub collation: Option<String>, // MySQL only
+    pub application_name: Option<String>, // Postgres only, defaults to "sqlMate"
+    pub statement_timeout_ms: Option<u64>, // Postgres only, server-side statement_timeout
+    pub default_browse_limit: Option<u32>, // rows fetched when a table is browsed with limit=0
+    #[serde(default)]
+    pub tag_queries: bool, // prefix executed SQL with a /* sqlMate ... */ comment for server logs
+    pub max_rows: Option<u32>,     // rows execute_query keeps in memory, whatever LIMIT the SQL has
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SshHop {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: String,
+    pub auth_method: Option<String>, // "password" | "key"
+    pub password: Option<String>,
+    pub private_key_path: Option<String>,
+}
+
+impl SshHop {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(22)
+    }
+}
+
+impl ConnectionConfig {
+    /// The configured port, or the server's standard one (0 for SQLite, which has none)
+    pub fn port_or_default(&self) -> u16 {
+        self.port.unwrap_or(match self.db_type {
+            DatabaseType::Postgres => 5432,
+            DatabaseType::MySql => 3306,
+            DatabaseType::Sqlite => 0,
+        })
+    }
+
+    /// Ordered SSH hops for the tunnel: every jump host, then the final `ssh_host`
+    pub fn ssh_hops(&self) -> anyhow::Result<Vec<SshHop>> {
+        let host = self
+            .ssh_host
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("SSH host missing"))?;
+        let username = self
+            .ssh_username
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("SSH username missing"))?;
+
+        for (i, hop) in self.ssh_jump_hosts.iter().enumerate() {
+            if hop.host.trim().is_empty() || hop.username.trim().is_empty() {
+                return Err(anyhow::anyhow!(
+                    "SSH jump host #{} needs a host and username",
+                    i + 1
+                ));
+            }
+        }
+
+        let mut hops = self.ssh_jump_hosts.clone();
+        hops.push(SshHop {
+            host,
+            port: self.ssh_port,
+            username,
+            auth_method: self.ssh_auth_method.clone(),
+            password: self.ssh_password.clone(),
+            private_key_path: self.ssh_private_key_path.clone(),
+        });
+        Ok(hops)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionStageStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+/// One step of a connection test. TLS is negotiated inside the sqlx handshake, so only
+/// a failed TLS stage carries its own timing; a successful handshake is timed under auth.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionTestStage {
+    pub name: String, // "ssh", "dns", "tcp", "tls", "auth", "ping", or "open" for SQLite
+    pub status: ConnectionStageStatus,
+    pub duration_ms: Option<u64>,
+    pub message: Option<String>,
+}
+
+/// Stages run by `test_connection`, up to and including the first one that failed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub stages: Vec<ConnectionTestStage>,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -50,17 +155,83 @@ pub struct FilterConfig {
     pub operator: String,
     pub value: String,
     pub enabled: bool,
+    /// Match LIKE-style operators regardless of case on every backend
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Key path for the JSON operators, e.g. `address.city` or `$.tags[0]`
+    #[serde(default)]
+    pub json_path: Option<String>,
+}
+
+/// Lazy paging and name search for catalog listings (databases, tables)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CatalogFilter {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// SQL LIKE pattern matched against the object name
+    pub name_filter: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
+    /// Driver type name per column (e.g. INT4, VARCHAR), parallel to `columns`
+    #[serde(default)]
+    pub column_types: Vec<String>,
     pub rows: Vec<Vec<serde_json::Value>>,
     pub affected_rows: u64,
     pub execution_time_ms: u64,
     pub total_count: Option<u64>,
     pub page: Option<u32>,
     pub page_size: Option<u32>,
+    /// Set when `auto_limit` cut the result short; more rows are available
+    #[serde(default)]
+    pub truncated: bool,
+    /// Server-reported planning/execution split, only filled when requested
+    #[serde(default)]
+    pub server_timing: Option<ServerTiming>,
+}
+
+/// Aggregate statistics for one column; `avg` is only computed for numeric columns
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColumnProfile {
+    pub column: String,
+    pub data_type: String,
+    pub total_count: u64,
+    pub null_count: u64,
+    pub distinct_count: u64,
+    /// Rendered as text so every column type fits
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub avg: Option<f64>,
+}
+
+/// Outcome of `validate_sql`; the error fields describe the first statement that failed
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SqlValidation {
+    pub valid: bool,
+    /// 1-based index of the failing statement within the script
+    pub statement: Option<usize>,
+    /// Stable error code, as used for `StreamingError`
+    pub code: Option<String>,
+    pub message: Option<String>,
+    /// 1-based character offset of the error within the failing statement (Postgres only)
+    pub position: Option<usize>,
+    /// Total number of bind placeholders across the prepared statements
+    pub parameter_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerTiming {
+    pub planning_time_ms: f64,
+    pub execution_time_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MutationResult {
+    pub affected_rows: u64,
+    /// Id generated by an INSERT, when the backend can report one
+    pub last_insert_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -69,9 +240,50 @@ pub struct TableMetadata {
     pub data_size: Option<String>,
     pub index_size: Option<String>,
     pub comment: Option<String>,
+    /// Pages used by the table and its indexes (SQLite only)
+    #[serde(default)]
+    pub page_count: Option<u64>,
+    #[serde(default)]
+    pub avg_row_size: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatabaseSize {
+    pub total_size: String,
+    pub total_bytes: u64,
+}
+
+/// An SSH tunnel held by the connection manager
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TunnelInfo {
+    pub id: Uuid, // connection id, or the id handed out by open_tunnel
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+    pub standalone: bool, // opened with open_tunnel rather than by a connection
+}
+
+/// What the server reports about itself, as opposed to what the config says
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerInfo {
+    pub db_type: String, // "postgres" | "mysql" | "sqlite"
+    pub version: String,
+    pub is_mariadb: bool,
+}
+
+/// A server session from pg_stat_activity or the MySQL process list
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DbSession {
+    pub pid: i64,
+    pub user: Option<String>,
+    pub database: Option<String>,
+    pub client_addr: Option<String>,
+    pub state: Option<String>, // Postgres state ("active", "idle", ...) or MySQL command
+    pub query: Option<String>,
+    pub duration_ms: Option<u64>, // time since the current query started
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TableColumnStructure {
     pub name: String,
     pub data_type: String,
@@ -81,7 +293,7 @@ pub struct TableColumnStructure {
     pub comment: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TableIndexStructure {
     pub name: String,
     pub columns: Vec<String>,
@@ -89,13 +301,38 @@ pub struct TableIndexStructure {
     pub index_type: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TableConstraintStructure {
     pub name: String,
     pub constraint_type: String,
     pub definition: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForeignKey {
+    pub name: String,
+    /// Referencing (child) table
+    pub table: String,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+/// Relationships of one table: keys it declares and keys other tables point at it with
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TableForeignKeys {
+    pub outgoing: Vec<ForeignKey>,
+    pub incoming: Vec<ForeignKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableTrigger {
+    pub name: String,
+    pub timing: String, // "BEFORE" | "AFTER" | "INSTEAD OF"
+    pub event: String,  // "INSERT" | "UPDATE" | "DELETE"
+    pub definition: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TableStructure {
     pub columns: Vec<TableColumnStructure>,
@@ -103,6 +340,26 @@ pub struct TableStructure {
     pub constraints: Vec<TableConstraintStructure>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+/// Differences that turn table structure A into structure B, matched by name
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StructureDiff {
+    pub added_columns: Vec<TableColumnStructure>,
+    pub removed_columns: Vec<TableColumnStructure>,
+    pub changed_columns: Vec<Change<TableColumnStructure>>,
+    pub added_indexes: Vec<TableIndexStructure>,
+    pub removed_indexes: Vec<TableIndexStructure>,
+    pub changed_indexes: Vec<Change<TableIndexStructure>>,
+    pub added_constraints: Vec<TableConstraintStructure>,
+    pub removed_constraints: Vec<TableConstraintStructure>,
+    pub changed_constraints: Vec<Change<TableConstraintStructure>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum SidebarItemType {
     Table,
@@ -116,6 +373,12 @@ pub struct SidebarItem {
     pub name: String,
     pub item_type: SidebarItemType,
     pub schema: Option<String>,
+    /// Pinned by the user; pinned items are listed first
+    #[serde(default)]
+    pub pinned: bool,
+    /// How often the table was opened from the sidebar
+    #[serde(default)]
+    pub usage_count: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -132,16 +395,55 @@ pub struct AiSchemaCacheEntry {
     pub cached_at: Instant,
 }
 
+/// Generated SQL together with what produced it, for debugging AI output
+#[derive(Debug, Serialize, Clone)]
+pub struct AiResult {
+    pub sql: String,
+    pub model: String,
+    /// Rough size of the system and user prompts, at ~4 characters per token
+    pub prompt_tokens_estimate: u64,
+    /// Tables whose columns were described in the schema context
+    pub schema_tables_used: Vec<String>,
+}
+
 pub struct AppState {
     pub connection_manager: Arc<connection_manager::ConnectionManager>,
     pub active_queries: Arc<Mutex<HashMap<Uuid, CancellationToken>>>,
     pub ai_schema_cache: Arc<Mutex<HashMap<Uuid, AiSchemaCacheEntry>>>,
+    pub query_history: Arc<QueryHistoryStore>,
+    pub saved_queries: Arc<SavedQueryStore>,
+    pub sidebar_preferences: Arc<SidebarPreferenceStore>,
+    pub connection_configs: Arc<ConnectionConfigStore>,
+    pub active_imports: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    pub active_exports: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+/// Postgres server-side cursor kept open between fetches.
+/// The surrounding transaction pins one pooled connection and a stable snapshot.
+pub struct ResultCursor {
+    pub(crate) tx: sqlx::Transaction<'static, sqlx::Postgres>,
+    pub(crate) name: String,
+    pub(crate) columns: Vec<String>,
 }
 
+/// A transaction held open across commands until explicitly committed or rolled back
+pub enum UserTransaction {
+    Postgres(sqlx::Transaction<'static, sqlx::Postgres>),
+    MySql(sqlx::Transaction<'static, sqlx::MySql>),
+    Sqlite(sqlx::Transaction<'static, sqlx::Sqlite>),
+}
+
+/// Returned by long-running imports/exports when their cancellation token fires
+#[derive(Debug, thiserror::Error)]
+#[error("Operation cancelled")]
+pub struct Cancelled;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StreamingMetadata {
     pub query_id: Uuid,
     pub columns: Vec<String>,
+    #[serde(default)]
+    pub column_types: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -150,6 +452,14 @@ pub struct StreamingBatch {
     pub rows: Vec<Vec<serde_json::Value>>,
 }
 
+/// One base64-encoded chunk of the `arrow` streaming format. The first chunk starts with
+/// the schema and the last ends the stream; concatenated in order they form one Arrow IPC stream.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StreamingArrowBatch {
+    pub query_id: Uuid,
+    pub data: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StreamingComplete {
     pub query_id: Uuid,
@@ -157,3 +467,97 @@ pub struct StreamingComplete {
     pub total_rows: u64,
     pub affected_rows: u64,
 }
+
+/// Periodic throughput report for a streaming query
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StreamingStats {
+    pub query_id: Uuid,
+    pub elapsed_ms: u64,
+    pub total_rows: u64,
+    pub rows_per_sec: f64, // over the interval since the previous report
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StreamingError {
+    pub query_id: Uuid,
+    /// Stable category such as `syntax_error` or `connection_error`, see `query_engine::error_code`
+    pub code: String,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ssh_config(jump_hosts: serde_json::Value) -> ConnectionConfig {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "tunnelled",
+            "db_type": "Postgres",
+            "ssl_enabled": false,
+            "ssh_enabled": true,
+            "ssh_host": "db-gateway.internal",
+            "ssh_port": 2200,
+            "ssh_username": "deploy",
+            "ssh_auth_method": "key",
+            "ssh_private_key_path": "~/.ssh/id_ed25519",
+            "ssh_jump_hosts": jump_hosts,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ssh_hops_list_jump_hosts_then_the_target() {
+        let config = ssh_config(json!([
+            { "host": "bastion.example.com", "username": "jump", "auth_method": "password", "password": "pw" },
+            { "host": "10.0.0.5", "port": 2222, "username": "jump2" }
+        ]));
+        let hops = config.ssh_hops().unwrap();
+
+        let route: Vec<(&str, u16, &str)> = hops
+            .iter()
+            .map(|h| (h.host.as_str(), h.port(), h.username.as_str()))
+            .collect();
+        assert_eq!(
+            route,
+            [
+                ("bastion.example.com", 22, "jump"),
+                ("10.0.0.5", 2222, "jump2"),
+                ("db-gateway.internal", 2200, "deploy"),
+            ]
+        );
+        assert_eq!(hops[0].password.as_deref(), Some("pw"));
+        assert_eq!(
+            hops[2].private_key_path.as_deref(),
+            Some("~/.ssh/id_ed25519")
+        );
+    }
+
+    #[test]
+    fn ssh_hops_without_jump_hosts_is_the_target_alone() {
+        let config: ConnectionConfig = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "direct",
+            "db_type": "MySql",
+            "ssl_enabled": false,
+            "ssh_enabled": true,
+            "ssh_host": "gateway",
+            "ssh_username": "me",
+        }))
+        .unwrap();
+        let hops = config.ssh_hops().unwrap();
+        assert_eq!(hops.len(), 1);
+        assert_eq!(hops[0].port(), 22);
+    }
+
+    #[test]
+    fn ssh_hops_reject_incomplete_jump_hosts() {
+        let config = ssh_config(json!([
+            { "host": "bastion", "username": "jump" },
+            { "host": " ", "username": "jump2" }
+        ]));
+        let err = config.ssh_hops().unwrap_err();
+        assert!(err.to_string().contains("#2"));
+    }
+}
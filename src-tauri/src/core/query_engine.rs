@@ -1,21 +1,48 @@
 use crate::core::{
-    connection_manager::ConnectionManager, AiSchemaTable, FilterConfig, QueryResult, SidebarItem,
-    SidebarItemType, StreamingBatch, StreamingComplete, StreamingMetadata, TableColumnStructure,
-    TableConstraintStructure, TableIndexStructure, TableMetadata, TableStructure,
+    connection_manager::ConnectionManager, AiSchemaTable, Cancelled, CatalogFilter, Change,
+    ColumnProfile, DatabaseSize, DbSession, FilterConfig, ForeignKey, MutationResult, QueryResult,
+    ResultCursor, ServerTiming, SidebarItem, SidebarItemType, SqlValidation, StreamingArrowBatch,
+    StreamingBatch, StreamingComplete, StreamingError, StreamingMetadata, StreamingStats,
+    StructureDiff, TableColumnStructure, TableConstraintStructure, TableForeignKeys,
+    TableIndexStructure, TableMetadata, TableStructure, TableTrigger, UserTransaction,
+};
+use crate::utils::arrow_ipc::{ArrowBatchBuilder, ArrowStreamEncoder};
+use crate::utils::csv_writer::csv_writer;
+use crate::utils::sql_splitter::{
+    after_top_level_keyword, code_words, leading_keyword, split_statements, trim_statement_end,
+};
+use crate::utils::{
+    format_bytes, quote_ident, unique_column_names, validate_identifier, validate_type_name,
+    CsvQuoting, JsonNullMode,
 };
 use anyhow::{anyhow, Result};
+use arrow::datatypes::DataType;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use futures::StreamExt;
 use serde_json::Value;
+use sqlx::postgres::types::PgInterval;
+use sqlx::postgres::{PgValueFormat, PgValueRef};
 use sqlx::{Column, Executor, Row, Statement, TypeInfo, ValueRef};
 use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
 use std::time::Instant;
-use tauri::Emitter;
+use tauri::{Emitter, Runtime};
 use tokio::time::{sleep, Duration};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// Rows between progress callbacks in `export_table_data`, matching the exporter's cadence
+const EXPORT_PROGRESS_INTERVAL: u64 = 1000;
+/// Time between `query-stats` events while a streaming query runs
+const QUERY_STATS_INTERVAL: Duration = Duration::from_millis(500);
+/// Rows per FETCH when a Postgres streaming query runs through a server-side cursor
+const DEFAULT_PG_FETCH_SIZE: u32 = 1000;
+/// Facet sizes for `get_distinct_values`: the default, and the most a caller may ask for
+const DEFAULT_DISTINCT_VALUES: u32 = 100;
+const MAX_DISTINCT_VALUES: u32 = 1000;
+
 fn type_name_is_text(name: &str) -> bool {
     name == "text"
         || name.contains("char")
@@ -26,26 +53,619 @@ fn type_name_is_text(name: &str) -> bool {
         || name == "enum"
 }
 
+/// MariaDB 10.2.7+ reports COLUMN_DEFAULT as an SQL expression: literals arrive quoted
+/// and a missing default is the string `NULL`. Convert to MySQL's unquoted form.
+fn normalize_mariadb_default(default: &str) -> Option<String> {
+    if default.eq_ignore_ascii_case("NULL") {
+        return None;
+    }
+    match default
+        .strip_prefix('\'')
+        .and_then(|d| d.strip_suffix('\''))
+    {
+        Some(literal) => Some(literal.replace("''", "'")),
+        None => Some(default.to_string()),
+    }
+}
+
+/// Labels of a MySQL `enum(...)`/`set(...)` COLUMN_TYPE; quotes inside labels are doubled
+fn parse_mysql_enum_labels(column_type: &str) -> Option<Vec<String>> {
+    let lower = column_type.to_ascii_lowercase();
+    let body = if lower.starts_with("enum(") {
+        &column_type[5..]
+    } else if lower.starts_with("set(") {
+        &column_type[4..]
+    } else {
+        return None;
+    };
+    let mut chars = body.strip_suffix(')')?.chars().peekable();
+    let mut labels = Vec::new();
+    loop {
+        if chars.next()? != '\'' {
+            return None;
+        }
+        let mut label = String::new();
+        loop {
+            match chars.next()? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    label.push('\'');
+                }
+                '\'' => break,
+                c => label.push(c),
+            }
+        }
+        labels.push(label);
+        match chars.next() {
+            Some(',') => {}
+            None => return Some(labels),
+            Some(_) => return None,
+        }
+    }
+}
+
+/// Extracts timing and event from `CREATE TRIGGER name [BEFORE|AFTER|INSTEAD OF] event ON ...`.
+/// SQLite defaults to BEFORE when no timing is written.
+fn parse_sqlite_trigger_header(sql: &str) -> (String, String) {
+    let upper = sql.to_uppercase();
+    let words: Vec<&str> = upper.split_whitespace().collect();
+    let on_pos = words.iter().position(|w| *w == "ON").unwrap_or(words.len());
+    let header = &words[..on_pos];
+
+    let timing = if header.contains(&"INSTEAD") {
+        "INSTEAD OF"
+    } else if header.contains(&"AFTER") {
+        "AFTER"
+    } else {
+        "BEFORE"
+    };
+    let event = header
+        .iter()
+        .find(|w| matches!(**w, "INSERT" | "UPDATE" | "DELETE"))
+        .copied()
+        .unwrap_or("");
+    (timing.to_string(), event.to_string())
+}
+
+/// Appends one column pair of a foreign key, extending the previous key when it continues it.
+/// Rows must arrive ordered by table, key and column position.
+fn push_fk_column(
+    keys: &mut Vec<ForeignKey>,
+    name: String,
+    table: String,
+    column: String,
+    referenced_table: String,
+    referenced_column: String,
+) {
+    if let Some(last) = keys.last_mut() {
+        if last.name == name && last.table == table {
+            last.columns.push(column);
+            last.referenced_columns.push(referenced_column);
+            return;
+        }
+    }
+    keys.push(ForeignKey {
+        name,
+        table,
+        columns: vec![column],
+        referenced_table,
+        referenced_columns: vec![referenced_column],
+    });
+}
+
+/// Splits keys into those declared on `table_name` and those referencing it.
+/// A self-referencing key shows up in both lists.
+fn split_foreign_keys(table_name: &str, keys: Vec<ForeignKey>) -> TableForeignKeys {
+    let mut result = TableForeignKeys::default();
+    for key in keys {
+        if key.referenced_table == table_name {
+            result.incoming.push(key.clone());
+        }
+        if key.table == table_name {
+            result.outgoing.push(key);
+        }
+    }
+    result
+}
+
+/// Splits two named lists into (added, removed, changed) entries going from `a` to `b`
+fn diff_by_name<T: Clone + PartialEq>(
+    a: &[T],
+    b: &[T],
+    name: impl Fn(&T) -> &str,
+) -> (Vec<T>, Vec<T>, Vec<Change<T>>) {
+    let added = b
+        .iter()
+        .filter(|item| !a.iter().any(|other| name(other) == name(item)))
+        .cloned()
+        .collect();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for before in a {
+        match b.iter().find(|after| name(after) == name(before)) {
+            None => removed.push(before.clone()),
+            Some(after) if after != before => changed.push(Change {
+                before: before.clone(),
+                after: after.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    (added, removed, changed)
+}
+
+#[derive(Default)]
+struct MigrationScript {
+    lines: Vec<String>,
+}
+
+impl MigrationScript {
+    /// Adds a statement, commented out when it isn't safe to run as-is
+    fn push(&mut self, sql: String, enabled: bool) {
+        if enabled {
+            self.lines.push(format!("{};", sql));
+        } else {
+            self.lines.push(format!("-- {};", sql));
+        }
+    }
+
+    fn note(&mut self, text: String) {
+        self.lines.push(format!("-- {}", text));
+    }
+}
+
+fn column_definition(col: &TableColumnStructure, db_type: &str) -> String {
+    let mut def = format!("{} {}", quote_ident(&col.name, db_type), col.data_type);
+    if !col.is_nullable {
+        def.push_str(" NOT NULL");
+    }
+    if let Some(default) = &col.default_value {
+        def.push_str(&format!(" DEFAULT {}", default_literal(default, db_type)));
+    }
+    def
+}
+
+/// Splits a column type such as `numeric(10,2)` or `int(11) unsigned` into its base name,
+/// numeric arguments and signedness. Types with non-numeric arguments (ENUM) yield `None`.
+fn parse_column_type(data_type: &str) -> Option<(String, Vec<u32>, bool)> {
+    let lower = data_type.trim().to_lowercase();
+    let (base, rest) = match lower.split_once('(') {
+        Some((base, rest)) => (base.trim(), rest),
+        None => (lower.as_str(), ""),
+    };
+    let (args, suffix) = rest.split_once(')').unwrap_or(("", rest));
+    let args = args
+        .split(',')
+        .filter(|a| !a.trim().is_empty())
+        .map(|a| a.trim().parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    let unsigned = base.contains("unsigned") || suffix.contains("unsigned");
+    let base = base.replace("unsigned", "").replace("zerofill", "");
+    Some((base.trim().to_string(), args, unsigned))
+}
+
+/// Whether a column type change keeps every existing value: a larger integer type, or the
+/// same type with at least the old length and precision. Anything else can truncate, round
+/// or fail on existing rows, so migrations treat it as destructive.
+fn is_widening_type_change(before: &str, after: &str) -> bool {
+    const INTEGER_RANKS: [(&str, u8); 10] = [
+        ("tinyint", 1),
+        ("smallint", 2),
+        ("int2", 2),
+        ("mediumint", 3),
+        ("int", 4),
+        ("integer", 4),
+        ("int4", 4),
+        ("bigint", 5),
+        ("int8", 5),
+        ("serial", 4),
+    ];
+    let (Some(before), Some(after)) = (parse_column_type(before), parse_column_type(after)) else {
+        return false;
+    };
+    if before.2 != after.2 {
+        return false;
+    }
+    let rank = |base: &str| INTEGER_RANKS.iter().find(|(t, _)| *t == base).map(|r| r.1);
+    if let (Some(old), Some(new)) = (rank(&before.0), rank(&after.0)) {
+        return new >= old;
+    }
+    if before.0 != after.0 {
+        return false;
+    }
+    match (before.1.as_slice(), after.1.as_slice()) {
+        (_, []) => true, // unbounded, e.g. character varying without a length
+        ([old], [new]) => new >= old,
+        // precision and scale: keep both the fractional and the integer digits
+        ([old_p, old_s], [new_p, new_s]) => {
+            new_s >= old_s && new_p.saturating_sub(*new_s) >= old_p.saturating_sub(*old_s)
+        }
+        _ => false,
+    }
+}
+
+/// Postgres and SQLite report defaults as SQL expressions; MySQL reports bare values
+fn default_literal(default: &str, db_type: &str) -> String {
+    let is_expression = default.parse::<f64>().is_ok()
+        || default.eq_ignore_ascii_case("NULL")
+        || default.to_uppercase().starts_with("CURRENT_TIMESTAMP");
+    if db_type == "mysql" && !is_expression {
+        format!("'{}'", default.replace('\'', "''"))
+    } else {
+        default.to_string()
+    }
+}
+
+/// Renders a JSON value as a SQL literal; MySQL also treats backslashes as escapes
+fn sql_literal(value: &Value, db_type: &str) -> String {
+    let quote = |s: &str| {
+        let s = s.replace('\'', "''");
+        if db_type == "mysql" {
+            format!("'{}'", s.replace('\\', "\\\\"))
+        } else {
+            format!("'{}'", s)
+        }
+    };
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote(s),
+        other => quote(&other.to_string()),
+    }
+}
+
+/// A MySQL column as information_schema.COLUMNS describes it, enough to redeclare it
+struct MySqlColumnDefinition {
+    column_type: String,
+    is_nullable: bool,
+    default: Option<String>,
+    extra: String,
+    comment: String,
+    generation_expression: Option<String>,
+}
+
+/// Defaults that are functions of the clock and are written without parentheses
+fn is_timestamp_default(default: &str) -> bool {
+    let lower = default.to_ascii_lowercase();
+    ["current_timestamp", "localtimestamp", "localtime", "now("]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+}
+
+/// Renders the column definition for `CHANGE COLUMN`, keeping the current default,
+/// AUTO_INCREMENT, ON UPDATE, generation expression, visibility and comment.
+///
+/// MySQL reports literal defaults unquoted and flags expression defaults with
+/// DEFAULT_GENERATED; MariaDB reports every default as an SQL expression already.
+fn mysql_column_definition(
+    column: &MySqlColumnDefinition,
+    new_type: Option<&str>,
+    nullable: Option<bool>,
+    is_mariadb: bool,
+) -> String {
+    let extra = column.extra.to_ascii_lowercase();
+    let mut definition = new_type.unwrap_or(&column.column_type).to_string();
+    let generation_expression = column
+        .generation_expression
+        .as_deref()
+        .filter(|e| !e.is_empty());
+    if let Some(expression) = generation_expression {
+        let storage = if extra.contains("stored generated") {
+            "STORED"
+        } else {
+            "VIRTUAL"
+        };
+        definition.push_str(&format!(
+            " GENERATED ALWAYS AS ({}) {}",
+            expression, storage
+        ));
+    }
+    definition.push_str(if nullable.unwrap_or(column.is_nullable) {
+        " NULL"
+    } else {
+        " NOT NULL"
+    });
+
+    let default = column
+        .default
+        .as_deref()
+        .filter(|_| generation_expression.is_none());
+    let default = match default {
+        Some(d) if is_mariadb && d.eq_ignore_ascii_case("NULL") => None,
+        Some(d) if is_timestamp_default(d) => Some(d.to_string()),
+        Some(d) if is_mariadb => {
+            let is_literal = d.starts_with('\'')
+                || d.parse::<f64>().is_ok()
+                || d.to_ascii_lowercase().starts_with("b'");
+            Some(if is_literal {
+                d.to_string()
+            } else {
+                format!("({})", d)
+            })
+        }
+        Some(d) if extra.contains("default_generated") => Some(format!("({})", d)),
+        // BIT defaults arrive as b'...' literals
+        Some(d) if d.starts_with("b'") => Some(d.to_string()),
+        Some(d) => Some(sql_literal(&Value::String(d.to_string()), "mysql")),
+        None => None,
+    };
+    if let Some(default) = default {
+        definition.push_str(&format!(" DEFAULT {}", default));
+    }
+
+    if extra.contains("auto_increment") {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+    if let Some(position) = extra.find("on update ") {
+        if let Some(expression) = column.extra[position + "on update ".len()..]
+            .split_whitespace()
+            .next()
+        {
+            definition.push_str(&format!(" ON UPDATE {}", expression));
+        }
+    }
+    if extra.split_whitespace().any(|word| word == "invisible") {
+        definition.push_str(" INVISIBLE");
+    }
+    if !column.comment.is_empty() {
+        definition.push_str(&format!(
+            " COMMENT {}",
+            sql_literal(&Value::String(column.comment.clone()), "mysql")
+        ));
+    }
+    definition
+}
+
+/// ALTER TABLE ... DROP COLUMN arrived in SQLite 3.35
+fn sqlite_supports_drop_column(version: &str) -> bool {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    (major, minor) >= (3, 35)
+}
+
+fn is_numeric_type(data_type: &str) -> bool {
+    let lower = data_type.to_lowercase();
+    let base = lower.split(['(', ' ']).next().unwrap_or_default();
+    matches!(
+        base,
+        "smallint"
+            | "integer"
+            | "int"
+            | "bigint"
+            | "tinyint"
+            | "mediumint"
+            | "int2"
+            | "int4"
+            | "int8"
+            | "smallserial"
+            | "serial"
+            | "bigserial"
+            | "numeric"
+            | "decimal"
+            | "real"
+            | "double"
+            | "float"
+            | "float4"
+            | "float8"
+    )
+}
+
+/// A single SELECT that only reads: no SELECT INTO and no locking clause
+fn is_plain_select(sql: &str) -> bool {
+    leading_keyword(sql) == "SELECT"
+        && split_statements(sql, false).len() == 1
+        && !code_words(sql)
+            .iter()
+            .any(|word| matches!(word.as_str(), "INTO" | "FOR" | "LOCK"))
+}
+
+/// True for a plain SELECT that doesn't already bound its row count and can take a
+/// trailing LIMIT. OFFSET is left alone, since the LIMIT would have to go before it.
+fn needs_auto_limit(sql: &str) -> bool {
+    is_plain_select(sql)
+        && !code_words(sql)
+            .iter()
+            .any(|word| matches!(word.as_str(), "LIMIT" | "FETCH" | "TOP" | "OFFSET"))
+}
+
+/// Whether a statement removes data or schema objects (DROP, TRUNCATE, DELETE).
+/// CTEs are scanned for a data-modifying DELETE since it need not lead the statement.
+pub fn is_destructive_statement(sql: &str) -> bool {
+    match leading_keyword(sql).as_str() {
+        "DROP" | "TRUNCATE" | "DELETE" => true,
+        "WITH" => code_words(sql).iter().any(|word| word == "DELETE"),
+        _ => false,
+    }
+}
+
+/// Whether any statement of a script would pass is_destructive_statement
+pub fn contains_destructive_statement(sql: &str) -> bool {
+    split_statements(sql, false)
+        .iter()
+        .any(|statement| is_destructive_statement(statement))
+}
+
+fn is_insert_statement(sql: &str) -> bool {
+    matches!(leading_keyword(sql).as_str(), "INSERT" | "REPLACE")
+}
+
+/// Maps an error to a stable, machine-readable code the frontend can branch on
+pub fn error_code(err: &anyhow::Error) -> &'static str {
+    if is_connection_error(err) {
+        return "connection_error";
+    }
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db)) => {
+            let mysql_number = db
+                .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                .map(|e| e.number());
+            match db.kind() {
+                sqlx::error::ErrorKind::UniqueViolation => "unique_violation",
+                sqlx::error::ErrorKind::ForeignKeyViolation => "foreign_key_violation",
+                sqlx::error::ErrorKind::NotNullViolation => "not_null_violation",
+                sqlx::error::ErrorKind::CheckViolation => "check_violation",
+                _ => match db.code().as_deref() {
+                    // 42000 is shared by many MySQL errors; 1064 is the parse error itself
+                    Some("42601") => "syntax_error",
+                    Some("42000") if mysql_number == Some(1064) => "syntax_error",
+                    Some("42P01") | Some("42S02") => "undefined_table",
+                    Some("42703") | Some("42S22") => "undefined_column",
+                    Some("42501") => "permission_denied",
+                    // SQLite only reports the generic SQLITE_ERROR code
+                    _ if db.message().contains("syntax error") => "syntax_error",
+                    _ if db.message().starts_with("no such table") => "undefined_table",
+                    _ if db.message().starts_with("no such column") => "undefined_column",
+                    _ => "database_error",
+                },
+            }
+        }
+        Some(sqlx::Error::ColumnDecode { .. }) | Some(sqlx::Error::Decode(_)) => "decode_error",
+        Some(_) => "query_error",
+        None => "error",
+    }
+}
+
+/// The `query-error` payload for a streaming query that failed with `err`
+pub fn streaming_error(query_id: Uuid, err: &anyhow::Error) -> StreamingError {
+    StreamingError {
+        query_id,
+        code: error_code(err).to_string(),
+        message: err.to_string(),
+    }
+}
+
+/// Character offset Postgres reports for parse and resolution errors
+fn error_position(err: &anyhow::Error) -> Option<usize> {
+    let db = err.downcast_ref::<sqlx::Error>()?.as_database_error()?;
+    match db
+        .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()?
+        .position()?
+    {
+        sqlx::postgres::PgErrorPosition::Original(position) => Some(position),
+        _ => None,
+    }
+}
+
+/// Whether a statement produces a result set rather than only an affected-row count.
+fn returns_rows(sql: &str) -> bool {
+    matches!(
+        leading_keyword(sql).as_str(),
+        "SELECT"
+            | "WITH"
+            | "SHOW"
+            | "PRAGMA"
+            | "EXPLAIN"
+            | "DESCRIBE"
+            | "DESC"
+            | "VALUES"
+            | "TABLE"
+    ) || code_words(sql).iter().any(|word| word == "RETURNING")
+}
+
+/// The statements of a multi-statement SQLite script that `execute_query` can run in its
+/// own transaction. Scripts with triggers (whose BEGIN ... END bodies the splitter can't
+/// see) or their own transaction control are left to SQLite as a whole.
+fn sqlite_script_statements(sql: &str) -> Option<Vec<String>> {
+    let statements = split_statements(sql, false);
+    let self_managed = statements.iter().any(|s| {
+        matches!(
+            leading_keyword(s).as_str(),
+            "BEGIN" | "COMMIT" | "END" | "ROLLBACK" | "SAVEPOINT" | "RELEASE"
+        ) || is_create_trigger(s)
+    });
+    (statements.len() > 1 && !self_managed).then_some(statements)
+}
+
+/// `CREATE [TEMP|TEMPORARY] TRIGGER ...`; the name, strings and comments don't count
+fn is_create_trigger(sql: &str) -> bool {
+    if leading_keyword(sql) != "CREATE" {
+        return false;
+    }
+    code_words(sql)
+        .iter()
+        .skip(1)
+        .find(|word| !matches!(word.as_str(), "TEMP" | "TEMPORARY"))
+        .is_some_and(|word| word == "TRIGGER")
+}
+
+/// Errors caused by a broken connection rather than by the SQL itself.
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<sqlx::Error>(),
+        Some(
+            sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::WorkerCrashed
+        )
+    )
+}
+
+/// Whether a statement can be used as a subquery: a plain SELECT or a (recursive) CTE.
+/// Postgres only allows data-modifying CTEs at the top level, so those are not wrapped.
+fn is_wrappable_query(sql: &str) -> bool {
+    match leading_keyword(sql).as_str() {
+        "SELECT" => true,
+        "WITH" => !code_words(sql)
+            .iter()
+            .any(|word| matches!(word.as_str(), "INSERT" | "UPDATE" | "DELETE" | "MERGE")),
+        _ => false,
+    }
+}
+
+/// Single plain queries that can run through `DECLARE ... CURSOR`. WITH is left out because
+/// cursors reject data-modifying CTEs.
+fn is_cursor_query(sql: &str) -> bool {
+    matches!(leading_keyword(sql).as_str(), "SELECT" | "VALUES" | "TABLE")
+        && split_statements(sql, false).len() == 1
+}
+
 fn wrap_pagination(sql: &str, limit: u32, offset: u32) -> String {
-    let trimmed = sql.trim();
-    if trimmed.to_uppercase().starts_with("SELECT") {
+    if is_wrappable_query(sql) {
         format!(
             "SELECT * FROM ({}) AS __sqlmate_q LIMIT {} OFFSET {}",
-            trimmed.trim_end_matches(';'),
+            trim_statement_end(sql),
             limit,
             offset
         )
     } else {
-        trimmed.to_string()
+        sql.trim().to_string()
+    }
+}
+
+fn catalog_pattern(filter: &CatalogFilter) -> String {
+    match filter.name_filter.as_deref() {
+        Some(pattern) if !pattern.is_empty() => pattern.to_string(),
+        _ => "%".to_string(),
+    }
+}
+
+fn catalog_limit_clause(filter: &CatalogFilter, db_type: &str) -> String {
+    limit_offset_clause(filter.limit, filter.offset, db_type)
+}
+
+/// MySQL and SQLite only accept OFFSET after a LIMIT, so pad with the largest one
+fn limit_offset_clause(limit: Option<u32>, offset: Option<u32>, db_type: &str) -> String {
+    match (limit, offset) {
+        (Some(limit), Some(offset)) => format!(" LIMIT {} OFFSET {}", limit, offset),
+        (Some(limit), None) => format!(" LIMIT {}", limit),
+        (None, Some(offset)) => match db_type {
+            "mysql" => format!(" LIMIT {} OFFSET {}", u64::MAX, offset),
+            "sqlite" => format!(" LIMIT -1 OFFSET {}", offset),
+            _ => format!(" OFFSET {}", offset),
+        },
+        (None, None) => String::new(),
     }
 }
 
 fn wrap_count(sql: &str) -> String {
-    let trimmed = sql.trim();
-    if trimmed.to_uppercase().starts_with("SELECT") {
+    if is_wrappable_query(sql) {
         format!(
             "SELECT COUNT(*) FROM ({}) AS __sqlmate_count_q",
-            trimmed.trim_end_matches(';')
+            trim_statement_end(sql)
         )
     } else {
         "".to_string()
@@ -60,338 +680,809 @@ fn sidebar_item_type_from_table_type(table_type: &str) -> SidebarItemType {
     }
 }
 
-macro_rules! postgres_row_to_values {
-    ($row:expr) => {{
-        let mut result_row = Vec::new();
-        for i in 0..$row.columns().len() {
-            let val: Value = if $row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
-                Value::Null
-            } else {
-                let type_name = $row.column(i).type_info().name().to_lowercase();
-                if type_name == "bool" || type_name == "boolean" || type_name == "bit" {
-                    if let Ok(b) = $row.try_get::<bool, usize>(i) {
-                        Value::Bool(b)
-                    } else {
-                        Value::Null
-                    }
-                } else if type_name == "uuid" {
-                    if let Ok(u) = $row.try_get::<uuid::Uuid, usize>(i) {
-                        Value::String(u.to_string())
-                    } else {
-                        Value::String("Invalid UUID".to_string())
-                    }
-                } else if type_name.contains("int") || type_name == "serial" || type_name == "year"
-                {
-                    if let Ok(n) = $row.try_get::<i64, usize>(i) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i32, usize>(i) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i16, usize>(i) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i8, usize>(i) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else {
-                        Value::String(format!("NumError({})", type_name))
-                    }
-                } else if type_name.contains("float")
-                    || type_name == "real"
-                    || type_name == "double"
-                    || type_name == "numeric"
-                    || type_name == "decimal"
+/// Renders a timestamptz (or MySQL TIMESTAMP), converted to `tz` when a display timezone was
+/// requested. Naive timestamps and MySQL DATETIME carry no zone and are never converted.
+fn format_timestamptz(dt: chrono::DateTime<chrono::Utc>, tz: Option<chrono_tz::Tz>) -> String {
+    match tz {
+        Some(tz) => dt.with_timezone(&tz).to_rfc3339(),
+        None => dt.to_rfc3339(),
+    }
+}
+
+fn parse_display_timezone(name: Option<&str>) -> Result<Option<chrono_tz::Tz>> {
+    name.map(|n| {
+        n.parse::<chrono_tz::Tz>()
+            .map_err(|e| anyhow!("Unknown display timezone '{}': {}", n, e))
+    })
+    .transpose()
+}
+
+/// JSON numbers can't hold NaN or infinities, so those are spelled out the way
+/// Postgres prints them instead of collapsing to null
+pub(crate) fn float_to_json(f: f64) -> Value {
+    match serde_json::Number::from_f64(f) {
+        Some(n) => Value::Number(n),
+        None if f.is_nan() => Value::String("NaN".to_string()),
+        None if f > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+/// rust_decimal rejects the special NUMERIC values, so read them from the raw value.
+/// Binary NUMERIC is ndigits, weight, sign, dscale (all 16-bit); the sign word marks
+/// NaN (0xC000), Infinity (0xD000) and -Infinity (0xF000).
+fn pg_numeric_special(value: &PgValueRef<'_>) -> Option<String> {
+    let special = match value.format() {
+        PgValueFormat::Text => value.as_str().ok()?,
+        PgValueFormat::Binary => match value.as_bytes().ok()?.get(4..6)? {
+            [0xC0, 0x00] => "NaN",
+            [0xD0, 0x00] => "Infinity",
+            [0xF0, 0x00] => "-Infinity",
+            _ => return None,
+        },
+    };
+    matches!(special, "NaN" | "Infinity" | "-Infinity").then(|| special.to_string())
+}
+
+/// Unprepared (text protocol) results arrive already rendered by Postgres
+fn pg_text_value(value: &PgValueRef<'_>) -> Option<String> {
+    match value.format() {
+        PgValueFormat::Text => value.as_str().ok().map(str::to_string),
+        PgValueFormat::Binary => None,
+    }
+}
+
+fn pg_inet_value(value: &PgValueRef<'_>) -> Option<String> {
+    match value.format() {
+        PgValueFormat::Text => value.as_str().ok().map(str::to_string),
+        PgValueFormat::Binary => decode_pg_inet(value.as_bytes().ok()?),
+    }
+}
+
+fn pg_bit_value(value: &PgValueRef<'_>) -> Option<String> {
+    match value.format() {
+        PgValueFormat::Text => value.as_str().ok().map(str::to_string),
+        PgValueFormat::Binary => decode_pg_bit(value.as_bytes().ok()?),
+    }
+}
+
+/// Decodes the binary bit/varbit layout: a 32-bit bit length followed by the bits,
+/// most significant first, padded out to whole bytes
+fn decode_pg_bit(bytes: &[u8]) -> Option<String> {
+    let (len, data) = bytes.split_first_chunk::<4>()?;
+    let len = usize::try_from(i32::from_be_bytes(*len)).ok()?;
+    if data.len() * 8 < len {
+        return None;
+    }
+    Some(
+        (0..len)
+            .map(|i| {
+                if data[i / 8] & (0x80 >> (i % 8)) != 0 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect(),
+    )
+}
+
+/// MySQL sends BIT(n) as up to 8 big-endian bytes in both protocols
+fn mysql_bit_value(value: sqlx::mysql::MySqlValueRef<'_>) -> Option<u64> {
+    let bytes = <&[u8] as sqlx::Decode<sqlx::MySql>>::decode(value).ok()?;
+    if bytes.len() > 8 {
+        return None;
+    }
+    Some(bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+}
+
+/// Decodes the binary inet/cidr layout: family, prefix bits, is_cidr flag, length, address
+fn decode_pg_inet(bytes: &[u8]) -> Option<String> {
+    let [family, bits, is_cidr, len, addr @ ..] = bytes else {
+        return None;
+    };
+    let (ip, full_bits): (std::net::IpAddr, u8) = match (*family, *len as usize) {
+        (2, 4) => (<[u8; 4]>::try_from(addr).ok()?.into(), 32),
+        (3, 16) => (<[u8; 16]>::try_from(addr).ok()?.into(), 128),
+        _ => return None,
+    };
+    // Postgres omits the prefix for single-host inet values, but always shows it for cidr
+    if *is_cidr == 0 && *bits == full_bits {
+        Some(ip.to_string())
+    } else {
+        Some(format!("{}/{}", ip, bits))
+    }
+}
+
+/// Renders an interval as an ISO-8601 duration, e.g. `P1Y2M3DT4H5M6.5S`
+fn format_pg_interval(interval: &PgInterval) -> String {
+    let mut out = String::from("P");
+    let (years, months) = (interval.months / 12, interval.months % 12);
+    if years != 0 {
+        out.push_str(&format!("{}Y", years));
+    }
+    if months != 0 {
+        out.push_str(&format!("{}M", months));
+    }
+    if interval.days != 0 {
+        out.push_str(&format!("{}D", interval.days));
+    }
+
+    let micros = interval.microseconds;
+    if micros != 0 {
+        out.push('T');
+        let hours = micros / 3_600_000_000;
+        let minutes = (micros % 3_600_000_000) / 60_000_000;
+        let seconds = micros % 60_000_000;
+        if hours != 0 {
+            out.push_str(&format!("{}H", hours));
+        }
+        if minutes != 0 {
+            out.push_str(&format!("{}M", minutes));
+        }
+        if seconds != 0 {
+            let formatted = format!("{:.6}", seconds as f64 / 1_000_000.0);
+            out.push_str(formatted.trim_end_matches('0').trim_end_matches('.'));
+            out.push('S');
+        }
+    }
+
+    if out == "P" {
+        out.push_str("T0S");
+    }
+    out
+}
+
+/// Converts column `$i` of a Postgres row to its JSON value
+macro_rules! postgres_cell_value {
+    ($row:expr, $i:expr, $tz:expr) => {{
+        let i: usize = $i;
+        if $row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+            Value::Null
+        } else {
+            let type_name = $row.column(i).type_info().name().to_lowercase();
+            if type_name == "bool" || type_name == "boolean" {
+                if let Ok(b) = $row.try_get::<bool, usize>(i) {
+                    Value::Bool(b)
+                } else {
+                    Value::Null
+                }
+            } else if type_name == "bit" || type_name == "varbit" {
+                $row.try_get_raw(i)
+                    .ok()
+                    .and_then(|v| pg_bit_value(&v))
+                    .map(Value::String)
+                    .unwrap_or_else(|| Value::String(format!("Binary/Complex ({})", type_name)))
+            } else if type_name == "uuid" {
+                if let Ok(u) = $row.try_get::<uuid::Uuid, usize>(i) {
+                    Value::String(u.to_string())
+                } else {
+                    Value::String("Invalid UUID".to_string())
+                }
+            } else if (type_name.contains("int") && type_name != "interval")
+                || type_name == "serial"
+                || type_name == "year"
+            {
+                if let Ok(n) = $row.try_get::<i64, usize>(i) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i32, usize>(i) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i16, usize>(i) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i8, usize>(i) {
+                    Value::Number(serde_json::Number::from(n))
+                } else {
+                    Value::String(format!("NumError({})", type_name))
+                }
+            } else if type_name.contains("float")
+                || type_name == "real"
+                || type_name == "double"
+                || type_name == "numeric"
+                || type_name == "decimal"
+            {
+                if let Ok(f) = $row.try_get::<f64, usize>(i) {
+                    float_to_json(f)
+                } else if let Ok(f) = $row.try_get::<f32, usize>(i) {
+                    float_to_json(f as f64)
+                } else if let Ok(d) = $row.try_get::<rust_decimal::Decimal, usize>(i) {
+                    Value::String(d.to_string())
+                } else if let Some(s) = $row
+                    .try_get_raw(i)
+                    .ok()
+                    .and_then(|v| pg_numeric_special(&v))
                 {
-                    if let Ok(f) = $row.try_get::<f64, usize>(i) {
-                        serde_json::Number::from_f64(f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else if let Ok(f) = $row.try_get::<f32, usize>(i) {
-                        serde_json::Number::from_f64(f as f64)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else if let Ok(d) = $row.try_get::<rust_decimal::Decimal, usize>(i) {
-                        Value::String(d.to_string())
-                    } else {
-                        Value::Null
-                    }
-                } else if type_name_is_text(&type_name) {
-                    if let Ok(s) = $row.try_get::<String, usize>(i) {
-                        Value::String(s)
-                    } else {
-                        Value::String("".to_string())
-                    }
-                } else if type_name.contains("time") || type_name == "date" {
-                    if let Ok(dt) = $row.try_get::<chrono::DateTime<chrono::Utc>, usize>(i) {
-                        Value::String(dt.to_rfc3339())
-                    } else if let Ok(dt) = $row.try_get::<chrono::NaiveDateTime, usize>(i) {
-                        Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    } else if let Ok(dt) = $row.try_get::<chrono::NaiveDate, usize>(i) {
-                        Value::String(dt.to_string())
-                    } else if let Ok(t) = $row.try_get::<chrono::NaiveTime, usize>(i) {
-                        Value::String(t.to_string())
-                    } else {
-                        if let Ok(s) = $row.try_get::<String, usize>(i) {
-                            Value::String(s)
-                        } else {
-                            Value::String("Invalid Date".to_string())
-                        }
-                    }
-                } else if type_name.contains("bytea") {
-                    if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i) {
-                        let hex_string: String = bytes
-                            .iter()
-                            .map(|b| format!("{:02x}", b))
-                            .collect::<String>();
-                        Value::String(format!("0x{}", hex_string))
-                    } else {
-                        Value::String(format!("BinaryErr({})", type_name))
-                    }
+                    Value::String(s)
+                } else {
+                    Value::Null
+                }
+            } else if type_name_is_text(&type_name) {
+                if let Ok(s) = $row.try_get::<String, usize>(i) {
+                    Value::String(s)
+                } else {
+                    Value::String("".to_string())
+                }
+            } else if type_name.contains("time") || type_name == "date" {
+                if let Ok(dt) = $row.try_get::<chrono::DateTime<chrono::Utc>, usize>(i) {
+                    Value::String(format_timestamptz(dt, $tz))
+                } else if let Ok(dt) = $row.try_get::<chrono::NaiveDateTime, usize>(i) {
+                    Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                } else if let Ok(dt) = $row.try_get::<chrono::NaiveDate, usize>(i) {
+                    Value::String(dt.to_string())
+                } else if let Ok(t) = $row.try_get::<chrono::NaiveTime, usize>(i) {
+                    Value::String(t.to_string())
                 } else {
                     if let Ok(s) = $row.try_get::<String, usize>(i) {
                         Value::String(s)
                     } else {
-                        Value::String(format!("Binary/Complex ({})", type_name))
+                        Value::String("Invalid Date".to_string())
                     }
                 }
-            };
-            result_row.push(val);
+            } else if type_name.contains("bytea") {
+                if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i) {
+                    let hex_string: String = bytes
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>();
+                    Value::String(format!("0x{}", hex_string))
+                } else {
+                    Value::String(format!("BinaryErr({})", type_name))
+                }
+            } else if type_name == "interval" {
+                if let Ok(iv) = $row.try_get::<PgInterval, usize>(i) {
+                    Value::String(format_pg_interval(&iv))
+                } else {
+                    $row.try_get_raw(i)
+                        .ok()
+                        .and_then(|v| pg_text_value(&v))
+                        .map(Value::String)
+                        .unwrap_or_else(|| Value::String(format!("Binary/Complex ({})", type_name)))
+                }
+            } else if type_name == "inet" || type_name == "cidr" {
+                $row.try_get_raw(i)
+                    .ok()
+                    .and_then(|v| pg_inet_value(&v))
+                    .map(Value::String)
+                    .unwrap_or_else(|| Value::String(format!("Binary/Complex ({})", type_name)))
+            } else if type_name == "uuid[]" {
+                if let Ok(ids) = $row.try_get::<Vec<uuid::Uuid>, usize>(i) {
+                    Value::Array(
+                        ids.into_iter()
+                            .map(|u| Value::String(u.to_string()))
+                            .collect(),
+                    )
+                } else {
+                    $row.try_get_raw(i)
+                        .ok()
+                        .and_then(|v| pg_text_value(&v))
+                        .map(Value::String)
+                        .unwrap_or_else(|| Value::String(format!("Binary/Complex ({})", type_name)))
+                }
+            } else {
+                if let Ok(s) = $row.try_get::<String, usize>(i) {
+                    Value::String(s)
+                } else if let Some(s) = $row.try_get_raw(i).ok().and_then(|v| pg_text_value(&v)) {
+                    Value::String(s)
+                } else {
+                    Value::String(format!("Binary/Complex ({})", type_name))
+                }
+            }
         }
-        result_row
     }};
 }
 
-macro_rules! mysql_row_to_values {
-    ($row:expr) => {{
+macro_rules! postgres_row_to_values {
+    ($row:expr, $tz:expr) => {{
         let mut result_row = Vec::new();
         for i in 0..$row.columns().len() {
-            let val: Value = if Row::try_get_raw($row, i as usize)
-                .map(|v| v.is_null())
-                .unwrap_or(true)
+            result_row.push(postgres_cell_value!($row, i, $tz));
+        }
+        result_row
+    }};
+    ($row:expr) => {
+        postgres_row_to_values!($row, None)
+    };
+}
+
+/// Converts column `$i` of a MySQL row to its JSON value
+macro_rules! mysql_cell_value {
+    ($row:expr, $i:expr, $tz:expr) => {{
+        let i: usize = $i;
+        if Row::try_get_raw($row, i as usize)
+            .map(|v| v.is_null())
+            .unwrap_or(true)
+        {
+            Value::Null
+        } else {
+            let type_info = $row.column(i as usize).type_info();
+            let type_name = type_info.name().to_lowercase();
+            if type_name == "tinyint" && type_info.to_string().contains("TINYINT(1)") {
+                if let Ok(b) = $row.try_get::<bool, usize>(i as usize) {
+                    Value::Bool(b)
+                } else if let Ok(v) = $row.try_get::<i8, usize>(i as usize) {
+                    Value::Bool(v != 0)
+                } else {
+                    Value::Null
+                }
+            } else if type_name == "bit" {
+                Row::try_get_raw($row, i as usize)
+                    .ok()
+                    .and_then(mysql_bit_value)
+                    .map(|n| Value::Number(serde_json::Number::from(n)))
+                    .unwrap_or(Value::Null)
+            } else if type_name.contains("int") || type_name == "serial" || type_name == "year" {
+                if let Ok(n) = $row.try_get::<i64, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i32, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i16, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i8, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<u64, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<u32, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else {
+                    Value::String(format!("NumError({})", type_name))
+                }
+            } else if type_name.contains("float")
+                || type_name == "real"
+                || type_name == "double"
+                || type_name == "numeric"
+                || type_name == "decimal"
             {
-                Value::Null
-            } else {
-                let type_info = $row.column(i as usize).type_info();
-                let type_name = type_info.name().to_lowercase();
-                if type_name == "tinyint" && type_info.to_string().contains("TINYINT(1)") {
-                    if let Ok(b) = $row.try_get::<bool, usize>(i as usize) {
-                        Value::Bool(b)
-                    } else if let Ok(v) = $row.try_get::<i8, usize>(i as usize) {
-                        Value::Bool(v != 0)
+                if let Ok(f) = $row.try_get::<f64, usize>(i as usize) {
+                    float_to_json(f)
+                } else if let Ok(f) = $row.try_get::<f32, usize>(i as usize) {
+                    float_to_json(f as f64)
+                } else if let Ok(d) = $row.try_get::<rust_decimal::Decimal, usize>(i as usize) {
+                    Value::String(d.to_string())
+                } else {
+                    Value::Null
+                }
+            } else if type_name == "enum" || type_name == "set" {
+                // ENUM/SET arrive as text but may not carry a text type flag, so skip the
+                // compatibility check; SET members stay comma-joined as MySQL returns them
+                if let Ok(s) = $row.try_get_unchecked::<String, usize>(i as usize) {
+                    Value::String(s)
+                } else {
+                    Value::String("".to_string())
+                }
+            } else if type_name_is_text(&type_name) {
+                if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
+                    Value::String(s)
+                } else {
+                    Value::String("".to_string())
+                }
+            } else if type_name.contains("time") || type_name == "date" || type_name == "timestamp"
+            {
+                if let Ok(dt) = $row.try_get::<chrono::DateTime<chrono::Utc>, usize>(i as usize) {
+                    // sqlx decodes DATETIME as UTC too, but only TIMESTAMP is stored zoned
+                    let tz = if type_name == "timestamp" { $tz } else { None };
+                    Value::String(format_timestamptz(dt, tz))
+                } else if let Ok(dt) = $row.try_get::<chrono::NaiveDateTime, usize>(i as usize) {
+                    Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                } else if let Ok(dt) = $row.try_get::<chrono::NaiveDate, usize>(i as usize) {
+                    Value::String(dt.to_string())
+                } else if let Ok(t) = $row.try_get::<chrono::NaiveTime, usize>(i as usize) {
+                    Value::String(t.to_string())
+                } else {
+                    if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
+                        Value::String(s)
                     } else {
-                        Value::Null
+                        Value::String("Invalid Date".to_string())
                     }
-                } else if type_name.contains("int") || type_name == "serial" || type_name == "year"
-                {
-                    if let Ok(n) = $row.try_get::<i64, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i32, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i16, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i8, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<u64, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<u32, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else {
-                        Value::String(format!("NumError({})", type_name))
-                    }
-                } else if type_name.contains("float")
-                    || type_name == "real"
-                    || type_name == "double"
-                    || type_name == "numeric"
-                    || type_name == "decimal"
-                {
-                    if let Ok(f) = $row.try_get::<f64, usize>(i as usize) {
-                        serde_json::Number::from_f64(f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else if let Ok(f) = $row.try_get::<f32, usize>(i as usize) {
-                        serde_json::Number::from_f64(f as f64)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else if let Ok(d) = $row.try_get::<rust_decimal::Decimal, usize>(i as usize) {
-                        Value::String(d.to_string())
-                    } else {
-                        Value::Null
-                    }
-                } else if type_name_is_text(&type_name) {
-                    if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
-                        Value::String(s)
-                    } else {
-                        Value::String("".to_string())
-                    }
-                } else if type_name.contains("time")
-                    || type_name == "date"
-                    || type_name == "timestamp"
-                {
-                    if let Ok(dt) = $row.try_get::<chrono::DateTime<chrono::Utc>, usize>(i as usize)
-                    {
-                        Value::String(dt.to_rfc3339())
-                    } else if let Ok(dt) = $row.try_get::<chrono::NaiveDateTime, usize>(i as usize)
-                    {
-                        Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    } else if let Ok(dt) = $row.try_get::<chrono::NaiveDate, usize>(i as usize) {
-                        Value::String(dt.to_string())
-                    } else if let Ok(t) = $row.try_get::<chrono::NaiveTime, usize>(i as usize) {
-                        Value::String(t.to_string())
-                    } else {
-                        if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
-                            Value::String(s)
-                        } else {
-                            Value::String("Invalid Date".to_string())
-                        }
-                    }
-                } else if type_name.contains("blob") || type_name.contains("binary") {
-                    if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i as usize) {
-                        let hex_string: String = bytes
-                            .iter()
-                            .map(|b| format!("{:02x}", b))
-                            .collect::<String>();
-                        if bytes.len() == 16 {
-                            if let Ok(u) = uuid::Uuid::from_slice(&bytes) {
-                                Value::String(u.to_string())
-                            } else {
-                                Value::String(format!("0x{}", hex_string))
-                            }
+                }
+            } else if type_name.contains("blob") || type_name.contains("binary") {
+                if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i as usize) {
+                    let hex_string: String = bytes
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>();
+                    if bytes.len() == 16 {
+                        if let Ok(u) = uuid::Uuid::from_slice(&bytes) {
+                            Value::String(u.to_string())
                         } else {
                             Value::String(format!("0x{}", hex_string))
                         }
                     } else {
-                        Value::String(format!("BinaryErr({})", type_name))
+                        Value::String(format!("0x{}", hex_string))
                     }
                 } else {
-                    if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
-                        Value::String(s)
-                    } else {
-                        Value::String(format!("Binary/Complex ({})", type_name))
-                    }
+                    Value::String(format!("BinaryErr({})", type_name))
                 }
-            };
-            result_row.push(val);
+            } else {
+                if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
+                    Value::String(s)
+                } else {
+                    Value::String(format!("Binary/Complex ({})", type_name))
+                }
+            }
         }
-        result_row
     }};
 }
 
-macro_rules! sqlite_row_to_values {
-    ($row:expr) => {{
+macro_rules! mysql_row_to_values {
+    ($row:expr, $tz:expr) => {{
         let mut result_row = Vec::new();
         for i in 0..$row.columns().len() {
-            let val: Value = if Row::try_get_raw($row, i as usize)
-                .map(|v| v.is_null())
-                .unwrap_or(true)
-            {
-                Value::Null
+            result_row.push(mysql_cell_value!($row, i, $tz));
+        }
+        result_row
+    }};
+    ($row:expr) => {
+        mysql_row_to_values!($row, None)
+    };
+}
+
+/// Converts column `$i` of a SQLite row to its JSON value
+macro_rules! sqlite_cell_value {
+    ($row:expr, $i:expr, $tz:expr) => {{
+        // SQLite has no timestamptz type; stored date text is returned as-is
+        let _: Option<chrono_tz::Tz> = $tz;
+        let i: usize = $i;
+        if Row::try_get_raw($row, i as usize)
+            .map(|v| v.is_null())
+            .unwrap_or(true)
+        {
+            Value::Null
+        } else {
+            let type_name = $row.column(i as usize).type_info().name().to_lowercase();
+            if type_name == "bool" || type_name == "boolean" {
+                if let Ok(b) = $row.try_get::<bool, usize>(i as usize) {
+                    Value::Bool(b)
+                } else {
+                    Value::Null
+                }
+            } else if type_name.contains("int") || type_name == "integer" {
+                if let Ok(n) = $row.try_get::<i64, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i32, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i16, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else if let Ok(n) = $row.try_get::<i8, usize>(i as usize) {
+                    Value::Number(serde_json::Number::from(n))
+                } else {
+                    Value::String(format!("NumError({})", type_name))
+                }
+            } else if type_name.contains("float") || type_name == "real" || type_name == "double" {
+                if let Ok(f) = $row.try_get::<f64, usize>(i as usize) {
+                    float_to_json(f)
+                } else {
+                    Value::Null
+                }
+            } else if type_name_is_text(&type_name) {
+                if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
+                    Value::String(s)
+                } else {
+                    Value::String("".to_string())
+                }
+            } else if type_name.contains("blob") {
+                if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i as usize) {
+                    let hex_string: String = bytes
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>();
+                    Value::String(format!("0x{}", hex_string))
+                } else {
+                    Value::String("Blob Error".to_string())
+                }
             } else {
-                let type_name = $row.column(i as usize).type_info().name().to_lowercase();
-                if type_name == "bool" || type_name == "boolean" {
-                    if let Ok(b) = $row.try_get::<bool, usize>(i as usize) {
-                        Value::Bool(b)
-                    } else {
-                        Value::Null
-                    }
-                } else if type_name.contains("int") || type_name == "integer" {
-                    if let Ok(n) = $row.try_get::<i64, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i32, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i16, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else if let Ok(n) = $row.try_get::<i8, usize>(i as usize) {
-                        Value::Number(serde_json::Number::from(n))
-                    } else {
-                        Value::String(format!("NumError({})", type_name))
-                    }
-                } else if type_name.contains("float")
-                    || type_name == "real"
-                    || type_name == "double"
-                {
-                    if let Ok(f) = $row.try_get::<f64, usize>(i as usize) {
-                        serde_json::Number::from_f64(f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else {
-                        Value::Null
-                    }
-                } else if type_name_is_text(&type_name) {
-                    if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
-                        Value::String(s)
-                    } else {
-                        Value::String("".to_string())
-                    }
-                } else if type_name.contains("blob") {
-                    if let Ok(bytes) = $row.try_get::<Vec<u8>, usize>(i as usize) {
-                        let hex_string: String = bytes
-                            .iter()
-                            .map(|b| format!("{:02x}", b))
-                            .collect::<String>();
-                        Value::String(format!("0x{}", hex_string))
-                    } else {
-                        Value::String("Blob Error".to_string())
-                    }
+                if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
+                    Value::String(s)
                 } else {
-                    if let Ok(s) = $row.try_get::<String, usize>(i as usize) {
-                        Value::String(s)
-                    } else {
-                        Value::String(format!("Binary/Complex ({})", type_name))
-                    }
+                    Value::String(format!("Binary/Complex ({})", type_name))
                 }
-            };
-            result_row.push(val);
+            }
         }
-        result_row
     }};
 }
 
-fn build_where_clause(filters: Vec<FilterConfig>, db_type: &str) -> String {
-    if filters.is_empty() {
-        return String::new();
-    }
+macro_rules! sqlite_row_to_values {
+    ($row:expr, $tz:expr) => {{
+        let mut result_row = Vec::new();
+        for i in 0..$row.columns().len() {
+            result_row.push(sqlite_cell_value!($row, i, $tz));
+        }
+        result_row
+    }};
+    ($row:expr) => {
+        sqlite_row_to_values!($row, None)
+    };
+}
 
-    let conditions: Vec<String> = filters
-        .iter()
-        .filter(|f| f.enabled)
-        .map(|f| {
-            let col = match db_type {
-                "mysql" => format!("`{}`", f.column.replace("`", "``")),
-                _ => format!("\"{}\"", f.column.replace("\"", "\"\"")),
+/// Binds JSON values as typed query parameters; arrays and objects are sent as JSON text
+macro_rules! bind_json_params {
+    ($query:expr, $params:expr) => {{
+        let mut query = $query;
+        for param in $params {
+            query = match param {
+                Value::Null => query.bind(None::<String>),
+                Value::Bool(b) => query.bind(*b),
+                Value::Number(n) => match n.as_i64() {
+                    Some(i) => query.bind(i),
+                    None => query.bind(n.as_f64()),
+                },
+                Value::String(s) => query.bind(s.clone()),
+                other => query.bind(other.to_string()),
             };
+        }
+        query
+    }};
+}
+pub(crate) use bind_json_params;
+
+/// Reads a cell as raw bytes; text columns fall back to their UTF-8 encoding
+macro_rules! cell_bytes {
+    ($row:expr, $column:expr) => {{
+        match $row.try_get::<Option<Vec<u8>>, _>($column) {
+            Ok(bytes) => bytes,
+            Err(_) => $row
+                .try_get::<Option<String>, _>($column)?
+                .map(String::into_bytes),
+        }
+        .ok_or_else(|| anyhow!("Column '{}' is NULL", $column))
+    }};
+}
 
-            let val = &f.value;
-            // Basic SQL escaping for value - THIS IS NOT SECURE against clever attacks but standard precaution for now.
-            // Ideally we should use bind parameters, but dynamic binding with sqlx is complex.
-            // For this task, simple escaping of single quotes should suffice for string literals.
-            let escaped_val = val.replace("'", "''");
-
-            match f.operator.as_str() {
-                "=" => format!("{} = '{}'", col, escaped_val),
-                "!=" => format!("{} != '{}'", col, escaped_val),
-                ">" => format!("{} > '{}'", col, escaped_val),
-                "<" => format!("{} < '{}'", col, escaped_val),
-                ">=" => format!("{} >= '{}'", col, escaped_val),
-                "<=" => format!("{} <= '{}'", col, escaped_val),
-                "Contains" | "LIKE" => format!("{} LIKE '%{}%'", col, escaped_val),
-                "Starts With" | "ILIKE" => {
-                    if db_type == "postgres" && f.operator == "ILIKE" {
-                        format!("{} ILIKE '{}%'", col, escaped_val)
-                    } else {
-                        format!("{} LIKE '{}%'", col, escaped_val)
-                    }
+async fn detect_db_type(manager: &ConnectionManager, connection_id: &Uuid) -> Result<&'static str> {
+    if manager
+        .get_postgres_pools()
+        .await
+        .contains_key(connection_id)
+    {
+        Ok("postgres")
+    } else if manager.get_mysql_pools().await.contains_key(connection_id) {
+        Ok("mysql")
+    } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
+        Ok("sqlite")
+    } else {
+        Err(anyhow!("Connection not found"))
+    }
+}
+
+/// The WHERE clause for `filters` and the values its placeholders bind, in order.
+/// Filter values are bound rather than spliced, except for Postgres comparisons (see
+/// `comparison_value`). Postgres placeholders number from `$1`, so the clause has to
+/// precede any other parameter in the statement.
+pub(crate) fn build_where_clause(
+    filters: Vec<FilterConfig>,
+    db_type: &str,
+) -> (String, Vec<Value>) {
+    let mut params: Vec<Value> = Vec::new();
+    let mut placeholder = |value: String| {
+        params.push(Value::String(value));
+        match db_type {
+            "postgres" => format!("${}", params.len()),
+            _ => "?".to_string(),
+        }
+    };
+
+    let mut conditions: Vec<String> = Vec::new();
+    for f in filters.iter().filter(|f| f.enabled) {
+        let col = quote_ident(&f.column, db_type);
+
+        let val = &f.value;
+        conditions.push(match f.operator.as_str() {
+            op @ ("=" | "!=" | ">" | "<" | ">=" | "<=") => format!(
+                "{} {} {}",
+                col,
+                op,
+                comparison_value(val, db_type, &mut placeholder)
+            ),
+            "Contains" | "LIKE" => like_condition(
+                &col,
+                format!("%{}%", val),
+                f.case_insensitive,
+                db_type,
+                &mut placeholder,
+            ),
+            "Starts With" => like_condition(
+                &col,
+                format!("{}%", val),
+                f.case_insensitive,
+                db_type,
+                &mut placeholder,
+            ),
+            "ILIKE" => like_condition(&col, format!("{}%", val), true, db_type, &mut placeholder),
+            "Ends With" => like_condition(
+                &col,
+                format!("%{}", val),
+                f.case_insensitive,
+                db_type,
+                &mut placeholder,
+            ),
+            "IN" => {
+                // User types "1, 2, 3" or "'a', 'b'"
+                let items: Vec<String> = val
+                    .split(',')
+                    .map(|item| unquote_in_item(item.trim()))
+                    .filter(|item| !item.is_empty())
+                    .map(|item| comparison_value(&item, db_type, &mut placeholder))
+                    .collect();
+                if items.is_empty() {
+                    "1 = 0".to_string()
+                } else {
+                    format!("{} IN ({})", col, items.join(", "))
                 }
-                "Ends With" => format!("{} LIKE '%{}'", col, escaped_val),
-                "IN" => format!("{} IN ({})", col, val), // User types "1, 2, 3"
-                "IS NULL" => format!("{} IS NULL", col),
-                "IS NOT NULL" => format!("{} IS NOT NULL", col),
-                _ => format!("{} = '{}'", col, escaped_val),
             }
-        })
-        .collect();
+            "IS NULL" => format!("{} IS NULL", col),
+            "IS NOT NULL" => format!("{} IS NOT NULL", col),
+            "json_contains" => json_contains_condition(
+                &col,
+                &json_path_segments(f.json_path.as_deref().unwrap_or("")),
+                val,
+                db_type,
+                &mut placeholder,
+            ),
+            "json_path_eq" => {
+                let path_text = json_path_text(
+                    &col,
+                    &json_path_segments(f.json_path.as_deref().unwrap_or("")),
+                    db_type,
+                    &mut placeholder,
+                );
+                format!("{} = {}", path_text, placeholder(val.clone()))
+            }
+            _ => format!(
+                "{} = {}",
+                col,
+                comparison_value(val, db_type, &mut placeholder)
+            ),
+        });
+    }
 
     if conditions.is_empty() {
-        return String::new();
+        return (String::new(), params);
+    }
+
+    (format!("WHERE {}", conditions.join(" AND ")), params)
+}
+
+/// Splits `a.b.0` or `$.a.b[0]` into keys and array indexes. Quote and backslash
+/// characters are dropped from keys so they can't change the path's structure.
+fn json_path_segments(path: &str) -> Vec<String> {
+    path.trim_start_matches('$')
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .map(|segment| segment.replace(['"', '\\', '\''], ""))
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Postgres text-array path (`{"a","0"}`) or MySQL/SQLite JSON path (`$."a"[0]`),
+/// bound as a parameter
+fn json_path_param(segments: &[String], db_type: &str) -> String {
+    if db_type == "postgres" {
+        let keys: Vec<String> = segments.iter().map(|s| format!("\"{}\"", s)).collect();
+        return format!("{{{}}}", keys.join(","));
+    }
+    let mut path = String::from("$");
+    for segment in segments {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            path.push_str(&format!("[{}]", segment));
+        } else {
+            path.push_str(&format!(".\"{}\"", segment));
+        }
+    }
+    path
+}
+
+/// The value at `segments` as text, for comparing against a filter value
+fn json_path_text(
+    col: &str,
+    segments: &[String],
+    db_type: &str,
+    placeholder: &mut impl FnMut(String) -> String,
+) -> String {
+    let path = placeholder(json_path_param(segments, db_type));
+    match db_type {
+        "postgres" => format!("{} #>> CAST({} AS text[])", col, path),
+        "mysql" => format!("JSON_UNQUOTE(JSON_EXTRACT({}, {}))", col, path),
+        _ => format!("CAST(json_extract({}, {}) AS TEXT)", col, path),
+    }
+}
+
+/// Whether the document (or the part at `segments`) contains the JSON in `value`.
+/// SQLite has no containment operator, so there it matches an element of the array or
+/// object at the path. Parameters are bound in the order their placeholders appear.
+fn json_contains_condition(
+    col: &str,
+    segments: &[String],
+    value: &str,
+    db_type: &str,
+    placeholder: &mut impl FnMut(String) -> String,
+) -> String {
+    let path = json_path_param(segments, db_type);
+    match db_type {
+        "postgres" => {
+            let path = placeholder(path);
+            let value = placeholder(value.to_string());
+            format!(
+                "({}::jsonb #> CAST({} AS text[])) @> CAST({} AS jsonb)",
+                col, path, value
+            )
+        }
+        "mysql" => {
+            let value = placeholder(value.to_string());
+            let path = placeholder(path);
+            format!("JSON_CONTAINS({}, {}, {})", col, value, path)
+        }
+        _ => {
+            let path = placeholder(path);
+            let value = placeholder(value.to_string());
+            format!(
+                "EXISTS (SELECT 1 FROM json_each({}, {}) WHERE json_each.value = json_extract({}, '$'))",
+                col, path, value
+            )
+        }
+    }
+}
+
+/// The right-hand side of a filter comparison. Postgres gets a quoted literal: a bound
+/// parameter is typed text and fails against numeric or date columns, while an untyped
+/// literal takes the column's type. With standard_conforming_strings (on by default)
+/// doubling quotes is enough there. Elsewhere the value is bound.
+fn comparison_value(
+    value: &str,
+    db_type: &str,
+    placeholder: &mut impl FnMut(String) -> String,
+) -> String {
+    match db_type {
+        "postgres" => format!("'{}'", value.replace('\'', "''")),
+        _ => placeholder(value.to_string()),
+    }
+}
+
+/// An IN list item without the single quotes a user may have typed around it
+fn unquote_in_item(item: &str) -> String {
+    match item.strip_prefix('\'').and_then(|i| i.strip_suffix('\'')) {
+        Some(quoted) => quoted.replace("''", "'"),
+        None => item.to_string(),
+    }
+}
+
+/// Builds a LIKE comparison with the pattern bound; case-insensitive matching lowers both
+/// sides outside Postgres because MySQL/SQLite LIKE sensitivity otherwise depends on collation.
+fn like_condition(
+    col: &str,
+    pattern: String,
+    case_insensitive: bool,
+    db_type: &str,
+    placeholder: &mut impl FnMut(String) -> String,
+) -> String {
+    let pattern = placeholder(pattern);
+    match (case_insensitive, db_type) {
+        (false, _) => format!("{} LIKE {}", col, pattern),
+        (true, "postgres") => format!("{} ILIKE {}", col, pattern),
+        (true, _) => format!("LOWER({}) LIKE LOWER({})", col, pattern),
     }
+}
 
-    format!("WHERE {}", conditions.join(" AND "))
+/// The COUNT and SELECT statements for one filtered, sorted page of a table, and the
+/// filter values both bind
+fn table_page_sql(
+    table_name: &str,
+    db_type: &str,
+    filters: Vec<FilterConfig>,
+    sort_column: Option<String>,
+    sort_direction: Option<String>,
+    page_limit: Option<u32>,
+    page_offset: Option<u32>,
+) -> (String, String, Vec<Value>) {
+    let table = quote_ident(table_name, db_type);
+    let (where_clause, params) = build_where_clause(filters, db_type);
+    let count_sql = format!("SELECT COUNT(*) FROM {} {};", table, where_clause);
+    let data_sql = format!(
+        "SELECT * FROM {} {} {}{};",
+        table,
+        where_clause,
+        build_order_clause(sort_column, sort_direction, db_type),
+        limit_offset_clause(page_limit, page_offset, db_type)
+    );
+    (count_sql, data_sql, params)
 }
 
 fn build_order_clause(
@@ -401,10 +1492,7 @@ fn build_order_clause(
 ) -> String {
     match (sort_column, sort_direction) {
         (Some(col), dir) => {
-            let quoted_col = match db_type {
-                "mysql" => format!("`{}`", col.replace("`", "``")),
-                _ => format!("\"{}\"", col.replace("\"", "\"\"")),
-            };
+            let quoted_col = quote_ident(&col, db_type);
             let direction = match dir.as_deref() {
                 Some("DESC") => "DESC",
                 _ => "ASC",
@@ -415,31 +1503,418 @@ fn build_order_clause(
     }
 }
 
+fn column_type_names<C: Column>(columns: &[C]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|c| c.type_info().name().to_string())
+        .collect()
+}
+
+/// Column names and type names from the prepared statement, for results with no rows.
+/// Preparing only parses the statement, so nothing is executed a second time.
+macro_rules! describe_columns {
+    ($executor:expr, $sql:expr) => {{
+        match Executor::prepare($executor, $sql).await {
+            Ok(stmt) => (
+                stmt.columns()
+                    .iter()
+                    .map(|c| Column::name(c).to_string())
+                    .collect::<Vec<String>>(),
+                column_type_names(stmt.columns()),
+            ),
+            Err(_) => (Vec::new(), Vec::new()),
+        }
+    }};
+}
+
+/// Runs one statement on a held transaction, returning (columns, rows, affected_rows)
+macro_rules! run_in_transaction {
+    ($tx:expr, $sql:expr, $to_values:ident) => {{
+        if returns_rows($sql) {
+            let rows = sqlx::query($sql).fetch_all(&mut **$tx).await?;
+            let columns: Vec<String> = match rows.first() {
+                Some(row) => row.columns().iter().map(|c| c.name().to_string()).collect(),
+                None => describe_columns!(&mut **$tx, $sql).0,
+            };
+            let values: Vec<Vec<Value>> = rows.iter().map(|row| $to_values!(row)).collect();
+            (columns, values, 0)
+        } else {
+            let result = sqlx::query($sql).execute(&mut **$tx).await?;
+            (Vec::new(), Vec::new(), result.rows_affected())
+        }
+    }};
+}
+
+/// Counts and reads one table page on an open transaction, then commits it, returning
+/// (columns, column_types, rows, total_count, truncated). Both statements bind `$params`;
+/// reading stops at `$max_rows`.
+macro_rules! table_page_in_transaction {
+    ($tx:expr, $count_sql:expr, $data_sql:expr, $params:expr, $max_rows:expr, $to_values:ident) => {{
+        let total: i64 = bind_json_params!(sqlx::query_scalar($count_sql), $params)
+            .fetch_one(&mut *$tx)
+            .await?;
+        let max_rows: Option<usize> = $max_rows;
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        {
+            let mut stream = bind_json_params!(sqlx::query($data_sql), $params).fetch(&mut *$tx);
+            while let Some(row) = StreamExt::next(&mut stream).await {
+                if max_rows.is_some_and(|max| rows.len() >= max) {
+                    truncated = true;
+                    break;
+                }
+                rows.push(row?);
+            }
+        }
+        let (columns, column_types) = match rows.first() {
+            Some(row) => (
+                row.columns().iter().map(|c| c.name().to_string()).collect(),
+                column_type_names(row.columns()),
+            ),
+            None => describe_columns!(&mut *$tx, $data_sql),
+        };
+        let values: Vec<Vec<Value>> = rows.iter().map(|row| $to_values!(row)).collect();
+        $tx.commit().await?;
+        (
+            columns,
+            column_types,
+            values,
+            total.max(0) as u64,
+            truncated,
+        )
+    }};
+}
+
+/// The statement that terminates session `pid`; Postgres' returns whether it existed
+fn kill_session_sql(db_type: &str, pid: i64) -> Result<String> {
+    match db_type {
+        "postgres" => Ok(format!("SELECT pg_terminate_backend({})", pid)),
+        "mysql" => Ok(format!("KILL CONNECTION {}", pid)),
+        "sqlite" => Err(anyhow!("SQLite has no server sessions")),
+        _ => Err(anyhow!("Unknown database type")),
+    }
+}
+
+/// Collects (table, column) rows, already in column order, into table -> columns
+fn group_columns_by_table(rows: Vec<(String, String)>) -> BTreeMap<String, Vec<String>> {
+    let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (table, column) in rows {
+        tables.entry(table).or_default().push(column);
+    }
+    tables
+}
+
+/// Appends one driver row to an `ArrowBatchBuilder`. Numbers and booleans are decoded
+/// straight into their Arrow column; other cells go through the backend's cell macro.
+macro_rules! append_arrow_row {
+    ($builder:expr, $row:expr, $tz:expr, $cell_macro:ident) => {{
+        let builder: &mut ArrowBatchBuilder = $builder;
+        for i in 0..$row.columns().len() {
+            let is_null = Row::try_get_raw($row, i)
+                .map(|v| v.is_null())
+                .unwrap_or(true);
+            match builder.data_type(i) {
+                _ if is_null => builder.append_value(i, Value::Null),
+                DataType::Int64 => match $row
+                    .try_get::<i64, usize>(i)
+                    .ok()
+                    .or_else(|| $row.try_get::<i32, usize>(i).ok().map(i64::from))
+                    .or_else(|| $row.try_get::<i16, usize>(i).ok().map(i64::from))
+                {
+                    Some(n) => builder.append_i64(i, n),
+                    None => builder.append_value(i, $cell_macro!($row, i, $tz)),
+                },
+                DataType::Float64 => match $row.try_get::<f64, usize>(i) {
+                    Ok(f) => builder.append_f64(i, f),
+                    Err(_) => builder.append_value(i, $cell_macro!($row, i, $tz)),
+                },
+                DataType::Boolean => match $row.try_get::<bool, usize>(i) {
+                    Ok(b) => builder.append_bool(i, b),
+                    Err(_) => builder.append_value(i, $cell_macro!($row, i, $tz)),
+                },
+                _ => builder.append_value(i, $cell_macro!($row, i, $tz)),
+            }
+        }
+        builder.end_row();
+    }};
+}
+
+/// Rows of a streaming query waiting to be sent. In the `arrow` format they are built
+/// into one record batch at a time of a single IPC stream, whose schema goes out once.
+enum StreamingRows {
+    Json(Vec<Vec<Value>>),
+    Arrow(ArrowStreamEncoder, ArrowBatchBuilder),
+}
+
+impl StreamingRows {
+    fn len(&self) -> usize {
+        match self {
+            StreamingRows::Json(rows) => rows.len(),
+            StreamingRows::Arrow(_, builder) => builder.len(),
+        }
+    }
+
+    /// Sends the pending rows on the `query-batch` or `query-arrow-batch` event
+    fn flush<R: Runtime>(&mut self, window: &tauri::Window<R>, query_id: Uuid) -> Result<()> {
+        match self {
+            StreamingRows::Json(rows) => {
+                if !rows.is_empty() {
+                    let rows = std::mem::take(rows);
+                    window.emit("query-batch", StreamingBatch { query_id, rows })?;
+                }
+            }
+            StreamingRows::Arrow(encoder, builder) => {
+                if !builder.is_empty() {
+                    let next = encoder.batch_builder(builder.len());
+                    let data = encoder.encode(std::mem::replace(builder, next))?;
+                    emit_arrow_chunk(window, query_id, data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the remaining rows and, for Arrow, the end of the IPC stream
+    fn finish<R: Runtime>(mut self, window: &tauri::Window<R>, query_id: Uuid) -> Result<()> {
+        self.flush(window, query_id)?;
+        if let StreamingRows::Arrow(encoder, _) = self {
+            emit_arrow_chunk(window, query_id, encoder.finish()?)?;
+        }
+        Ok(())
+    }
+}
+
+fn emit_arrow_chunk<R: Runtime>(
+    window: &tauri::Window<R>,
+    query_id: Uuid,
+    data: Vec<u8>,
+) -> Result<()> {
+    window.emit(
+        "query-arrow-batch",
+        StreamingArrowBatch {
+            query_id,
+            data: BASE64_STANDARD.encode(data),
+        },
+    )?;
+    Ok(())
+}
+
+fn csv_field(value: Value) -> String {
+    match value {
+        Value::Null => "".to_string(),
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Format settings for `RowFileWriter`
+struct RowFileOptions<'a> {
+    delimiter: Option<char>,
+    excel_bom: bool,
+    csv_quoting: &'a CsvQuoting,
+    json_null_mode: &'a JsonNullMode,
+    /// Table and dialect of the INSERT statements written by the `sql` format
+    sql_target: Option<(&'a str, &'a str)>,
+}
+
+/// Incremental CSV, JSON-array or INSERT-statement output used by `stream_rows_to_file`
+enum RowFileWriter {
+    Csv(csv::Writer<File>),
+    /// `keys` are the column names made unique by `write_header`
+    Json {
+        writer: BufWriter<File>,
+        keys: Vec<String>,
+        null_mode: JsonNullMode,
+    },
+    /// `insert` is the statement up to VALUES, built by `write_header`
+    Sql {
+        writer: BufWriter<File>,
+        table: String,
+        db_type: String,
+        insert: String,
+    },
+}
+
+impl RowFileWriter {
+    fn create(format: &str, file_path: &str, options: &RowFileOptions) -> Result<Self> {
+        match format {
+            "csv" => Ok(Self::Csv(csv_writer(
+                File::create(file_path)?,
+                options.delimiter,
+                options.excel_bom,
+                options.csv_quoting,
+            )?)),
+            "json" => {
+                let mut writer = BufWriter::new(File::create(file_path)?);
+                writer.write_all(b"[\n")?;
+                Ok(Self::Json {
+                    writer,
+                    keys: Vec::new(),
+                    null_mode: options.json_null_mode.clone(),
+                })
+            }
+            "sql" => {
+                let (table, db_type) = options
+                    .sql_target
+                    .ok_or_else(|| anyhow!("SQL exports need a target table"))?;
+                Ok(Self::Sql {
+                    writer: BufWriter::new(File::create(file_path)?),
+                    table: quote_ident(table, db_type),
+                    db_type: db_type.to_string(),
+                    insert: String::new(),
+                })
+            }
+            _ => Err(anyhow!("Unsupported export format")),
+        }
+    }
+
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        match self {
+            Self::Csv(writer) => writer.write_record(columns)?,
+            Self::Json { keys, .. } => *keys = unique_column_names(columns),
+            Self::Sql {
+                table,
+                db_type,
+                insert,
+                ..
+            } => {
+                *insert = format!(
+                    "INSERT INTO {} ({}) VALUES",
+                    table,
+                    columns
+                        .iter()
+                        .map(|c| quote_ident(c, db_type))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: Vec<Value>, index: u64) -> Result<()> {
+        match self {
+            Self::Csv(writer) => writer.write_record(row.into_iter().map(csv_field))?,
+            Self::Json {
+                writer,
+                keys,
+                null_mode,
+            } => {
+                if index > 0 {
+                    writer.write_all(b",\n")?;
+                }
+                let mut obj = serde_json::Map::new();
+                for (key, value) in keys.iter().cloned().zip(row) {
+                    null_mode.insert(&mut obj, key, value);
+                }
+                serde_json::to_writer(&mut *writer, &Value::Object(obj))?;
+            }
+            Self::Sql { writer, insert, .. } => {
+                let values: Vec<String> = row
+                    .into_iter()
+                    .map(|v| match v {
+                        Value::Null => "NULL".to_string(),
+                        Value::String(s) => format!("'{}'", s.replace("'", "''")),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => if b { "true" } else { "false" }.to_string(),
+                        _ => format!("'{}'", v.to_string().replace("'", "''")),
+                    })
+                    .collect();
+                writeln!(writer, "{} ({});", insert, values.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Csv(mut writer) => writer.flush()?,
+            Self::Json { mut writer, .. } => {
+                writer.write_all(b"\n]\n")?;
+                writer.flush()?;
+            }
+            Self::Sql { mut writer, .. } => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
 pub struct QueryEngine;
 
 impl QueryEngine {
-    pub async fn execute_query_streaming(
+    /// On Postgres, plain SELECTs are read through a server-side cursor `fetch_size` rows
+    /// at a time (default `DEFAULT_PG_FETCH_SIZE`, 0 turns the cursor off) so neither side
+    /// holds more than one fetch of a huge result.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_query_streaming<R: Runtime>(
         manager: &ConnectionManager,
         connection_id: &Uuid,
         sql: &str,
         query_id: Uuid,
-        window: &tauri::Window,
+        window: &tauri::Window<R>,
         token: CancellationToken,
+        display_timezone: Option<&str>,
+        format: Option<&str>,
+        fetch_size: Option<u32>,
     ) -> Result<()> {
         let start = Instant::now();
+        let tz = parse_display_timezone(display_timezone)?;
+        let tagged_sql = manager.tag_query(connection_id, sql).await;
+        let sql = tagged_sql.as_str();
+        let arrow = match format {
+            None | Some("json") => false,
+            Some("arrow") => true,
+            Some(other) => return Err(anyhow!("Unsupported result format: {}", other)),
+        };
         use futures::StreamExt;
 
         macro_rules! stream_db {
-            ($pool:expr, $db_macro:ident) => {{
+            ($pool:expr, $stream:expr, $db_macro:ident, $cell_macro:ident) => {{
                 use sqlx::Either;
-                let mut stream = sqlx::raw_sql(sql).fetch_many($pool);
+                let mut stream = $stream;
                 let mut columns_sent = false;
-                let mut batch = Vec::new();
+                let mut pending = StreamingRows::Json(Vec::new());
                 let mut total_rows = 0u64;
                 let mut affected_rows = 0u64;
                 let batch_size = 1000;
 
-                while let Some(res_result) = StreamExt::next(&mut stream).await {
+                // Stats come from a timer rather than the row loop, so they keep
+                // arriving while the server is still working on the first row
+                let mut stats_timer = tokio::time::interval_at(
+                    tokio::time::Instant::now() + QUERY_STATS_INTERVAL,
+                    QUERY_STATS_INTERVAL,
+                );
+                stats_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                let mut last_stats = (start, 0u64);
+
+                loop {
+                    let res_result = tokio::select! {
+                        res = StreamExt::next(&mut stream) => match res {
+                            Some(res) => res,
+                            None => break,
+                        },
+                        _ = stats_timer.tick() => {
+                            let (last_at, last_rows) = last_stats;
+                            let secs = last_at.elapsed().as_secs_f64();
+                            window.emit(
+                                "query-stats",
+                                StreamingStats {
+                                    query_id,
+                                    elapsed_ms: start.elapsed().as_millis() as u64,
+                                    total_rows,
+                                    rows_per_sec: if secs > 0.0 {
+                                        (total_rows - last_rows) as f64 / secs
+                                    } else {
+                                        0.0
+                                    },
+                                },
+                            )?;
+                            last_stats = (Instant::now(), total_rows);
+                            continue;
+                        }
+                    };
                     if token.is_cancelled() {
                         return Ok(());
                     }
@@ -455,55 +1930,54 @@ impl QueryEngine {
                                     .iter()
                                     .map(|c| Column::name(c).to_string())
                                     .collect::<Vec<String>>();
+                                let column_types = column_type_names(row.columns());
+                                if arrow {
+                                    let encoder = ArrowStreamEncoder::new(&columns, &column_types)?;
+                                    let builder = encoder.batch_builder(batch_size);
+                                    pending = StreamingRows::Arrow(encoder, builder);
+                                }
                                 window.emit(
                                     "query-metadata",
-                                    StreamingMetadata { query_id, columns },
+                                    StreamingMetadata {
+                                        query_id,
+                                        columns,
+                                        column_types,
+                                    },
                                 )?;
                                 columns_sent = true;
                             }
 
-                            batch.push($db_macro!(&row));
+                            match &mut pending {
+                                StreamingRows::Json(rows) => rows.push($db_macro!(&row, tz)),
+                                StreamingRows::Arrow(_, builder) => {
+                                    append_arrow_row!(builder, &row, tz, $cell_macro)
+                                }
+                            }
                             total_rows += 1;
 
-                            if batch.len() >= batch_size {
-                                window.emit(
-                                    "query-batch",
-                                    StreamingBatch {
-                                        query_id,
-                                        rows: batch.clone(),
-                                    },
-                                )?;
-                                batch.clear();
+                            if pending.len() >= batch_size {
+                                pending.flush(window, query_id)?;
                                 sleep(Duration::from_millis(5)).await;
                             }
                         }
                     }
                 }
 
-                if !columns_sent {
-                    let trimmed = sql.trim().to_uppercase();
-                    if trimmed.starts_with("SELECT") || trimmed.starts_with("WITH") {
-                        if let Ok(stmt) = Executor::prepare($pool, sql).await {
-                            let columns = stmt
-                                .columns()
-                                .iter()
-                                .map(|c| Column::name(c).to_string())
-                                .collect::<Vec<String>>();
-                            window
-                                .emit("query-metadata", StreamingMetadata { query_id, columns })?;
-                        }
+                if !columns_sent && returns_rows(sql) {
+                    let (columns, column_types) = describe_columns!($pool, sql);
+                    if !columns.is_empty() {
+                        window.emit(
+                            "query-metadata",
+                            StreamingMetadata {
+                                query_id,
+                                columns,
+                                column_types,
+                            },
+                        )?;
                     }
                 }
 
-                if !batch.is_empty() {
-                    window.emit(
-                        "query-batch",
-                        StreamingBatch {
-                            query_id,
-                            rows: batch,
-                        },
-                    )?;
-                }
+                pending.finish(window, query_id)?;
 
                 window.emit(
                     "query-complete",
@@ -523,7 +1997,56 @@ impl QueryEngine {
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                stream_db!(pool, postgres_row_to_values);
+                let fetch_size = fetch_size.unwrap_or(DEFAULT_PG_FETCH_SIZE);
+                if fetch_size > 0 && is_cursor_query(sql) {
+                    let mut tx = pool.begin().await?;
+                    let declare = format!(
+                        "DECLARE __sqlmate_cursor NO SCROLL CURSOR FOR {}",
+                        trim_statement_end(sql)
+                    );
+                    // Statements a cursor can't hold (e.g. SELECT ... INTO) fall back to
+                    // the plain stream below, which also reports any real error
+                    if sqlx::query(&declare).execute(&mut *tx).await.is_ok() {
+                        type CursorItem = std::result::Result<
+                            sqlx::Either<sqlx::postgres::PgQueryResult, sqlx::postgres::PgRow>,
+                            sqlx::Error,
+                        >;
+                        let fetch_sql =
+                            format!("FETCH FORWARD {} FROM __sqlmate_cursor", fetch_size);
+                        let batches = futures::stream::try_unfold(Some(tx), move |tx| {
+                            let fetch_sql = fetch_sql.clone();
+                            async move {
+                                let Some(mut tx) = tx else {
+                                    return Ok(None);
+                                };
+                                let rows = sqlx::query(&fetch_sql).fetch_all(&mut *tx).await?;
+                                // A short fetch means the cursor is exhausted
+                                let tx = if rows.len() < fetch_size as usize {
+                                    tx.commit().await?;
+                                    None
+                                } else {
+                                    Some(tx)
+                                };
+                                let rows = rows
+                                    .into_iter()
+                                    .map(|row| -> CursorItem { Ok(sqlx::Either::Right(row)) });
+                                Ok::<_, sqlx::Error>(Some((futures::stream::iter(rows), tx)))
+                            }
+                        });
+                        stream_db!(
+                            pool,
+                            Box::pin(futures::TryStreamExt::try_flatten(batches)),
+                            postgres_row_to_values,
+                            postgres_cell_value
+                        );
+                    }
+                }
+                stream_db!(
+                    pool,
+                    sqlx::raw_sql(sql).fetch_many(pool),
+                    postgres_row_to_values,
+                    postgres_cell_value
+                );
             }
         }
 
@@ -531,7 +2054,12 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                stream_db!(pool, mysql_row_to_values);
+                stream_db!(
+                    pool,
+                    sqlx::raw_sql(sql).fetch_many(pool),
+                    mysql_row_to_values,
+                    mysql_cell_value
+                );
             }
         }
 
@@ -539,7 +2067,12 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                stream_db!(pool, sqlite_row_to_values);
+                stream_db!(
+                    pool,
+                    sqlx::raw_sql(sql).fetch_many(pool),
+                    sqlite_row_to_values,
+                    sqlite_cell_value
+                );
             }
         }
 
@@ -552,8 +2085,116 @@ impl QueryEngine {
         sql: &str,
         page: Option<u32>,
         page_size: Option<u32>,
+        display_timezone: Option<&str>,
+        auto_limit: Option<u32>,
+    ) -> Result<QueryResult> {
+        let tz = parse_display_timezone(display_timezone)?;
+
+        // Fetch one extra row so truncation is only reported when more rows exist
+        let limited_sql = match auto_limit {
+            Some(limit) if page.is_none() && needs_auto_limit(sql) => Some((
+                format!("{} LIMIT {}", trim_statement_end(sql), limit as u64 + 1),
+                limit as usize,
+            )),
+            _ => None,
+        };
+        let exec_sql = limited_sql.as_ref().map_or(sql, |(s, _)| s.as_str());
+        let tagged_sql = manager.tag_query(connection_id, exec_sql).await;
+        let exec_sql = tagged_sql.as_str();
+        let max_rows = manager.max_result_rows(connection_id).await;
+
+        let result = Self::execute_query_once(
+            manager,
+            connection_id,
+            exec_sql,
+            page,
+            page_size,
+            tz,
+            max_rows,
+        )
+        .await;
+
+        // Only row-returning statements are retried; a mutation may already have been applied
+        let mut result = match result {
+            Err(e) if is_connection_error(&e) && returns_rows(sql) => {
+                manager.reconnect(connection_id).await?;
+                Self::execute_query_once(
+                    manager,
+                    connection_id,
+                    exec_sql,
+                    page,
+                    page_size,
+                    tz,
+                    max_rows,
+                )
+                .await
+            }
+            other => other,
+        }?;
+
+        if let Some((_, limit)) = limited_sql {
+            if result.rows.len() > limit {
+                result.rows.truncate(limit);
+                result.truncated = true;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Separates planning from execution time using `EXPLAIN (ANALYZE, FORMAT JSON)`.
+    ///
+    /// This runs the statement a second time, so volatile functions in it run twice. It is
+    /// therefore limited to plain SELECTs on Postgres and runs in a read-only transaction
+    /// that is rolled back: a function that writes fails the timing instead of writing
+    /// again. Other backends and statements yield `None`.
+    pub async fn server_timing(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+    ) -> Result<Option<ServerTiming>> {
+        if !is_plain_select(sql) {
+            return Ok(None);
+        }
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let explain = format!("EXPLAIN (ANALYZE, FORMAT JSON) {}", trim_statement_end(sql));
+                let mut tx = pool.begin().await?;
+                sqlx::query("SET TRANSACTION READ ONLY")
+                    .execute(&mut *tx)
+                    .await?;
+                let row = sqlx::query(&explain).fetch_one(&mut *tx).await?;
+                tx.rollback().await?;
+                let plan: Value = row.try_get(0)?;
+                return Ok(plan.get(0).and_then(|p| {
+                    Some(ServerTiming {
+                        planning_time_ms: p.get("Planning Time")?.as_f64()?,
+                        execution_time_ms: p.get("Execution Time")?.as_f64()?,
+                    })
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reading stops at `max_rows` for a single statement. In a multi-statement batch the
+    /// rows past it are read but not kept, so later statements still run and report their
+    /// affected rows.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_query_once(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        tz: Option<chrono_tz::Tz>,
+        max_rows: Option<usize>,
     ) -> Result<QueryResult> {
         let start = Instant::now();
+        let single_statement = split_statements(sql, false).len() <= 1;
         let mut total_count = None;
         let mut final_sql = sql.to_string();
 
@@ -567,6 +2208,22 @@ impl QueryEngine {
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
+                if !returns_rows(sql) {
+                    let result = sqlx::raw_sql(sql).execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page,
+                        page_size,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
                 // Get count if requested
                 if page.is_some() {
                     let c_sql = wrap_count(sql);
@@ -581,7 +2238,9 @@ impl QueryEngine {
                 let mut stream = sqlx::raw_sql(&final_sql).fetch_many(pool);
                 let mut result_rows = Vec::new();
                 let mut columns = Vec::new();
+                let mut column_types = Vec::new();
                 let mut affected_rows = 0;
+                let mut truncated = false;
 
                 while let Some(res) = StreamExt::next(&mut stream).await {
                     match res? {
@@ -595,34 +2254,36 @@ impl QueryEngine {
                                     .iter()
                                     .map(|c| Column::name(c).to_string())
                                     .collect::<Vec<String>>();
+                                column_types = column_type_names(row.columns());
                             }
-                            result_rows.push(postgres_row_to_values!(&row));
+                            if max_rows.is_some_and(|max| result_rows.len() >= max) {
+                                truncated = true;
+                                if single_statement {
+                                    break;
+                                }
+                                continue;
+                            }
+                            result_rows.push(postgres_row_to_values!(&row, tz));
                         }
                     }
                 }
 
-                // Fallback for empty SELECT columns
+                // No rows came back, so take the headers from the statement itself
                 if columns.is_empty() {
-                    let trimmed = sql.trim().to_uppercase();
-                    if trimmed.starts_with("SELECT") || trimmed.starts_with("WITH") {
-                        if let Ok(stmt) = Executor::prepare(pool, sql).await {
-                            columns = stmt
-                                .columns()
-                                .iter()
-                                .map(|c| Column::name(c).to_string())
-                                .collect::<Vec<String>>();
-                        }
-                    }
+                    (columns, column_types) = describe_columns!(pool, sql);
                 }
 
                 return Ok(QueryResult {
                     columns,
+                    column_types,
                     rows: result_rows,
                     affected_rows,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                     total_count,
                     page,
                     page_size,
+                    truncated,
+                    server_timing: None,
                 });
             }
         }
@@ -631,6 +2292,22 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
+                if !returns_rows(sql) {
+                    let result = sqlx::raw_sql(sql).execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page,
+                        page_size,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
                 if page.is_some() {
                     let c_sql = wrap_count(sql);
                     if !c_sql.is_empty() {
@@ -644,7 +2321,9 @@ impl QueryEngine {
                 let mut stream = sqlx::raw_sql(&final_sql).fetch_many(pool);
                 let mut result_rows = Vec::new();
                 let mut columns = Vec::new();
+                let mut column_types = Vec::new();
                 let mut affected_rows = 0;
+                let mut truncated = false;
 
                 while let Some(res) = StreamExt::next(&mut stream).await {
                     match res? {
@@ -658,34 +2337,36 @@ impl QueryEngine {
                                     .iter()
                                     .map(|c| Column::name(c).to_string())
                                     .collect::<Vec<String>>();
+                                column_types = column_type_names(row.columns());
                             }
-                            result_rows.push(mysql_row_to_values!(&row));
+                            if max_rows.is_some_and(|max| result_rows.len() >= max) {
+                                truncated = true;
+                                if single_statement {
+                                    break;
+                                }
+                                continue;
+                            }
+                            result_rows.push(mysql_row_to_values!(&row, tz));
                         }
                     }
                 }
 
-                // Fallback for empty SELECT columns
+                // No rows came back, so take the headers from the statement itself
                 if columns.is_empty() {
-                    let trimmed = sql.trim().to_uppercase();
-                    if trimmed.starts_with("SELECT") || trimmed.starts_with("WITH") {
-                        if let Ok(stmt) = Executor::prepare(pool, sql).await {
-                            columns = stmt
-                                .columns()
-                                .iter()
-                                .map(|c| Column::name(c).to_string())
-                                .collect::<Vec<String>>();
-                        }
-                    }
+                    (columns, column_types) = describe_columns!(pool, sql);
                 }
 
                 return Ok(QueryResult {
                     columns,
+                    column_types,
                     rows: result_rows,
                     affected_rows,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                     total_count,
                     page,
                     page_size,
+                    truncated,
+                    server_timing: None,
                 });
             }
         }
@@ -694,6 +2375,79 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
+                // Scripts run one statement at a time in a transaction, so a failing
+                // statement leaves none of the script applied. The last statement that
+                // returned rows provides the result set.
+                if let Some(statements) = sqlite_script_statements(sql) {
+                    let mut tx = pool.begin().await?;
+                    let mut result_rows = Vec::new();
+                    let mut columns = Vec::new();
+                    let mut column_types = Vec::new();
+                    let mut affected_rows = 0;
+                    let mut truncated = false;
+
+                    for statement in &statements {
+                        use sqlx::Either;
+                        let mut stream = sqlx::raw_sql(statement).fetch_many(&mut *tx);
+                        let mut has_rows = false;
+                        while let Some(res) = StreamExt::next(&mut stream).await {
+                            match res? {
+                                Either::Left(result) => {
+                                    affected_rows += result.rows_affected();
+                                }
+                                Either::Right(row) => {
+                                    if !has_rows {
+                                        has_rows = true;
+                                        columns = row
+                                            .columns()
+                                            .iter()
+                                            .map(|c| Column::name(c).to_string())
+                                            .collect::<Vec<String>>();
+                                        column_types = column_type_names(row.columns());
+                                        result_rows.clear();
+                                        truncated = false;
+                                    }
+                                    if max_rows.is_some_and(|max| result_rows.len() >= max) {
+                                        truncated = true;
+                                        continue;
+                                    }
+                                    result_rows.push(sqlite_row_to_values!(&row, tz));
+                                }
+                            }
+                        }
+                    }
+                    tx.commit().await?;
+
+                    return Ok(QueryResult {
+                        columns,
+                        column_types,
+                        rows: result_rows,
+                        affected_rows,
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page,
+                        page_size,
+                        truncated,
+                        server_timing: None,
+                    });
+                }
+
+                if !returns_rows(sql) {
+                    let result = sqlx::raw_sql(sql).execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page,
+                        page_size,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
                 if page.is_some() {
                     let c_sql = wrap_count(sql);
                     if !c_sql.is_empty() {
@@ -707,7 +2461,9 @@ impl QueryEngine {
                 let mut stream = sqlx::raw_sql(&final_sql).fetch_many(pool);
                 let mut result_rows = Vec::new();
                 let mut columns = Vec::new();
+                let mut column_types = Vec::new();
                 let mut affected_rows = 0;
+                let mut truncated = false;
 
                 while let Some(res) = StreamExt::next(&mut stream).await {
                     match res? {
@@ -721,34 +2477,36 @@ impl QueryEngine {
                                     .iter()
                                     .map(|c| Column::name(c).to_string())
                                     .collect::<Vec<String>>();
+                                column_types = column_type_names(row.columns());
                             }
-                            result_rows.push(sqlite_row_to_values!(&row));
+                            if max_rows.is_some_and(|max| result_rows.len() >= max) {
+                                truncated = true;
+                                if single_statement {
+                                    break;
+                                }
+                                continue;
+                            }
+                            result_rows.push(sqlite_row_to_values!(&row, tz));
                         }
                     }
                 }
 
-                // Fallback for empty SELECT columns
+                // No rows came back, so take the headers from the statement itself
                 if columns.is_empty() {
-                    let trimmed = sql.trim().to_uppercase();
-                    if trimmed.starts_with("SELECT") || trimmed.starts_with("WITH") {
-                        if let Ok(stmt) = Executor::prepare(pool, sql).await {
-                            columns = stmt
-                                .columns()
-                                .iter()
-                                .map(|c| Column::name(c).to_string())
-                                .collect::<Vec<String>>();
-                        }
-                    }
+                    (columns, column_types) = describe_columns!(pool, sql);
                 }
 
                 return Ok(QueryResult {
                     columns,
+                    column_types,
                     rows: result_rows,
                     affected_rows,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                     total_count,
                     page,
                     page_size,
+                    truncated,
+                    server_timing: None,
                 });
             }
         }
@@ -756,18 +2514,64 @@ impl QueryEngine {
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn create_database(
+    /// Execute a statement with `?`/`$n` placeholders bound to JSON values,
+    /// so callers never splice user input into SQL text
+    pub async fn execute_parameterized(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-        db_name: &str,
-    ) -> Result<()> {
+        sql: &str,
+        params: Vec<Value>,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let tagged_sql = manager.tag_query(connection_id, sql).await;
+        let sql = tagged_sql.as_str();
+
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let sql = format!("CREATE DATABASE \"{}\"", db_name);
-                sqlx::query(&sql).execute(pool).await?;
-                return Ok(());
+                let query = bind_json_params!(sqlx::query(sql), &params);
+                if !returns_rows(sql) {
+                    let result = query.execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page: None,
+                        page_size: None,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
+                let rows = query.fetch_all(pool).await?;
+                let columns = match rows.first() {
+                    Some(row) => row
+                        .columns()
+                        .iter()
+                        .map(|c| Column::name(c).to_string())
+                        .collect(),
+                    None => describe_columns!(pool, sql).0,
+                };
+                let rows = rows
+                    .iter()
+                    .map(|row| postgres_row_to_values!(row))
+                    .collect();
+                return Ok(QueryResult {
+                    columns,
+                    column_types: Vec::new(),
+                    rows,
+                    affected_rows: 0,
+                    execution_time_ms: start.elapsed().as_millis() as u64,
+                    total_count: None,
+                    page: None,
+                    page_size: None,
+                    truncated: false,
+                    server_timing: None,
+                });
             }
         }
 
@@ -775,38 +2579,329 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let sql = format!("CREATE DATABASE `{}`", db_name);
-                sqlx::query(&sql).execute(pool).await?;
-                return Ok(());
+                let query = bind_json_params!(sqlx::query(sql), &params);
+                if !returns_rows(sql) {
+                    let result = query.execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page: None,
+                        page_size: None,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
+                let rows = query.fetch_all(pool).await?;
+                let columns = match rows.first() {
+                    Some(row) => row
+                        .columns()
+                        .iter()
+                        .map(|c| Column::name(c).to_string())
+                        .collect(),
+                    None => describe_columns!(pool, sql).0,
+                };
+                let rows = rows.iter().map(|row| mysql_row_to_values!(row)).collect();
+                return Ok(QueryResult {
+                    columns,
+                    column_types: Vec::new(),
+                    rows,
+                    affected_rows: 0,
+                    execution_time_ms: start.elapsed().as_millis() as u64,
+                    total_count: None,
+                    page: None,
+                    page_size: None,
+                    truncated: false,
+                    server_timing: None,
+                });
             }
         }
 
         // Check SQLite
         {
             let pools = manager.get_sqlite_pools().await;
-            if let Some(_) = pools.get(connection_id) {
-                return Err(anyhow!("Creation of new databases in SQLite is not supported via this command. Please create a new connection for a different SQLite file."));
+            if let Some(pool) = pools.get(connection_id) {
+                let query = bind_json_params!(sqlx::query(sql), &params);
+                if !returns_rows(sql) {
+                    let result = query.execute(pool).await?;
+                    return Ok(QueryResult {
+                        columns: Vec::new(),
+                        column_types: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: result.rows_affected(),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        total_count: None,
+                        page: None,
+                        page_size: None,
+                        truncated: false,
+                        server_timing: None,
+                    });
+                }
+
+                let rows = query.fetch_all(pool).await?;
+                let columns = match rows.first() {
+                    Some(row) => row
+                        .columns()
+                        .iter()
+                        .map(|c| Column::name(c).to_string())
+                        .collect(),
+                    None => describe_columns!(pool, sql).0,
+                };
+                let rows = rows.iter().map(|row| sqlite_row_to_values!(row)).collect();
+                return Ok(QueryResult {
+                    columns,
+                    column_types: Vec::new(),
+                    rows,
+                    affected_rows: 0,
+                    execution_time_ms: start.elapsed().as_millis() as u64,
+                    total_count: None,
+                    page: None,
+                    page_size: None,
+                    truncated: false,
+                    server_timing: None,
+                });
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_databases(
+    pub async fn begin_transaction(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-    ) -> Result<Vec<String>> {
+    ) -> Result<Uuid> {
+        let tx = match detect_db_type(manager, connection_id).await? {
+            "postgres" => {
+                let pools = manager.get_postgres_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                UserTransaction::Postgres(pool.begin().await?)
+            }
+            "mysql" => {
+                let pools = manager.get_mysql_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                UserTransaction::MySql(pool.begin().await?)
+            }
+            _ => {
+                let pools = manager.get_sqlite_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                UserTransaction::Sqlite(pool.begin().await?)
+            }
+        };
+        Ok(manager.add_transaction(*connection_id, tx).await)
+    }
+
+    pub async fn execute_in_transaction(
+        manager: &ConnectionManager,
+        tx_id: &Uuid,
+        sql: &str,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let handle = manager
+            .get_transaction(tx_id)
+            .await
+            .ok_or_else(|| anyhow!("Transaction not found"))?;
+        let mut guard = handle.lock().await;
+        let tx = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("Transaction has already finished"))?;
+
+        let (columns, rows, affected_rows) = match tx {
+            UserTransaction::Postgres(tx) => run_in_transaction!(tx, sql, postgres_row_to_values),
+            UserTransaction::MySql(tx) => run_in_transaction!(tx, sql, mysql_row_to_values),
+            UserTransaction::Sqlite(tx) => run_in_transaction!(tx, sql, sqlite_row_to_values),
+        };
+
+        Ok(QueryResult {
+            columns,
+            column_types: Vec::new(),
+            rows,
+            affected_rows,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            total_count: None,
+            page: None,
+            page_size: None,
+            truncated: false,
+            server_timing: None,
+        })
+    }
+
+    pub async fn commit_transaction(manager: &ConnectionManager, tx_id: &Uuid) -> Result<()> {
+        match Self::take_transaction(manager, tx_id).await? {
+            UserTransaction::Postgres(tx) => tx.commit().await?,
+            UserTransaction::MySql(tx) => tx.commit().await?,
+            UserTransaction::Sqlite(tx) => tx.commit().await?,
+        }
+        Ok(())
+    }
+
+    pub async fn rollback_transaction(manager: &ConnectionManager, tx_id: &Uuid) -> Result<()> {
+        match Self::take_transaction(manager, tx_id).await? {
+            UserTransaction::Postgres(tx) => tx.rollback().await?,
+            UserTransaction::MySql(tx) => tx.rollback().await?,
+            UserTransaction::Sqlite(tx) => tx.rollback().await?,
+        }
+        Ok(())
+    }
+
+    /// Waits for any statement in flight, then unregisters the transaction
+    async fn take_transaction(
+        manager: &ConnectionManager,
+        tx_id: &Uuid,
+    ) -> Result<UserTransaction> {
+        let handle = manager
+            .get_transaction(tx_id)
+            .await
+            .ok_or_else(|| anyhow!("Transaction not found"))?;
+        let tx = handle.lock().await.take();
+        manager.remove_transaction(tx_id).await;
+        tx.ok_or_else(|| anyhow!("Transaction has already finished"))
+    }
+
+    /// Declares a cursor for `sql` and registers it with the manager, returning its id.
+    /// Only supported on Postgres.
+    pub async fn open_result_cursor(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+    ) -> Result<Uuid> {
+        if !is_wrappable_query(sql) {
+            return Err(anyhow!("Only SELECT queries can be opened as a cursor"));
+        }
+
+        if detect_db_type(manager, connection_id).await? != "postgres" {
+            return Err(anyhow!("Result cursors are only supported on PostgreSQL"));
+        }
+
+        // Checked before BEGIN so a refused cursor never takes a pooled connection
+        manager.ensure_cursor_capacity(connection_id).await?;
+        let cursor = {
+            let pools = manager.get_postgres_pools().await;
+            let pool = pools
+                .get(connection_id)
+                .ok_or_else(|| anyhow!("Connection not found"))?;
+            let mut tx = pool.begin().await?;
+            let name = format!("sqlmate_cursor_{}", Uuid::new_v4().simple());
+            let declare = format!(
+                "DECLARE {} NO SCROLL CURSOR FOR {}",
+                name,
+                trim_statement_end(sql)
+            );
+            sqlx::raw_sql(&declare).execute(&mut *tx).await?;
+            ResultCursor {
+                tx,
+                name,
+                columns: Vec::new(),
+            }
+        };
+
+        manager.add_cursor(*connection_id, cursor).await
+    }
+
+    /// Fetches the next `count` rows; fewer rows than requested means the cursor is exhausted.
+    pub async fn fetch_cursor(
+        manager: &ConnectionManager,
+        cursor_id: &Uuid,
+        count: u32,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let cursor = manager
+            .get_cursor(cursor_id)
+            .await
+            .ok_or_else(|| anyhow!("Cursor not found"))?;
+        let mut cursor = cursor.lock().await;
+
+        // The simple protocol returns text-format rows, as the streaming path reads them,
+        // and each row is converted as it arrives rather than buffered as a whole page
+        use sqlx::Either;
+        let sql = format!("FETCH FORWARD {} FROM {}", count, cursor.name);
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+        {
+            let mut stream = sqlx::raw_sql(&sql).fetch_many(&mut *cursor.tx);
+            while let Some(res) = StreamExt::next(&mut stream).await {
+                if let Either::Right(row) = res? {
+                    if columns.is_empty() {
+                        columns = row
+                            .columns()
+                            .iter()
+                            .map(|c| Column::name(c).to_string())
+                            .collect();
+                    }
+                    rows.push(postgres_row_to_values!(&row));
+                }
+            }
+        }
+        if !columns.is_empty() {
+            cursor.columns = columns;
+        }
+
+        Ok(QueryResult {
+            columns: cursor.columns.clone(),
+            column_types: Vec::new(),
+            rows,
+            affected_rows: 0,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            total_count: None,
+            page: None,
+            page_size: Some(count),
+            truncated: false,
+            server_timing: None,
+        })
+    }
+
+    pub async fn close_cursor(manager: &ConnectionManager, cursor_id: &Uuid) -> Result<()> {
+        let cursor = manager
+            .remove_cursor(cursor_id)
+            .await
+            .ok_or_else(|| anyhow!("Cursor not found"))?;
+        // A fetch still in flight keeps its own handle; dropping that rolls back instead
+        if let Ok(cursor) = Arc::try_unwrap(cursor) {
+            cursor.into_inner().tx.rollback().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the raw bytes of one cell (e.g. an image blob) to `file_path`.
+    /// Returns the number of bytes written.
+    pub async fn export_cell_bytes(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+        row_index: usize,
+        column: &str,
+        file_path: &str,
+    ) -> Result<u64> {
+        let bytes = Self::fetch_cell_bytes(manager, connection_id, sql, row_index, column).await?;
+        std::fs::write(file_path, &bytes)?;
+        Ok(bytes.len() as u64)
+    }
+
+    async fn fetch_cell_bytes(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+        row_index: usize,
+        column: &str,
+    ) -> Result<Vec<u8>> {
+        let not_found = || anyhow!("Row {} not found in result", row_index);
+
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                // List databases. Removed datallowconn filter to match TablePlus behavior.
-                let sql = "SELECT datname::text FROM pg_database WHERE datistemplate = false ORDER BY datname;";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                return Ok(rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(0).ok())
-                    .collect());
+                let mut rows = sqlx::query(sql).fetch(pool).skip(row_index);
+                let row = rows.next().await.ok_or_else(not_found)??;
+                return cell_bytes!(row, column);
             }
         }
 
@@ -814,12 +2909,9 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let sql = "SHOW DATABASES;";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                return Ok(rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(0).ok())
-                    .collect());
+                let mut rows = sqlx::query(sql).fetch(pool).skip(row_index);
+                let row = rows.next().await.ok_or_else(not_found)??;
+                return cell_bytes!(row, column);
             }
         }
 
@@ -827,36 +2919,73 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                // SQLite usually has one main database, but we can list attached ones
-                let sql = "PRAGMA database_list;";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                return Ok(rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(1).ok())
-                    .collect());
+                let mut rows = sqlx::query(sql).fetch(pool).skip(row_index);
+                let row = rows.next().await.ok_or_else(not_found)??;
+                return cell_bytes!(row, column);
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_tables(
+    /// Checks every statement of `sql` by preparing it on the server, without executing anything.
+    /// Preparing also resolves tables and columns, so a statement that depends on an object
+    /// created earlier in the same script is reported as invalid.
+    pub async fn validate_sql(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-    ) -> Result<Vec<String>> {
+        sql: &str,
+    ) -> Result<SqlValidation> {
+        let backslash_escapes = manager.get_mysql_pools().await.contains_key(connection_id);
+        let statements = split_statements(sql, backslash_escapes);
+        let mut parameter_count = 0;
+
+        macro_rules! prepare_all {
+            ($pool:expr) => {{
+                use sqlx::Either;
+                for (i, stmt) in statements.iter().enumerate() {
+                    match Executor::prepare($pool, stmt.as_str()).await {
+                        Ok(prepared) => {
+                            parameter_count += match prepared.parameters() {
+                                Some(Either::Left(params)) => params.len(),
+                                Some(Either::Right(count)) => count,
+                                None => 0,
+                            };
+                        }
+                        // MySQL refuses to prepare some valid statements (ER_UNSUPPORTED_PS)
+                        Err(sqlx::Error::Database(db))
+                            if db
+                                .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                                .is_some_and(|e| e.number() == 1295) => {}
+                        Err(e) => {
+                            let err = anyhow::Error::from(e);
+                            if is_connection_error(&err) {
+                                return Err(err);
+                            }
+                            return Ok(SqlValidation {
+                                valid: false,
+                                statement: Some(i + 1),
+                                code: Some(error_code(&err).to_string()),
+                                position: error_position(&err),
+                                message: Some(err.to_string()),
+                                parameter_count,
+                            });
+                        }
+                    }
+                }
+                return Ok(SqlValidation {
+                    valid: true,
+                    parameter_count,
+                    ..Default::default()
+                });
+            }};
+        }
+
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                // Explicitly check current search path or public schema
-                let sql = "SELECT table_name::text FROM information_schema.tables WHERE table_schema = ANY(current_schemas(false)) AND table_type = 'BASE TABLE';";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                let tables: Vec<String> = rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(0).ok())
-                    .collect();
-
-                return Ok(tables);
+                prepare_all!(pool);
             }
         }
 
@@ -864,12 +2993,7 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let sql = "SHOW TABLES;";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                return Ok(rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(0).ok())
-                    .collect());
+                prepare_all!(pool);
             }
         }
 
@@ -877,108 +3001,189 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let sql = "SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                return Ok(rows
-                    .into_iter()
-                    .filter_map(|row| row.try_get::<String, _>(0).ok())
-                    .collect());
+                prepare_all!(pool);
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_table_data(
+    /// Execute a multi-statement script, returning one result per statement in order
+    pub async fn execute_script(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-        table_name: &str,
-        limit: u32,
-        offset: u32,
-        filters: Vec<FilterConfig>,
-        sort_column: Option<String>,
-        sort_direction: Option<String>,
-    ) -> Result<QueryResult> {
-        let db_type = {
-            if manager
-                .get_postgres_pools()
+        sql: &str,
+    ) -> Result<Vec<QueryResult>> {
+        let backslash_escapes = manager.get_mysql_pools().await.contains_key(connection_id);
+        let statements = split_statements(sql, backslash_escapes);
+
+        let mut results = Vec::with_capacity(statements.len());
+        for (i, stmt) in statements.iter().enumerate() {
+            let result = Self::execute_query(manager, connection_id, stmt, None, None, None, None)
                 .await
-                .contains_key(connection_id)
-            {
-                Some("postgres")
-            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
-                Some("mysql")
-            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
-                Some("sqlite")
-            } else {
-                None
+                .map_err(|e| anyhow!("Statement {} failed: {}", i + 1, e))?;
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    pub async fn create_database(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        db_name: &str,
+    ) -> Result<()> {
+        validate_identifier(db_name)?;
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = format!("CREATE DATABASE {}", quote_ident(db_name, "postgres"));
+                sqlx::query(&sql).execute(pool).await?;
+                return Ok(());
             }
-        };
+        }
 
-        match db_type {
-            Some("postgres") => {
-                let where_clause = build_where_clause(filters, "postgres");
-                let order_clause = build_order_clause(sort_column, sort_direction, "postgres");
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = format!("CREATE DATABASE {}", quote_ident(db_name, "mysql"));
+                sqlx::query(&sql).execute(pool).await?;
+                return Ok(());
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(_) = pools.get(connection_id) {
+                return Err(anyhow!("Creation of new databases in SQLite is not supported via this command. Please create a new connection for a different SQLite file."));
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    pub async fn get_databases(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        filter: &CatalogFilter,
+    ) -> Result<Vec<String>> {
+        let pattern = catalog_pattern(filter);
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // List databases. Removed datallowconn filter to match TablePlus behavior.
                 let sql = format!(
-                    "SELECT * FROM \"{}\" {} {} LIMIT {} OFFSET {};",
-                    table_name.replace("\"", "\"\""),
-                    where_clause,
-                    order_clause,
-                    limit,
-                    offset
+                    "SELECT datname::text FROM pg_database WHERE datistemplate = false AND datname ILIKE $1 ORDER BY datname{};",
+                    catalog_limit_clause(filter, "postgres")
                 );
-                Self::execute_query(manager, connection_id, &sql, None, None).await
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
             }
-            Some("mysql") => {
-                let where_clause = build_where_clause(filters, "mysql");
-                let order_clause = build_order_clause(sort_column, sort_direction, "mysql");
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
                 let sql = format!(
-                    "SELECT * FROM `{}` {} {} LIMIT {} OFFSET {};",
-                    table_name.replace("`", "``"),
-                    where_clause,
-                    order_clause,
-                    limit,
-                    offset
+                    "SELECT SCHEMA_NAME FROM information_schema.SCHEMATA WHERE SCHEMA_NAME LIKE ? ORDER BY SCHEMA_NAME{};",
+                    catalog_limit_clause(filter, "mysql")
                 );
-                Self::execute_query(manager, connection_id, &sql, None, None).await
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
             }
-            Some("sqlite") => {
-                let where_clause = build_where_clause(filters, "sqlite");
-                let order_clause = build_order_clause(sort_column, sort_direction, "sqlite");
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // SQLite usually has one main database, but we can list attached ones
                 let sql = format!(
-                    "SELECT * FROM \"{}\" {} {} LIMIT {} OFFSET {};",
-                    table_name.replace("\"", "\"\""),
-                    where_clause,
-                    order_clause,
-                    limit,
-                    offset
+                    "SELECT name FROM pragma_database_list WHERE name LIKE ? ORDER BY seq{};",
+                    catalog_limit_clause(filter, "sqlite")
                 );
-                Self::execute_query(manager, connection_id, &sql, None, None).await
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
             }
-            Some(_) => Err(anyhow!("Unknown database type")),
-            None => Err(anyhow!("Connection not found")),
         }
+
+        Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_table_count(
+    /// Attach another SQLite file under `alias` so it can be queried as `alias.table`
+    pub async fn attach_database(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-        table_name: &str,
-        filters: Vec<FilterConfig>,
-    ) -> Result<u64> {
+        file_path: &str,
+        alias: &str,
+    ) -> Result<()> {
+        validate_identifier(alias)?;
+        let pools = manager.get_sqlite_pools().await;
+        let pool = pools
+            .get(connection_id)
+            .ok_or_else(|| anyhow!("ATTACH DATABASE is only supported for SQLite connections"))?;
+
+        // The pool holds a single long-lived connection, so the attachment persists
+        let sql = format!("ATTACH DATABASE ? AS {}", quote_ident(alias, "sqlite"));
+        sqlx::query(&sql).bind(file_path).execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn detach_database(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        alias: &str,
+    ) -> Result<()> {
+        validate_identifier(alias)?;
+        let pools = manager.get_sqlite_pools().await;
+        let pool = pools
+            .get(connection_id)
+            .ok_or_else(|| anyhow!("DETACH DATABASE is only supported for SQLite connections"))?;
+
+        let sql = format!("DETACH DATABASE {}", quote_ident(alias, "sqlite"));
+        sqlx::query(&sql).execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn get_tables(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        filter: &CatalogFilter,
+    ) -> Result<Vec<String>> {
+        let pattern = catalog_pattern(filter);
+
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                // Use exact count for accuracy, as reltuples can be 0 for unanalyzed tables
-                let where_clause = build_where_clause(filters, "postgres");
+                // Explicitly check current search path or public schema
                 let sql = format!(
-                    "SELECT COUNT(*) FROM \"{}\" {};",
-                    table_name.replace("\"", "\"\""),
-                    where_clause
+                    "SELECT table_name::text FROM information_schema.tables WHERE table_schema = ANY(current_schemas(false)) AND table_type = 'BASE TABLE' AND table_name ILIKE $1 ORDER BY table_name{};",
+                    catalog_limit_clause(filter, "postgres")
                 );
-                let row = sqlx::query(&sql).fetch_one(pool).await?;
-                return Ok(row.try_get::<i64, _>(0)? as u64);
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                let tables: Vec<String> = rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect();
+
+                return Ok(tables);
             }
         }
 
@@ -986,14 +3191,16 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let where_clause = build_where_clause(filters, "mysql");
+                // Same rows as SHOW TABLES (views included), but filterable and pageable
                 let sql = format!(
-                    "SELECT COUNT(*) FROM `{}` {};",
-                    table_name.replace("`", "``"),
-                    where_clause
+                    "SELECT TABLE_NAME FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME LIKE ? ORDER BY TABLE_NAME{};",
+                    catalog_limit_clause(filter, "mysql")
                 );
-                let row = sqlx::query(&sql).fetch_one(pool).await?;
-                return Ok(row.try_get::<i64, _>(0).unwrap_or(0) as u64);
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
             }
         }
 
@@ -1001,37 +3208,99 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                let where_clause = build_where_clause(filters, "sqlite");
                 let sql = format!(
-                    "SELECT COUNT(*) FROM \"{}\" {};",
-                    table_name.replace("\"", "\"\""),
-                    where_clause
+                    "SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%' AND name LIKE ? ORDER BY name{};",
+                    catalog_limit_clause(filter, "sqlite")
                 );
-                let row = sqlx::query(&sql).fetch_one(pool).await?;
-                return Ok(row.try_get::<i64, _>(0)? as u64);
+                let rows = sqlx::query(&sql).bind(pattern).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    /// Execute multiple SQL statements (mutations) - used for committing changes
-    pub async fn execute_mutations(
+    pub async fn get_views(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-        statements: Vec<String>,
-    ) -> Result<u64> {
-        let mut total_affected = 0u64;
+        schema: Option<String>,
+    ) -> Result<Vec<String>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // Without an explicit schema, list views on the current search path
+                let sql = r#"
+                    SELECT table_name::text FROM information_schema.views
+                    WHERE ($1::text IS NULL AND table_schema = ANY(current_schemas(false)))
+                       OR table_schema = $1
+                    ORDER BY table_name
+                "#;
+                let rows = sqlx::query(sql).bind(schema).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT TABLE_NAME FROM information_schema.VIEWS
+                    WHERE TABLE_SCHEMA = COALESCE(?, DATABASE())
+                    ORDER BY TABLE_NAME
+                "#;
+                let rows = sqlx::query(sql).bind(schema).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // An ATTACHed database's alias acts as the schema
+                let master = match &schema {
+                    Some(s) => format!("{}.sqlite_master", quote_ident(s, "sqlite")),
+                    None => "sqlite_master".to_string(),
+                };
+                let sql = format!(
+                    "SELECT name FROM {} WHERE type = 'view' ORDER BY name;",
+                    master
+                );
+                let rows = sqlx::query(&sql).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .filter_map(|row| row.try_get::<String, _>(0).ok())
+                    .collect());
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
 
+    /// Returns the SELECT that defines a view, without the CREATE VIEW around it
+    pub async fn get_view_definition(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        view_name: &str,
+    ) -> Result<String> {
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                for sql in &statements {
-                    let result = sqlx::query(sql).execute(pool).await?;
-                    total_affected += result.rows_affected();
-                }
-                return Ok(total_affected);
+                let sql = "SELECT pg_get_viewdef(quote_ident($1)::regclass, true)";
+                let row = sqlx::query(sql).bind(view_name).fetch_one(pool).await?;
+                return Ok(row.try_get(0)?);
             }
         }
 
@@ -1039,11 +3308,13 @@ impl QueryEngine {
         {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                for sql in &statements {
-                    let result = sqlx::query(sql).execute(pool).await?;
-                    total_affected += result.rows_affected();
-                }
-                return Ok(total_affected);
+                let sql = "SELECT VIEW_DEFINITION FROM information_schema.VIEWS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?";
+                let row = sqlx::query(sql)
+                    .bind(view_name)
+                    .fetch_optional(pool)
+                    .await?
+                    .ok_or_else(|| anyhow!("View '{}' not found", view_name))?;
+                return Ok(row.try_get(0)?);
             }
         }
 
@@ -1051,41 +3322,62 @@ impl QueryEngine {
         {
             let pools = manager.get_sqlite_pools().await;
             if let Some(pool) = pools.get(connection_id) {
-                for sql in &statements {
-                    let result = sqlx::query(sql).execute(pool).await?;
-                    total_affected += result.rows_affected();
-                }
-                return Ok(total_affected);
+                let sql = "SELECT sql FROM sqlite_master WHERE type = 'view' AND name = ?";
+                let row = sqlx::query(sql)
+                    .bind(view_name)
+                    .fetch_optional(pool)
+                    .await?
+                    .ok_or_else(|| anyhow!("View '{}' not found", view_name))?;
+                // sqlite_master keeps the whole CREATE VIEW; the SELECT follows its AS
+                let create_sql: String = row.try_get(0)?;
+                return after_top_level_keyword(&create_sql, "AS")
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        anyhow!("Could not parse the definition of view '{}'", view_name)
+                    });
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_table_metadata(
+    pub async fn get_foreign_keys(
         manager: &ConnectionManager,
         connection_id: &Uuid,
         table_name: &str,
-    ) -> Result<TableMetadata> {
+    ) -> Result<TableForeignKeys> {
+        let mut keys = Vec::new();
+
         // Check Postgres
         {
             let pools = manager.get_postgres_pools().await;
             if let Some(pool) = pools.get(connection_id) {
                 let sql = r#"
-                    SELECT 
-                        pg_size_pretty(pg_total_relation_size(quote_ident($1))) as total_size,
-                        pg_size_pretty(pg_relation_size(quote_ident($1))) as data_size,
-                        pg_size_pretty(pg_indexes_size(quote_ident($1))) as index_size,
-                        obj_description(quote_ident($1)::regclass, 'pg_class') as comment
+                    SELECT c.conname::text, src.relname::text, sa.attname::text,
+                           tgt.relname::text, ta.attname::text
+                    FROM pg_constraint c
+                    JOIN pg_class src ON src.oid = c.conrelid
+                    JOIN pg_class tgt ON tgt.oid = c.confrelid
+                    CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(src_att, tgt_att, ord)
+                    JOIN pg_attribute sa ON sa.attrelid = c.conrelid AND sa.attnum = k.src_att
+                    JOIN pg_attribute ta ON ta.attrelid = c.confrelid AND ta.attnum = k.tgt_att
+                    WHERE c.contype = 'f'
+                      AND ((src.relname = $1 AND pg_table_is_visible(src.oid))
+                        OR (tgt.relname = $1 AND pg_table_is_visible(tgt.oid)))
+                    ORDER BY src.relname, c.conname, k.ord
                 "#;
-                let row = sqlx::query(sql).bind(table_name).fetch_one(pool).await?;
-
-                return Ok(TableMetadata {
-                    total_size: row.try_get(0).ok(),
-                    data_size: row.try_get(1).ok(),
-                    index_size: row.try_get(2).ok(),
-                    comment: row.try_get(3).ok(),
-                });
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                for row in rows {
+                    push_fk_column(
+                        &mut keys,
+                        row.get(0),
+                        row.get(1),
+                        row.get(2),
+                        row.get(3),
+                        row.get(4),
+                    );
+                }
+                return Ok(split_foreign_keys(table_name, keys));
             }
         }
 
@@ -1094,786 +3386,5633 @@ impl QueryEngine {
             let pools = manager.get_mysql_pools().await;
             if let Some(pool) = pools.get(connection_id) {
                 let sql = r#"
-                    SELECT 
-                        (DATA_LENGTH + INDEX_LENGTH) as total_size,
-                        DATA_LENGTH as data_size,
-                        INDEX_LENGTH as index_size,
-                        TABLE_COMMENT as comment
-                    FROM information_schema.TABLES
-                    WHERE TABLE_NAME = ?
+                    SELECT CONSTRAINT_NAME, TABLE_NAME, COLUMN_NAME,
+                           REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME
+                    FROM information_schema.KEY_COLUMN_USAGE
+                    WHERE REFERENCED_TABLE_NAME IS NOT NULL
+                      AND ((TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?)
+                        OR (REFERENCED_TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME = ?))
+                    ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION
                 "#;
-                let row = sqlx::query(sql).bind(table_name).fetch_one(pool).await?;
-
-                let total: Option<u64> = row.try_get(0).ok();
-                let data: Option<u64> = row.try_get(1).ok();
-                let index: Option<u64> = row.try_get(2).ok();
-
-                return Ok(TableMetadata {
-                    total_size: total.map(|s| format!("{} KB", s / 1024)),
-                    data_size: data.map(|s| format!("{} KB", s / 1024)),
-                    index_size: index.map(|s| format!("{} KB", s / 1024)),
-                    comment: row.try_get(3).ok(),
-                });
+                let rows = sqlx::query(sql)
+                    .bind(table_name)
+                    .bind(table_name)
+                    .fetch_all(pool)
+                    .await?;
+                for row in rows {
+                    push_fk_column(
+                        &mut keys,
+                        row.get(0),
+                        row.get(1),
+                        row.get(2),
+                        row.get(3),
+                        row.get(4),
+                    );
+                }
+                return Ok(split_foreign_keys(table_name, keys));
             }
         }
 
         // Check SQLite
         {
             let pools = manager.get_sqlite_pools().await;
-            if let Some(_) = pools.get(connection_id) {
-                // SQLite doesn't easily provide per-table size in standard SQL
-                return Ok(TableMetadata {
-                    total_size: Some("Unknown".to_string()),
-                    data_size: Some("Unknown".to_string()),
-                    index_size: Some("Unknown".to_string()),
-                    comment: None,
-                });
+            if let Some(pool) = pools.get(connection_id) {
+                // SQLite keys are unnamed; `to` is NULL when the parent's primary key is implied
+                let sql = r#"
+                    SELECT m.name, f.id, f."from", f."table", f."to"
+                    FROM sqlite_schema m
+                    JOIN pragma_foreign_key_list(m.name) f
+                    WHERE m.type = 'table' AND (m.name = ? OR f."table" = ?)
+                    ORDER BY m.name, f.id, f.seq
+                "#;
+                let rows = sqlx::query(sql)
+                    .bind(table_name)
+                    .bind(table_name)
+                    .fetch_all(pool)
+                    .await?;
+                for row in rows {
+                    let id: i64 = row.get(1);
+                    let referenced_table: String = row.get(3);
+                    push_fk_column(
+                        &mut keys,
+                        format!("fk_{}_{}", referenced_table, id),
+                        row.get(0),
+                        row.get(2),
+                        referenced_table,
+                        row.try_get::<Option<String>, _>(4)?.unwrap_or_default(),
+                    );
+                }
+                return Ok(split_foreign_keys(table_name, keys));
             }
         }
 
         Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_ai_schema_tables(
+    /// Primary key columns of `table_name` in key order, empty when the table has none
+    pub async fn get_primary_keys(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-    ) -> Result<Vec<AiSchemaTable>> {
-        let db_type = {
-            if manager
-                .get_postgres_pools()
-                .await
-                .contains_key(connection_id)
-            {
-                Some("postgres")
-            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
-                Some("mysql")
-            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
-                Some("sqlite")
-            } else {
-                None
-            }
-        };
-
-        match db_type {
-            Some("postgres") => {
-                let pool = manager
-                    .get_postgres_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
                 let sql = r#"
-                    SELECT
-                        c.table_schema,
-                        c.table_name,
-                        t.table_type,
-                        c.column_name,
-                        c.data_type,
-                        c.is_nullable,
-                        c.column_default,
-                        (pk.column_name IS NOT NULL) AS is_primary
-                    FROM information_schema.columns c
-                    JOIN information_schema.tables t
-                      ON c.table_schema = t.table_schema
-                     AND c.table_name = t.table_name
-                    LEFT JOIN (
-                        SELECT
-                            kcu.table_schema,
-                            kcu.table_name,
-                            kcu.column_name
-                        FROM information_schema.table_constraints tc
-                        JOIN information_schema.key_column_usage kcu
-                          ON tc.constraint_name = kcu.constraint_name
-                         AND tc.table_schema = kcu.table_schema
-                         AND tc.table_name = kcu.table_name
-                        WHERE tc.constraint_type = 'PRIMARY KEY'
-                    ) pk
-                      ON pk.table_schema = c.table_schema
-                     AND pk.table_name = c.table_name
-                     AND pk.column_name = c.column_name
-                    WHERE c.table_schema NOT IN ('information_schema', 'pg_catalog')
-                    ORDER BY c.table_schema, c.table_name, c.ordinal_position;
+                    SELECT a.attname::text
+                    FROM pg_index i
+                    JOIN pg_class c ON c.oid = i.indrelid
+                    CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord)
+                    JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum
+                    WHERE i.indisprimary AND c.relname = $1 AND pg_table_is_visible(c.oid)
+                    ORDER BY k.ord
                 "#;
-
-                let rows = sqlx::query(sql).fetch_all(&pool).await?;
-                let mut tables = BTreeMap::new();
-
-                for row in rows {
-                    let schema: String = row.get(0);
-                    let table_name: String = row.get(1);
-                    let table_type: String = row.get(2);
-                    let key = format!("{}.{}", schema, table_name);
-
-                    let entry = tables.entry(key).or_insert_with(|| AiSchemaTable {
-                        name: table_name.clone(),
-                        schema: Some(schema.clone()),
-                        item_type: sidebar_item_type_from_table_type(&table_type),
-                        columns: Vec::new(),
-                    });
-
-                    entry.columns.push(TableColumnStructure {
-                        name: row.get(3),
-                        data_type: row.get(4),
-                        is_nullable: row.get::<String, _>(5) == "YES",
-                        default_value: row.get(6),
-                        is_primary_key: row.get(7),
-                        comment: None,
-                    });
-                }
-
-                Ok(tables.into_values().collect())
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
             }
-            Some("mysql") => {
-                let pool = manager
-                    .get_mysql_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
                 let sql = r#"
-                    SELECT
-                        c.TABLE_SCHEMA,
-                        c.TABLE_NAME,
-                        t.TABLE_TYPE,
-                        c.COLUMN_NAME,
-                        c.COLUMN_TYPE,
-                        c.IS_NULLABLE,
-                        c.COLUMN_DEFAULT,
-                        c.COLUMN_KEY
-                    FROM information_schema.COLUMNS c
-                    JOIN information_schema.TABLES t
-                      ON c.TABLE_SCHEMA = t.TABLE_SCHEMA
-                     AND c.TABLE_NAME = t.TABLE_NAME
-                    WHERE c.TABLE_SCHEMA = DATABASE()
-                    ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION;
+                    SELECT COLUMN_NAME
+                    FROM information_schema.KEY_COLUMN_USAGE
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND CONSTRAINT_NAME = 'PRIMARY'
+                    ORDER BY ORDINAL_POSITION
                 "#;
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
+            }
+        }
 
-                let rows = sqlx::query(sql).fetch_all(&pool).await?;
-                let mut tables = BTreeMap::new();
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // `pk` is the 1-based position within the key, 0 for non-key columns
+                let sql = "SELECT name FROM pragma_table_info(?) WHERE pk > 0 ORDER BY pk";
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
+            }
+        }
 
-                for row in rows {
-                    let schema: String = row.get(0);
-                    let table_name: String = row.get(1);
-                    let table_type: String = row.get(2);
-                    let key = format!("{}.{}", schema, table_name);
+        Err(anyhow!("Connection not found"))
+    }
 
-                    let entry = tables.entry(key).or_insert_with(|| AiSchemaTable {
-                        name: table_name.clone(),
-                        schema: Some(schema.clone()),
-                        item_type: sidebar_item_type_from_table_type(&table_type),
-                        columns: Vec::new(),
-                    });
+    /// Identity, auto-increment and generated columns of `table_name`, whose values the
+    /// database supplies itself
+    pub async fn get_generated_columns(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT a.attname::text
+                    FROM pg_attribute a
+                    JOIN pg_class c ON c.oid = a.attrelid
+                    WHERE c.relname = $1 AND pg_table_is_visible(c.oid)
+                      AND a.attnum > 0 AND NOT a.attisdropped
+                      AND (a.attidentity <> '' OR a.attgenerated <> '')
+                "#;
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
+            }
+        }
 
-                    entry.columns.push(TableColumnStructure {
-                        name: row.get(3),
-                        data_type: row.get(4),
-                        is_nullable: row.get::<String, _>(5) == "YES",
-                        default_value: row.get(6),
-                        is_primary_key: row.get::<String, _>(7) == "PRI",
-                        comment: None,
-                    });
-                }
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // EXTRA also reports DEFAULT_GENERATED for expression defaults, which are settable
+                let sql = r#"
+                    SELECT COLUMN_NAME
+                    FROM information_schema.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+                      AND (EXTRA LIKE '%VIRTUAL GENERATED%' OR EXTRA LIKE '%STORED GENERATED%'
+                           OR EXTRA LIKE '%auto_increment%')
+                "#;
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
+            }
+        }
 
-                Ok(tables.into_values().collect())
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // `hidden` is 2 for virtual and 3 for stored generated columns
+                let sql = "SELECT name FROM pragma_table_xinfo(?) WHERE hidden IN (2, 3)";
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows.iter().map(|row| row.get(0)).collect());
             }
-            Some("sqlite") => {
-                let pool = manager
-                    .get_sqlite_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let rows = sqlx::query(
-                    "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;",
-                )
-                .fetch_all(&pool)
-                .await?;
+        }
 
-                let mut tables = Vec::new();
+        Err(anyhow!("Connection not found"))
+    }
 
-                for row in rows {
-                    let table_name: String = row.get(0);
-                    let table_type: String = row.get(1);
-                    let pragma_sql =
-                        format!("PRAGMA table_info(\"{}\")", table_name.replace('"', "\"\""));
-                    let col_rows = sqlx::query(&pragma_sql).fetch_all(&pool).await?;
-                    let columns = col_rows
-                        .into_iter()
-                        .map(|col_row| TableColumnStructure {
-                            name: col_row.get("name"),
-                            data_type: col_row.get("type"),
-                            is_nullable: col_row.get::<i64, _>("notnull") == 0,
-                            default_value: col_row.get("dflt_value"),
-                            is_primary_key: col_row.get::<i64, _>("pk") > 0,
-                            comment: None,
-                        })
-                        .collect();
+    pub async fn get_triggers(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+    ) -> Result<Vec<TableTrigger>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // One row per (trigger, event), so multi-event triggers appear more than once
+                let sql = r#"
+                    SELECT trigger_name::text, action_timing::text, event_manipulation::text, action_statement::text
+                    FROM information_schema.triggers
+                    WHERE event_object_table = $1
+                      AND event_object_schema = ANY(current_schemas(false))
+                    ORDER BY trigger_name, event_manipulation
+                "#;
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .map(|row| TableTrigger {
+                        name: row.get(0),
+                        timing: row.get(1),
+                        event: row.get(2),
+                        definition: row.try_get(3).ok(),
+                    })
+                    .collect());
+            }
+        }
 
-                    tables.push(AiSchemaTable {
-                        name: table_name,
-                        schema: None,
-                        item_type: sidebar_item_type_from_table_type(&table_type),
-                        columns,
-                    });
-                }
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT TRIGGER_NAME, ACTION_TIMING, EVENT_MANIPULATION, ACTION_STATEMENT
+                    FROM information_schema.TRIGGERS
+                    WHERE EVENT_OBJECT_TABLE = ? AND TRIGGER_SCHEMA = DATABASE()
+                    ORDER BY TRIGGER_NAME
+                "#;
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .map(|row| TableTrigger {
+                        name: row.get(0),
+                        timing: row.get(1),
+                        event: row.get(2),
+                        definition: row.try_get(3).ok(),
+                    })
+                    .collect());
+            }
+        }
 
-                Ok(tables)
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = "SELECT name, sql FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ? ORDER BY name";
+                let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
+                return Ok(rows
+                    .into_iter()
+                    .map(|row| {
+                        let definition: Option<String> = row.try_get(1).ok();
+                        let (timing, event) = definition
+                            .as_deref()
+                            .map(parse_sqlite_trigger_header)
+                            .unwrap_or_default();
+                        TableTrigger {
+                            name: row.get(0),
+                            timing,
+                            event,
+                            definition,
+                        }
+                    })
+                    .collect());
             }
-            _ => Err(anyhow!("Connection not found")),
         }
+
+        Err(anyhow!("Connection not found"))
     }
 
-    pub async fn get_table_structure(
+    /// Page bounds for table browsing. A limit of 0 fetches the whole table, unless the
+    /// connection caps browsing.
+    async fn table_page_bounds(
         manager: &ConnectionManager,
         connection_id: &Uuid,
-        table_name: &str,
-    ) -> Result<TableStructure> {
-        let db_type = {
-            if manager
-                .get_postgres_pools()
+        limit: u32,
+        offset: u32,
+    ) -> (Option<u32>, Option<u32>) {
+        let limit = match limit {
+            0 => manager
+                .default_browse_limit(connection_id)
                 .await
-                .contains_key(connection_id)
-            {
-                Some("postgres")
-            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
-                Some("mysql")
-            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
-                Some("sqlite")
-            } else {
-                None
-            }
+                .unwrap_or(0),
+            limit => limit,
         };
+        ((limit > 0).then_some(limit), (offset > 0).then_some(offset))
+    }
 
-        match db_type {
-            Some("postgres") => {
+    pub async fn get_table_data(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        limit: u32,
+        offset: u32,
+        filters: Vec<FilterConfig>,
+        sort_column: Option<String>,
+        sort_direction: Option<String>,
+    ) -> Result<QueryResult> {
+        let (page_limit, page_offset) =
+            Self::table_page_bounds(manager, connection_id, limit, offset).await;
+        let start = Instant::now();
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let (_, sql, params) = table_page_sql(
+            table_name,
+            db_type,
+            filters,
+            sort_column,
+            sort_direction,
+            page_limit,
+            page_offset,
+        );
+        let sql = manager.tag_query(connection_id, &sql).await;
+        let max_rows = manager.max_result_rows(connection_id).await;
+
+        // The page only reads, so a dropped connection is retried like execute_query
+        let result =
+            Self::table_rows_once(manager, connection_id, db_type, &sql, &params, max_rows).await;
+        let (columns, column_types, rows, truncated) = match result {
+            Err(e) if is_connection_error(&e) => {
+                manager.reconnect(connection_id).await?;
+                Self::table_rows_once(manager, connection_id, db_type, &sql, &params, max_rows)
+                    .await
+            }
+            other => other,
+        }?;
+
+        Ok(QueryResult {
+            columns,
+            column_types,
+            rows,
+            affected_rows: 0,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            total_count: None,
+            page: None,
+            page_size: None,
+            truncated,
+            server_timing: None,
+        })
+    }
+
+    /// One attempt at `get_table_data`'s read, returning (columns, column_types, rows,
+    /// truncated). Like `execute_query`, reading stops at the connection's max_rows.
+    async fn table_rows_once(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        db_type: &str,
+        sql: &str,
+        params: &[Value],
+        max_rows: Option<usize>,
+    ) -> Result<(Vec<String>, Vec<String>, Vec<Vec<Value>>, bool)> {
+        macro_rules! read_rows {
+            ($pool:expr, $to_values:ident) => {{
+                let mut columns = Vec::new();
+                let mut column_types = Vec::new();
+                let mut rows = Vec::new();
+                let mut truncated = false;
+                let mut stream = bind_json_params!(sqlx::query(sql), params).fetch($pool);
+                while let Some(row) = StreamExt::next(&mut stream).await {
+                    let row = row?;
+                    if columns.is_empty() {
+                        columns = row
+                            .columns()
+                            .iter()
+                            .map(|c| Column::name(c).to_string())
+                            .collect::<Vec<String>>();
+                        column_types = column_type_names(row.columns());
+                    }
+                    if max_rows.is_some_and(|max| rows.len() >= max) {
+                        truncated = true;
+                        break;
+                    }
+                    rows.push($to_values!(&row));
+                }
+                // Release the streaming connection before describing on a single-connection pool
+                drop(stream);
+                if columns.is_empty() {
+                    (columns, column_types) = describe_columns!($pool, sql);
+                }
+                (columns, column_types, rows, truncated)
+            }};
+        }
+
+        Ok(match db_type {
+            "postgres" => {
                 let pool = manager
                     .get_postgres_pools()
                     .await
                     .get(connection_id)
                     .cloned()
-                    .unwrap();
-
-                // Fetch columns
-                let col_sql = r#"
-                    SELECT 
-                        column_name, 
-                        data_type, 
-                        is_nullable, 
-                        column_default,
-                        EXISTS (
-                            SELECT 1 FROM information_schema.key_column_usage kcu
-                            JOIN information_schema.table_constraints tc ON kcu.constraint_name = tc.constraint_name
-                            WHERE kcu.table_name = c.table_name AND kcu.column_name = c.column_name AND tc.constraint_type = 'PRIMARY KEY'
-                        ) as is_primary
-                    FROM information_schema.columns c
-                    WHERE table_name = $1 AND table_schema = 'public'
-                    ORDER BY ordinal_position;
-                "#;
-                let col_rows = sqlx::query(col_sql)
-                    .bind(table_name)
-                    .fetch_all(&pool)
-                    .await?;
-                let columns = col_rows
-                    .into_iter()
-                    .map(|row| {
-                        TableColumnStructure {
-                            name: row.get(0),
-                            data_type: row.get(1),
-                            is_nullable: row.get::<String, _>(2) == "YES",
-                            default_value: row.get(3),
-                            is_primary_key: row.get(4),
-                            comment: None, // We could fetch this too if needed
-                        }
-                    })
-                    .collect();
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                read_rows!(&pool, postgres_row_to_values)
+            }
+            "mysql" => {
+                let pool = manager
+                    .get_mysql_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                read_rows!(&pool, mysql_row_to_values)
+            }
+            _ => {
+                let pool = manager
+                    .get_sqlite_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                read_rows!(&pool, sqlite_row_to_values)
+            }
+        })
+    }
 
-                // Fetch indexes
-                let idx_sql = "SELECT indexname, indexdef FROM pg_indexes WHERE tablename = $1 AND schemaname = 'public';";
-                let idx_rows = sqlx::query(idx_sql)
-                    .bind(table_name)
-                    .fetch_all(&pool)
-                    .await?;
-                let indexes = idx_rows
-                    .into_iter()
-                    .map(|row| {
-                        let def: String = row.get(1);
-                        TableIndexStructure {
-                            name: row.get(0),
-                            columns: vec![], // Logic to parse columns from def would be complex, leaving empty for now or could just show def
-                            is_unique: def.contains("UNIQUE"),
-                            index_type: "btree".to_string(), // Default in PG
-                        }
-                    })
-                    .collect();
+    /// Same page as `get_table_data`, plus the matching row count read from the same
+    /// REPEATABLE READ snapshot, so concurrent writes cannot make the two disagree
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_table_page(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        limit: u32,
+        offset: u32,
+        filters: Vec<FilterConfig>,
+        sort_column: Option<String>,
+        sort_direction: Option<String>,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let (page_limit, page_offset) =
+            Self::table_page_bounds(manager, connection_id, limit, offset).await;
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let (count_sql, data_sql, params) = table_page_sql(
+            table_name,
+            db_type,
+            filters,
+            sort_column,
+            sort_direction,
+            page_limit,
+            page_offset,
+        );
+        let count_sql = manager.tag_query(connection_id, &count_sql).await;
+        let data_sql = manager.tag_query(connection_id, &data_sql).await;
+        let max_rows = manager.max_result_rows(connection_id).await;
+
+        // Both statements only read, so a dropped connection is retried like execute_query
+        let result = Self::table_page_once(
+            manager,
+            connection_id,
+            db_type,
+            &count_sql,
+            &data_sql,
+            &params,
+            max_rows,
+        )
+        .await;
+        let (columns, column_types, rows, total, truncated) = match result {
+            Err(e) if is_connection_error(&e) => {
+                manager.reconnect(connection_id).await?;
+                Self::table_page_once(
+                    manager,
+                    connection_id,
+                    db_type,
+                    &count_sql,
+                    &data_sql,
+                    &params,
+                    max_rows,
+                )
+                .await
+            }
+            other => other,
+        }?;
+
+        Ok(QueryResult {
+            columns,
+            column_types,
+            rows,
+            affected_rows: 0,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            total_count: Some(total),
+            page: page_limit.map(|l| offset / l),
+            page_size: page_limit,
+            truncated,
+            server_timing: None,
+        })
+    }
 
-                // Fetch constraints
-                let cons_sql = r#"
-                    SELECT 
-                        constraint_name, 
-                        constraint_type
-                    FROM information_schema.table_constraints 
-                    WHERE table_name = $1 AND table_schema = 'public';
-                "#;
-                let cons_rows = sqlx::query(cons_sql)
-                    .bind(table_name)
-                    .fetch_all(&pool)
+    /// One attempt at `get_table_page`'s snapshot read, stopping at the connection's max_rows
+    async fn table_page_once(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        db_type: &str,
+        count_sql: &str,
+        data_sql: &str,
+        params: &[Value],
+        max_rows: Option<usize>,
+    ) -> Result<(Vec<String>, Vec<String>, Vec<Vec<Value>>, u64, bool)> {
+        Ok(match db_type {
+            "postgres" => {
+                let pool = manager
+                    .get_postgres_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let mut tx = pool.begin().await?;
+                sqlx::raw_sql("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+                    .execute(&mut *tx)
                     .await?;
-                let constraints = cons_rows
-                    .into_iter()
-                    .map(|row| TableConstraintStructure {
-                        name: row.get(0),
-                        constraint_type: row.get(1),
-                        definition: "".to_string(),
-                    })
-                    .collect();
-
-                Ok(TableStructure {
-                    columns,
-                    indexes,
-                    constraints,
-                })
+                table_page_in_transaction!(
+                    tx,
+                    count_sql,
+                    data_sql,
+                    params,
+                    max_rows,
+                    postgres_row_to_values
+                )
             }
-            Some("mysql") => {
+            "mysql" => {
                 let pool = manager
                     .get_mysql_pools()
                     .await
                     .get(connection_id)
                     .cloned()
-                    .unwrap();
-
-                // Fetch columns
-                let col_sql = r#"
-                    SELECT 
-                        COLUMN_NAME, 
-                        COLUMN_TYPE, 
-                        IS_NULLABLE, 
-                        COLUMN_DEFAULT, 
-                        COLUMN_KEY,
-                        COLUMN_COMMENT
-                    FROM information_schema.COLUMNS 
-                    WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE()
-                    ORDER BY ORDINAL_POSITION;
-                "#;
-                let col_rows = sqlx::query(col_sql)
-                    .bind(table_name)
-                    .fetch_all(&pool)
-                    .await?;
-                let columns = col_rows
-                    .into_iter()
-                    .map(|row| TableColumnStructure {
-                        name: row.get(0),
-                        data_type: row.get(1),
-                        is_nullable: row.get::<String, _>(2) == "YES",
-                        default_value: row.get(3),
-                        is_primary_key: row.get::<String, _>(4) == "PRI",
-                        comment: row.get(5),
-                    })
-                    .collect();
-
-                // Fetch indexes
-                let idx_sql = format!("SHOW INDEX FROM `{}`", table_name.replace("`", "``"));
-                let idx_rows = sqlx::query(&idx_sql).fetch_all(&pool).await?;
-
-                // Group by index name
-                let mut indexes_map: std::collections::HashMap<String, TableIndexStructure> =
-                    std::collections::HashMap::new();
-                for row in idx_rows {
-                    let name: String = row.get("Key_name");
-                    let column: String = row.get("Column_name");
-                    let non_unique: i32 = row.get("Non_unique");
-
-                    let entry = indexes_map
-                        .entry(name.clone())
-                        .or_insert(TableIndexStructure {
-                            name: name.clone(),
-                            columns: vec![],
-                            is_unique: non_unique == 0,
-                            index_type: row.get("Index_type"),
-                        });
-                    entry.columns.push(column);
-                }
-                let indexes = indexes_map.into_values().collect();
-
-                // Fetch constraints
-                let cons_sql = "SELECT CONSTRAINT_NAME, CONSTRAINT_TYPE FROM information_schema.TABLE_CONSTRAINTS WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE();";
-                let cons_rows = sqlx::query(cons_sql)
-                    .bind(table_name)
-                    .fetch_all(&pool)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                // MySQL applies SET TRANSACTION to the next transaction on this session,
+                // so it has to run on the same connection right before BEGIN
+                let mut conn = pool.acquire().await?;
+                sqlx::raw_sql("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                    .execute(&mut *conn)
                     .await?;
-                let constraints = cons_rows
-                    .into_iter()
-                    .map(|row| TableConstraintStructure {
-                        name: row.get(0),
-                        constraint_type: row.get(1),
-                        definition: "".to_string(),
-                    })
-                    .collect();
-
-                Ok(TableStructure {
-                    columns,
-                    indexes,
-                    constraints,
-                })
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                table_page_in_transaction!(
+                    tx,
+                    count_sql,
+                    data_sql,
+                    params,
+                    max_rows,
+                    mysql_row_to_values
+                )
             }
-            Some("sqlite") => {
+            _ => {
+                // SQLite transactions are always serializable
                 let pool = manager
                     .get_sqlite_pools()
                     .await
                     .get(connection_id)
                     .cloned()
-                    .unwrap();
-
-                // Fetch columns
-                let col_sql = format!(
-                    "PRAGMA table_info(\"{}\")",
-                    table_name.replace("\"", "\"\"")
-                );
-                let col_rows = sqlx::query(&col_sql).fetch_all(&pool).await?;
-                let columns = col_rows
-                    .into_iter()
-                    .map(|row| TableColumnStructure {
-                        name: row.get("name"),
-                        data_type: row.get("type"),
-                        is_nullable: row.get::<i32, _>("notnull") == 0,
-                        default_value: row.get("dflt_value"),
-                        is_primary_key: row.get::<i32, _>("pk") > 0,
-                        comment: None,
-                    })
-                    .collect();
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let mut tx = pool.begin().await?;
+                table_page_in_transaction!(
+                    tx,
+                    count_sql,
+                    data_sql,
+                    params,
+                    max_rows,
+                    sqlite_row_to_values
+                )
+            }
+        })
+    }
 
-                // Fetch indexes
-                let idx_list_sql = format!(
-                    "PRAGMA index_list(\"{}\")",
-                    table_name.replace("\"", "\"\"")
-                );
-                let idx_list_rows = sqlx::query(&idx_list_sql).fetch_all(&pool).await?;
-                let mut indexes = Vec::new();
-                for row in idx_list_rows {
-                    let name: String = row.get("name");
-                    let unique: i32 = row.get("unique");
+    /// Null, distinct, min/max and (for numeric columns) average statistics in one table scan
+    pub async fn profile_column(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<ColumnProfile> {
+        validate_identifier(table_name)?;
+        validate_identifier(column_name)?;
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let structure = Self::get_table_structure(manager, connection_id, table_name).await?;
+        let data_type = structure
+            .columns
+            .into_iter()
+            .find(|c| c.name == column_name)
+            .map(|c| c.data_type)
+            .ok_or_else(|| anyhow!("Unknown column '{}'", column_name))?;
+        let numeric = is_numeric_type(&data_type);
+
+        let column = quote_ident(column_name, db_type);
+        let text = |expr: String| match db_type {
+            "postgres" => format!("({})::text", expr),
+            "mysql" => format!("CAST({} AS CHAR)", expr),
+            _ => format!("CAST({} AS TEXT)", expr),
+        };
+        // Postgres lacks MIN/MAX (and equality) for types like boolean and json, so
+        // non-numeric columns are compared by their text form there
+        let comparable = match db_type {
+            "postgres" if !numeric => format!("{}::text", column),
+            _ => column.clone(),
+        };
+        let sql = format!(
+            "SELECT COUNT(*), COUNT({}), COUNT(DISTINCT {}), {}, {}, {} FROM {}",
+            column,
+            comparable,
+            text(format!("MIN({})", comparable)),
+            text(format!("MAX({})", comparable)),
+            if numeric {
+                text(format!("AVG({})", column))
+            } else {
+                text("NULL".to_string())
+            },
+            quote_ident(table_name, db_type)
+        );
+
+        macro_rules! fetch_profile {
+            ($pool:expr) => {{
+                let row = sqlx::query(&sql).fetch_one($pool).await?;
+                let total_count = row.try_get::<i64, _>(0)? as u64;
+                let non_null_count = row.try_get::<i64, _>(1)? as u64;
+                return Ok(ColumnProfile {
+                    column: column_name.to_string(),
+                    data_type,
+                    total_count,
+                    null_count: total_count - non_null_count,
+                    distinct_count: row.try_get::<i64, _>(2)? as u64,
+                    min: row.try_get(3)?,
+                    max: row.try_get(4)?,
+                    avg: row
+                        .try_get::<Option<String>, _>(5)?
+                        .and_then(|avg| avg.parse().ok()),
+                });
+            }};
+        }
 
-                    // Get columns for this index
-                    let idx_info_sql =
-                        format!("PRAGMA index_info(\"{}\")", name.replace("\"", "\"\""));
-                    let idx_info_rows = sqlx::query(&idx_info_sql).fetch_all(&pool).await?;
-                    let cols: Vec<String> = idx_info_rows
-                        .into_iter()
-                        .filter_map(|r| r.try_get("name").ok())
-                        .collect();
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                fetch_profile!(pool);
+            }
+        }
 
-                    indexes.push(TableIndexStructure {
-                        name,
-                        columns: cols,
-                        is_unique: unique > 0,
-                        index_type: "btree".to_string(),
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                fetch_profile!(pool);
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                fetch_profile!(pool);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Returns up to `count` rows picked without a full scan where the backend allows it.
+    /// The methods differ statistically: Postgres `TABLESAMPLE SYSTEM` samples whole pages, so
+    /// rows stored together tend to be picked together, while MySQL and SQLite sort the table
+    /// randomly, which is uniform but reads every row.
+    pub async fn get_table_sample(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        count: u32,
+    ) -> Result<QueryResult> {
+        validate_identifier(table_name)?;
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let quoted_table = quote_ident(table_name, db_type);
+
+        let sql = match db_type {
+            "postgres" => {
+                let estimate: Option<f64> = {
+                    let pools = manager.get_postgres_pools().await;
+                    let pool = pools
+                        .get(connection_id)
+                        .ok_or_else(|| anyhow!("Connection not found"))?;
+                    sqlx::query_scalar(
+                        "SELECT reltuples::float8 FROM pg_class WHERE oid = to_regclass($1)",
+                    )
+                    .bind(&quoted_table)
+                    .fetch_optional(pool)
+                    .await?
+                };
+                // Oversample twice over since page sampling yields an uneven number of rows;
+                // reltuples is -1 (or 0) before the first ANALYZE
+                let percent = match estimate {
+                    Some(rows) if rows > 0.0 => (count as f64 * 2.0 / rows * 100.0).min(100.0),
+                    _ => 100.0,
+                };
+                format!(
+                    "SELECT * FROM {} TABLESAMPLE SYSTEM ({}) LIMIT {}",
+                    quoted_table, percent, count
+                )
+            }
+            "mysql" => format!(
+                "SELECT * FROM {} ORDER BY RAND() LIMIT {}",
+                quoted_table, count
+            ),
+            _ => format!(
+                "SELECT * FROM {} ORDER BY RANDOM() LIMIT {}",
+                quoted_table, count
+            ),
+        };
+
+        Self::execute_query(manager, connection_id, &sql, None, None, None, None).await
+    }
+
+    pub async fn get_table_count(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        filters: Vec<FilterConfig>,
+    ) -> Result<u64> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // Use exact count for accuracy, as reltuples can be 0 for unanalyzed tables
+                let (where_clause, params) = build_where_clause(filters, "postgres");
+                let sql = format!(
+                    "SELECT COUNT(*) FROM {} {};",
+                    quote_ident(table_name, "postgres"),
+                    where_clause
+                );
+                let row = bind_json_params!(sqlx::query(&sql), &params)
+                    .fetch_one(pool)
+                    .await?;
+                return Ok(row.try_get::<i64, _>(0)? as u64);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let (where_clause, params) = build_where_clause(filters, "mysql");
+                let sql = format!(
+                    "SELECT COUNT(*) FROM {} {};",
+                    quote_ident(table_name, "mysql"),
+                    where_clause
+                );
+                let row = bind_json_params!(sqlx::query(&sql), &params)
+                    .fetch_one(pool)
+                    .await?;
+                return Ok(row.try_get::<i64, _>(0).unwrap_or(0) as u64);
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let (where_clause, params) = build_where_clause(filters, "sqlite");
+                let sql = format!(
+                    "SELECT COUNT(*) FROM {} {};",
+                    quote_ident(table_name, "sqlite"),
+                    where_clause
+                );
+                let row = bind_json_params!(sqlx::query(&sql), &params)
+                    .fetch_one(pool)
+                    .await?;
+                return Ok(row.try_get::<i64, _>(0)? as u64);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Execute multiple SQL statements (mutations) - used for committing changes
+    pub async fn execute_mutations(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        statements: Vec<String>,
+    ) -> Result<u64> {
+        let results = Self::execute_mutations_detailed(manager, connection_id, statements).await?;
+        Ok(results.iter().map(|r| r.affected_rows).sum())
+    }
+
+    /// Runs each statement and reports its affected rows and, for INSERTs, the new row id.
+    /// Postgres has no implicit insert id, so it is only reported for `INSERT ... RETURNING`.
+    pub async fn execute_mutations_detailed(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        statements: Vec<String>,
+    ) -> Result<Vec<MutationResult>> {
+        let mut results = Vec::with_capacity(statements.len());
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                for sql in &statements {
+                    if is_insert_statement(sql) && returns_rows(sql) {
+                        let rows = sqlx::query(sql).fetch_all(pool).await?;
+                        results.push(MutationResult {
+                            affected_rows: rows.len() as u64,
+                            last_insert_id: rows.last().and_then(|row| {
+                                row.try_get::<i64, _>(0)
+                                    .or_else(|_| row.try_get::<i32, _>(0).map(i64::from))
+                                    .ok()
+                            }),
+                        });
+                    } else {
+                        let result = sqlx::query(sql).execute(pool).await?;
+                        results.push(MutationResult {
+                            affected_rows: result.rows_affected(),
+                            last_insert_id: None,
+                        });
+                    }
+                }
+                return Ok(results);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                for sql in &statements {
+                    let result = sqlx::query(sql).execute(pool).await?;
+                    // LAST_INSERT_ID() is 0 when the table has no AUTO_INCREMENT column
+                    let last_insert_id = Some(result.last_insert_id())
+                        .filter(|&id| id > 0 && is_insert_statement(sql))
+                        .map(|id| id as i64);
+                    results.push(MutationResult {
+                        affected_rows: result.rows_affected(),
+                        last_insert_id,
                     });
                 }
+                return Ok(results);
+            }
+        }
 
-                // Fetch constraints (foreign keys)
-                let fk_sql = format!(
-                    "PRAGMA foreign_key_list(\"{}\")",
-                    table_name.replace("\"", "\"\"")
-                );
-                let fk_rows = sqlx::query(&fk_sql).fetch_all(&pool).await?;
-                let constraints = fk_rows
-                    .into_iter()
-                    .map(|row| {
-                        let table: String = row.get("table");
-                        let from: String = row.get("from");
-                        let to: String = row.get("to");
-                        TableConstraintStructure {
-                            name: format!("fk_{}_{}", table, from),
-                            constraint_type: "FOREIGN KEY".to_string(),
-                            definition: format!("{} -> {}({})", from, table, to),
-                        }
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                for sql in &statements {
+                    let result = sqlx::query(sql).execute(pool).await?;
+                    results.push(MutationResult {
+                        affected_rows: result.rows_affected(),
+                        last_insert_id: is_insert_statement(sql)
+                            .then(|| result.last_insert_rowid()),
+                    });
+                }
+                return Ok(results);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Copies the row identified by `pk_columns`/`pk_values` within the same table and returns
+    /// the new row's generated id. Primary key columns are left to their defaults unless listed
+    /// in `overrides`, which also replaces values that would break UNIQUE constraints.
+    /// Identity and generated columns are always left to the database.
+    pub async fn duplicate_row(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        pk_columns: Vec<String>,
+        pk_values: Vec<Value>,
+        overrides: BTreeMap<String, Value>,
+    ) -> Result<Option<i64>> {
+        if pk_columns.is_empty() || pk_columns.len() != pk_values.len() {
+            return Err(anyhow!(
+                "Each primary key column needs exactly one value to identify the row"
+            ));
+        }
+        validate_identifier(table_name)?;
+        manager.ensure_writable(connection_id).await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let structure = Self::get_table_structure(manager, connection_id, table_name).await?;
+        if let Some(unknown) = overrides
+            .keys()
+            .find(|name| !structure.columns.iter().any(|c| &c.name == *name))
+        {
+            return Err(anyhow!("Unknown column '{}'", unknown));
+        }
+        let generated = Self::get_generated_columns(manager, connection_id, table_name).await?;
+        if let Some(column) = overrides.keys().find(|name| generated.contains(*name)) {
+            return Err(anyhow!(
+                "Column '{}' is generated by the database and cannot be set",
+                column
+            ));
+        }
+
+        // Postgres types select-list literals as text, so overrides are cast to the exact
+        // column type (information_schema only reports USER-DEFINED or ARRAY for some)
+        let pg_types: BTreeMap<String, String> = if db_type == "postgres" && !overrides.is_empty() {
+            let pools = manager.get_postgres_pools().await;
+            let pool = pools
+                .get(connection_id)
+                .ok_or_else(|| anyhow!("Connection not found"))?;
+            let sql = r#"
+                SELECT a.attname::text, format_type(a.atttypid, a.atttypmod)
+                FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                WHERE c.relname = $1 AND pg_table_is_visible(c.oid)
+                  AND a.attnum > 0 AND NOT a.attisdropped
+            "#;
+            sqlx::query(sql)
+                .bind(table_name)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| (row.get(0), row.get(1)))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        // Overrides and key values are bound in the order their placeholders appear
+        let mut params: Vec<Value> = Vec::new();
+        let mut placeholder = |value: &Value| {
+            params.push(value.clone());
+            match db_type {
+                "postgres" => format!("${}", params.len()),
+                _ => "?".to_string(),
+            }
+        };
+
+        // The row is copied server-side, so values keep their exact types
+        let mut targets = Vec::new();
+        let mut sources = Vec::new();
+        for column in &structure.columns {
+            let source = match overrides.get(&column.name) {
+                Some(value) => match pg_types.get(&column.name) {
+                    Some(pg_type) => format!("CAST({} AS {})", placeholder(value), pg_type),
+                    None => placeholder(value),
+                },
+                None if column.is_primary_key || generated.contains(&column.name) => continue,
+                None => quote_ident(&column.name, db_type),
+            };
+            targets.push(quote_ident(&column.name, db_type));
+            sources.push(source);
+        }
+
+        let predicate = pk_columns
+            .iter()
+            .zip(&pk_values)
+            .map(|(column, value)| -> Result<String> {
+                validate_identifier(column)?;
+                Ok(format!(
+                    "{} = {}",
+                    quote_ident(column, db_type),
+                    placeholder(value)
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join(" AND ");
+
+        let quoted_table = quote_ident(table_name, db_type);
+        let mut sql = format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {} WHERE {}",
+            quoted_table,
+            targets.join(", "),
+            sources.join(", "),
+            quoted_table,
+            predicate
+        );
+        if db_type == "postgres" {
+            if let Some(pk) = structure.columns.iter().find(|c| c.is_primary_key) {
+                sql.push_str(&format!(" RETURNING {}", quote_ident(&pk.name, db_type)));
+            }
+        }
+        let (affected_rows, last_insert_id) = match db_type {
+            "postgres" => {
+                let pools = manager.get_postgres_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let rows = bind_json_params!(sqlx::query(&sql), &params)
+                    .fetch_all(pool)
+                    .await?;
+                let id = rows.last().and_then(|row| {
+                    row.try_get::<i64, _>(0)
+                        .or_else(|_| row.try_get::<i32, _>(0).map(i64::from))
+                        .ok()
+                });
+                (rows.len() as u64, id)
+            }
+            "mysql" => {
+                let pools = manager.get_mysql_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let result = bind_json_params!(sqlx::query(&sql), &params)
+                    .execute(pool)
+                    .await?;
+                // LAST_INSERT_ID() is 0 when the table has no AUTO_INCREMENT column
+                let id = Some(result.last_insert_id())
+                    .filter(|&id| id > 0)
+                    .map(|id| id as i64);
+                (result.rows_affected(), id)
+            }
+            _ => {
+                let pools = manager.get_sqlite_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let result = bind_json_params!(sqlx::query(&sql), &params)
+                    .execute(pool)
+                    .await?;
+                (result.rows_affected(), Some(result.last_insert_rowid()))
+            }
+        };
+        if affected_rows == 0 {
+            return Err(anyhow!("Row not found in '{}'", table_name));
+        }
+        Ok(last_insert_id)
+    }
+
+    /// Remove every row from a table, returning the number of rows it held beforehand
+    pub async fn truncate_table(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        confirm: bool,
+        confirm_production: bool,
+    ) -> Result<u64> {
+        if !confirm {
+            return Err(anyhow!("Truncating a table requires explicit confirmation"));
+        }
+        validate_identifier(table_name)?;
+        manager.ensure_writable(connection_id).await?;
+        manager
+            .ensure_production_confirmed(connection_id, confirm_production)
+            .await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let row_count =
+            Self::get_table_count(manager, connection_id, table_name, Vec::new()).await?;
+
+        // SQLite has no TRUNCATE; an unqualified DELETE uses its truncate optimization
+        let quoted_table = quote_ident(table_name, db_type);
+        let sql = match db_type {
+            "sqlite" => format!("DELETE FROM {}", quoted_table),
+            _ => format!("TRUNCATE TABLE {}", quoted_table),
+        };
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(row_count)
+    }
+
+    pub async fn drop_table(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        cascade: bool,
+        confirm_production: bool,
+    ) -> Result<()> {
+        validate_identifier(table_name)?;
+        manager.ensure_writable(connection_id).await?;
+        manager
+            .ensure_production_confirmed(connection_id, confirm_production)
+            .await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let quoted_table = quote_ident(table_name, db_type);
+        // SQLite has no CASCADE clause; MySQL parses it but ignores it
+        let sql = match db_type {
+            "postgres" | "mysql" if cascade => format!("DROP TABLE {} CASCADE", quoted_table),
+            _ => format!("DROP TABLE {}", quoted_table),
+        };
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(())
+    }
+
+    /// Runs a maintenance operation on one table and returns the server's status messages.
+    /// `op` is "vacuum", "analyze" or "optimize"; each backend maps it to its closest command.
+    /// SQLite can only VACUUM the whole database.
+    pub async fn maintain_table(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        op: &str,
+    ) -> Result<Vec<String>> {
+        validate_identifier(table_name)?;
+        // VACUUM and OPTIMIZE rewrite the table, so read-only connections refuse them
+        manager.ensure_writable(connection_id).await?;
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let quoted_table = quote_ident(table_name, db_type);
+
+        let sql = match (db_type, op) {
+            ("postgres", "vacuum") => format!("VACUUM {}", quoted_table),
+            ("postgres", "analyze") => format!("ANALYZE {}", quoted_table),
+            ("postgres", "optimize") => format!("VACUUM ANALYZE {}", quoted_table),
+            // InnoDB implements OPTIMIZE as a rebuild, which is what VACUUM does elsewhere
+            ("mysql", "vacuum" | "optimize") => format!("OPTIMIZE TABLE {}", quoted_table),
+            ("mysql", "analyze") => format!("ANALYZE TABLE {}", quoted_table),
+            ("sqlite", "vacuum") => "VACUUM".to_string(),
+            ("sqlite", "analyze") => format!("ANALYZE {}", quoted_table),
+            ("sqlite", "optimize") => "PRAGMA optimize".to_string(),
+            _ => return Err(anyhow!("Unknown maintenance operation: {}", op)),
+        };
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                sqlx::raw_sql(&sql).execute(pool).await?;
+                return Ok(Vec::new());
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // One (Table, Op, Msg_type, Msg_text) row per message
+                let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+                return rows
+                    .iter()
+                    .map(|row| -> Result<String> {
+                        let msg_type: String = row.try_get("Msg_type")?;
+                        let msg_text: String = row.try_get("Msg_text")?;
+                        Ok(format!("{}: {}", msg_type, msg_text))
                     })
                     .collect();
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                sqlx::raw_sql(&sql).execute(pool).await?;
+                return Ok(Vec::new());
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    pub async fn rename_table(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        validate_identifier(old_name)?;
+        validate_identifier(new_name)?;
+        manager.ensure_writable(connection_id).await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let sql = match db_type {
+            "mysql" => format!(
+                "RENAME TABLE {} TO {}",
+                quote_ident(old_name, db_type),
+                quote_ident(new_name, db_type)
+            ),
+            _ => format!(
+                "ALTER TABLE {} RENAME TO {}",
+                quote_ident(old_name, db_type),
+                quote_ident(new_name, db_type)
+            ),
+        };
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(())
+    }
+
+    /// Renames a column and/or changes its type and nullability.
+    ///
+    /// A MySQL rename on its own uses RENAME COLUMN. Type or nullability changes go through
+    /// CHANGE COLUMN, which redeclares the whole column, so the definition is rebuilt from
+    /// information_schema to keep its default, AUTO_INCREMENT, ON UPDATE and comment.
+    /// SQLite can only rename columns; changing a type or nullability there means
+    /// recreating the table.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn alter_column(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        column_name: &str,
+        new_name: Option<String>,
+        new_type: Option<String>,
+        nullable: Option<bool>,
+        confirm_production: bool,
+    ) -> Result<()> {
+        validate_identifier(table_name)?;
+        validate_identifier(column_name)?;
+        let new_name = new_name.filter(|n| n != column_name);
+        if let Some(name) = &new_name {
+            validate_identifier(name)?;
+        }
+        if let Some(data_type) = &new_type {
+            validate_type_name(data_type)?;
+        }
+        if new_name.is_none() && new_type.is_none() && nullable.is_none() {
+            return Ok(());
+        }
+        manager.ensure_writable(connection_id).await?;
+        manager
+            .ensure_production_confirmed(connection_id, confirm_production)
+            .await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let quoted_table = quote_ident(table_name, db_type);
+        let quoted_column = quote_ident(column_name, db_type);
+
+        let statements = match db_type {
+            "postgres" => {
+                let mut statements = Vec::new();
+                if let Some(data_type) = &new_type {
+                    // USING makes casts explicit, e.g. text to integer
+                    statements.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{}",
+                        quoted_table, quoted_column, data_type, quoted_column, data_type
+                    ));
+                }
+                if let Some(nullable) = nullable {
+                    statements.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL",
+                        quoted_table,
+                        quoted_column,
+                        if nullable { "DROP" } else { "SET" }
+                    ));
+                }
+                if let Some(name) = &new_name {
+                    statements.push(format!(
+                        "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                        quoted_table,
+                        quoted_column,
+                        quote_ident(name, db_type)
+                    ));
+                }
+                statements
+            }
+            "mysql" if new_type.is_none() && nullable.is_none() => {
+                let name = new_name.as_deref().unwrap_or(column_name);
+                vec![format!(
+                    "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                    quoted_table,
+                    quoted_column,
+                    quote_ident(name, db_type)
+                )]
+            }
+            "mysql" => {
+                let pools = manager.get_mysql_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                let sql = r#"
+                    SELECT COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, EXTRA, COLUMN_COMMENT,
+                           GENERATION_EXPRESSION
+                    FROM information_schema.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?
+                "#;
+                let row = sqlx::query(sql)
+                    .bind(table_name)
+                    .bind(column_name)
+                    .fetch_optional(pool)
+                    .await?
+                    .ok_or_else(|| {
+                        anyhow!("Column '{}' not found in '{}'", column_name, table_name)
+                    })?;
+                let current = MySqlColumnDefinition {
+                    column_type: row.get(0),
+                    is_nullable: row.get::<String, _>(1) == "YES",
+                    default: row.get(2),
+                    extra: row.get(3),
+                    comment: row.get(4),
+                    generation_expression: row.try_get(5).ok().flatten(),
+                };
+                let is_mariadb = manager.is_mariadb(connection_id).await;
+                vec![format!(
+                    "ALTER TABLE {} CHANGE COLUMN {} {} {}",
+                    quoted_table,
+                    quoted_column,
+                    quote_ident(new_name.as_deref().unwrap_or(column_name), db_type),
+                    mysql_column_definition(&current, new_type.as_deref(), nullable, is_mariadb)
+                )]
+            }
+            _ => {
+                if new_type.is_some() || nullable.is_some() {
+                    return Err(anyhow!(
+                        "SQLite can only rename columns; recreate the table to change a column's type or nullability"
+                    ));
+                }
+                let name = new_name.as_deref().unwrap_or(column_name);
+                vec![format!(
+                    "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                    quoted_table,
+                    quoted_column,
+                    quote_ident(name, db_type)
+                )]
+            }
+        };
+        Self::execute_mutations(manager, connection_id, statements).await?;
+
+        Ok(())
+    }
+
+    /// Adds a column; `default` is rendered as a literal, so strings are quoted
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_column(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        column_name: &str,
+        data_type: &str,
+        nullable: bool,
+        default: Option<Value>,
+    ) -> Result<()> {
+        validate_identifier(table_name)?;
+        validate_identifier(column_name)?;
+        validate_type_name(data_type)?;
+        manager.ensure_writable(connection_id).await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let mut sql = format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            quote_ident(table_name, db_type),
+            quote_ident(column_name, db_type),
+            data_type
+        );
+        if !nullable {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &default {
+            sql.push_str(&format!(" DEFAULT {}", sql_literal(default, db_type)));
+        }
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(())
+    }
+
+    pub async fn drop_column(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        column_name: &str,
+        confirm_production: bool,
+    ) -> Result<()> {
+        validate_identifier(table_name)?;
+        validate_identifier(column_name)?;
+        manager.ensure_writable(connection_id).await?;
+        manager
+            .ensure_production_confirmed(connection_id, confirm_production)
+            .await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        if db_type == "sqlite" {
+            let version: String = {
+                let pools = manager.get_sqlite_pools().await;
+                let pool = pools
+                    .get(connection_id)
+                    .ok_or_else(|| anyhow!("Connection not found"))?;
+                sqlx::query_scalar("SELECT sqlite_version()")
+                    .fetch_one(pool)
+                    .await?
+            };
+            if !sqlite_supports_drop_column(&version) {
+                return Err(anyhow!(
+                    "SQLite {} can't drop columns (3.35 or newer is required); recreate the table without the column",
+                    version
+                ));
+            }
+        }
+
+        let sql = format!(
+            "ALTER TABLE {} DROP COLUMN {}",
+            quote_ident(table_name, db_type),
+            quote_ident(column_name, db_type)
+        );
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(())
+    }
+
+    /// Create an index and return its name (generated when not supplied)
+    pub async fn create_index(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        columns: Vec<String>,
+        unique: bool,
+        index_name: Option<String>,
+        method: Option<String>,
+    ) -> Result<String> {
+        if columns.is_empty() {
+            return Err(anyhow!(
+                "At least one column is required to create an index"
+            ));
+        }
+        validate_identifier(table_name)?;
+        for column in &columns {
+            validate_identifier(column)?;
+        }
+        manager.ensure_writable(connection_id).await?;
+
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let index_name = index_name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "{}_{}_{}",
+                    if unique { "ux" } else { "idx" },
+                    table_name,
+                    columns.join("_")
+                )
+            });
+        validate_identifier(&index_name)?;
+
+        // The access method can't be quoted, so only well-known methods are accepted
+        let method = method.map(|m| m.to_lowercase());
+        let using = match (db_type, method.as_deref()) {
+            (_, None) => None,
+            ("postgres", Some(m @ ("btree" | "hash" | "gist" | "gin" | "spgist" | "brin"))) => {
+                Some(m.to_string())
+            }
+            ("mysql", Some(m @ ("btree" | "hash"))) => Some(m.to_uppercase()),
+            (_, Some(m)) => {
+                return Err(anyhow!(
+                    "Index method '{}' is not supported for {}",
+                    m,
+                    db_type
+                ))
+            }
+        };
+
+        let quoted_columns = columns
+            .iter()
+            .map(|c| quote_ident(c, db_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let unique_kw = if unique { "UNIQUE " } else { "" };
+        let quoted_index = quote_ident(&index_name, db_type);
+        let quoted_table = quote_ident(table_name, db_type);
+
+        let sql = match (db_type, using) {
+            ("postgres", Some(m)) => format!(
+                "CREATE {}INDEX {} ON {} USING {} ({})",
+                unique_kw, quoted_index, quoted_table, m, quoted_columns
+            ),
+            ("mysql", Some(m)) => format!(
+                "CREATE {}INDEX {} ON {} ({}) USING {}",
+                unique_kw, quoted_index, quoted_table, quoted_columns, m
+            ),
+            _ => format!(
+                "CREATE {}INDEX {} ON {} ({})",
+                unique_kw, quoted_index, quoted_table, quoted_columns
+            ),
+        };
+        Self::execute_mutations(manager, connection_id, vec![sql]).await?;
+
+        Ok(index_name)
+    }
+
+    /// Size of the current database: Postgres reports it directly, MySQL sums table data and
+    /// indexes, and SQLite multiplies the page count by the page size of the main file
+    pub async fn get_database_size(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+    ) -> Result<DatabaseSize> {
+        let size = |bytes: i64| DatabaseSize {
+            total_size: format_bytes(bytes as u64),
+            total_bytes: bytes as u64,
+        };
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let bytes: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+                    .fetch_one(pool)
+                    .await?;
+                return Ok(size(bytes));
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT CAST(COALESCE(SUM(DATA_LENGTH + INDEX_LENGTH), 0) AS SIGNED)
+                    FROM information_schema.TABLES
+                    WHERE TABLE_SCHEMA = DATABASE()
+                "#;
+                let bytes: i64 = sqlx::query_scalar(sql).fetch_one(pool).await?;
+                return Ok(size(bytes));
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let bytes: i64 = sqlx::query_scalar(
+                    "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                )
+                .fetch_one(pool)
+                .await?;
+                return Ok(size(bytes));
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Distinct values of a column in sort order, for filter facets. `limit` defaults to
+    /// `DEFAULT_DISTINCT_VALUES` and is capped at `MAX_DISTINCT_VALUES`.
+    pub async fn get_distinct_values(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        column_name: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<Value>> {
+        validate_identifier(table_name)?;
+        validate_identifier(column_name)?;
+        let limit = limit
+            .unwrap_or(DEFAULT_DISTINCT_VALUES)
+            .clamp(1, MAX_DISTINCT_VALUES);
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let quoted_column = quote_ident(column_name, db_type);
+        let sql = format!(
+            "SELECT DISTINCT {} FROM {} ORDER BY {} LIMIT {}",
+            quoted_column,
+            quote_ident(table_name, db_type),
+            quoted_column,
+            limit
+        );
+
+        macro_rules! distinct_values {
+            ($pool:expr, $db_macro:ident) => {{
+                let rows = sqlx::query(&sql).fetch_all($pool).await?;
+                return Ok(rows
+                    .iter()
+                    .filter_map(|row| $db_macro!(row).into_iter().next())
+                    .collect());
+            }};
+        }
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                distinct_values!(pool, postgres_row_to_values);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                distinct_values!(pool, mysql_row_to_values);
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                distinct_values!(pool, sqlite_row_to_values);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Allowed values of an enum, in definition order. On Postgres `enum_type_name` is the
+    /// (optionally schema-qualified) type name; MySQL enums belong to a column, so there it
+    /// names the column of `table_name`.
+    pub async fn get_enum_values(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        enum_type_name: &str,
+        table_name: Option<&str>,
+    ) -> Result<Vec<String>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let labels: Vec<String> = sqlx::query_scalar(
+                    "SELECT enumlabel::text FROM pg_enum WHERE enumtypid = to_regtype($1)::oid ORDER BY enumsortorder",
+                )
+                .bind(enum_type_name)
+                .fetch_all(pool)
+                .await?;
+                if labels.is_empty() {
+                    return Err(anyhow!("Enum type '{}' not found", enum_type_name));
+                }
+                return Ok(labels);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let table_name =
+                    table_name.ok_or_else(|| anyhow!("MySQL enums need a table name"))?;
+                let column_type: Option<String> = sqlx::query_scalar(
+                    "SELECT COLUMN_TYPE FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?",
+                )
+                .bind(table_name)
+                .bind(enum_type_name)
+                .fetch_optional(pool)
+                .await?;
+                let column_type = column_type.ok_or_else(|| {
+                    anyhow!("Column '{}.{}' not found", table_name, enum_type_name)
+                })?;
+                return parse_mysql_enum_labels(&column_type).ok_or_else(|| {
+                    anyhow!(
+                        "Column '{}.{}' is not an ENUM or SET",
+                        table_name,
+                        enum_type_name
+                    )
+                });
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if pools.contains_key(connection_id) {
+                return Err(anyhow!("SQLite has no enum types"));
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Client sessions on the server, including other applications' sessions
+    pub async fn list_sessions(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+    ) -> Result<Vec<DbSession>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT pid::int8, usename::text, datname::text, client_addr::text, state, query,
+                           (EXTRACT(EPOCH FROM now() - query_start) * 1000)::int8
+                    FROM pg_stat_activity
+                    WHERE backend_type = 'client backend'
+                    ORDER BY pid
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                return Ok(rows
+                    .iter()
+                    .map(|row| DbSession {
+                        pid: row.get(0),
+                        user: row.get(1),
+                        database: row.get(2),
+                        client_addr: row.get(3),
+                        state: row.get(4),
+                        query: row.get(5),
+                        duration_ms: row.get::<Option<i64>, _>(6).map(|ms| ms.max(0) as u64),
+                    })
+                    .collect());
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT CAST(ID AS SIGNED), USER, DB, HOST, COMMAND, INFO, CAST(TIME AS SIGNED)
+                    FROM information_schema.PROCESSLIST
+                    ORDER BY ID
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                return Ok(rows
+                    .iter()
+                    .map(|row| DbSession {
+                        pid: row.get(0),
+                        user: row.get(1),
+                        database: row.get(2),
+                        client_addr: row.get(3),
+                        state: row.get(4),
+                        query: row.get(5),
+                        duration_ms: row
+                            .get::<Option<i64>, _>(6)
+                            .map(|secs| secs.max(0) as u64 * 1000),
+                    })
+                    .collect());
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if pools.contains_key(connection_id) {
+                return Err(anyhow!("SQLite has no server sessions"));
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Terminates a server session by pid. Returns false when no such session exists.
+    pub async fn kill_session(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        pid: i64,
+    ) -> Result<bool> {
+        manager.ensure_writable(connection_id).await?;
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let sql = kill_session_sql(db_type, pid)?;
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let killed: bool = sqlx::query_scalar(&sql).fetch_one(pool).await?;
+                return Ok(killed);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                return match sqlx::raw_sql(&sql).execute(pool).await {
+                    Ok(_) => Ok(true),
+                    // ER_NO_SUCH_THREAD
+                    Err(sqlx::Error::Database(db))
+                        if db
+                            .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                            .is_some_and(|e| e.number() == 1094) =>
+                    {
+                        Ok(false)
+                    }
+                    Err(e) => Err(e.into()),
+                };
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    pub async fn get_table_metadata(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+    ) -> Result<TableMetadata> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT 
+                        pg_size_pretty(pg_total_relation_size(quote_ident($1))) as total_size,
+                        pg_size_pretty(pg_relation_size(quote_ident($1))) as data_size,
+                        pg_size_pretty(pg_indexes_size(quote_ident($1))) as index_size,
+                        obj_description(quote_ident($1)::regclass, 'pg_class') as comment
+                "#;
+                let row = sqlx::query(sql).bind(table_name).fetch_one(pool).await?;
+
+                return Ok(TableMetadata {
+                    total_size: row.try_get(0).ok(),
+                    data_size: row.try_get(1).ok(),
+                    index_size: row.try_get(2).ok(),
+                    comment: row.try_get(3).ok(),
+                    page_count: None,
+                    avg_row_size: None,
+                });
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let mut conn = pool.acquire().await?;
+                // MySQL 8 serves cached table statistics for up to a day; 5.7 skips the
+                // versioned comment. MariaDB has no such cache and would run the comment
+                // (10.x > 8.0) against a variable it doesn't have.
+                if !manager.is_mariadb(connection_id).await {
+                    sqlx::raw_sql("/*!80000 SET SESSION information_schema_stats_expiry = 0 */")
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                let sql = r#"
+                    SELECT 
+                        (DATA_LENGTH + INDEX_LENGTH) as total_size,
+                        DATA_LENGTH as data_size,
+                        INDEX_LENGTH as index_size,
+                        TABLE_COMMENT as comment
+                    FROM information_schema.TABLES
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+                "#;
+                let row = sqlx::query(sql)
+                    .bind(table_name)
+                    .fetch_one(&mut *conn)
+                    .await?;
+
+                let total: Option<u64> = row.try_get(0).ok();
+                let data: Option<u64> = row.try_get(1).ok();
+                let index: Option<u64> = row.try_get(2).ok();
+
+                return Ok(TableMetadata {
+                    total_size: total.map(format_bytes),
+                    data_size: data.map(format_bytes),
+                    index_size: index.map(format_bytes),
+                    comment: row.try_get(3).ok(),
+                    page_count: None,
+                    avg_row_size: None,
+                });
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // Per-table sizes need the dbstat virtual table, which not every build includes
+                let sql = r#"
+                    SELECT
+                        COALESCE(SUM(CASE WHEN name = ?1 THEN pgsize END), 0) AS data_size,
+                        COALESCE(SUM(CASE WHEN name != ?1 THEN pgsize END), 0) AS index_size,
+                        COUNT(*) AS page_count
+                    FROM dbstat
+                    WHERE name = ?1
+                       OR name IN (SELECT name FROM sqlite_schema WHERE type = 'index' AND tbl_name = ?1)
+                "#;
+                let Ok(row) = sqlx::query(sql).bind(table_name).fetch_one(pool).await else {
+                    return Ok(TableMetadata {
+                        total_size: Some("Unknown".to_string()),
+                        data_size: Some("Unknown".to_string()),
+                        index_size: Some("Unknown".to_string()),
+                        comment: None,
+                        page_count: None,
+                        avg_row_size: None,
+                    });
+                };
+
+                let data: i64 = row.try_get(0)?;
+                let index: i64 = row.try_get(1)?;
+                let pages: i64 = row.try_get(2)?;
+                let row_count = sqlx::query(&format!(
+                    "SELECT COUNT(*) FROM {}",
+                    quote_ident(table_name, "sqlite")
+                ))
+                .fetch_one(pool)
+                .await?
+                .try_get::<i64, _>(0)?;
+
+                return Ok(TableMetadata {
+                    total_size: Some(format_bytes((data + index) as u64)),
+                    data_size: Some(format_bytes(data as u64)),
+                    index_size: Some(format_bytes(index as u64)),
+                    comment: None,
+                    page_count: Some(pages as u64),
+                    avg_row_size: (row_count > 0).then(|| format_bytes((data / row_count) as u64)),
+                });
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    pub async fn get_ai_schema_tables(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+    ) -> Result<Vec<AiSchemaTable>> {
+        let db_type = {
+            if manager
+                .get_postgres_pools()
+                .await
+                .contains_key(connection_id)
+            {
+                Some("postgres")
+            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
+                Some("mysql")
+            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
+                Some("sqlite")
+            } else {
+                None
+            }
+        };
+
+        match db_type {
+            Some("postgres") => {
+                let pool = manager
+                    .get_postgres_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+                let sql = r#"
+                    SELECT
+                        c.table_schema,
+                        c.table_name,
+                        t.table_type,
+                        c.column_name,
+                        c.data_type,
+                        c.is_nullable,
+                        c.column_default,
+                        (pk.column_name IS NOT NULL) AS is_primary
+                    FROM information_schema.columns c
+                    JOIN information_schema.tables t
+                      ON c.table_schema = t.table_schema
+                     AND c.table_name = t.table_name
+                    LEFT JOIN (
+                        SELECT
+                            kcu.table_schema,
+                            kcu.table_name,
+                            kcu.column_name
+                        FROM information_schema.table_constraints tc
+                        JOIN information_schema.key_column_usage kcu
+                          ON tc.constraint_name = kcu.constraint_name
+                         AND tc.table_schema = kcu.table_schema
+                         AND tc.table_name = kcu.table_name
+                        WHERE tc.constraint_type = 'PRIMARY KEY'
+                    ) pk
+                      ON pk.table_schema = c.table_schema
+                     AND pk.table_name = c.table_name
+                     AND pk.column_name = c.column_name
+                    WHERE c.table_schema NOT IN ('information_schema', 'pg_catalog')
+                    ORDER BY c.table_schema, c.table_name, c.ordinal_position;
+                "#;
+
+                let rows = sqlx::query(sql).fetch_all(&pool).await?;
+                let mut tables = BTreeMap::new();
+
+                for row in rows {
+                    let schema: String = row.get(0);
+                    let table_name: String = row.get(1);
+                    let table_type: String = row.get(2);
+                    let key = format!("{}.{}", schema, table_name);
+
+                    let entry = tables.entry(key).or_insert_with(|| AiSchemaTable {
+                        name: table_name.clone(),
+                        schema: Some(schema.clone()),
+                        item_type: sidebar_item_type_from_table_type(&table_type),
+                        columns: Vec::new(),
+                    });
+
+                    entry.columns.push(TableColumnStructure {
+                        name: row.get(3),
+                        data_type: row.get(4),
+                        is_nullable: row.get::<String, _>(5) == "YES",
+                        default_value: row.get(6),
+                        is_primary_key: row.get(7),
+                        comment: None,
+                    });
+                }
+
+                Ok(tables.into_values().collect())
+            }
+            Some("mysql") => {
+                let pool = manager
+                    .get_mysql_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+                let sql = r#"
+                    SELECT
+                        c.TABLE_SCHEMA,
+                        c.TABLE_NAME,
+                        t.TABLE_TYPE,
+                        c.COLUMN_NAME,
+                        c.COLUMN_TYPE,
+                        c.IS_NULLABLE,
+                        c.COLUMN_DEFAULT,
+                        c.COLUMN_KEY
+                    FROM information_schema.COLUMNS c
+                    JOIN information_schema.TABLES t
+                      ON c.TABLE_SCHEMA = t.TABLE_SCHEMA
+                     AND c.TABLE_NAME = t.TABLE_NAME
+                    WHERE c.TABLE_SCHEMA = DATABASE()
+                    ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION;
+                "#;
+
+                let rows = sqlx::query(sql).fetch_all(&pool).await?;
+                let mut tables = BTreeMap::new();
+
+                for row in rows {
+                    let schema: String = row.get(0);
+                    let table_name: String = row.get(1);
+                    let table_type: String = row.get(2);
+                    let key = format!("{}.{}", schema, table_name);
+
+                    let entry = tables.entry(key).or_insert_with(|| AiSchemaTable {
+                        name: table_name.clone(),
+                        schema: Some(schema.clone()),
+                        item_type: sidebar_item_type_from_table_type(&table_type),
+                        columns: Vec::new(),
+                    });
+
+                    entry.columns.push(TableColumnStructure {
+                        name: row.get(3),
+                        data_type: row.get(4),
+                        is_nullable: row.get::<String, _>(5) == "YES",
+                        default_value: row.get(6),
+                        is_primary_key: row.get::<String, _>(7) == "PRI",
+                        comment: None,
+                    });
+                }
+
+                Ok(tables.into_values().collect())
+            }
+            Some("sqlite") => {
+                let pool = manager
+                    .get_sqlite_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+                let rows = sqlx::query(
+                    "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;",
+                )
+                .fetch_all(&pool)
+                .await?;
+
+                let mut tables = Vec::new();
+
+                for row in rows {
+                    let table_name: String = row.get(0);
+                    let table_type: String = row.get(1);
+                    let pragma_sql =
+                        format!("PRAGMA table_info({})", quote_ident(table_name, "sqlite"));
+                    let col_rows = sqlx::query(&pragma_sql).fetch_all(&pool).await?;
+                    let columns = col_rows
+                        .into_iter()
+                        .map(|col_row| TableColumnStructure {
+                            name: col_row.get("name"),
+                            data_type: col_row.get("type"),
+                            is_nullable: col_row.get::<i64, _>("notnull") == 0,
+                            default_value: col_row.get("dflt_value"),
+                            is_primary_key: col_row.get::<i64, _>("pk") > 0,
+                            comment: None,
+                        })
+                        .collect();
+
+                    tables.push(AiSchemaTable {
+                        name: table_name,
+                        schema: None,
+                        item_type: sidebar_item_type_from_table_type(&table_type),
+                        columns,
+                    });
+                }
+
+                Ok(tables)
+            }
+            _ => Err(anyhow!("Connection not found")),
+        }
+    }
+
+    /// Table -> column names (in column order) for editor autocompletion, fetched for the
+    /// whole current schema in one query instead of one structure lookup per table
+    pub async fn get_completion_schema(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+    ) -> Result<BTreeMap<String, Vec<String>>> {
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT table_name::text, column_name::text
+                    FROM information_schema.columns
+                    WHERE table_schema = ANY(current_schemas(false))
+                    ORDER BY table_name, ordinal_position
+                "#;
+                let rows = sqlx::query_as(sql).fetch_all(pool).await?;
+                return Ok(group_columns_by_table(rows));
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT TABLE_NAME, COLUMN_NAME
+                    FROM information_schema.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE()
+                    ORDER BY TABLE_NAME, ORDINAL_POSITION
+                "#;
+                let rows = sqlx::query_as(sql).fetch_all(pool).await?;
+                return Ok(group_columns_by_table(rows));
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = r#"
+                    SELECT m.name, p.name
+                    FROM sqlite_master m
+                    JOIN pragma_table_info(m.name) p
+                    WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%'
+                    ORDER BY m.name, p.cid
+                "#;
+                let rows = sqlx::query_as(sql).fetch_all(pool).await?;
+                return Ok(group_columns_by_table(rows));
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    /// Compare two table structures, describing what changes turn `a` into `b`
+    pub fn diff_table_structure(a: &TableStructure, b: &TableStructure) -> StructureDiff {
+        let (added_columns, removed_columns, changed_columns) =
+            diff_by_name(&a.columns, &b.columns, |c| &c.name);
+        let (added_indexes, removed_indexes, changed_indexes) =
+            diff_by_name(&a.indexes, &b.indexes, |i| &i.name);
+        let (added_constraints, removed_constraints, changed_constraints) =
+            diff_by_name(&a.constraints, &b.constraints, |c| &c.name);
+
+        StructureDiff {
+            added_columns,
+            removed_columns,
+            changed_columns,
+            added_indexes,
+            removed_indexes,
+            changed_indexes,
+            added_constraints,
+            removed_constraints,
+            changed_constraints,
+        }
+    }
+
+    /// Emit the ALTER statements that turn structure A into structure B for `table_name`.
+    ///
+    /// DROP statements and narrowing type changes are commented out unless `allow_destructive`
+    /// is set, and changes the backend can't express (e.g. altering SQLite columns) are left as
+    /// comments.
+    pub fn generate_migration_sql(
+        diff: &StructureDiff,
+        table_name: &str,
+        db_type: &str,
+        allow_destructive: bool,
+    ) -> String {
+        let table = quote_ident(table_name, db_type);
+        let mut script = MigrationScript::default();
+
+        for col in &diff.added_columns {
+            script.push(
+                format!(
+                    "ALTER TABLE {} ADD COLUMN {}",
+                    table,
+                    column_definition(col, db_type)
+                ),
+                true,
+            );
+        }
+
+        for change in &diff.changed_columns {
+            let (before, after) = (&change.before, &change.after);
+            let col = quote_ident(&after.name, db_type);
+            // Narrowing a type can lose data, so it needs the destructive flag like a DROP
+            let type_safe = before.data_type == after.data_type
+                || is_widening_type_change(&before.data_type, &after.data_type);
+            match db_type {
+                "postgres" => {
+                    if before.data_type != after.data_type {
+                        script.push(
+                            format!(
+                                "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
+                                table, col, after.data_type
+                            ),
+                            allow_destructive || type_safe,
+                        );
+                    }
+                    if before.is_nullable != after.is_nullable {
+                        let action = if after.is_nullable { "DROP" } else { "SET" };
+                        script.push(
+                            format!(
+                                "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL",
+                                table, col, action
+                            ),
+                            true,
+                        );
+                    }
+                    if before.default_value != after.default_value {
+                        let action = match &after.default_value {
+                            Some(d) => format!("SET DEFAULT {}", d),
+                            None => "DROP DEFAULT".to_string(),
+                        };
+                        script.push(
+                            format!("ALTER TABLE {} ALTER COLUMN {} {}", table, col, action),
+                            true,
+                        );
+                    }
+                }
+                "mysql" => script.push(
+                    format!(
+                        "ALTER TABLE {} MODIFY COLUMN {}",
+                        table,
+                        column_definition(after, db_type)
+                    ),
+                    allow_destructive || type_safe,
+                ),
+                _ => script.note(format!(
+                    "SQLite cannot alter column {}; rebuild the table to apply this change",
+                    col
+                )),
+            }
+        }
+
+        for col in &diff.removed_columns {
+            script.push(
+                format!(
+                    "ALTER TABLE {} DROP COLUMN {}",
+                    table,
+                    quote_ident(&col.name, db_type)
+                ),
+                allow_destructive,
+            );
+        }
+
+        let changed_before = diff.changed_indexes.iter().map(|c| &c.before);
+        let changed_after = diff.changed_indexes.iter().map(|c| &c.after);
+        for idx in diff.removed_indexes.iter().chain(changed_before) {
+            let index = quote_ident(&idx.name, db_type);
+            let sql = match db_type {
+                "mysql" => format!("DROP INDEX {} ON {}", index, table),
+                _ => format!("DROP INDEX {}", index),
+            };
+            script.push(sql, allow_destructive);
+        }
+        for idx in diff.added_indexes.iter().chain(changed_after) {
+            if idx.columns.is_empty() {
+                script.note(format!(
+                    "Index {} has no known columns; create it manually",
+                    idx.name
+                ));
+                continue;
+            }
+            // A changed index can only be recreated once its old definition is dropped
+            let enabled = allow_destructive
+                || !diff
+                    .changed_indexes
+                    .iter()
+                    .any(|c| c.after.name == idx.name);
+            let columns = idx
+                .columns
+                .iter()
+                .map(|c| quote_ident(c, db_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            script.push(
+                format!(
+                    "CREATE {}INDEX {} ON {} ({})",
+                    if idx.is_unique { "UNIQUE " } else { "" },
+                    quote_ident(&idx.name, db_type),
+                    table,
+                    columns
+                ),
+                enabled,
+            );
+        }
+
+        let changed_before = diff.changed_constraints.iter().map(|c| &c.before);
+        let changed_after = diff.changed_constraints.iter().map(|c| &c.after);
+        for con in diff.removed_constraints.iter().chain(changed_before) {
+            if db_type == "sqlite" {
+                script.note(format!(
+                    "SQLite cannot drop constraint {}; rebuild the table",
+                    con.name
+                ));
+                continue;
+            }
+            script.push(
+                format!(
+                    "ALTER TABLE {} DROP CONSTRAINT {}",
+                    table,
+                    quote_ident(&con.name, db_type)
+                ),
+                allow_destructive,
+            );
+        }
+        for con in diff.added_constraints.iter().chain(changed_after) {
+            if db_type == "sqlite" || con.definition.is_empty() {
+                script.note(format!(
+                    "Constraint {} ({}) must be added manually",
+                    con.name, con.constraint_type
+                ));
+                continue;
+            }
+            let enabled = allow_destructive
+                || !diff
+                    .changed_constraints
+                    .iter()
+                    .any(|c| c.after.name == con.name);
+            script.push(
+                format!(
+                    "ALTER TABLE {} ADD CONSTRAINT {} {}",
+                    table,
+                    quote_ident(&con.name, db_type),
+                    con.definition
+                ),
+                enabled,
+            );
+        }
+
+        script.lines.join("\n")
+    }
+
+    pub async fn get_table_structure(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+    ) -> Result<TableStructure> {
+        let db_type = {
+            if manager
+                .get_postgres_pools()
+                .await
+                .contains_key(connection_id)
+            {
+                Some("postgres")
+            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
+                Some("mysql")
+            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
+                Some("sqlite")
+            } else {
+                None
+            }
+        };
+
+        match db_type {
+            Some("postgres") => {
+                let pool = manager
+                    .get_postgres_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+
+                // Fetch columns
+                let col_sql = r#"
+                    SELECT 
+                        column_name, 
+                        format_type(a.atttypid, a.atttypmod) AS data_type, 
+                        is_nullable, 
+                        column_default,
+                        EXISTS (
+                            SELECT 1 FROM information_schema.key_column_usage kcu
+                            JOIN information_schema.table_constraints tc ON kcu.constraint_name = tc.constraint_name
+                            WHERE kcu.table_name = c.table_name AND kcu.column_name = c.column_name AND tc.constraint_type = 'PRIMARY KEY'
+                        ) as is_primary
+                    FROM information_schema.columns c
+                    JOIN pg_attribute a
+                        ON a.attrelid = (quote_ident(c.table_schema) || '.' || quote_ident(c.table_name))::regclass
+                        AND a.attname = c.column_name
+                    WHERE table_name = $1 AND table_schema = 'public'
+                    ORDER BY ordinal_position;
+                "#;
+                let col_rows = sqlx::query(col_sql)
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?;
+                let columns = col_rows
+                    .into_iter()
+                    .map(|row| {
+                        TableColumnStructure {
+                            name: row.get(0),
+                            data_type: row.get(1),
+                            is_nullable: row.get::<String, _>(2) == "YES",
+                            default_value: row.get(3),
+                            is_primary_key: row.get(4),
+                            comment: None, // We could fetch this too if needed
+                        }
+                    })
+                    .collect();
+
+                // Fetch indexes
+                let idx_sql = "SELECT indexname, indexdef FROM pg_indexes WHERE tablename = $1 AND schemaname = 'public';";
+                let idx_rows = sqlx::query(idx_sql)
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?;
+                let indexes = idx_rows
+                    .into_iter()
+                    .map(|row| {
+                        let def: String = row.get(1);
+                        TableIndexStructure {
+                            name: row.get(0),
+                            columns: vec![], // Logic to parse columns from def would be complex, leaving empty for now or could just show def
+                            is_unique: def.contains("UNIQUE"),
+                            index_type: "btree".to_string(), // Default in PG
+                        }
+                    })
+                    .collect();
+
+                // Fetch constraints
+                let cons_sql = r#"
+                    SELECT 
+                        constraint_name, 
+                        constraint_type
+                    FROM information_schema.table_constraints 
+                    WHERE table_name = $1 AND table_schema = 'public';
+                "#;
+                let cons_rows = sqlx::query(cons_sql)
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?;
+                let constraints = cons_rows
+                    .into_iter()
+                    .map(|row| TableConstraintStructure {
+                        name: row.get(0),
+                        constraint_type: row.get(1),
+                        definition: "".to_string(),
+                    })
+                    .collect();
+
+                Ok(TableStructure {
+                    columns,
+                    indexes,
+                    constraints,
+                })
+            }
+            Some("mysql") => {
+                let pool = manager
+                    .get_mysql_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+
+                // Fetch columns
+                let col_sql = r#"
+                    SELECT 
+                        COLUMN_NAME, 
+                        COLUMN_TYPE, 
+                        IS_NULLABLE, 
+                        COLUMN_DEFAULT, 
+                        COLUMN_KEY,
+                        COLUMN_COMMENT
+                    FROM information_schema.COLUMNS 
+                    WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE()
+                    ORDER BY ORDINAL_POSITION;
+                "#;
+                let col_rows = sqlx::query(col_sql)
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?;
+                let is_mariadb = manager.is_mariadb(connection_id).await;
+                let columns = col_rows
+                    .into_iter()
+                    .map(|row| {
+                        let default_value: Option<String> = row.get(3);
+                        TableColumnStructure {
+                            name: row.get(0),
+                            data_type: row.get(1),
+                            is_nullable: row.get::<String, _>(2) == "YES",
+                            default_value: if is_mariadb {
+                                default_value.and_then(|d| normalize_mariadb_default(&d))
+                            } else {
+                                default_value
+                            },
+                            is_primary_key: row.get::<String, _>(4) == "PRI",
+                            comment: row.get(5),
+                        }
+                    })
+                    .collect();
+
+                // Fetch indexes
+                let idx_sql = format!("SHOW INDEX FROM {}", quote_ident(table_name, "mysql"));
+                let idx_rows = sqlx::query(&idx_sql).fetch_all(&pool).await?;
+
+                // Group by index name
+                let mut indexes_map: std::collections::HashMap<String, TableIndexStructure> =
+                    std::collections::HashMap::new();
+                for row in idx_rows {
+                    let name: String = row.get("Key_name");
+                    let column: String = row.get("Column_name");
+                    let non_unique: i32 = row.get("Non_unique");
+
+                    let entry = indexes_map
+                        .entry(name.clone())
+                        .or_insert(TableIndexStructure {
+                            name: name.clone(),
+                            columns: vec![],
+                            is_unique: non_unique == 0,
+                            index_type: row.get("Index_type"),
+                        });
+                    entry.columns.push(column);
+                }
+                let indexes = indexes_map.into_values().collect();
+
+                // Fetch constraints
+                let cons_sql = "SELECT CONSTRAINT_NAME, CONSTRAINT_TYPE FROM information_schema.TABLE_CONSTRAINTS WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE();";
+                let cons_rows = sqlx::query(cons_sql)
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?;
+                let constraints = cons_rows
+                    .into_iter()
+                    .map(|row| TableConstraintStructure {
+                        name: row.get(0),
+                        constraint_type: row.get(1),
+                        definition: "".to_string(),
+                    })
+                    .collect();
+
+                Ok(TableStructure {
+                    columns,
+                    indexes,
+                    constraints,
+                })
+            }
+            Some("sqlite") => {
+                let pool = manager
+                    .get_sqlite_pools()
+                    .await
+                    .get(connection_id)
+                    .cloned()
+                    .unwrap();
+
+                // Fetch columns
+                let col_sql = format!("PRAGMA table_info({})", quote_ident(table_name, "sqlite"));
+                let col_rows = sqlx::query(&col_sql).fetch_all(&pool).await?;
+                let columns = col_rows
+                    .into_iter()
+                    .map(|row| TableColumnStructure {
+                        name: row.get("name"),
+                        data_type: row.get("type"),
+                        is_nullable: row.get::<i32, _>("notnull") == 0,
+                        default_value: row.get("dflt_value"),
+                        is_primary_key: row.get::<i32, _>("pk") > 0,
+                        comment: None,
+                    })
+                    .collect();
+
+                // Fetch indexes
+                let idx_list_sql =
+                    format!("PRAGMA index_list({})", quote_ident(table_name, "sqlite"));
+                let idx_list_rows = sqlx::query(&idx_list_sql).fetch_all(&pool).await?;
+                let mut indexes = Vec::new();
+                for row in idx_list_rows {
+                    let name: String = row.get("name");
+                    let unique: i32 = row.get("unique");
+
+                    // Get columns for this index
+                    let idx_info_sql =
+                        format!("PRAGMA index_info({})", quote_ident(&name, "sqlite"));
+                    let idx_info_rows = sqlx::query(&idx_info_sql).fetch_all(&pool).await?;
+                    let cols: Vec<String> = idx_info_rows
+                        .into_iter()
+                        .filter_map(|r| r.try_get("name").ok())
+                        .collect();
+
+                    indexes.push(TableIndexStructure {
+                        name,
+                        columns: cols,
+                        is_unique: unique > 0,
+                        index_type: "btree".to_string(),
+                    });
+                }
+
+                // Fetch constraints (foreign keys)
+                let fk_sql = format!(
+                    "PRAGMA foreign_key_list({})",
+                    quote_ident(table_name, "sqlite")
+                );
+                let fk_rows = sqlx::query(&fk_sql).fetch_all(&pool).await?;
+                let constraints = fk_rows
+                    .into_iter()
+                    .map(|row| {
+                        let table: String = row.get("table");
+                        let from: String = row.get("from");
+                        let to: String = row.get("to");
+                        TableConstraintStructure {
+                            name: format!("fk_{}_{}", table, from),
+                            constraint_type: "FOREIGN KEY".to_string(),
+                            definition: format!("{} -> {}({})", from, table, to),
+                        }
+                    })
+                    .collect();
+
+                Ok(TableStructure {
+                    columns,
+                    indexes,
+                    constraints,
+                })
+            }
+            Some(_) => Err(anyhow!("Unknown database type")),
+            None => Err(anyhow!("Connection not found")),
+        }
+    }
+
+    /// The SELECT list for exporting `table_name`: `*`, or the requested columns in the
+    /// requested order after checking each one exists
+    pub async fn export_select_list(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        columns: Option<&[String]>,
+        db_type: &str,
+    ) -> Result<String> {
+        let Some(columns) = columns else {
+            return Ok("*".to_string());
+        };
+        if columns.is_empty() {
+            return Err(anyhow!("No columns selected for export"));
+        }
+
+        let structure = Self::get_table_structure(manager, connection_id, table_name).await?;
+        if let Some(unknown) = columns
+            .iter()
+            .find(|name| !structure.columns.iter().any(|c| &c.name == *name))
+        {
+            return Err(anyhow!(
+                "Unknown column '{}' in table '{}'",
+                unknown,
+                table_name
+            ));
+        }
+        Ok(columns
+            .iter()
+            .map(|c| quote_ident(c, db_type))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn export_table_data(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        table_name: &str,
+        columns: Option<&[String]>,
+        filters: Vec<FilterConfig>,
+        sort_column: Option<String>,
+        sort_direction: Option<String>,
+        format: &str,
+        file_path: &str,
+        delimiter: Option<char>,
+        excel_bom: bool,
+        csv_quoting: &CsvQuoting,
+        json_null_mode: &JsonNullMode,
+        on_progress: Option<&(dyn Fn(u64, u64) + Send + Sync)>,
+    ) -> Result<u64> {
+        let db_type = detect_db_type(manager, connection_id).await?;
+        let select_list =
+            Self::export_select_list(manager, connection_id, table_name, columns, db_type).await?;
+        let total_rows =
+            Self::get_table_count(manager, connection_id, table_name, filters.clone()).await?;
+        let (where_clause, params) = build_where_clause(filters, db_type);
+        let sql = format!(
+            "SELECT {} FROM {} {} {};",
+            select_list,
+            quote_ident(table_name, db_type),
+            where_clause,
+            build_order_clause(sort_column, sort_direction, db_type)
+        );
+
+        let options = RowFileOptions {
+            delimiter,
+            excel_bom,
+            csv_quoting,
+            json_null_mode,
+            sql_target: Some((table_name, db_type)),
+        };
+        let report_progress = |written: u64| {
+            if let Some(on_progress) = on_progress {
+                on_progress(written, total_rows);
+            }
+        };
+        // Rows go straight to the file, so neither memory nor the connection's max_rows
+        // limits the size of an export
+        Self::stream_rows_to_file(
+            manager,
+            connection_id,
+            &sql,
+            &params,
+            format,
+            file_path,
+            &options,
+            &CancellationToken::new(),
+            Some(&report_progress),
+        )
+        .await
+    }
+
+    /// Streams the rows of `sql` straight into a CSV or JSON file without buffering the
+    /// result or emitting window events, returning the number of rows written
+    pub async fn stream_query_to_file(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+        format: &str,
+        file_path: &str,
+        json_null_mode: &JsonNullMode,
+        token: &CancellationToken,
+    ) -> Result<u64> {
+        let options = RowFileOptions {
+            delimiter: None,
+            excel_bom: false,
+            csv_quoting: &CsvQuoting::default(),
+            json_null_mode,
+            sql_target: None,
+        };
+        Self::stream_rows_to_file(
+            manager,
+            connection_id,
+            sql,
+            &[],
+            format,
+            file_path,
+            &options,
+            token,
+            None,
+        )
+        .await
+    }
+
+    /// `stream_query_to_file` with full format options, calling `on_progress` with the
+    /// number of rows written every EXPORT_PROGRESS_INTERVAL rows
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_rows_to_file(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+        sql: &str,
+        params: &[Value],
+        format: &str,
+        file_path: &str,
+        options: &RowFileOptions<'_>,
+        token: &CancellationToken,
+        on_progress: Option<&(dyn Fn(u64) + Send + Sync)>,
+    ) -> Result<u64> {
+        macro_rules! stream_to_file {
+            ($pool:expr, $db_macro:ident) => {{
+                let mut out = RowFileWriter::create(format, file_path, options)?;
+                let mut columns = Vec::new();
+                let mut total_rows = 0u64;
+
+                // Scripts need the simple protocol; filtered table exports bind their values
+                let mut stream = if params.is_empty() {
+                    sqlx::raw_sql(sql).fetch($pool)
+                } else {
+                    bind_json_params!(sqlx::query(sql), params).fetch($pool)
+                };
+                while let Some(row) = StreamExt::next(&mut stream).await {
+                    if token.is_cancelled() {
+                        return Err(Cancelled.into());
+                    }
+                    let row = row?;
+                    if total_rows == 0 {
+                        columns = row
+                            .columns()
+                            .iter()
+                            .map(|c| Column::name(c).to_string())
+                            .collect::<Vec<String>>();
+                        out.write_header(&columns)?;
+                    }
+                    out.write_row($db_macro!(&row), total_rows)?;
+                    total_rows += 1;
+                    if let Some(on_progress) = on_progress {
+                        if total_rows % EXPORT_PROGRESS_INTERVAL == 0 {
+                            on_progress(total_rows);
+                        }
+                    }
+                }
+                // Release the streaming connection before describing on a single-connection pool
+                drop(stream);
+
+                if total_rows == 0 && returns_rows(sql) {
+                    columns = describe_columns!($pool, sql).0;
+                    if !columns.is_empty() {
+                        out.write_header(&columns)?;
+                    }
+                }
+                out.finish()?;
+
+                return Ok(total_rows);
+            }};
+        }
+
+        // Pools are cloned out of the map so a long export does not hold the lock
+        // Check Postgres
+        {
+            let pool = manager
+                .get_postgres_pools()
+                .await
+                .get(connection_id)
+                .cloned();
+            if let Some(pool) = pool {
+                stream_to_file!(&pool, postgres_row_to_values);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pool = manager.get_mysql_pools().await.get(connection_id).cloned();
+            if let Some(pool) = pool {
+                stream_to_file!(&pool, mysql_row_to_values);
+            }
+        }
+
+        // Check SQLite
+        {
+            let pool = manager.get_sqlite_pools().await.get(connection_id).cloned();
+            if let Some(pool) = pool {
+                stream_to_file!(&pool, sqlite_row_to_values);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+
+    pub async fn get_sidebar_items(
+        manager: &ConnectionManager,
+        connection_id: &Uuid,
+    ) -> Result<Vec<SidebarItem>> {
+        let mut items = Vec::new();
+
+        // Check Postgres
+        {
+            let pools = manager.get_postgres_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // Tables and Views
+                let sql = r#"
+                    SELECT table_name, table_type, table_schema
+                    FROM information_schema.tables
+                    WHERE table_schema NOT IN ('information_schema', 'pg_catalog')
+                    ORDER BY table_name;
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                for row in rows {
+                    let name: String = row.get(0);
+                    let table_type: String = row.get(1);
+                    let schema: String = row.get(2);
+                    let item_type = if table_type == "VIEW" {
+                        SidebarItemType::View
+                    } else {
+                        SidebarItemType::Table
+                    };
+                    items.push(SidebarItem {
+                        name,
+                        item_type,
+                        schema: Some(schema),
+                        pinned: false,
+                        usage_count: 0,
+                    });
+                }
+
+                // Functions and Procedures
+                let sql = r#"
+                    SELECT routine_name, routine_type, routine_schema
+                    FROM information_schema.routines
+                    WHERE routine_schema NOT IN ('information_schema', 'pg_catalog')
+                    ORDER BY routine_name;
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                for row in rows {
+                    let name: String = row.get(0);
+                    let routine_type: String = row.get(1);
+                    let schema: String = row.get(2);
+                    let item_type = if routine_type == "PROCEDURE" {
+                        SidebarItemType::Procedure
+                    } else {
+                        SidebarItemType::Function
+                    };
+                    items.push(SidebarItem {
+                        name,
+                        item_type,
+                        schema: Some(schema),
+                        pinned: false,
+                        usage_count: 0,
+                    });
+                }
+                return Ok(items);
+            }
+        }
+
+        // Check MySQL
+        {
+            let pools = manager.get_mysql_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                // Tables and Views
+                let sql = r#"
+                    SELECT TABLE_NAME, TABLE_TYPE, TABLE_SCHEMA
+                    FROM information_schema.TABLES
+                    WHERE TABLE_SCHEMA = DATABASE()
+                    ORDER BY TABLE_NAME;
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                for row in rows {
+                    let name: String = row.get(0);
+                    let table_type: String = row.get(1);
+                    let schema: String = row.get(2);
+                    let item_type = if table_type == "VIEW" {
+                        SidebarItemType::View
+                    } else {
+                        SidebarItemType::Table
+                    };
+                    items.push(SidebarItem {
+                        name,
+                        item_type,
+                        schema: Some(schema),
+                        pinned: false,
+                        usage_count: 0,
+                    });
+                }
+
+                // Routines
+                let sql = r#"
+                    SELECT ROUTINE_NAME, ROUTINE_TYPE, ROUTINE_SCHEMA
+                    FROM information_schema.ROUTINES
+                    WHERE ROUTINE_SCHEMA = DATABASE()
+                    ORDER BY ROUTINE_NAME;
+                "#;
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                for row in rows {
+                    let name: String = row.get(0);
+                    let routine_type: String = row.get(1);
+                    let schema: String = row.get(2);
+                    let item_type = if routine_type == "PROCEDURE" {
+                        SidebarItemType::Procedure
+                    } else {
+                        SidebarItemType::Function
+                    };
+                    items.push(SidebarItem {
+                        name,
+                        item_type,
+                        schema: Some(schema),
+                        pinned: false,
+                        usage_count: 0,
+                    });
+                }
+                return Ok(items);
+            }
+        }
+
+        // Check SQLite
+        {
+            let pools = manager.get_sqlite_pools().await;
+            if let Some(pool) = pools.get(connection_id) {
+                let sql = "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;";
+                let rows = sqlx::query(sql).fetch_all(pool).await?;
+                for row in rows {
+                    let name: String = row.get(0);
+                    let item_type_str: String = row.get(1);
+                    let item_type = if item_type_str == "view" {
+                        SidebarItemType::View
+                    } else {
+                        SidebarItemType::Table
+                    };
+                    items.push(SidebarItem {
+                        name,
+                        item_type,
+                        schema: None,
+                        pinned: false,
+                        usage_count: 0,
+                    });
+                }
+                return Ok(items);
+            }
+        }
+
+        Err(anyhow!("Connection not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support;
+
+    fn mysql_column(
+        column_type: &str,
+        default: Option<&str>,
+        extra: &str,
+    ) -> MySqlColumnDefinition {
+        MySqlColumnDefinition {
+            column_type: column_type.to_string(),
+            is_nullable: true,
+            default: default.map(str::to_string),
+            extra: extra.to_string(),
+            comment: String::new(),
+            generation_expression: None,
+        }
+    }
+
+    #[test]
+    fn mysql_definition_keeps_literal_default_and_comment() {
+        let mut column = mysql_column("varchar(20)", Some("it's"), "");
+        column.comment = "Shown name".to_string();
+        assert_eq!(
+            mysql_column_definition(&column, Some("varchar(40)"), Some(false), false),
+            "varchar(40) NOT NULL DEFAULT 'it''s' COMMENT 'Shown name'"
+        );
+    }
+
+    #[test]
+    fn mysql_definition_keeps_auto_increment_and_on_update() {
+        let column = mysql_column("int unsigned", None, "auto_increment");
+        assert_eq!(
+            mysql_column_definition(&column, None, Some(false), false),
+            "int unsigned NOT NULL AUTO_INCREMENT"
+        );
+        let column = mysql_column(
+            "timestamp(3)",
+            Some("CURRENT_TIMESTAMP(3)"),
+            "DEFAULT_GENERATED on update CURRENT_TIMESTAMP(3)",
+        );
+        assert_eq!(
+            mysql_column_definition(&column, None, None, false),
+            "timestamp(3) NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3)"
+        );
+    }
+
+    #[test]
+    fn mysql_definition_parenthesizes_expression_defaults() {
+        let column = mysql_column("json", Some("json_array()"), "DEFAULT_GENERATED");
+        assert_eq!(
+            mysql_column_definition(&column, None, None, false),
+            "json NULL DEFAULT (json_array())"
+        );
+    }
+
+    #[test]
+    fn mysql_definition_reads_mariadb_defaults_as_sql() {
+        let column = mysql_column("varchar(10)", Some("'abc'"), "");
+        assert_eq!(
+            mysql_column_definition(&column, None, None, true),
+            "varchar(10) NULL DEFAULT 'abc'"
+        );
+        let column = mysql_column("int(11)", Some("NULL"), "");
+        assert_eq!(
+            mysql_column_definition(&column, None, None, true),
+            "int(11) NULL"
+        );
+        let column = mysql_column(
+            "timestamp",
+            Some("current_timestamp()"),
+            "on update current_timestamp()",
+        );
+        assert_eq!(
+            mysql_column_definition(&column, None, Some(false), true),
+            "timestamp NOT NULL DEFAULT current_timestamp() ON UPDATE current_timestamp()"
+        );
+    }
+
+    #[test]
+    fn mysql_definition_keeps_generation_expression() {
+        let mut column = mysql_column("int", None, "STORED GENERATED");
+        column.generation_expression = Some("`a` + 1".to_string());
+        assert_eq!(
+            mysql_column_definition(&column, Some("bigint"), None, false),
+            "bigint GENERATED ALWAYS AS (`a` + 1) STORED NULL"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn cursor_pages_are_bounded_and_decoded_from_text() {
+        let (manager, id) = test_support::postgres().await;
+        let sql = "SELECT g, g::numeric / 4 AS quarter, repeat('x', 1000) AS filler, \
+                   DATE '2024-01-01' + g AS day FROM generate_series(1, 2500) g";
+        let cursor = QueryEngine::open_result_cursor(&manager, &id, sql)
+            .await
+            .unwrap();
+
+        let mut fetched = 0;
+        loop {
+            let page = QueryEngine::fetch_cursor(&manager, &cursor, 1000)
+                .await
+                .unwrap();
+            assert!(page.rows.len() <= 1000);
+            assert_eq!(page.columns, ["g", "quarter", "filler", "day"]);
+            if let Some(row) = page.rows.first() {
+                let g = fetched + 1;
+                assert_eq!(row[0], Value::from(g));
+                assert!(!row[1].is_null());
+                assert_eq!(row[2].as_str().map(str::len), Some(1000));
+                assert!(row[3].as_str().is_some_and(|d| d.len() == 10));
+            }
+            fetched += page.rows.len() as i64;
+            if page.rows.len() < 1000 {
+                break;
+            }
+        }
+        assert_eq!(fetched, 2500);
+        QueryEngine::close_cursor(&manager, &cursor).await.unwrap();
+    }
+
+    fn json_filter(operator: &str, path: &str, value: &str) -> FilterConfig {
+        FilterConfig {
+            id: "f".to_string(),
+            column: "doc".to_string(),
+            operator: operator.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            case_insensitive: false,
+            json_path: Some(path.to_string()),
+        }
+    }
+
+    #[test]
+    fn json_filters_bind_their_path_and_value() {
+        let filters = vec![
+            json_filter("json_path_eq", "$.name", "O'Brien"),
+            json_filter("json_contains", "tags", "[\"a\"]"),
+        ];
+        let (clause, params) = build_where_clause(filters.clone(), "postgres");
+        assert_eq!(
+            clause,
+            "WHERE \"doc\" #>> CAST($1 AS text[]) = $2 AND \
+             (\"doc\"::jsonb #> CAST($3 AS text[])) @> CAST($4 AS jsonb)"
+        );
+        assert_eq!(params, ["{\"name\"}", "O'Brien", "{\"tags\"}", "[\"a\"]"]);
+
+        // MySQL's JSON_CONTAINS takes the candidate before the path
+        let (clause, params) = build_where_clause(filters, "mysql");
+        assert_eq!(
+            clause,
+            "WHERE JSON_UNQUOTE(JSON_EXTRACT(`doc`, ?)) = ? AND JSON_CONTAINS(`doc`, ?, ?)"
+        );
+        assert_eq!(params, ["$.\"name\"", "O'Brien", "[\"a\"]", "$.\"tags\""]);
+    }
+
+    #[test]
+    fn json_path_segments_drop_quotes_and_split_indexes() {
+        assert_eq!(json_path_segments("$.a.b[0]"), ["a", "b", "0"]);
+        assert_eq!(json_path_segments("a.b'c\"d"), ["a", "bcd"]);
+        assert!(json_path_segments("$").is_empty());
+        assert_eq!(
+            json_path_param(&json_path_segments("items[2].name"), "sqlite"),
+            "$.\"items\"[2].\"name\""
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_json_filters_match_quoted_values() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            r#"CREATE TABLE people (id INTEGER PRIMARY KEY, doc TEXT);
+               INSERT INTO people (doc) VALUES
+                 ('{"name": "O''Brien", "tags": ["a", "b"]}'),
+                 ('{"name": "Smith", "tags": ["c"]}');"#,
+        )
+        .await;
+
+        let by_name = vec![json_filter("json_path_eq", "name", "O'Brien")];
+        let page = QueryEngine::get_table_data(
+            &manager,
+            &id,
+            "people",
+            10,
+            0,
+            by_name.clone(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.rows.len(), 1);
+        assert_eq!(page.rows[0][0], Value::from(1));
+        let count = QueryEngine::get_table_count(&manager, &id, "people", by_name)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let by_tag = vec![json_filter("json_contains", "tags", "\"c\"")];
+        let page = QueryEngine::get_table_page(&manager, &id, "people", 10, 0, by_tag, None, None)
+            .await
+            .unwrap();
+        assert_eq!(page.total_count, Some(1));
+        assert_eq!(page.rows[0][0], Value::from(2));
+
+        // A value that would have closed the old string literal is just a value now
+        let injected = vec![json_filter("json_path_eq", "name", "x' OR '1'='1")];
+        let page =
+            QueryEngine::get_table_data(&manager, &id, "people", 10, 0, injected, None, None)
+                .await
+                .unwrap();
+        assert!(page.rows.is_empty());
+        assert_eq!(page.columns, ["id", "doc"]);
+    }
+
+    #[test]
+    fn sqlite_scripts_run_per_statement_unless_they_create_triggers() {
+        let script = "INSERT INTO t VALUES ('TRIGGER'); UPDATE trigger_log SET n = 1;";
+        assert_eq!(sqlite_script_statements(script).map(|s| s.len()), Some(2));
+
+        let script = "CREATE TEMP TRIGGER t AFTER INSERT ON a BEGIN SELECT 1; END; SELECT 2;";
+        assert!(sqlite_script_statements(script).is_none());
+        let script = "-- set up\nCREATE TRIGGER t AFTER INSERT ON a BEGIN SELECT 1; END;";
+        assert!(sqlite_script_statements(script).is_none());
+        assert!(sqlite_script_statements("SELECT 1; COMMIT;").is_none());
+        assert!(sqlite_script_statements("SELECT 1;").is_none());
+    }
+
+    #[tokio::test]
+    async fn execute_query_reports_affected_rows_for_mutations() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER);
+             INSERT INTO t (n) VALUES (1), (2), (3);",
+        )
+        .await;
+
+        let update =
+            test_support::query(&manager, &id, "UPDATE t SET n = n + 10 WHERE n >= 2").await;
+        assert_eq!(update.affected_rows, 2);
+        assert!(update.columns.is_empty());
+        assert!(update.rows.is_empty());
+
+        let select = test_support::query(&manager, &id, "SELECT n FROM t ORDER BY n").await;
+        assert_eq!(select.columns, ["n"]);
+        assert_eq!(
+            select.rows,
+            vec![
+                vec![Value::from(1)],
+                vec![Value::from(12)],
+                vec![Value::from(13)]
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_script_returns_one_result_per_statement_in_order() {
+        let (manager, id) = test_support::sqlite().await;
+        let results = QueryEngine::execute_script(
+            &manager,
+            &id,
+            "CREATE TABLE t (name TEXT);
+             INSERT INTO t VALUES ('a;b'), ('c');
+             SELECT name FROM t ORDER BY name;",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].columns.is_empty());
+        assert_eq!(results[1].affected_rows, 2);
+        assert_eq!(results[2].columns, ["name"]);
+        assert_eq!(
+            results[2].rows,
+            vec![vec![Value::from("a;b")], vec![Value::from("c")]]
+        );
+    }
+
+    #[tokio::test]
+    async fn truncate_table_empties_a_populated_table() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE logs (msg TEXT); INSERT INTO logs VALUES ('a'), ('b'), ('c');",
+        )
+        .await;
+
+        assert!(
+            QueryEngine::truncate_table(&manager, &id, "logs", false, false)
+                .await
+                .is_err()
+        );
+        let removed = QueryEngine::truncate_table(&manager, &id, "logs", true, false)
+            .await
+            .unwrap();
+        assert_eq!(removed, 3);
+        let count = QueryEngine::get_table_count(&manager, &id, "logs", Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn dropped_and_renamed_tables_show_in_get_tables() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE keep (id INTEGER); CREATE TABLE old_name (id INTEGER);
+             CREATE TABLE doomed (id INTEGER);",
+        )
+        .await;
+        let all = CatalogFilter::default();
+
+        QueryEngine::drop_table(&manager, &id, "doomed", false, false)
+            .await
+            .unwrap();
+        QueryEngine::rename_table(&manager, &id, "old_name", "new name")
+            .await
+            .unwrap();
+
+        let tables = QueryEngine::get_tables(&manager, &id, &all).await.unwrap();
+        assert_eq!(tables, ["keep", "new name"]);
+    }
+
+    #[tokio::test]
+    async fn unique_multi_column_index_shows_in_table_structure() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE members (org_id INTEGER, user_id INTEGER, role TEXT);",
+        )
+        .await;
+
+        let name = QueryEngine::create_index(
+            &manager,
+            &id,
+            "members",
+            vec!["org_id".to_string(), "user_id".to_string()],
+            true,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(name, "ux_members_org_id_user_id");
+
+        let structure = QueryEngine::get_table_structure(&manager, &id, "members")
+            .await
+            .unwrap();
+        let index = structure
+            .indexes
+            .iter()
+            .find(|i| i.name == name)
+            .expect("index listed");
+        assert!(index.is_unique);
+        assert_eq!(index.columns, ["org_id", "user_id"]);
+
+        // SQLite takes no access method
+        assert!(QueryEngine::create_index(
+            &manager,
+            &id,
+            "members",
+            vec!["role".to_string()],
+            false,
+            None,
+            Some("hash".to_string()),
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn queries_reconnect_after_the_pool_is_dropped() {
+        let (manager, id) = test_support::sqlite().await;
+        let pool = manager.get_sqlite_pools().await.get(&id).cloned().unwrap();
+        pool.close().await;
+
+        let result = test_support::query(&manager, &id, "SELECT 1 AS one").await;
+        assert_eq!(result.rows, vec![vec![Value::from(1)]]);
+        assert!(!manager.get_sqlite_pools().await[&id].is_closed());
+    }
+
+    fn filter(column: &str, operator: &str, value: &str) -> FilterConfig {
+        FilterConfig {
+            id: "f".to_string(),
+            column: column.to_string(),
+            operator: operator.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            case_insensitive: false,
+            json_path: None,
+        }
+    }
+
+    async fn mixed_case_filters_match_any_casing(manager: &ConnectionManager, id: &Uuid) {
+        test_support::run(
+            manager,
+            id,
+            "DROP TABLE IF EXISTS sqlmate_cities;
+             CREATE TABLE sqlmate_cities (name VARCHAR(40));
+             INSERT INTO sqlmate_cities VALUES ('Amsterdam'), ('AMSTERDAM'), ('amsterdam'), ('Berlin');",
+        )
+        .await;
+
+        for operator in ["Contains", "Starts With", "Ends With"] {
+            let value = if operator == "Ends With" {
+                "DaM"
+            } else {
+                "amSTer"
+            };
+            let filters = vec![FilterConfig {
+                case_insensitive: true,
+                ..filter("name", operator, value)
+            }];
+            let page = QueryEngine::get_table_data(
+                manager,
+                id,
+                "sqlmate_cities",
+                10,
+                0,
+                filters.clone(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(page.rows.len(), 3, "{}", operator);
+            let count = QueryEngine::get_table_count(manager, id, "sqlmate_cities", filters)
+                .await
+                .unwrap();
+            assert_eq!(count, 3, "{}", operator);
+        }
+        test_support::run(manager, id, "DROP TABLE sqlmate_cities").await;
+    }
+
+    #[test]
+    fn like_patterns_are_bound() {
+        let filters = vec![
+            filter("name", "Contains", "x\\' OR 1=1 -- "),
+            FilterConfig {
+                case_insensitive: true,
+                ..filter("city", "Starts With", "Am")
+            },
+        ];
+        let (clause, params) = build_where_clause(filters.clone(), "mysql");
+        assert_eq!(
+            clause,
+            "WHERE `name` LIKE ? AND LOWER(`city`) LIKE LOWER(?)"
+        );
+        assert_eq!(params, ["%x\\' OR 1=1 -- %", "Am%"]);
+
+        let (clause, _) = build_where_clause(filters, "postgres");
+        assert_eq!(clause, "WHERE \"name\" LIKE $1 AND \"city\" ILIKE $2");
+    }
+
+    #[test]
+    fn comparisons_are_bound_outside_postgres() {
+        let filters = vec![
+            filter("name", "=", "O'Brien\\"),
+            filter("id", "IN", "1, '2', 'it''s'"),
+            filter("age", ">=", "30"),
+        ];
+        let (clause, params) = build_where_clause(filters.clone(), "sqlite");
+        assert_eq!(
+            clause,
+            "WHERE \"name\" = ? AND \"id\" IN (?, ?, ?) AND \"age\" >= ?"
+        );
+        assert_eq!(params, ["O'Brien\\", "1", "2", "it's", "30"]);
+
+        // Untyped literals let Postgres compare against the column's own type
+        let (clause, params) = build_where_clause(filters, "postgres");
+        assert_eq!(
+            clause,
+            "WHERE \"name\" = 'O''Brien\\' AND \"id\" IN ('1', '2', 'it''s') AND \"age\" >= '30'"
+        );
+        assert!(params.is_empty());
+
+        let (clause, _) = build_where_clause(vec![filter("id", "IN", " , ")], "mysql");
+        assert_eq!(clause, "WHERE 1 = 0");
+    }
+
+    #[tokio::test]
+    async fn sqlite_bound_comparisons_match_numbers_and_quoted_text() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO people VALUES (1, 'Ada'), (2, 'O''Brien'), (3, 'Grace'), (10, 'Linus');",
+        )
+        .await;
+        let count = |filters| QueryEngine::get_table_count(&manager, &id, "people", filters);
+
+        assert_eq!(
+            count(vec![filter("name", "=", "O'Brien")]).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            count(vec![filter("id", "IN", "1, 3, 10")]).await.unwrap(),
+            3
+        );
+        // Numeric, not text, ordering: 10 > 2
+        assert_eq!(count(vec![filter("id", ">", "2")]).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn sqlite_like_filters_match_quotes_and_backslashes_literally() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            r"CREATE TABLE paths (p TEXT);
+              INSERT INTO paths VALUES ('C:\it''s'), ('C:\its'), ('other');",
+        )
+        .await;
+
+        let filters = vec![filter("p", "Contains", r"\it's")];
+        let count = QueryEngine::get_table_count(&manager, &id, "paths", filters)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn case_insensitive_filters_match_any_casing() {
+        for (manager, id) in test_support::backends().await {
+            mixed_case_filters_match_any_casing(&manager, &id).await;
+        }
+    }
+
+    /// Browses, counts and exports a table and column named after SQL keywords
+    async fn reserved_word_table_works(manager: &ConnectionManager, id: &Uuid, db_type: &str) {
+        let table = quote_ident("group", db_type);
+        let column = quote_ident("select", db_type);
+        test_support::run(
+            manager,
+            id,
+            &format!(
+                "DROP TABLE IF EXISTS {0}; CREATE TABLE {0} ({1} VARCHAR(10));
+                 INSERT INTO {0} ({1}) VALUES ('a'), ('b');",
+                table, column
+            ),
+        )
+        .await;
+        let filters = vec![filter("select", "=", "b")];
+
+        let page = QueryEngine::get_table_data(
+            manager,
+            id,
+            "group",
+            10,
+            0,
+            Vec::new(),
+            Some("select".to_string()),
+            Some("DESC".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            page.rows,
+            vec![vec![Value::from("b")], vec![Value::from("a")]]
+        );
+        let count = QueryEngine::get_table_count(manager, id, "group", filters.clone())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let path = test_support::temp_file("csv", b"");
+        let exported = QueryEngine::export_table_data(
+            manager,
+            id,
+            "group",
+            Some(&["select".to_string()]),
+            filters,
+            None,
+            None,
+            "csv",
+            &path.to_string_lossy(),
+            None,
+            false,
+            &CsvQuoting::default(),
+            &JsonNullMode::Null,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(exported, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "select\nb\n");
+        std::fs::remove_file(path).unwrap();
+        test_support::run(manager, id, &format!("DROP TABLE {}", table)).await;
+    }
+
+    #[tokio::test]
+    async fn reserved_word_table_is_browsable_and_exportable() {
+        for (manager, id) in test_support::backends().await {
+            let db_type = detect_db_type(&manager, &id).await.unwrap();
+            reserved_word_table_works(&manager, &id, db_type).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn parameterized_queries_bind_quotes_and_nulls_as_values() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, author TEXT, pinned BOOLEAN);",
+        )
+        .await;
+
+        let inserted = QueryEngine::execute_parameterized(
+            &manager,
+            &id,
+            "INSERT INTO notes (body, author, pinned) VALUES (?, ?, ?), (?, ?, ?)",
+            vec![
+                Value::from("it's a \"quoted\" '); DROP TABLE notes; --"),
+                Value::Null,
+                Value::from(true),
+                Value::from("plain"),
+                Value::from("ann"),
+                Value::from(false),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(inserted.affected_rows, 2);
+
+        let found = QueryEngine::execute_parameterized(
+            &manager,
+            &id,
+            "SELECT body, author FROM notes WHERE body = ? AND author IS ?",
+            vec![
+                Value::from("it's a \"quoted\" '); DROP TABLE notes; --"),
+                Value::Null,
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            found.rows,
+            vec![vec![
+                Value::from("it's a \"quoted\" '); DROP TABLE notes; --"),
+                Value::Null
+            ]]
+        );
+
+        let by_id = QueryEngine::execute_parameterized(
+            &manager,
+            &id,
+            "SELECT author FROM notes WHERE id = ?",
+            vec![Value::from(2)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(by_id.rows, vec![vec![Value::from("ann")]]);
+    }
+
+    #[tokio::test]
+    async fn attached_sqlite_files_are_queryable_through_their_alias() {
+        let other = std::env::temp_dir().join(format!("sqlmate-attach-{}.db", Uuid::new_v4()));
+        {
+            let pool = sqlx::SqlitePool::connect(&format!("sqlite://{}?mode=rwc", other.display()))
+                .await
+                .unwrap();
+            sqlx::raw_sql(
+                "CREATE TABLE customers (name TEXT); INSERT INTO customers VALUES ('Ada');",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+        }
+        let (manager, id) = test_support::sqlite().await;
+
+        QueryEngine::attach_database(&manager, &id, &other.to_string_lossy(), "archive")
+            .await
+            .unwrap();
+        let databases = QueryEngine::get_databases(&manager, &id, &CatalogFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(databases, ["main", "archive"]);
+        let result = test_support::query(&manager, &id, "SELECT name FROM archive.customers").await;
+        assert_eq!(result.rows, vec![vec![Value::from("Ada")]]);
+
+        QueryEngine::detach_database(&manager, &id, "archive")
+            .await
+            .unwrap();
+        let databases = QueryEngine::get_databases(&manager, &id, &CatalogFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(databases, ["main"]);
+        std::fs::remove_file(other).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sqlite_table_metadata_reports_dbstat_sizes() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE blobs (id INTEGER PRIMARY KEY, body TEXT);
+             CREATE INDEX blobs_body ON blobs (body);
+             INSERT INTO blobs (body)
+             WITH RECURSIVE c(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM c WHERE n < 200)
+             SELECT printf('%.500d', n) FROM c;",
+        )
+        .await;
+        let dbstat_available = QueryEngine::execute_query(
+            &manager,
+            &id,
+            "SELECT 1 FROM dbstat LIMIT 1",
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .is_ok();
+
+        let metadata = QueryEngine::get_table_metadata(&manager, &id, "blobs")
+            .await
+            .unwrap();
+        if !dbstat_available {
+            // Builds without dbstat fall back to unknown sizes rather than failing
+            for size in [
+                &metadata.total_size,
+                &metadata.data_size,
+                &metadata.index_size,
+            ] {
+                assert_eq!(size.as_deref(), Some("Unknown"));
+            }
+            assert_eq!(metadata.page_count, None);
+            assert_eq!(metadata.avg_row_size, None);
+            return;
+        }
+        for size in [
+            &metadata.total_size,
+            &metadata.data_size,
+            &metadata.index_size,
+        ] {
+            let size = size.as_deref().unwrap();
+            assert!(size != "Unknown" && size != "0 B", "{}", size);
+        }
+        assert!(metadata.page_count.unwrap() > 2);
+        assert!(metadata.avg_row_size.is_some());
+    }
+
+    #[test]
+    fn timestamptz_converts_to_the_display_timezone() {
+        use chrono::TimeZone;
+        let winter = chrono::Utc
+            .with_ymd_and_hms(2024, 1, 15, 17, 30, 0)
+            .unwrap();
+        let summer = chrono::Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        let new_york = parse_display_timezone(Some("America/New_York")).unwrap();
+
+        assert_eq!(
+            format_timestamptz(winter, new_york),
+            "2024-01-15T12:30:00-05:00"
+        );
+        assert_eq!(
+            format_timestamptz(summer, new_york),
+            "2024-07-01T08:00:00-04:00"
+        );
+        assert_eq!(
+            format_timestamptz(winter, None),
+            "2024-01-15T17:30:00+00:00"
+        );
+        assert!(parse_display_timezone(Some("Mars/Olympus_Mons")).is_err());
+        assert_eq!(parse_display_timezone(None).unwrap(), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_timestamptz_renders_in_the_display_timezone() {
+        let (manager, id) = test_support::postgres().await;
+        let sql = "SELECT TIMESTAMPTZ '2024-01-15 17:30:00+00' AS at, \
+                   TIMESTAMP '2024-01-15 17:30:00' AS naive";
+        let result = QueryEngine::execute_query(
+            &manager,
+            &id,
+            sql,
+            None,
+            None,
+            Some("America/New_York"),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.rows[0][0], Value::from("2024-01-15T12:30:00-05:00"));
+        // Naive timestamps carry no zone and are left alone
+        assert!(result.rows[0][1]
+            .as_str()
+            .unwrap()
+            .starts_with("2024-01-15"));
+        assert!(result.rows[0][1].as_str().unwrap().contains("17:30:00"));
+    }
+
+    #[test]
+    fn intervals_render_as_iso_8601_durations() {
+        let interval = |months, days, microseconds| PgInterval {
+            months,
+            days,
+            microseconds,
+        };
+        assert_eq!(
+            format_pg_interval(&interval(
+                14,
+                3,
+                4 * 3_600_000_000 + 5 * 60_000_000 + 6_500_000
+            )),
+            "P1Y2M3DT4H5M6.5S"
+        );
+        assert_eq!(format_pg_interval(&interval(0, 0, 90_000_000)), "PT1M30S");
+        assert_eq!(format_pg_interval(&interval(0, 0, 0)), "PT0S");
+    }
+
+    #[test]
+    fn inet_and_cidr_decode_from_binary() {
+        assert_eq!(
+            decode_pg_inet(&[2, 32, 0, 4, 192, 168, 0, 1]).as_deref(),
+            Some("192.168.0.1")
+        );
+        assert_eq!(
+            decode_pg_inet(&[2, 24, 1, 4, 10, 0, 0, 0]).as_deref(),
+            Some("10.0.0.0/24")
+        );
+        let mut v6 = vec![3, 64, 0, 16, 0x20, 0x01, 0x0d, 0xb8];
+        v6.extend([0; 12]);
+        assert_eq!(decode_pg_inet(&v6).as_deref(), Some("2001:db8::/64"));
+        assert_eq!(decode_pg_inet(&[2, 32, 0, 16, 1, 2, 3, 4]), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_interval_inet_and_uuid_arrays_are_readable() {
+        let (manager, id) = test_support::postgres().await;
+        let result = test_support::query(
+            &manager,
+            &id,
+            "SELECT INTERVAL '1 year 2 months 3 days 04:05:06.5' AS iv, \
+             INET '192.168.0.1' AS host, CIDR '10.0.0.0/24' AS net, \
+             ARRAY['00000000-0000-0000-0000-000000000001'::uuid, \
+                   '00000000-0000-0000-0000-000000000002'::uuid] AS ids",
+        )
+        .await;
+        assert_eq!(
+            result.rows[0],
+            [
+                Value::from("P1Y2M3DT4H5M6.5S"),
+                Value::from("192.168.0.1"),
+                Value::from("10.0.0.0/24"),
+                serde_json::json!([
+                    "00000000-0000-0000-0000-000000000001",
+                    "00000000-0000-0000-0000-000000000002"
+                ]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_enum_and_set_columns_decode_to_strings() {
+        let (manager, id) = test_support::mysql().await;
+        test_support::run(
+            &manager,
+            &id,
+            "DROP TABLE IF EXISTS sqlmate_shirts;
+             CREATE TABLE sqlmate_shirts (
+               size ENUM('small', 'medium', 'large'),
+               colors SET('red', 'green', 'blue')
+             );
+             INSERT INTO sqlmate_shirts VALUES ('medium', 'red,blue'), (NULL, '');",
+        )
+        .await;
+
+        let result = test_support::query(
+            &manager,
+            &id,
+            "SELECT size, colors FROM sqlmate_shirts ORDER BY size IS NULL",
+        )
+        .await;
+        test_support::run(&manager, &id, "DROP TABLE sqlmate_shirts").await;
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::from("medium"), Value::from("red,blue")],
+                vec![Value::Null, Value::from("")],
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_limit_only_applies_to_unbounded_selects() {
+        for sql in [
+            "SELECT * FROM t",
+            "select id from t where name = 'LIMIT 5';",
+            "SELECT * FROM t -- LIMIT 5",
+        ] {
+            assert!(needs_auto_limit(sql), "{}", sql);
+        }
+        for sql in [
+            "SELECT * FROM t LIMIT 5",
+            "SELECT * FROM t OFFSET 10",
+            "SELECT * FROM t FETCH FIRST 5 ROWS ONLY",
+            "SELECT * INTO copy FROM t",
+            "SELECT * FROM t FOR UPDATE",
+            "SELECT 1; SELECT 2",
+            "UPDATE t SET n = 1",
+            "WITH x AS (SELECT 1) SELECT * FROM x",
+        ] {
+            assert!(!needs_auto_limit(sql), "{}", sql);
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_limit_truncates_unbounded_selects_only() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE t (n INTEGER); INSERT INTO t VALUES (1), (2), (3), (4), (5);",
+        )
+        .await;
+        let run = |sql, limit| {
+            QueryEngine::execute_query(&manager, &id, sql, None, None, None, Some(limit))
+        };
+
+        let limited = run("SELECT n FROM t ORDER BY n", 3).await.unwrap();
+        assert_eq!(limited.rows.len(), 3);
+        assert!(limited.truncated);
+
+        // Exactly `limit` rows exist, so nothing was cut off
+        let exact = run("SELECT n FROM t", 5).await.unwrap();
+        assert_eq!(exact.rows.len(), 5);
+        assert!(!exact.truncated);
+
+        let own_limit = run("SELECT n FROM t LIMIT 4", 2).await.unwrap();
+        assert_eq!(own_limit.rows.len(), 4);
+        assert!(!own_limit.truncated);
+    }
+
+    #[tokio::test]
+    async fn sqlite_views_are_listed_with_their_select() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE users (id INTEGER, name TEXT, active INTEGER);
+             CREATE VIEW active_users (user_id, label) AS
+               SELECT id, name AS label FROM users WHERE active = 1;",
+        )
+        .await;
+
+        let views = QueryEngine::get_views(&manager, &id, None).await.unwrap();
+        assert_eq!(views, ["active_users"]);
+        let definition = QueryEngine::get_view_definition(&manager, &id, "active_users")
+            .await
+            .unwrap();
+        assert_eq!(
+            definition,
+            "SELECT id, name AS label FROM users WHERE active = 1"
+        );
+        assert!(QueryEngine::get_view_definition(&manager, &id, "users")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_view_definition_is_the_select() {
+        let (manager, id) = test_support::postgres().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE OR REPLACE VIEW sqlmate_answer AS SELECT 42 AS answer",
+        )
+        .await;
+
+        let views = QueryEngine::get_views(&manager, &id, None).await.unwrap();
+        let definition = QueryEngine::get_view_definition(&manager, &id, "sqlmate_answer")
+            .await
+            .unwrap();
+        test_support::run(&manager, &id, "DROP VIEW sqlmate_answer").await;
+        assert!(views.iter().any(|v| v == "sqlmate_answer"));
+        assert_eq!(definition.trim(), "SELECT 42 AS answer;");
+    }
+
+    #[test]
+    fn sqlite_trigger_headers_give_timing_and_event() {
+        let header = |sql| parse_sqlite_trigger_header(sql);
+        assert_eq!(
+            header("CREATE TRIGGER t AFTER UPDATE OF name ON users BEGIN SELECT 1; END"),
+            ("AFTER".to_string(), "UPDATE".to_string())
+        );
+        assert_eq!(
+            header("create trigger t instead of delete on v begin select 1; end"),
+            ("INSTEAD OF".to_string(), "DELETE".to_string())
+        );
+        // No timing means BEFORE; words after ON (the body) are ignored
+        assert_eq!(
+            header("CREATE TRIGGER t INSERT ON users BEGIN DELETE FROM log; END"),
+            ("BEFORE".to_string(), "INSERT".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_triggers_are_listed_with_timing_and_event() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE users (id INTEGER, name TEXT); CREATE TABLE audit (msg TEXT);",
+        )
+        .await;
+        // The trigger body holds semicolons, so it runs as one statement
+        test_support::query(
+            &manager,
+            &id,
+            "CREATE TRIGGER users_audit AFTER DELETE ON users
+             BEGIN INSERT INTO audit VALUES ('deleted ' || old.name); END",
+        )
+        .await;
+
+        let triggers = QueryEngine::get_triggers(&manager, &id, "users")
+            .await
+            .unwrap();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].name, "users_audit");
+        assert_eq!(triggers[0].timing, "AFTER");
+        assert_eq!(triggers[0].event, "DELETE");
+        assert!(triggers[0]
+            .definition
+            .as_deref()
+            .is_some_and(|d| d.contains("INSERT INTO audit")));
+        assert!(QueryEngine::get_triggers(&manager, &id, "audit")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Structures of `people_v1` and `people_v2`, which adds a nullable `email` column
+    async fn people_structures(
+        manager: &ConnectionManager,
+        id: &Uuid,
+    ) -> (TableStructure, TableStructure) {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE people_v1 (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE people_v2 (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT);",
+        )
+        .await;
+        (
+            QueryEngine::get_table_structure(manager, id, "people_v1")
+                .await
+                .unwrap(),
+            QueryEngine::get_table_structure(manager, id, "people_v2")
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn diff_lists_exactly_the_added_column() {
+        let (manager, id) = test_support::sqlite().await;
+        let (v1, v2) = people_structures(&manager, &id).await;
+
+        let diff = QueryEngine::diff_table_structure(&v1, &v2);
+        let added: Vec<&str> = diff.added_columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(added, ["email"]);
+        assert!(diff.removed_columns.is_empty());
+        assert!(diff.changed_columns.is_empty());
+        assert!(diff.added_indexes.is_empty() && diff.removed_indexes.is_empty());
+
+        // The other direction reports it as removed
+        let reverse = QueryEngine::diff_table_structure(&v2, &v1);
+        assert!(reverse.added_columns.is_empty());
+        assert_eq!(reverse.removed_columns[0].name, "email");
+    }
+
+    fn column(
+        name: &str,
+        data_type: &str,
+        is_nullable: bool,
+        default: Option<&str>,
+    ) -> TableColumnStructure {
+        TableColumnStructure {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_nullable,
+            default_value: default.map(str::to_string),
+            is_primary_key: false,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn migration_adds_columns_and_comments_out_drops() {
+        let diff = StructureDiff {
+            added_columns: vec![column("status", "varchar(20)", false, Some("new"))],
+            removed_columns: vec![column("legacy", "text", true, None)],
+            changed_columns: Vec::new(),
+            added_indexes: Vec::new(),
+            removed_indexes: Vec::new(),
+            changed_indexes: Vec::new(),
+            added_constraints: Vec::new(),
+            removed_constraints: Vec::new(),
+            changed_constraints: Vec::new(),
+        };
+
+        assert_eq!(
+            QueryEngine::generate_migration_sql(&diff, "orders", "mysql", false),
+            "ALTER TABLE `orders` ADD COLUMN `status` varchar(20) NOT NULL DEFAULT 'new';\n\
+             -- ALTER TABLE `orders` DROP COLUMN `legacy`;"
+        );
+        // With the flag set the DROP runs too
+        let sql = QueryEngine::generate_migration_sql(&diff, "orders", "postgres", true);
+        assert_eq!(
+            sql.lines().last(),
+            Some("ALTER TABLE \"orders\" DROP COLUMN \"legacy\";")
+        );
+    }
+
+    #[tokio::test]
+    async fn migration_sql_brings_a_table_in_line_with_the_diff() {
+        let (manager, id) = test_support::sqlite().await;
+        let (v1, v2) = people_structures(&manager, &id).await;
+        let diff = QueryEngine::diff_table_structure(&v1, &v2);
+
+        let sql = QueryEngine::generate_migration_sql(&diff, "people_v1", "sqlite", false);
+        assert_eq!(sql, "ALTER TABLE \"people_v1\" ADD COLUMN \"email\" TEXT;");
+        test_support::run(&manager, &id, &sql).await;
+
+        let migrated = QueryEngine::get_table_structure(&manager, &id, "people_v1")
+            .await
+            .unwrap();
+        let remaining = QueryEngine::diff_table_structure(&migrated, &v2);
+        assert!(remaining.added_columns.is_empty());
+        assert!(remaining.changed_columns.is_empty());
+    }
+
+    #[tokio::test]
+    async fn table_listing_filters_by_name_and_pages() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE app_users (id INTEGER); CREATE TABLE app_roles (id INTEGER);
+             CREATE TABLE app_grants (id INTEGER); CREATE TABLE audit_log (id INTEGER);
+             CREATE TABLE billing (id INTEGER);",
+        )
+        .await;
+        let list = |name_filter: Option<&str>, limit, offset| {
+            let filter = CatalogFilter {
+                limit,
+                offset,
+                name_filter: name_filter.map(str::to_string),
+            };
+            let manager = &manager;
+            let id = &id;
+            async move { QueryEngine::get_tables(manager, id, &filter).await.unwrap() }
+        };
+
+        assert_eq!(list(None, None, None).await.len(), 5);
+        assert_eq!(
+            list(Some("app_%"), None, None).await,
+            ["app_grants", "app_roles", "app_users"]
+        );
+        assert_eq!(
+            list(Some("app_%"), Some(2), None).await,
+            ["app_grants", "app_roles"]
+        );
+        assert_eq!(list(Some("app_%"), Some(2), Some(2)).await, ["app_users"]);
+        assert_eq!(list(None, None, Some(3)).await, ["audit_log", "billing"]);
+    }
+
+    #[test]
+    fn count_wrapper_accepts_ctes_and_trims_the_terminator() {
+        assert_eq!(
+            wrap_count(
+                "WITH big AS (SELECT * FROM orders WHERE total > 100) SELECT * FROM big;  \n"
+            ),
+            "SELECT COUNT(*) FROM (WITH big AS (SELECT * FROM orders WHERE total > 100) \
+             SELECT * FROM big) AS __sqlmate_count_q"
+        );
+        assert_eq!(
+            wrap_count(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 5) \
+                 SELECT i FROM n ; "
+            ),
+            "SELECT COUNT(*) FROM (WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 \
+             FROM n WHERE i < 5) SELECT i FROM n) AS __sqlmate_count_q"
+        );
+        assert_eq!(
+            wrap_count("select 1;"),
+            "SELECT COUNT(*) FROM (select 1) AS __sqlmate_count_q"
+        );
+    }
+
+    #[test]
+    fn data_modifying_statements_are_not_wrapped() {
+        assert!(is_wrappable_query("with x as (select 1) select * from x"));
+        assert!(!is_wrappable_query(
+            "WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone"
+        ));
+        assert!(!is_wrappable_query("UPDATE t SET n = 1"));
+        assert_eq!(wrap_count("INSERT INTO t VALUES (1)"), "");
+    }
+
+    #[tokio::test]
+    async fn paginated_recursive_ctes_report_a_total() {
+        let (manager, id) = test_support::sqlite().await;
+        let sql = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 5)
+                   SELECT i FROM n;";
+        let page = QueryEngine::execute_query(&manager, &id, sql, Some(1), Some(2), None, None)
+            .await
+            .unwrap();
+        assert_eq!(page.total_count, Some(5));
+        assert_eq!(page.rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn server_timing_is_none_outside_postgres() {
+        let (manager, id) = test_support::sqlite().await;
+        let timing = QueryEngine::server_timing(&manager, &id, "SELECT 1")
+            .await
+            .unwrap();
+        assert!(timing.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_server_timing_splits_planning_and_execution() {
+        let (manager, id) = test_support::postgres().await;
+        let sql = "SELECT count(*) FROM generate_series(1, 10000);";
+        let timing = QueryEngine::server_timing(&manager, &id, sql)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(timing.planning_time_ms >= 0.0);
+        assert!(timing.execution_time_ms > 0.0);
+
+        let skipped = QueryEngine::server_timing(&manager, &id, "CREATE TEMP TABLE t (x int)")
+            .await
+            .unwrap();
+        assert!(skipped.is_none());
+    }
+
+    #[tokio::test]
+    async fn foreign_keys_are_reported_from_both_ends() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER REFERENCES authors(id));",
+        )
+        .await;
+
+        let books = QueryEngine::get_foreign_keys(&manager, &id, "books")
+            .await
+            .unwrap();
+        assert!(books.incoming.is_empty());
+        assert_eq!(books.outgoing.len(), 1);
+        let key = &books.outgoing[0];
+        assert_eq!(key.table, "books");
+        assert_eq!(key.columns, ["author_id"]);
+        assert_eq!(key.referenced_table, "authors");
+        assert_eq!(key.referenced_columns, ["id"]);
+
+        let authors = QueryEngine::get_foreign_keys(&manager, &id, "authors")
+            .await
+            .unwrap();
+        assert!(authors.outgoing.is_empty());
+        assert_eq!(authors.incoming.len(), 1);
+        assert_eq!(authors.incoming[0].table, "books");
+        assert_eq!(authors.incoming[0].columns, ["author_id"]);
+    }
+
+    #[test]
+    fn scripts_with_a_delete_are_destructive() {
+        assert!(contains_destructive_statement("DELETE FROM orders"));
+        assert!(contains_destructive_statement(
+            "SELECT 1; delete from orders where id = 1;"
+        ));
+        assert!(contains_destructive_statement(
+            "WITH gone AS (DELETE FROM orders RETURNING id) SELECT count(*) FROM gone"
+        ));
+        assert!(contains_destructive_statement(
+            "TRUNCATE orders; DROP TABLE orders"
+        ));
+        assert!(!contains_destructive_statement(
+            "SELECT 'DELETE FROM orders'; -- DELETE"
+        ));
+        assert!(!contains_destructive_statement(
+            "UPDATE orders SET deleted = true"
+        ));
+    }
+
+    /// Inserts two rows into an empty `notes` table, then updates both
+    async fn insert_ids_are_reported(manager: &ConnectionManager, id: &Uuid, insert: &str) {
+        let results = QueryEngine::execute_mutations_detailed(
+            manager,
+            id,
+            vec![
+                insert.replace("{}", "first"),
+                insert.replace("{}", "second"),
+                "UPDATE notes SET body = 'edited'".to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let ids: Vec<_> = results.iter().map(|r| r.last_insert_id).collect();
+        assert_eq!(ids, [Some(1), Some(2), None]);
+        let affected: Vec<_> = results.iter().map(|r| r.affected_rows).collect();
+        assert_eq!(affected, [1, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn mutations_report_the_inserted_id() {
+        for (manager, id) in test_support::backends().await {
+            // Postgres has no last insert id, so its inserts return the key instead
+            let (key, insert) = match detect_db_type(&manager, &id).await.unwrap() {
+                "postgres" => (
+                    "SERIAL",
+                    "INSERT INTO notes (body) VALUES ('{}') RETURNING id",
+                ),
+                "mysql" => (
+                    "INT AUTO_INCREMENT",
+                    "INSERT INTO notes (body) VALUES ('{}')",
+                ),
+                _ => ("INTEGER", "INSERT INTO notes (body) VALUES ('{}')"),
+            };
+            let create = format!("CREATE TABLE notes (id {} PRIMARY KEY, body TEXT)", key);
+            test_support::run(&manager, &id, &create).await;
+            insert_ids_are_reported(&manager, &id, insert).await;
+            test_support::run(&manager, &id, "DROP TABLE notes").await;
+        }
+    }
+
+    #[tokio::test]
+    async fn cell_bytes_are_written_verbatim() {
+        let (manager, id) = test_support::sqlite().await;
+        let payload: Vec<u8> = (0..=255).collect();
+        let hex: String = payload.iter().map(|b| format!("{:02X}", b)).collect();
+        test_support::run(
+            &manager,
+            &id,
+            &format!(
+                "CREATE TABLE files (id INTEGER PRIMARY KEY, data BLOB);
+                 INSERT INTO files VALUES (1, X'00'), (2, X'{}');",
+                hex
+            ),
+        )
+        .await;
+
+        let path = test_support::temp_file("bin", b"");
+        let written = QueryEngine::export_cell_bytes(
+            &manager,
+            &id,
+            "SELECT id, data FROM files ORDER BY id",
+            1,
+            "data",
+            &path.to_string_lossy(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(written, 256);
+        assert_eq!(std::fs::read(&path).unwrap(), payload);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn cursor_pages_keep_their_snapshot_under_concurrent_inserts() {
+        let (manager, id) = test_support::postgres().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE cursor_rows (id INT PRIMARY KEY);
+             INSERT INTO cursor_rows SELECT generate_series(1, 100);",
+        )
+        .await;
+        let cursor = QueryEngine::open_result_cursor(
+            &manager,
+            &id,
+            "SELECT id FROM cursor_rows ORDER BY id",
+        )
+        .await
+        .unwrap();
+
+        let mut seen = Vec::new();
+        loop {
+            let page = QueryEngine::fetch_cursor(&manager, &cursor, 30)
+                .await
+                .unwrap();
+            seen.extend(page.rows.iter().map(|row| row[0].as_i64().unwrap()));
+            if seen.len() == 30 {
+                // Lands before and after the rows already read; neither shows up
+                test_support::run(
+                    &manager,
+                    &id,
+                    "INSERT INTO cursor_rows SELECT generate_series(101, 150);
+                     INSERT INTO cursor_rows VALUES (0);",
+                )
+                .await;
+            }
+            if page.rows.len() < 30 {
+                break;
+            }
+        }
+        QueryEngine::close_cursor(&manager, &cursor).await.unwrap();
+
+        assert_eq!(seen, (1..=100).collect::<Vec<i64>>());
+        test_support::run(&manager, &id, "DROP TABLE cursor_rows").await;
+    }
+
+    /// Inserts 10 and -4 into `ledger` inside `tx` and checks the transaction sees both
+    async fn post_to_ledger(manager: &ConnectionManager, tx: &Uuid) {
+        for sql in [
+            "INSERT INTO ledger VALUES (10)",
+            "INSERT INTO ledger VALUES (-4)",
+        ] {
+            QueryEngine::execute_in_transaction(manager, tx, sql)
+                .await
+                .unwrap();
+        }
+        let sum =
+            QueryEngine::execute_in_transaction(manager, tx, "SELECT SUM(amount) FROM ledger")
+                .await
+                .unwrap();
+        assert_eq!(sum.rows[0][0], 6);
+    }
+
+    #[tokio::test]
+    async fn transactions_commit_or_roll_back_their_statements() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(&manager, &id, "CREATE TABLE ledger (amount INTEGER)").await;
+
+        let tx = QueryEngine::begin_transaction(&manager, &id).await.unwrap();
+        post_to_ledger(&manager, &tx).await;
+        QueryEngine::rollback_transaction(&manager, &tx)
+            .await
+            .unwrap();
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM ledger").await;
+        assert_eq!(count.rows[0][0], 0);
+
+        let tx = QueryEngine::begin_transaction(&manager, &id).await.unwrap();
+        post_to_ledger(&manager, &tx).await;
+        QueryEngine::commit_transaction(&manager, &tx)
+            .await
+            .unwrap();
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM ledger").await;
+        assert_eq!(count.rows[0][0], 2);
+
+        // A finished transaction is unregistered
+        let err = QueryEngine::commit_transaction(&manager, &tx)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Transaction not found");
+    }
+
+    /// Runs a misspelled SELECT and returns the streaming error payload it turns into
+    async fn syntax_error_payload(manager: &ConnectionManager, id: &Uuid) -> StreamingError {
+        let err = QueryEngine::execute_query(manager, id, "SELEC 1", None, None, None, None)
+            .await
+            .unwrap_err();
+        streaming_error(Uuid::nil(), &err)
+    }
+
+    #[tokio::test]
+    async fn syntax_errors_stream_a_syntax_code() {
+        for (manager, id) in test_support::backends().await {
+            let error = syntax_error_payload(&manager, &id).await;
+            assert_eq!(error.code, "syntax_error");
+            assert!(error.message.contains("syntax"), "{}", error.message);
+        }
+    }
+
+    #[test]
+    fn unbounded_pages_keep_their_offset() {
+        assert_eq!(limit_offset_clause(None, None, "postgres"), "");
+        assert_eq!(limit_offset_clause(Some(50), None, "sqlite"), " LIMIT 50");
+        assert_eq!(
+            limit_offset_clause(None, Some(10), "postgres"),
+            " OFFSET 10"
+        );
+        assert_eq!(
+            limit_offset_clause(None, Some(10), "sqlite"),
+            " LIMIT -1 OFFSET 10"
+        );
+        assert_eq!(
+            limit_offset_clause(None, Some(10), "mysql"),
+            " LIMIT 18446744073709551615 OFFSET 10"
+        );
+    }
+
+    #[tokio::test]
+    async fn limit_zero_fetches_every_row() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER);
+             WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 1500)
+             INSERT INTO numbers SELECT n FROM s;",
+        )
+        .await;
+
+        let all = QueryEngine::get_table_data(&manager, &id, "numbers", 0, 0, vec![], None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.rows.len(), 1500);
+
+        let rest =
+            QueryEngine::get_table_data(&manager, &id, "numbers", 0, 1490, vec![], None, None)
+                .await
+                .unwrap();
+        assert_eq!(rest.rows.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn integer_columns_report_an_integer_type() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO items VALUES (1, 'one');",
+        )
+        .await;
+
+        let result = test_support::query(&manager, &id, "SELECT id, name FROM items").await;
+        assert_eq!(result.column_types.len(), 2);
+        assert!(
+            result.column_types[0].to_uppercase().contains("INT"),
+            "{:?}",
+            result.column_types
+        );
+        assert_eq!(result.column_types[1].to_uppercase(), "TEXT");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_integer_columns_report_an_integer_type() {
+        let (manager, id) = test_support::postgres().await;
+        let result =
+            test_support::query(&manager, &id, "SELECT 1::int4 AS id, 'x'::text AS name").await;
+        assert_eq!(result.column_types, ["INT4", "TEXT"]);
+    }
+
+    #[tokio::test]
+    async fn empty_results_still_name_their_columns() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(&manager, &id, "CREATE TABLE t (id INTEGER, name TEXT)").await;
+
+        let result = test_support::query(&manager, &id, "SELECT id, name FROM t WHERE 1=0").await;
+        assert!(result.rows.is_empty());
+        assert_eq!(result.columns, ["id", "name"]);
+
+        // Paginated queries take the same path
+        let page = QueryEngine::execute_query(
+            &manager,
+            &id,
+            "SELECT id, name FROM t",
+            Some(1),
+            Some(50),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.columns, ["id", "name"]);
+        assert_eq!(page.total_count, Some(0));
+    }
+
+    /// Creates `enrollments`, whose primary key lists its columns in a different order
+    /// than the table does, and checks the key comes back in key order
+    async fn composite_key_order(manager: &ConnectionManager, id: &Uuid) {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE enrollments (
+                 student_id INTEGER, course_id INTEGER, term VARCHAR(10), grade TEXT,
+                 PRIMARY KEY (term, course_id, student_id)
+             )",
+        )
+        .await;
+        let keys = QueryEngine::get_primary_keys(manager, id, "enrollments")
+            .await
+            .unwrap();
+        assert_eq!(keys, ["term", "course_id", "student_id"]);
+    }
+
+    #[tokio::test]
+    async fn primary_keys_follow_key_order() {
+        for (manager, id) in test_support::backends().await {
+            composite_key_order(&manager, &id).await;
+            test_support::run(&manager, &id, "DROP TABLE enrollments").await;
+        }
+    }
+
+    #[tokio::test]
+    async fn large_queries_stream_to_csv_without_a_window() {
+        let (manager, id) = test_support::sqlite().await;
+        let sql = "WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 50000)
+                   SELECT n, 'row ' || n AS label FROM s";
+        let path = test_support::temp_file("csv", b"");
+
+        let written = QueryEngine::stream_query_to_file(
+            &manager,
+            &id,
+            sql,
+            "csv",
+            &path.to_string_lossy(),
+            &JsonNullMode::default(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(written, 50_000);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 50_001);
+        assert_eq!(lines[0], "n,label");
+        assert_eq!(lines[50_000], "50000,row 50000");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn limit_zero_uses_the_connection_browse_limit() {
+        let (manager, id) =
+            test_support::sqlite_with(serde_json::json!({ "default_browse_limit": 500 })).await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER);
+             WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 1500)
+             INSERT INTO numbers SELECT n FROM s;",
+        )
+        .await;
+
+        let browsed =
+            QueryEngine::get_table_data(&manager, &id, "numbers", 0, 0, vec![], None, None)
+                .await
+                .unwrap();
+        assert_eq!(browsed.rows.len(), 500);
+
+        // An explicit limit still wins
+        let explicit =
+            QueryEngine::get_table_data(&manager, &id, "numbers", 1000, 0, vec![], None, None)
+                .await
+                .unwrap();
+        assert_eq!(explicit.rows.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn table_pages_over_max_rows_are_truncated() {
+        let (manager, id) = test_support::sqlite_with(serde_json::json!({ "max_rows": 10 })).await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER);
+             WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 100)
+             INSERT INTO numbers SELECT n FROM s;",
+        )
+        .await;
+        let sort = || Some("n".to_string());
+
+        let data =
+            QueryEngine::get_table_data(&manager, &id, "numbers", 50, 0, vec![], sort(), None)
+                .await
+                .unwrap();
+        assert!(data.truncated);
+        assert_eq!(data.rows.len(), 10);
+        assert_eq!(data.rows[9][0], 10);
+
+        let page =
+            QueryEngine::get_table_page(&manager, &id, "numbers", 50, 20, vec![], sort(), None)
+                .await
+                .unwrap();
+        assert!(page.truncated);
+        assert_eq!(page.rows.len(), 10);
+        assert_eq!(page.rows[0][0], 21);
+        assert_eq!(page.total_count, Some(100));
+
+        // A page within the cap is complete
+        let small =
+            QueryEngine::get_table_page(&manager, &id, "numbers", 10, 90, vec![], sort(), None)
+                .await
+                .unwrap();
+        assert!(!small.truncated);
+        assert_eq!(small.rows.len(), 10);
+    }
+
+    /// Creates and analyzes the `analyzed` table, leaving it for the caller to inspect
+    /// and drop
+    async fn analyze_table(manager: &ConnectionManager, id: &Uuid) -> Vec<String> {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE analyzed (id INTEGER PRIMARY KEY, label VARCHAR(20));
+             INSERT INTO analyzed VALUES (1, 'one'), (2, 'two');",
+        )
+        .await;
+        QueryEngine::maintain_table(manager, id, "analyzed", "analyze")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sqlite_analyze_records_table_statistics() {
+        let (manager, id) = test_support::sqlite().await;
+        analyze_table(&manager, &id).await;
+
+        let stats = test_support::query(
+            &manager,
+            &id,
+            "SELECT COUNT(*) FROM sqlite_stat1 WHERE tbl = 'analyzed'",
+        )
+        .await;
+        assert_eq!(stats.rows[0][0], 1);
+
+        let err = QueryEngine::maintain_table(&manager, &id, "analyzed", "defrag")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unknown maintenance operation: defrag");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_analyze_sets_last_analyze() {
+        let (manager, id) = test_support::postgres().await;
+        analyze_table(&manager, &id).await;
+
+        // The statistics view can trail the ANALYZE by a moment
+        let mut analyzed = false;
+        for _ in 0..50 {
+            let result = test_support::query(
+                &manager,
+                &id,
+                "SELECT last_analyze IS NOT NULL FROM pg_stat_user_tables WHERE relname = 'analyzed'",
+            )
+            .await;
+            analyzed = result.rows.first().is_some_and(|row| row[0] == true);
+            if analyzed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        test_support::run(&manager, &id, "DROP TABLE analyzed").await;
+        assert!(analyzed, "last_analyze was never set");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_analyze_reports_its_status() {
+        let (manager, id) = test_support::mysql().await;
+        let messages = analyze_table(&manager, &id).await;
+        test_support::run(&manager, &id, "DROP TABLE analyzed").await;
+        assert!(
+            messages.contains(&"status: OK".to_string()),
+            "{:?}",
+            messages
+        );
+    }
+
+    /// Validates a good script with two placeholders written as `placeholders`, then one
+    /// whose second statement is misspelled, returning the failed validation
+    async fn validate_good_and_bad(
+        manager: &ConnectionManager,
+        id: &Uuid,
+        placeholders: [&str; 2],
+    ) -> SqlValidation {
+        let good = format!(
+            "SELECT 1 WHERE 1 = {}; SELECT 2 WHERE 2 = {}",
+            placeholders[0], placeholders[1]
+        );
+        let valid = QueryEngine::validate_sql(manager, id, &good).await.unwrap();
+        assert!(valid.valid, "{:?}", valid.message);
+        assert_eq!(valid.parameter_count, 2);
+
+        let invalid = QueryEngine::validate_sql(manager, id, "SELECT 1; SELEC 2")
+            .await
+            .unwrap();
+        assert!(!invalid.valid);
+        assert_eq!(invalid.statement, Some(2));
+        assert_eq!(invalid.code.as_deref(), Some("syntax_error"));
+        invalid
+    }
+
+    #[tokio::test]
+    async fn validation_prepares_without_executing() {
+        for (manager, id) in test_support::backends().await {
+            let postgres = detect_db_type(&manager, &id).await.unwrap() == "postgres";
+            let placeholders = if postgres {
+                ["$1::int", "$2::int"]
+            } else {
+                ["?", "?"]
+            };
+            let invalid = validate_good_and_bad(&manager, &id, placeholders).await;
+            // Only Postgres reports where in the statement the error is
+            if postgres {
+                assert_eq!(invalid.position, Some(1));
+            }
+
+            test_support::run(&manager, &id, "CREATE TABLE validated (n INTEGER)").await;
+            let insert =
+                QueryEngine::validate_sql(&manager, &id, "INSERT INTO validated VALUES (1)")
+                    .await
+                    .unwrap();
+            let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM validated").await;
+            test_support::run(&manager, &id, "DROP TABLE validated").await;
+            assert!(insert.valid);
+            assert_eq!(count.rows[0][0], 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicated_rows_get_a_new_key_and_the_same_data() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE people (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT, age INTEGER);
+             INSERT INTO people VALUES (7, 'ada@example.com', 'Ada', 36);",
+        )
+        .await;
+        let duplicate = |overrides: BTreeMap<String, Value>| {
+            QueryEngine::duplicate_row(
+                &manager,
+                &id,
+                "people",
+                vec!["id".to_string()],
+                vec![Value::from(7)],
+                overrides,
+            )
+        };
+
+        // The copy would repeat the UNIQUE email unless it is overridden
+        assert!(duplicate(BTreeMap::new()).await.is_err());
+        let overrides =
+            BTreeMap::from([("email".to_string(), Value::from("ada.copy@example.com"))]);
+        let new_id = duplicate(overrides).await.unwrap().unwrap();
+        assert_ne!(new_id, 7);
+
+        let rows = test_support::query(
+            &manager,
+            &id,
+            "SELECT id, email, name, age FROM people ORDER BY id",
+        )
+        .await;
+        assert_eq!(
+            rows.rows,
+            vec![
+                vec![
+                    Value::from(7),
+                    Value::from("ada@example.com"),
+                    Value::from("Ada"),
+                    Value::from(36)
+                ],
+                vec![
+                    Value::from(new_id),
+                    Value::from("ada.copy@example.com"),
+                    Value::from("Ada"),
+                    Value::from(36)
+                ],
+            ]
+        );
+    }
+
+    /// Takes a sample of `count` rows from `sampled`, checking that every row is a distinct
+    /// row of the table
+    async fn sampled_ids(manager: &ConnectionManager, id: &Uuid, count: u32) -> Vec<i64> {
+        let sample = QueryEngine::get_table_sample(manager, id, "sampled", count)
+            .await
+            .unwrap();
+        assert_eq!(sample.columns, ["id", "label"]);
+        let mut ids: Vec<i64> = sample.rows.iter().map(|r| r[0].as_i64().unwrap()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), sample.rows.len(), "a row was sampled twice");
+        if !ids.is_empty() {
+            let list: Vec<String> = ids.iter().map(i64::to_string).collect();
+            let found = test_support::query(
+                manager,
+                id,
+                &format!(
+                    "SELECT COUNT(*) FROM sampled WHERE id IN ({})",
+                    list.join(", ")
+                ),
+            )
+            .await;
+            assert_eq!(found.rows[0][0], ids.len());
+        }
+        ids
+    }
+
+    /// Fills `sampled` with `rows` rows and samples 10 of them, then more than the table
+    /// holds. Row-order sampling returns exactly the count; Postgres' page sampling only
+    /// guarantees some rows, up to the count.
+    async fn samples_are_bounded(manager: &ConnectionManager, id: &Uuid, rows: u32, exact: bool) {
+        test_support::run(
+            manager,
+            id,
+            &format!(
+                "CREATE TABLE sampled (id INTEGER, label VARCHAR(20));
+                 INSERT INTO sampled (id, label)
+                 WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < {0})
+                 SELECT n, 'row' FROM s;",
+                rows
+            ),
+        )
+        .await;
+
+        let ten = sampled_ids(manager, id, 10).await;
+        let everything = sampled_ids(manager, id, rows * 2).await;
+        test_support::run(manager, id, "DROP TABLE sampled").await;
+
+        if exact {
+            assert_eq!(ten.len(), 10);
+            assert_eq!(everything.len(), rows as usize);
+        } else {
+            assert!((1..=10).contains(&ten.len()), "{} rows", ten.len());
+            assert!((1..=rows as usize).contains(&everything.len()));
+        }
+    }
+
+    #[tokio::test]
+    async fn samples_return_up_to_count_rows() {
+        for (manager, id) in test_support::backends().await {
+            // Postgres samples pages, so its table needs enough of them to sample from
+            match detect_db_type(&manager, &id).await.unwrap() {
+                "postgres" => samples_are_bounded(&manager, &id, 5000, false).await,
+                _ => samples_are_bounded(&manager, &id, 100, true).await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn numeric_and_text_columns_profile_sanely() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE scores (name TEXT, points INTEGER);
+             INSERT INTO scores VALUES ('ada', 10), ('bob', 20), ('ada', 30), (NULL, NULL);",
+        )
+        .await;
+
+        let points = QueryEngine::profile_column(&manager, &id, "scores", "points")
+            .await
+            .unwrap();
+        assert_eq!(points.total_count, 4);
+        assert_eq!(points.null_count, 1);
+        assert_eq!(points.distinct_count, 3);
+        assert_eq!(points.min.as_deref(), Some("10"));
+        assert_eq!(points.max.as_deref(), Some("30"));
+        assert_eq!(points.avg, Some(20.0));
+
+        let name = QueryEngine::profile_column(&manager, &id, "scores", "name")
+            .await
+            .unwrap();
+        assert_eq!(name.null_count, 1);
+        assert_eq!(name.distinct_count, 2);
+        assert_eq!(name.min.as_deref(), Some("ada"));
+        assert_eq!(name.max.as_deref(), Some("bob"));
+        assert_eq!(name.avg, None);
+
+        let err = QueryEngine::profile_column(&manager, &id, "scores", "rank")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unknown column 'rank'");
+    }
+
+    #[tokio::test]
+    async fn populated_databases_report_a_nonzero_size() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE blobs (data BLOB);
+             INSERT INTO blobs VALUES (randomblob(100000));",
+        )
+        .await;
+
+        let size = QueryEngine::get_database_size(&manager, &id).await.unwrap();
+        assert!(size.total_bytes >= 100_000, "{} bytes", size.total_bytes);
+        assert_eq!(size.total_size, format_bytes(size.total_bytes));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_database_size_is_nonzero() {
+        let (manager, id) = test_support::postgres().await;
+        let size = QueryEngine::get_database_size(&manager, &id).await.unwrap();
+        assert!(size.total_bytes > 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_database_size_counts_table_data() {
+        let (manager, id) = test_support::mysql().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE sized (n INT); INSERT INTO sized VALUES (1); ANALYZE TABLE sized;",
+        )
+        .await;
+        let size = QueryEngine::get_database_size(&manager, &id).await.unwrap();
+        assert!(size.total_bytes > 0);
+        test_support::run(&manager, &id, "DROP TABLE sized").await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn table_page_counts_match_rows_under_concurrent_inserts() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let (manager, id) = test_support::postgres().await;
+        test_support::run(&manager, &id, "CREATE TABLE paged (id SERIAL PRIMARY KEY)").await;
+
+        // A second connection keeps inserting while pages are read
+        let (writer, writer_id) = test_support::postgres().await;
+        let stop = Arc::new(AtomicBool::new(false));
+        let inserting = {
+            let stop = stop.clone();
+            tokio::spawn(async move {
+                while !stop.load(Ordering::Relaxed) {
+                    test_support::run(&writer, &writer_id, "INSERT INTO paged DEFAULT VALUES")
+                        .await;
+                }
+            })
+        };
+
+        for _ in 0..50 {
+            let page =
+                QueryEngine::get_table_page(&manager, &id, "paged", 100_000, 0, vec![], None, None)
+                    .await
+                    .unwrap();
+            assert_eq!(page.total_count, Some(page.rows.len() as u64));
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        inserting.await.unwrap();
+        test_support::run(&manager, &id, "DROP TABLE paged").await;
+    }
+
+    #[tokio::test]
+    async fn table_exports_report_progress_every_interval() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER);
+             WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 2500)
+             INSERT INTO numbers SELECT n FROM s;",
+        )
+        .await;
+        let path = test_support::temp_file("csv", b"");
+        let reports = std::sync::Mutex::new(Vec::new());
+        let on_progress = |written: u64, total: u64| reports.lock().unwrap().push((written, total));
+
+        let exported = QueryEngine::export_table_data(
+            &manager,
+            &id,
+            "numbers",
+            None,
+            vec![],
+            None,
+            None,
+            "csv",
+            &path.to_string_lossy(),
+            None,
+            false,
+            &CsvQuoting::default(),
+            &JsonNullMode::Null,
+            Some(&on_progress),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exported, 2500);
+        assert_eq!(*reports.lock().unwrap(), [(1000, 2500), (2000, 2500)]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn json_exports_keep_both_values_of_duplicate_columns() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file("json", b"");
+
+        QueryEngine::stream_query_to_file(
+            &manager,
+            &id,
+            "SELECT 1 AS id, 2 AS id",
+            "json",
+            &path.to_string_lossy(),
+            &JsonNullMode::default(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        let exported: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(exported, serde_json::json!([{ "id": 1, "id_2": 2 }]));
+        std::fs::remove_file(path).unwrap();
 
-                Ok(TableStructure {
-                    columns,
-                    indexes,
-                    constraints,
-                })
-            }
-            Some(_) => Err(anyhow!("Unknown database type")),
-            None => Err(anyhow!("Connection not found")),
-        }
+        // The grid keeps the names as written
+        let result = test_support::query(&manager, &id, "SELECT 1 AS id, 2 AS id").await;
+        assert_eq!(result.columns, ["id", "id"]);
     }
 
-    pub async fn export_table_data(
-        manager: &ConnectionManager,
-        connection_id: &Uuid,
-        table_name: &str,
-        filters: Vec<FilterConfig>,
-        sort_column: Option<String>,
-        sort_direction: Option<String>,
-        format: &str,
-        file_path: &str,
-    ) -> Result<u64> {
-        let db_type = {
-            if manager
-                .get_postgres_pools()
+    #[tokio::test]
+    async fn json_exports_apply_the_null_mode() {
+        let (manager, id) = test_support::sqlite().await;
+        let export = |mode: &'static str| {
+            let (manager, id) = (&manager, &id);
+            async move {
+                let path = test_support::temp_file("json", b"");
+                QueryEngine::stream_query_to_file(
+                    manager,
+                    id,
+                    "SELECT 1 AS id, NULL AS email",
+                    "json",
+                    &path.to_string_lossy(),
+                    &JsonNullMode::parse(Some(mode)).unwrap(),
+                    &CancellationToken::new(),
+                )
                 .await
-                .contains_key(connection_id)
-            {
-                Some("postgres")
-            } else if manager.get_mysql_pools().await.contains_key(connection_id) {
-                Some("mysql")
-            } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
-                Some("sqlite")
-            } else {
-                None
+                .unwrap();
+                let text = std::fs::read_to_string(&path).unwrap();
+                std::fs::remove_file(path).unwrap();
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
             }
         };
 
-        if db_type.is_none() {
-            return Err(anyhow!("Connection not found"));
+        assert_eq!(
+            export("null").await,
+            serde_json::json!([{ "id": 1, "email": null }])
+        );
+        assert_eq!(export("omit").await, serde_json::json!([{ "id": 1 }]));
+        assert_eq!(
+            export("sentinel:NULL").await,
+            serde_json::json!([{ "id": 1, "email": "NULL" }])
+        );
+    }
+
+    #[test]
+    fn kill_session_sql_per_database() {
+        assert_eq!(
+            kill_session_sql("postgres", 4242).unwrap(),
+            "SELECT pg_terminate_backend(4242)"
+        );
+        assert_eq!(
+            kill_session_sql("mysql", 4242).unwrap(),
+            "KILL CONNECTION 4242"
+        );
+        assert!(kill_session_sql("sqlite", 4242).is_err());
+    }
+
+    #[tokio::test]
+    async fn sqlite_has_no_sessions() {
+        let (manager, id) = test_support::sqlite().await;
+        let err = QueryEngine::list_sessions(&manager, &id).await.unwrap_err();
+        assert_eq!(err.to_string(), "SQLite has no server sessions");
+        assert!(QueryEngine::kill_session(&manager, &id, 1).await.is_err());
+    }
+
+    /// The session list includes our own session, and killing an unknown pid reports false
+    async fn sessions_are_listed(manager: &ConnectionManager, id: &Uuid, own_pid_sql: &str) {
+        let own = test_support::query(manager, id, own_pid_sql).await.rows[0][0]
+            .as_i64()
+            .unwrap();
+
+        let sessions = QueryEngine::list_sessions(manager, id).await.unwrap();
+        assert!(sessions.iter().any(|s| s.pid == own));
+        assert!(!QueryEngine::kill_session(manager, id, i32::MAX as i64)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn server_sessions_are_listed() {
+        for (manager, id) in test_support::servers().await {
+            let own_pid_sql = match detect_db_type(&manager, &id).await.unwrap() {
+                "postgres" => "SELECT pg_backend_pid()::int8",
+                _ => "SELECT CAST(CONNECTION_ID() AS SIGNED)",
+            };
+            sessions_are_listed(&manager, &id, own_pid_sql).await;
         }
-        let db_type = db_type.unwrap();
+    }
 
-        let where_clause = build_where_clause(filters, db_type);
-        let order_clause = build_order_clause(sort_column, sort_direction, db_type);
+    #[tokio::test]
+    async fn tagged_queries_still_run() {
+        let (manager, id) =
+            test_support::sqlite_with(serde_json::json!({ "tag_queries": true })).await;
+        let sql = "WITH t(n) AS (SELECT 1) SELECT n FROM t";
 
-        let sql = match db_type {
-            "postgres" | "sqlite" => format!(
-                "SELECT * FROM \"{}\" {} {};",
-                table_name.replace("\"", "\"\""),
-                where_clause,
-                order_clause
-            ),
-            "mysql" => format!(
-                "SELECT * FROM `{}` {} {};",
-                table_name.replace("`", "``"),
-                where_clause,
-                order_clause
-            ),
-            _ => return Err(anyhow!("Unknown database type")),
-        };
+        let tagged = manager.tag_query(&id, sql).await;
+        assert!(tagged.starts_with("/* sqlMate conn=test user="));
+        assert!(tagged.ends_with(&format!("*/ {}", sql)));
 
-        let result = Self::execute_query(manager, connection_id, &sql, None, None).await?;
-        let rows_count = result.rows.len() as u64;
+        let result = test_support::query(&manager, &id, sql).await;
+        assert_eq!(result.rows, [[serde_json::json!(1)]]);
+        test_support::query(&manager, &id, "EXPLAIN QUERY PLAN SELECT 1").await;
 
-        let mut file = File::create(file_path)?;
+        let (untagged, untagged_id) = test_support::sqlite().await;
+        assert_eq!(untagged.tag_query(&untagged_id, sql).await, sql);
+    }
 
-        match format {
-            "csv" => {
-                let mut wtr = csv::Writer::from_writer(file);
-                // Write headers
-                wtr.write_record(&result.columns)?;
-                // Write rows
-                for row in result.rows {
-                    let record: Vec<String> = row
-                        .into_iter()
-                        .map(|v| match v {
-                            Value::Null => "".to_string(),
-                            Value::String(s) => s,
-                            Value::Number(n) => n.to_string(),
-                            Value::Bool(b) => b.to_string(),
-                            _ => v.to_string(),
-                        })
-                        .collect();
-                    wtr.write_record(&record)?;
-                }
-                wtr.flush()?;
-            }
-            "json" => {
-                let mut json_rows = Vec::new();
-                for row in result.rows {
-                    let mut obj = serde_json::Map::new();
-                    for (i, col) in result.columns.iter().enumerate() {
-                        obj.insert(col.clone(), row[i].clone());
-                    }
-                    json_rows.push(Value::Object(obj));
-                }
-                let json_data = serde_json::to_string_pretty(&json_rows)?;
-                file.write_all(json_data.as_bytes())?;
-            }
-            "sql" => {
-                for row in result.rows {
-                    let values: Vec<String> = row
-                        .into_iter()
-                        .map(|v| match v {
-                            Value::Null => "NULL".to_string(),
-                            Value::String(s) => format!("'{}'", s.replace("'", "''")),
-                            Value::Number(n) => n.to_string(),
-                            Value::Bool(b) => if b { "true" } else { "false" }.to_string(),
-                            _ => format!("'{}'", v.to_string().replace("'", "''")),
-                        })
-                        .collect();
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_sees_the_query_tag() {
+        let (manager, id) =
+            test_support::postgres_with(serde_json::json!({ "tag_queries": true })).await;
+        let result = test_support::query(
+            &manager,
+            &id,
+            "SELECT query FROM pg_stat_activity WHERE pid = pg_backend_pid()",
+        )
+        .await;
+        let seen = result.rows[0][0].as_str().unwrap();
+        assert!(seen.starts_with("/* sqlMate conn=test user="), "{}", seen);
+    }
 
-                    let insert_sql = match db_type {
-                        "mysql" => format!(
-                            "INSERT INTO `{}` ({}) VALUES ({});\n",
-                            table_name.replace("`", "``"),
-                            result
-                                .columns
-                                .iter()
-                                .map(|c| format!("`{}`", c.replace("`", "``")))
-                                .collect::<Vec<_>>()
-                                .join(", "),
-                            values.join(", ")
-                        ),
-                        _ => format!(
-                            "INSERT INTO \"{}\" ({}) VALUES ({});\n",
-                            table_name.replace("\"", "\"\""),
-                            result
-                                .columns
-                                .iter()
-                                .map(|c| format!("\"{}\"", c.replace("\"", "\"\"")))
-                                .collect::<Vec<_>>()
-                                .join(", "),
-                            values.join(", ")
-                        ),
-                    };
-                    file.write_all(insert_sql.as_bytes())?;
-                }
-            }
-            _ => return Err(anyhow!("Unsupported export format")),
+    #[test]
+    fn completion_rows_group_by_table_in_column_order() {
+        let rows = [("b", "id"), ("a", "x"), ("b", "name"), ("a", "y")]
+            .iter()
+            .map(|(t, c)| (t.to_string(), c.to_string()))
+            .collect();
+        let tables = group_columns_by_table(rows);
+        assert_eq!(tables["a"], ["x", "y"]);
+        assert_eq!(tables["b"], ["id", "name"]);
+    }
+
+    /// The completion map lists each table's columns in declaration order
+    async fn completion_schema_lists_columns(manager: &ConnectionManager, id: &Uuid) {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE completion_authors (id INTEGER PRIMARY KEY, name VARCHAR(50));
+             CREATE TABLE completion_books (id INTEGER PRIMARY KEY, title VARCHAR(50), author_id INTEGER);",
+        )
+        .await;
+
+        let schema = QueryEngine::get_completion_schema(manager, id)
+            .await
+            .unwrap();
+        assert_eq!(schema["completion_authors"], ["id", "name"]);
+        assert_eq!(schema["completion_books"], ["id", "title", "author_id"]);
+
+        test_support::run(
+            manager,
+            id,
+            "DROP TABLE completion_books; DROP TABLE completion_authors;",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn completion_schema_lists_columns_in_order() {
+        for (manager, id) in test_support::backends().await {
+            completion_schema_lists_columns(&manager, &id).await;
         }
+    }
 
-        Ok(rows_count)
+    #[test]
+    fn non_finite_floats_are_spelled_out() {
+        assert_eq!(float_to_json(1.5), serde_json::json!(1.5));
+        assert_eq!(float_to_json(f64::NAN), "NaN");
+        assert_eq!(float_to_json(f64::INFINITY), "Infinity");
+        assert_eq!(float_to_json(f64::NEG_INFINITY), "-Infinity");
     }
 
-    pub async fn get_sidebar_items(
-        manager: &ConnectionManager,
-        connection_id: &Uuid,
-    ) -> Result<Vec<SidebarItem>> {
-        let mut items = Vec::new();
+    #[tokio::test]
+    async fn sqlite_infinities_are_not_null() {
+        let (manager, id) = test_support::sqlite().await;
+        let result = test_support::query(&manager, &id, "SELECT 9e999, -9e999, NULL").await;
+        assert_eq!(
+            result.rows[0],
+            [
+                serde_json::json!("Infinity"),
+                serde_json::json!("-Infinity"),
+                serde_json::Value::Null
+            ]
+        );
+    }
 
-        // Check Postgres
-        {
-            let pools = manager.get_postgres_pools().await;
-            if let Some(pool) = pools.get(connection_id) {
-                // Tables and Views
-                let sql = r#"
-                    SELECT table_name, table_type, table_schema
-                    FROM information_schema.tables
-                    WHERE table_schema NOT IN ('information_schema', 'pg_catalog')
-                    ORDER BY table_name;
-                "#;
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                for row in rows {
-                    let name: String = row.get(0);
-                    let table_type: String = row.get(1);
-                    let schema: String = row.get(2);
-                    let item_type = if table_type == "VIEW" {
-                        SidebarItemType::View
-                    } else {
-                        SidebarItemType::Table
-                    };
-                    items.push(SidebarItem {
-                        name,
-                        item_type,
-                        schema: Some(schema),
-                    });
-                }
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_nan_and_infinity_are_not_null() {
+        let (manager, id) = test_support::postgres().await;
+        let sql = "SELECT 'NaN'::numeric, 'Infinity'::float8, '-Infinity'::float4, NULL::numeric";
+        let expected = [
+            serde_json::json!("NaN"),
+            serde_json::json!("Infinity"),
+            serde_json::json!("-Infinity"),
+            serde_json::Value::Null,
+        ];
+
+        let result = test_support::query(&manager, &id, sql).await;
+        assert_eq!(result.rows[0], expected);
+    }
 
-                // Functions and Procedures
-                let sql = r#"
-                    SELECT routine_name, routine_type, routine_schema
-                    FROM information_schema.routines
-                    WHERE routine_schema NOT IN ('information_schema', 'pg_catalog')
-                    ORDER BY routine_name;
-                "#;
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                for row in rows {
-                    let name: String = row.get(0);
-                    let routine_type: String = row.get(1);
-                    let schema: String = row.get(2);
-                    let item_type = if routine_type == "PROCEDURE" {
-                        SidebarItemType::Procedure
-                    } else {
-                        SidebarItemType::Function
-                    };
-                    items.push(SidebarItem {
-                        name,
-                        item_type,
-                        schema: Some(schema),
-                    });
-                }
-                return Ok(items);
-            }
-        }
+    #[test]
+    fn binary_bit_strings_decode_to_zeros_and_ones() {
+        assert_eq!(
+            decode_pg_bit(&[0, 0, 0, 3, 0b1010_0000]).as_deref(),
+            Some("101")
+        );
+        assert_eq!(
+            decode_pg_bit(&[0, 0, 0, 10, 0xFF, 0b0100_0000]).as_deref(),
+            Some("1111111101")
+        );
+        assert_eq!(decode_pg_bit(&[0, 0, 0, 0]).as_deref(), Some(""));
+        // Fewer bytes than the bit length claims
+        assert_eq!(decode_pg_bit(&[0, 0, 0, 9, 0xFF]), None);
+    }
 
-        // Check MySQL
-        {
-            let pools = manager.get_mysql_pools().await;
-            if let Some(pool) = pools.get(connection_id) {
-                // Tables and Views
-                let sql = r#"
-                    SELECT TABLE_NAME, TABLE_TYPE, TABLE_SCHEMA
-                    FROM information_schema.TABLES
-                    WHERE TABLE_SCHEMA = DATABASE()
-                    ORDER BY TABLE_NAME;
-                "#;
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                for row in rows {
-                    let name: String = row.get(0);
-                    let table_type: String = row.get(1);
-                    let schema: String = row.get(2);
-                    let item_type = if table_type == "VIEW" {
-                        SidebarItemType::View
-                    } else {
-                        SidebarItemType::Table
-                    };
-                    items.push(SidebarItem {
-                        name,
-                        item_type,
-                        schema: Some(schema),
-                    });
-                }
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_bits_come_back_as_bit_strings() {
+        let (manager, id) = test_support::postgres().await;
+        let result = test_support::query(
+            &manager,
+            &id,
+            "SELECT B'101', B'1'::bit(1), B'0110'::varbit",
+        )
+        .await;
+        assert_eq!(
+            result.rows[0],
+            [
+                serde_json::json!("101"),
+                serde_json::json!("1"),
+                serde_json::json!("0110")
+            ]
+        );
+    }
 
-                // Routines
-                let sql = r#"
-                    SELECT ROUTINE_NAME, ROUTINE_TYPE, ROUTINE_SCHEMA
-                    FROM information_schema.ROUTINES
-                    WHERE ROUTINE_SCHEMA = DATABASE()
-                    ORDER BY ROUTINE_NAME;
-                "#;
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                for row in rows {
-                    let name: String = row.get(0);
-                    let routine_type: String = row.get(1);
-                    let schema: String = row.get(2);
-                    let item_type = if routine_type == "PROCEDURE" {
-                        SidebarItemType::Procedure
-                    } else {
-                        SidebarItemType::Function
-                    };
-                    items.push(SidebarItem {
-                        name,
-                        item_type,
-                        schema: Some(schema),
-                    });
-                }
-                return Ok(items);
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_bits_come_back_as_integers() {
+        let (manager, id) = test_support::mysql().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE bit_flags (flags BIT(3), wide BIT(64));
+             INSERT INTO bit_flags VALUES (B'101', B'1');",
+        )
+        .await;
+
+        let result = test_support::query(&manager, &id, "SELECT flags, wide FROM bit_flags").await;
+        assert_eq!(result.rows[0], [serde_json::json!(5), serde_json::json!(1)]);
+        test_support::run(&manager, &id, "DROP TABLE bit_flags;").await;
+    }
+
+    #[tokio::test]
+    async fn max_rows_truncates_and_flags_the_result() {
+        let (manager, id) = test_support::sqlite_with(serde_json::json!({ "max_rows": 10 })).await;
+        let sql = "WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 100)
+                   SELECT n FROM s";
+
+        let capped = test_support::query(&manager, &id, sql).await;
+        assert!(capped.truncated);
+        assert_eq!(capped.rows.len(), 10);
+        assert_eq!(capped.rows[9][0], 10);
+
+        let (uncapped, uncapped_id) = test_support::sqlite().await;
+        let full = test_support::query(&uncapped, &uncapped_id, sql).await;
+        assert!(!full.truncated);
+        assert_eq!(full.rows.len(), 100);
+    }
+
+    #[test]
+    fn mysql_enum_labels_keep_definition_order() {
+        assert_eq!(
+            parse_mysql_enum_labels("enum('small','medium','large')").unwrap(),
+            ["small", "medium", "large"]
+        );
+        assert_eq!(
+            parse_mysql_enum_labels("set('it''s','a,b')").unwrap(),
+            ["it's", "a,b"]
+        );
+        assert_eq!(parse_mysql_enum_labels("varchar(10)"), None);
+        assert_eq!(parse_mysql_enum_labels("enum('open"), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_enum_values_come_in_definition_order() {
+        let (manager, id) = test_support::postgres().await;
+        test_support::run(
+            &manager,
+            &id,
+            "DROP TYPE IF EXISTS shirt_size; CREATE TYPE shirt_size AS ENUM ('small', 'medium', 'large');",
+        )
+        .await;
+
+        let labels = QueryEngine::get_enum_values(&manager, &id, "shirt_size", None).await;
+        let missing = QueryEngine::get_enum_values(&manager, &id, "no_such_enum", None).await;
+        test_support::run(&manager, &id, "DROP TYPE shirt_size;").await;
+        assert_eq!(labels.unwrap(), ["small", "medium", "large"]);
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_enum_values_come_in_definition_order() {
+        let (manager, id) = test_support::mysql().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE shirts (size ENUM('small', 'medium', 'large'));",
+        )
+        .await;
+
+        let labels = QueryEngine::get_enum_values(&manager, &id, "size", Some("shirts")).await;
+        test_support::run(&manager, &id, "DROP TABLE shirts;").await;
+        assert_eq!(labels.unwrap(), ["small", "medium", "large"]);
+    }
+
+    #[tokio::test]
+    async fn sqlite_has_no_enum_values() {
+        let (manager, id) = test_support::sqlite().await;
+        let err = QueryEngine::get_enum_values(&manager, &id, "size", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "SQLite has no enum types");
+    }
+
+    /// Renames `people.nm` to `name`, then widens it where the backend can; the data stays
+    async fn renames_and_widens_a_column(
+        manager: &ConnectionManager,
+        id: &Uuid,
+        widened_type: Option<&str>,
+    ) {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE people (id INTEGER PRIMARY KEY, nm VARCHAR(10));
+             INSERT INTO people VALUES (1, 'Ada');",
+        )
+        .await;
+
+        QueryEngine::alter_column(
+            manager,
+            id,
+            "people",
+            "nm",
+            Some("name".into()),
+            None,
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+        let widened = QueryEngine::alter_column(
+            manager,
+            id,
+            "people",
+            "name",
+            None,
+            Some("VARCHAR(100)".into()),
+            None,
+            false,
+        )
+        .await;
+
+        let structure = QueryEngine::get_table_structure(manager, id, "people")
+            .await
+            .unwrap();
+        let result = test_support::query(manager, id, "SELECT name FROM people").await;
+        test_support::run(manager, id, "DROP TABLE people;").await;
+
+        let names: Vec<&str> = structure.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "name"]);
+        assert_eq!(result.rows, [["Ada"]]);
+        match widened_type {
+            Some(data_type) => {
+                widened.unwrap();
+                assert_eq!(structure.columns[1].data_type, data_type);
             }
+            None => assert_eq!(
+                widened.unwrap_err().to_string(),
+                "SQLite can only rename columns; recreate the table to change a column's type or nullability"
+            ),
         }
+    }
 
-        // Check SQLite
-        {
-            let pools = manager.get_sqlite_pools().await;
-            if let Some(pool) = pools.get(connection_id) {
-                let sql = "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;";
-                let rows = sqlx::query(sql).fetch_all(pool).await?;
-                for row in rows {
-                    let name: String = row.get(0);
-                    let item_type_str: String = row.get(1);
-                    let item_type = if item_type_str == "view" {
-                        SidebarItemType::View
-                    } else {
-                        SidebarItemType::Table
-                    };
-                    items.push(SidebarItem {
-                        name,
-                        item_type,
-                        schema: None,
-                    });
-                }
-                return Ok(items);
-            }
+    #[tokio::test]
+    async fn renames_columns_and_widens_them_outside_sqlite() {
+        for (manager, id) in test_support::backends().await {
+            let widened_type = match detect_db_type(&manager, &id).await.unwrap() {
+                "postgres" => Some("character varying(100)"),
+                "mysql" => Some("varchar(100)"),
+                _ => None,
+            };
+            renames_and_widens_a_column(&manager, &id, widened_type).await;
         }
+    }
 
-        Err(anyhow!("Connection not found"))
+    #[test]
+    fn drop_column_needs_sqlite_3_35() {
+        assert!(sqlite_supports_drop_column("3.35.0"));
+        assert!(sqlite_supports_drop_column("3.45.1"));
+        assert!(sqlite_supports_drop_column("4.0"));
+        assert!(!sqlite_supports_drop_column("3.34.1"));
+        assert!(!sqlite_supports_drop_column("3.8.11"));
+    }
+
+    /// Adds a NOT NULL column with a default to a populated table, then drops it again
+    async fn adds_and_drops_a_column(manager: &ConnectionManager, id: &Uuid) {
+        test_support::run(
+            manager,
+            id,
+            "CREATE TABLE accounts (id INTEGER PRIMARY KEY);
+             INSERT INTO accounts VALUES (1);",
+        )
+        .await;
+
+        let added = QueryEngine::add_column(
+            manager,
+            id,
+            "accounts",
+            "status",
+            "VARCHAR(20)",
+            false,
+            Some(serde_json::json!("active")),
+        )
+        .await;
+        let with_status = QueryEngine::get_table_structure(manager, id, "accounts").await;
+        let existing = test_support::query(manager, id, "SELECT status FROM accounts").await;
+        let dropped = QueryEngine::drop_column(manager, id, "accounts", "status", false).await;
+        let without_status = QueryEngine::get_table_structure(manager, id, "accounts").await;
+        test_support::run(manager, id, "DROP TABLE accounts;").await;
+
+        added.unwrap();
+        let with_status = with_status.unwrap();
+        let status = with_status
+            .columns
+            .iter()
+            .find(|c| c.name == "status")
+            .unwrap();
+        assert!(!status.is_nullable);
+        assert!(status.default_value.as_deref().unwrap().contains("active"));
+        assert_eq!(existing.rows, [["active"]]);
+
+        dropped.unwrap();
+        let names: Vec<String> = without_status
+            .unwrap()
+            .columns
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, ["id"]);
+    }
+
+    #[tokio::test]
+    async fn adds_and_drops_columns() {
+        for (manager, id) in test_support::backends().await {
+            adds_and_drops_a_column(&manager, &id).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn long_streams_report_stats_along_the_way() {
+        let (manager, id) = test_support::sqlite().await;
+        let app = test_support::app();
+        let window = test_support::window(&app);
+        let stats = test_support::events(&app, "query-stats");
+        let complete = test_support::events(&app, "query-complete");
+        let query_id = Uuid::new_v4();
+
+        // 200 batches with a short pause after each keep the stream going well past 500ms
+        QueryEngine::execute_query_streaming(
+            &manager,
+            &id,
+            "WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 200000)
+             SELECT n, 'row ' || n AS label FROM s",
+            query_id,
+            &window,
+            CancellationToken::new(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let stats = stats.lock().unwrap();
+        assert!(!stats.is_empty(), "no query-stats within the stream");
+        let first = &stats[0];
+        assert_eq!(first["query_id"], query_id.to_string());
+        assert!(first["elapsed_ms"].as_u64().unwrap() >= 500);
+        assert!(first["rows_per_sec"].as_f64().unwrap() >= 0.0);
+        let rows: Vec<u64> = stats
+            .iter()
+            .map(|s| s["total_rows"].as_u64().unwrap())
+            .collect();
+        assert!(rows.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*rows.last().unwrap() <= 200_000);
+        assert_eq!(complete.lock().unwrap()[0]["total_rows"], 200_000);
+    }
+
+    #[tokio::test]
+    async fn distinct_values_are_sorted_unique_and_capped() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, status TEXT);
+             WITH RECURSIVE s(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM s WHERE n < 3000)
+             INSERT INTO orders
+             SELECT n, CASE n % 3 WHEN 0 THEN 'shipped' WHEN 1 THEN 'pending' ELSE 'cancelled' END
+             FROM s;",
+        )
+        .await;
+
+        let statuses = QueryEngine::get_distinct_values(&manager, &id, "orders", "status", None)
+            .await
+            .unwrap();
+        assert_eq!(statuses, ["cancelled", "pending", "shipped"]);
+
+        let two = QueryEngine::get_distinct_values(&manager, &id, "orders", "status", Some(2))
+            .await
+            .unwrap();
+        assert_eq!(two, ["cancelled", "pending"]);
+
+        // High-cardinality columns stop at the default, and no limit goes past the cap
+        let ids = QueryEngine::get_distinct_values(&manager, &id, "orders", "id", None)
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), DEFAULT_DISTINCT_VALUES as usize);
+        assert_eq!(ids[0], 1);
+        let ids = QueryEngine::get_distinct_values(&manager, &id, "orders", "id", Some(1_000_000))
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), MAX_DISTINCT_VALUES as usize);
+    }
+
+    #[tokio::test]
+    async fn sqlite_runs_ddl_and_dml_in_one_query() {
+        let (manager, id) = test_support::sqlite().await;
+
+        let script = test_support::query(
+            &manager,
+            &id,
+            "CREATE TABLE tags (name TEXT); INSERT INTO tags VALUES ('a'), ('b');",
+        )
+        .await;
+        assert_eq!(script.affected_rows, 2);
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM tags").await;
+        assert_eq!(count.rows[0][0], 2);
+
+        // The statements share a transaction, so a failure undoes the CREATE too
+        let failed = QueryEngine::execute_query(
+            &manager,
+            &id,
+            "CREATE TABLE labels (name TEXT); INSERT INTO missing VALUES (1);",
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert!(failed.is_err());
+        let tables = test_support::query(
+            &manager,
+            &id,
+            "SELECT COUNT(*) FROM sqlite_schema WHERE name = 'labels'",
+        )
+        .await;
+        assert_eq!(tables.rows[0][0], 0);
     }
 }
@@ -0,0 +1,198 @@
+//! Live connections for tests. SQLite runs in memory; Postgres and MySQL tests are `#[ignore]`d
+//! and read their server from `SQLMATE_TEST_{POSTGRES,MYSQL}_{HOST,PORT,USER,PASSWORD,DATABASE}`.
+//! Tests that behave the same everywhere loop over `backends()` instead, which adds a server
+//! only when its `HOST` variable is set.
+
+use super::connection_manager::ConnectionManager;
+use super::query_engine::QueryEngine;
+use super::{ConnectionConfig, QueryResult};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::test::MockRuntime;
+use tauri::{App, Listener};
+use uuid::Uuid;
+
+/// A config named "test" of `db_type` ("Sqlite", "Postgres", "MySql"), with extra fields
+pub fn config(db_type: &str, fields: serde_json::Value) -> ConnectionConfig {
+    let mut config = json!({
+        "id": Uuid::new_v4(),
+        "name": "test",
+        "db_type": db_type,
+        "ssl_enabled": false,
+        "ssh_enabled": false,
+    });
+    if let (Some(config), Some(fields)) = (config.as_object_mut(), fields.as_object()) {
+        config.extend(fields.clone());
+    }
+    serde_json::from_value(config).expect("valid test connection config")
+}
+
+/// Connects to a fresh in-memory SQLite database; the pool keeps its one connection open
+pub async fn sqlite() -> (ConnectionManager, Uuid) {
+    sqlite_with(json!({})).await
+}
+
+/// Like `sqlite`, with extra `ConnectionConfig` fields
+pub async fn sqlite_with(fields: serde_json::Value) -> (ConnectionManager, Uuid) {
+    let mut config = config("Sqlite", fields);
+    config.database = Some(":memory:".to_string());
+    let id = config.id;
+    let manager = ConnectionManager::new();
+    manager.connect(config, None).await.expect("open SQLite");
+    (manager, id)
+}
+
+/// An in-memory SQLite connection, then one to each server whose `HOST` variable is set
+pub async fn backends() -> Vec<(ConnectionManager, Uuid)> {
+    let mut backends = vec![sqlite().await];
+    backends.extend(servers().await);
+    backends
+}
+
+/// Connections to the Postgres and MySQL servers whose `HOST` variables are set
+pub async fn servers() -> Vec<(ConnectionManager, Uuid)> {
+    let configured = |prefix: &str| std::env::var(format!("SQLMATE_TEST_{}_HOST", prefix)).is_ok();
+    let mut servers = Vec::new();
+    if configured("POSTGRES") {
+        servers.push(postgres().await);
+    }
+    if configured("MYSQL") {
+        servers.push(mysql().await);
+    }
+    servers
+}
+
+/// Runs setup statements (CREATE TABLE, INSERT ...) on a test connection
+pub async fn run(manager: &ConnectionManager, id: &Uuid, sql: &str) {
+    QueryEngine::execute_script(manager, id, sql)
+        .await
+        .expect("setup script");
+}
+
+/// Runs one statement through `execute_query` with no paging, timezone or auto-limit
+pub async fn query(manager: &ConnectionManager, id: &Uuid, sql: &str) -> QueryResult {
+    QueryEngine::execute_query(manager, id, sql, None, None, None, None)
+        .await
+        .expect("query")
+}
+
+/// Connects to the Postgres server named by the `SQLMATE_TEST_POSTGRES_*` variables
+pub async fn postgres() -> (ConnectionManager, Uuid) {
+    postgres_with(json!({})).await
+}
+
+/// Like `postgres`, with extra `ConnectionConfig` fields
+pub async fn postgres_with(fields: serde_json::Value) -> (ConnectionManager, Uuid) {
+    server(
+        "Postgres",
+        "POSTGRES",
+        ("5432", "postgres", "postgres"),
+        fields,
+    )
+    .await
+}
+
+/// Connects to the MySQL server named by the `SQLMATE_TEST_MYSQL_*` variables
+pub async fn mysql() -> (ConnectionManager, Uuid) {
+    mysql_with(json!({})).await
+}
+
+/// Like `mysql`, with extra `ConnectionConfig` fields
+pub async fn mysql_with(fields: serde_json::Value) -> (ConnectionManager, Uuid) {
+    server("MySql", "MYSQL", ("3306", "root", "mysql"), fields).await
+}
+
+/// Connects to the MariaDB server named by the `SQLMATE_TEST_MARIADB_*` variables
+#[cfg(feature = "mariadb-integration-tests")]
+pub async fn mariadb() -> (ConnectionManager, Uuid) {
+    server("MySql", "MARIADB", ("3306", "root", "mysql"), json!({})).await
+}
+
+/// The Postgres server's config and password, for tests that connect by other means
+pub fn postgres_config() -> (ConnectionConfig, String) {
+    server_config(
+        "Postgres",
+        "POSTGRES",
+        ("5432", "postgres", "postgres"),
+        json!({}),
+    )
+}
+
+async fn server(
+    db_type: &str,
+    prefix: &str,
+    defaults: (&str, &str, &str),
+    fields: serde_json::Value,
+) -> (ConnectionManager, Uuid) {
+    let (config, password) = server_config(db_type, prefix, defaults, fields);
+    let id = config.id;
+    let manager = ConnectionManager::new();
+    manager
+        .connect(config, Some(password))
+        .await
+        .unwrap_or_else(|e| panic!("connect to {}: {}", db_type, e));
+    (manager, id)
+}
+
+fn server_config(
+    db_type: &str,
+    prefix: &str,
+    (port, user, database): (&str, &str, &str),
+    fields: serde_json::Value,
+) -> (ConnectionConfig, String) {
+    let var = |name: &str, default: &str| {
+        std::env::var(format!("SQLMATE_TEST_{}_{}", prefix, name))
+            .unwrap_or_else(|_| default.to_string())
+    };
+    let port: u16 = var("PORT", port).parse().expect("numeric port");
+    let mut connection = json!({
+        "host": var("HOST", "localhost"),
+        "port": port,
+        "username": var("USER", user),
+        "database": var("DATABASE", database),
+    });
+    if let (Some(connection), Some(fields)) = (connection.as_object_mut(), fields.as_object()) {
+        connection.extend(fields.clone());
+    }
+    (config(db_type, connection), var("PASSWORD", user))
+}
+
+/// A mock Tauri app for code that reports progress through an `AppHandle`
+pub fn app() -> App<MockRuntime> {
+    tauri::test::mock_app()
+}
+
+/// A window of the mock app, for code that streams results through window events
+pub fn window(app: &App<MockRuntime>) -> tauri::Window<MockRuntime> {
+    let webview = tauri::WebviewWindowBuilder::new(app, "main", Default::default())
+        .build()
+        .expect("mock window");
+    webview.as_ref().window()
+}
+
+/// Collects the JSON payload of every `event` emitted through `app`, in order
+pub fn events(app: &App<MockRuntime>, event: &str) -> Arc<Mutex<Vec<serde_json::Value>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    app.listen_any(event, move |event| {
+        let payload = serde_json::from_str(event.payload()).expect("JSON event payload");
+        sink.lock().unwrap().push(payload);
+    });
+    events
+}
+
+/// Writes `contents` to a fresh file in the temp dir, named `*.{extension}`
+pub fn temp_file(extension: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sqlmate-test-{}.{}", Uuid::new_v4(), extension));
+    std::fs::write(&path, contents).expect("write temp file");
+    path
+}
+
+/// Gzip-compresses `data`, as for a `.gz` import file
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).expect("compress");
+    encoder.finish().expect("compress")
+}
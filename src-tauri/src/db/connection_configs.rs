@@ -0,0 +1,130 @@
+use crate::core::ConnectionConfig;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Persists connection configs as JSON in the app config dir.
+///
+/// Secrets are never written here; passwords belong in the keyring via `SecureStore`.
+pub struct ConnectionConfigStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl ConnectionConfigStore {
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .ok_or_else(|| anyhow!("Could not determine the app config directory"))?
+            .join("sqlmate.io");
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self::at(dir.join("connections.json")))
+    }
+
+    fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read_all(&self) -> Result<Vec<ConnectionConfig>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(&self.path)?;
+        if data.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn write_all(&self, configs: &[ConnectionConfig]) -> Result<()> {
+        // Write to a temp file first so a crash can't leave a truncated store behind
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(configs)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub async fn list(&self) -> Result<Vec<ConnectionConfig>> {
+        let _guard = self.lock.lock().await;
+        self.read_all()
+    }
+
+    /// Inserts or replaces the config with the same id
+    pub async fn save(&self, config: &ConnectionConfig) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut configs = self.read_all()?;
+
+        let mut stored = config.clone();
+        stored.ssh_password = None;
+        for hop in &mut stored.ssh_jump_hosts {
+            hop.password = None;
+        }
+
+        match configs.iter_mut().find(|c| c.id == stored.id) {
+            Some(existing) => *existing = stored,
+            None => configs.push(stored),
+        }
+        self.write_all(&configs)
+    }
+
+    pub async fn delete(&self, id: &Uuid) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut configs = self.read_all()?;
+        configs.retain(|c| c.id != *id);
+        self.write_all(&configs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn saves_lists_and_deletes_without_writing_passwords() {
+        let path =
+            std::env::temp_dir().join(format!("sqlmate-connections-{}.json", Uuid::new_v4()));
+        let store = ConnectionConfigStore::at(path.clone());
+        let config: ConnectionConfig = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "prod replica",
+            "db_type": "Postgres",
+            "host": "db.internal",
+            "ssl_enabled": false,
+            "ssh_enabled": true,
+            "ssh_host": "bastion.internal",
+            "ssh_username": "deploy",
+            "ssh_auth_method": "password",
+            "ssh_password": "ssh-secret",
+            "ssh_jump_hosts": [
+                { "host": "jump.internal", "username": "jump", "auth_method": "password", "password": "hop-secret" }
+            ],
+            "environment": "production",
+            "color_tag": "#ff0000"
+        }))
+        .unwrap();
+
+        store.save(&config).await.unwrap();
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, config.id);
+        assert_eq!(listed[0].environment.as_deref(), Some("production"));
+        assert_eq!(listed[0].color_tag.as_deref(), Some("#ff0000"));
+        assert_eq!(listed[0].ssh_jump_hosts[0].host, "jump.internal");
+
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("ssh-secret"));
+        assert!(!on_disk.contains("hop-secret"));
+        assert!(listed[0].ssh_password.is_none());
+        assert!(listed[0].ssh_jump_hosts[0].password.is_none());
+
+        store.delete(&config.id).await.unwrap();
+        assert!(store.list().await.unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryHistoryEntry {
+    pub id: i64,
+    pub connection_id: Uuid,
+    pub sql: String,
+    pub executed_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub row_count: Option<u64>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+pub struct QueryHistoryStore {
+    pool: SqlitePool,
+}
+
+impl QueryHistoryStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS query_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id TEXT NOT NULL,
+                sql TEXT NOT NULL,
+                executed_at TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                row_count INTEGER,
+                success INTEGER NOT NULL,
+                error TEXT
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_query_history_connection ON query_history (connection_id, executed_at)",
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn record(
+        &self,
+        connection_id: &Uuid,
+        sql: &str,
+        duration_ms: u64,
+        row_count: Option<u64>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO query_history (connection_id, sql, executed_at, duration_ms, row_count, success, error) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(connection_id.to_string())
+        .bind(sql)
+        .bind(Utc::now())
+        .bind(duration_ms as i64)
+        .bind(row_count.map(|c| c as i64))
+        .bind(error.is_none())
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Most recent entries first
+    pub async fn list(&self, connection_id: &Uuid, limit: u32) -> Result<Vec<QueryHistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, sql, executed_at, duration_ms, row_count, success, error FROM query_history WHERE connection_id = ? ORDER BY executed_at DESC, id DESC LIMIT ?",
+        )
+        .bind(connection_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| QueryHistoryEntry {
+                id: row.get(0),
+                connection_id: *connection_id,
+                sql: row.get(1),
+                executed_at: row.get(2),
+                duration_ms: row.get::<i64, _>(3) as u64,
+                row_count: row.get::<Option<i64>, _>(4).map(|c| c as u64),
+                success: row.get(5),
+                error: row.get(6),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_newest_first_including_failures() {
+        let store = QueryHistoryStore::new(crate::db::memory_db().await)
+            .await
+            .unwrap();
+        let connection = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        store
+            .record(&connection, "SELECT 1", 3, Some(1), None)
+            .await
+            .unwrap();
+        store
+            .record(&connection, "SELEC 2", 1, None, Some("syntax error"))
+            .await
+            .unwrap();
+        store
+            .record(&connection, "SELECT 3", 2, Some(1), None)
+            .await
+            .unwrap();
+        store
+            .record(&other, "SELECT 4", 2, Some(1), None)
+            .await
+            .unwrap();
+
+        let entries = store.list(&connection, 10).await.unwrap();
+        let sql: Vec<&str> = entries.iter().map(|e| e.sql.as_str()).collect();
+        assert_eq!(sql, ["SELECT 3", "SELEC 2", "SELECT 1"]);
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].executed_at >= pair[1].executed_at));
+        assert!(!entries[1].success);
+        assert_eq!(entries[1].error.as_deref(), Some("syntax error"));
+        assert_eq!(entries[1].row_count, None);
+        assert!(entries[0].success);
+
+        assert_eq!(store.list(&connection, 2).await.unwrap().len(), 2);
+    }
+}
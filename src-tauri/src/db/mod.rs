@@ -1 +1,39 @@
+pub mod connection_configs;
+pub mod history;
+pub mod saved_queries;
+pub mod sidebar_preferences;
 
+use anyhow::{anyhow, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+/// Opens (creating if needed) the local SQLite database used for app-level persistence.
+pub async fn open_app_db() -> Result<SqlitePool> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| anyhow!("Could not determine the app data directory"))?
+        .join("sqlmate.io");
+    std::fs::create_dir_all(&dir)?;
+
+    let opts = SqliteConnectOptions::new()
+        .filename(dir.join("sqlmate.db"))
+        .create_if_missing(true);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await?;
+
+    Ok(pool)
+}
+
+/// A private in-memory database standing in for the app database in tests
+#[cfg(test)]
+pub(crate) async fn memory_db() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory SQLite")
+}
@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedQuery {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub sql: String,
+    pub connection_id: Option<Uuid>, // None = available for every connection
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct SavedQueryStore {
+    pool: SqlitePool,
+}
+
+fn saved_query_from_row(row: SqliteRow) -> Result<SavedQuery> {
+    let id: String = row.get("id");
+    let connection_id: Option<String> = row.get("connection_id");
+    Ok(SavedQuery {
+        id: Uuid::parse_str(&id)?,
+        name: row.get("name"),
+        description: row.get("description"),
+        sql: row.get("sql"),
+        connection_id: connection_id.map(|c| Uuid::parse_str(&c)).transpose()?,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+impl SavedQueryStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS saved_queries (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                sql TEXT NOT NULL,
+                connection_id TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn save(
+        &self,
+        name: &str,
+        sql: &str,
+        description: Option<&str>,
+        connection_id: Option<Uuid>,
+    ) -> Result<SavedQuery> {
+        let now = Utc::now();
+        let saved = SavedQuery {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: description.map(|d| d.to_string()),
+            sql: sql.to_string(),
+            connection_id,
+            created_at: now,
+            updated_at: now,
+        };
+
+        sqlx::query(
+            "INSERT INTO saved_queries (id, name, description, sql, connection_id, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(saved.id.to_string())
+        .bind(&saved.name)
+        .bind(&saved.description)
+        .bind(&saved.sql)
+        .bind(saved.connection_id.map(|c| c.to_string()))
+        .bind(saved.created_at)
+        .bind(saved.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Lists global queries plus those scoped to `connection_id`; all queries when it is None.
+    pub async fn list(&self, connection_id: Option<Uuid>) -> Result<Vec<SavedQuery>> {
+        let rows = match connection_id {
+            Some(id) => {
+                sqlx::query(
+                    "SELECT * FROM saved_queries WHERE connection_id IS NULL OR connection_id = ? ORDER BY name",
+                )
+                .bind(id.to_string())
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query("SELECT * FROM saved_queries ORDER BY name")
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        rows.into_iter().map(saved_query_from_row).collect()
+    }
+
+    pub async fn update(
+        &self,
+        id: &Uuid,
+        name: &str,
+        sql: &str,
+        description: Option<&str>,
+        connection_id: Option<Uuid>,
+    ) -> Result<SavedQuery> {
+        let result = sqlx::query(
+            "UPDATE saved_queries SET name = ?, description = ?, sql = ?, connection_id = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(name)
+        .bind(description)
+        .bind(sql)
+        .bind(connection_id.map(|c| c.to_string()))
+        .bind(Utc::now())
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Saved query not found"));
+        }
+
+        let row = sqlx::query("SELECT * FROM saved_queries WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+        saved_query_from_row(row)
+    }
+
+    pub async fn delete(&self, id: &Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM saved_queries WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Saved query not found"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn saved_query_lifecycle() {
+        let store = SavedQueryStore::new(crate::db::memory_db().await)
+            .await
+            .unwrap();
+        let connection = Uuid::new_v4();
+        let global = store
+            .save("Active users", "SELECT * FROM users", None, None)
+            .await
+            .unwrap();
+        let scoped = store
+            .save("Big orders", "SELECT 1", Some("over 100"), Some(connection))
+            .await
+            .unwrap();
+        store
+            .save("Elsewhere", "SELECT 2", None, Some(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let names = |queries: Vec<SavedQuery>| -> Vec<String> {
+            queries.into_iter().map(|q| q.name).collect()
+        };
+        assert_eq!(
+            names(store.list(Some(connection)).await.unwrap()),
+            ["Active users", "Big orders"]
+        );
+        assert_eq!(store.list(None).await.unwrap().len(), 3);
+
+        let updated = store
+            .update(
+                &scoped.id,
+                "Huge orders",
+                "SELECT 3",
+                None,
+                Some(connection),
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.sql, "SELECT 3");
+        assert_eq!(updated.description, None);
+        assert!(updated.updated_at >= scoped.updated_at);
+
+        store.delete(&global.id).await.unwrap();
+        assert_eq!(
+            names(store.list(Some(connection)).await.unwrap()),
+            ["Huge orders"]
+        );
+        assert!(store.delete(&global.id).await.is_err());
+        assert!(store
+            .update(&global.id, "Gone", "SELECT 1", None, None)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::core::SidebarItem;
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Per-connection pinned flags and open counts for sidebar tables.
+///
+/// Rows are keyed by (schema, name); SQLite tables have no schema and use an empty string.
+pub struct SidebarPreferenceStore {
+    pool: SqlitePool,
+}
+
+impl SidebarPreferenceStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sidebar_preferences (
+                connection_id TEXT NOT NULL,
+                schema_name TEXT NOT NULL DEFAULT '',
+                table_name TEXT NOT NULL,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                usage_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (connection_id, schema_name, table_name)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn set_pinned(
+        &self,
+        connection_id: &Uuid,
+        schema: Option<&str>,
+        table_name: &str,
+        pinned: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sidebar_preferences (connection_id, schema_name, table_name, pinned) VALUES (?, ?, ?, ?) \
+             ON CONFLICT (connection_id, schema_name, table_name) DO UPDATE SET pinned = excluded.pinned",
+        )
+        .bind(connection_id.to_string())
+        .bind(schema.unwrap_or(""))
+        .bind(table_name)
+        .bind(pinned)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Counts one open of the table, for sorting the sidebar by usage
+    pub async fn record_access(
+        &self,
+        connection_id: &Uuid,
+        schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sidebar_preferences (connection_id, schema_name, table_name, usage_count) VALUES (?, ?, ?, 1) \
+             ON CONFLICT (connection_id, schema_name, table_name) DO UPDATE SET usage_count = usage_count + 1",
+        )
+        .bind(connection_id.to_string())
+        .bind(schema.unwrap_or(""))
+        .bind(table_name)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Fills in `pinned`/`usage_count` on the items, then orders them with
+    /// `sort_sidebar_items`
+    pub async fn apply(
+        &self,
+        connection_id: &Uuid,
+        items: &mut [SidebarItem],
+        by_usage: bool,
+    ) -> Result<()> {
+        let rows = sqlx::query(
+            "SELECT schema_name, table_name, pinned, usage_count FROM sidebar_preferences WHERE connection_id = ?",
+        )
+        .bind(connection_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let prefs: HashMap<(String, String), (bool, u64)> = rows
+            .into_iter()
+            .map(|row| {
+                let key: (String, String) = (row.get(0), row.get(1));
+                let pinned: bool = row.get(2);
+                (key, (pinned, row.get::<i64, _>(3) as u64))
+            })
+            .collect();
+
+        for item in items.iter_mut() {
+            let key = (item.schema.clone().unwrap_or_default(), item.name.clone());
+            if let Some(&(pinned, usage_count)) = prefs.get(&key) {
+                item.pinned = pinned;
+                item.usage_count = usage_count;
+            }
+        }
+        sort_sidebar_items(items, by_usage);
+        Ok(())
+    }
+}
+
+/// Moves pinned items to the front, optionally ordering each group by usage.
+/// The sort is stable, so ties keep the catalog's alphabetical order.
+pub fn sort_sidebar_items(items: &mut [SidebarItem], by_usage: bool) {
+    items.sort_by(|a, b| {
+        b.pinned.cmp(&a.pinned).then_with(|| {
+            if by_usage {
+                b.usage_count.cmp(&a.usage_count)
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::SidebarItemType;
+
+    fn table(name: &str) -> SidebarItem {
+        SidebarItem {
+            name: name.to_string(),
+            item_type: SidebarItemType::Table,
+            schema: None,
+            pinned: false,
+            usage_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn pinned_tables_come_first_regardless_of_name() {
+        let store = SidebarPreferenceStore::new(crate::db::memory_db().await)
+            .await
+            .unwrap();
+        let connection = Uuid::new_v4();
+        store
+            .set_pinned(&connection, None, "zebras", true)
+            .await
+            .unwrap();
+        store
+            .set_pinned(&connection, None, "orders", true)
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            store
+                .record_access(&connection, None, "customers")
+                .await
+                .unwrap();
+        }
+        store
+            .record_access(&connection, None, "zebras")
+            .await
+            .unwrap();
+        // Another connection's pins don't leak in
+        store
+            .set_pinned(&Uuid::new_v4(), None, "accounts", true)
+            .await
+            .unwrap();
+
+        let names = |items: &[SidebarItem]| -> Vec<String> {
+            items.iter().map(|item| item.name.clone()).collect()
+        };
+        let catalog = ["accounts", "customers", "orders", "zebras"].map(table);
+
+        let mut items = catalog.clone();
+        store.apply(&connection, &mut items, false).await.unwrap();
+        assert_eq!(names(&items), ["orders", "zebras", "accounts", "customers"]);
+        assert!(items[0].pinned && items[1].pinned && !items[2].pinned);
+
+        let mut items = catalog.clone();
+        store.apply(&connection, &mut items, true).await.unwrap();
+        assert_eq!(names(&items), ["zebras", "orders", "customers", "accounts"]);
+        assert_eq!(items[2].usage_count, 3);
+    }
+}
@@ -1,12 +1,19 @@
-use crate::core::AppState;
+use crate::core::query_engine::{bind_json_params, build_where_clause, float_to_json, QueryEngine};
+use crate::core::{AppState, Cancelled, CatalogFilter, FilterConfig};
+use crate::utils::csv_writer::csv_writer;
+use crate::utils::{quote_ident, unique_column_names, CsvQuoting, JsonNullMode};
 use anyhow::{anyhow, Result};
-use futures::TryStreamExt;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use sqlx::{Column, Row};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 #[derive(Serialize, Clone)]
@@ -14,8 +21,34 @@ pub struct ExportProgress {
     pub export_id: String,
     pub current_table: String,
     pub rows_exported: u64,
-    pub status: String, // "processing" | "complete" | "error"
+    pub total_rows: Option<u64>,
+    pub percentage: Option<f32>,
+    pub status: String, // "processing" | "complete" | "error" | "cancelled"
     pub error: Option<String>,
+    pub manifest_path: Option<String>, // only set on the final "complete" event
+}
+
+/// Sidecar written next to a finished `export_data` run so the output can be verified
+#[derive(Serialize)]
+pub struct ExportManifest {
+    pub export_id: String,
+    pub format: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<ExportManifestFile>,
+}
+
+#[derive(Serialize)]
+pub struct ExportManifestFile {
+    pub path: String,
+    pub rows: u64,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// One output file and the number of rows written to it
+struct ExportedFile {
+    path: String,
+    rows: u64,
 }
 
 #[derive(Deserialize, Debug)]
@@ -25,6 +58,31 @@ pub struct ExportOptions {
     pub format: String, // "csv" | "json" | "sql"
     pub include_schema: bool,
     pub include_data: bool,
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    #[serde(default)]
+    pub excel_bom: bool,
+    /// Skip the per-table COUNT(*) used for percentage progress on very large tables
+    #[serde(default)]
+    pub skip_row_count: bool,
+    /// Export every table in the database, ignoring `tables`
+    #[serde(default)]
+    pub dump_all: bool,
+    /// Maximum number of tables exported at once; bounded by the connection pool size
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Export only these columns, in this order; every exported table must have them
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    /// Row filters keyed by table name; tables without an entry are exported in full
+    #[serde(default)]
+    pub filters: HashMap<String, Vec<FilterConfig>>,
+    /// JSON only: "null" (default), "omit" or "sentinel:<text>"
+    #[serde(default)]
+    pub json_null_mode: Option<String>,
+    /// CSV only: quote character, quoting style and escape handling
+    #[serde(default)]
+    pub csv_quoting: CsvQuoting,
 }
 
 #[tauri::command]
@@ -33,9 +91,19 @@ pub async fn export_data(
     state: State<'_, AppState>,
     connection_id: Uuid,
     export_id: String,
-    options: ExportOptions,
+    mut options: ExportOptions,
 ) -> std::result::Result<(), String> {
     let manager = state.connection_manager.clone();
+    expand_dump_all(&manager, &connection_id, &mut options)
+        .await
+        .map_err(|e| e.to_string())?;
+    let token = CancellationToken::new();
+    state
+        .active_exports
+        .lock()
+        .await
+        .insert(export_id.clone(), token.clone());
+    let active_exports = state.active_exports.clone();
 
     tokio::spawn(async move {
         let result = match options.format.as_str() {
@@ -46,6 +114,7 @@ pub async fn export_data(
                     &connection_id,
                     &export_id,
                     &options,
+                    &token,
                 )
                 .await
             }
@@ -56,6 +125,7 @@ pub async fn export_data(
                     &connection_id,
                     &export_id,
                     &options,
+                    &token,
                 )
                 .await
             }
@@ -66,36 +136,210 @@ pub async fn export_data(
                     &connection_id,
                     &export_id,
                     &options,
+                    &token,
                 )
                 .await
             }
             _ => Err(anyhow!("Unsupported format")),
         };
+        let result = result.and_then(|files| write_export_manifest(&export_id, &options, &files));
 
-        if let Err(e) = result {
-            let _ = app_handle.emit(
-                "export-progress",
-                ExportProgress {
-                    export_id: export_id.clone(),
-                    current_table: "".to_string(),
-                    rows_exported: 0,
-                    status: "error".to_string(),
-                    error: Some(e.to_string()),
-                },
-            );
+        match result {
+            Ok(manifest_path) => {
+                let _ = app_handle.emit(
+                    "export-progress",
+                    ExportProgress {
+                        export_id: export_id.clone(),
+                        current_table: "".to_string(),
+                        rows_exported: 0,
+                        total_rows: None,
+                        percentage: None,
+                        status: "complete".to_string(),
+                        error: None,
+                        manifest_path: Some(manifest_path),
+                    },
+                );
+            }
+            Err(e) => {
+                let cancelled = e.downcast_ref::<Cancelled>().is_some();
+                let _ = app_handle.emit(
+                    "export-progress",
+                    ExportProgress {
+                        export_id: export_id.clone(),
+                        current_table: "".to_string(),
+                        rows_exported: 0,
+                        total_rows: None,
+                        percentage: None,
+                        status: if cancelled { "cancelled" } else { "error" }.to_string(),
+                        error: if cancelled { None } else { Some(e.to_string()) },
+                        manifest_path: None,
+                    },
+                );
+            }
         }
+
+        active_exports.lock().await.remove(&export_id);
     });
 
     Ok(())
 }
 
-async fn do_export_csv(
-    app_handle: AppHandle,
+#[tauri::command]
+pub async fn cancel_export(
+    state: State<'_, AppState>,
+    export_id: String,
+) -> std::result::Result<(), String> {
+    let mut active = state.active_exports.lock().await;
+    if let Some(token) = active.remove(&export_id) {
+        token.cancel();
+    }
+    Ok(())
+}
+
+/// Replaces `options.tables` with every table in the database when `dump_all` is set
+async fn expand_dump_all(
     manager: &crate::core::connection_manager::ConnectionManager,
     connection_id: &Uuid,
+    options: &mut ExportOptions,
+) -> Result<()> {
+    if options.dump_all {
+        options.tables =
+            QueryEngine::get_tables(manager, connection_id, &CatalogFilter::default()).await?;
+    }
+    Ok(())
+}
+
+/// Number of tables exported at once when the caller does not choose
+const DEFAULT_EXPORT_CONCURRENCY: usize = 4;
+
+/// Caps per-table export tasks so one connection stays free for the rest of the app
+async fn export_concurrency(
+    manager: &crate::core::connection_manager::ConnectionManager,
+    connection_id: &Uuid,
+    db_type: &str,
+    options: &ExportOptions,
+) -> usize {
+    let max_connections = match db_type {
+        "postgres" => manager
+            .get_postgres_pools()
+            .await
+            .get(connection_id)
+            .map(|p| p.options().get_max_connections()),
+        "mysql" => manager
+            .get_mysql_pools()
+            .await
+            .get(connection_id)
+            .map(|p| p.options().get_max_connections()),
+        "sqlite" => manager
+            .get_sqlite_pools()
+            .await
+            .get(connection_id)
+            .map(|p| p.options().get_max_connections()),
+        _ => None,
+    }
+    .unwrap_or(1) as usize;
+
+    options
+        .concurrency
+        .unwrap_or(DEFAULT_EXPORT_CONCURRENCY)
+        .clamp(1, max_connections.saturating_sub(1).max(1))
+}
+
+/// Counts a table's rows up front so progress can report a percentage
+async fn table_row_total(
+    manager: &crate::core::connection_manager::ConnectionManager,
+    connection_id: &Uuid,
+    table: &str,
+    options: &ExportOptions,
+) -> Result<Option<u64>> {
+    if options.skip_row_count {
+        return Ok(None);
+    }
+    let filters = options.filters.get(table).cloned().unwrap_or_default();
+    let total = QueryEngine::get_table_count(manager, connection_id, table, filters).await?;
+    Ok(Some(total))
+}
+
+/// The WHERE clause for `table` built from `options.filters`, empty when it has none,
+/// and the values its placeholders bind
+fn table_where_clause(options: &ExportOptions, table: &str, db_type: &str) -> (String, Vec<Value>) {
+    build_where_clause(
+        options.filters.get(table).cloned().unwrap_or_default(),
+        db_type,
+    )
+}
+
+pub(crate) fn export_percentage(rows_exported: u64, total_rows: Option<u64>) -> Option<f32> {
+    total_rows
+        .filter(|total| *total > 0)
+        .map(|total| (rows_exported as f32 / total as f32 * 100.0).min(100.0))
+}
+
+/// Hashes every output file and writes `<output_path>.manifest.json`, returning its path
+fn write_export_manifest(
     export_id: &str,
     options: &ExportOptions,
-) -> Result<()> {
+    files: &[ExportedFile],
+) -> Result<String> {
+    let mut manifest = ExportManifest {
+        export_id: export_id.to_string(),
+        format: options.format.clone(),
+        created_at: Utc::now(),
+        files: Vec::with_capacity(files.len()),
+    };
+    for file in files {
+        let mut hasher = Sha256::new();
+        let bytes = std::io::copy(&mut File::open(&file.path)?, &mut hasher)?;
+        manifest.files.push(ExportManifestFile {
+            path: file.path.clone(),
+            rows: file.rows,
+            bytes,
+            sha256: format!("{:x}", hasher.finalize()),
+        });
+    }
+
+    let manifest_path = format!("{}.manifest.json", options.output_path);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(manifest_path)
+}
+
+/// Object keys for JSON rows; repeated column names (e.g. from a join) get a suffix
+fn json_keys<C: Column>(columns: &[C]) -> Vec<String> {
+    let names: Vec<String> = columns.iter().map(|c| c.name().to_string()).collect();
+    unique_column_names(&names)
+}
+
+/// Reports a finished table at 100%, even if rows changed since it was counted
+fn emit_table_complete<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    export_id: &str,
+    table: &str,
+    rows_exported: u64,
+    total_rows: Option<u64>,
+) {
+    let _ = app_handle.emit(
+        "export-progress",
+        ExportProgress {
+            export_id: export_id.to_string(),
+            current_table: table.to_string(),
+            rows_exported,
+            total_rows,
+            percentage: Some(100.0),
+            status: "processing".to_string(),
+            error: None,
+            manifest_path: None,
+        },
+    );
+}
+
+async fn do_export_csv<R: Runtime>(
+    app_handle: AppHandle<R>,
+    manager: &crate::core::connection_manager::ConnectionManager,
+    connection_id: &Uuid,
+    export_id: &str,
+    options: &ExportOptions,
+    token: &CancellationToken,
+) -> Result<Vec<ExportedFile>> {
     let db_type = {
         if manager
             .get_postgres_pools()
@@ -113,157 +357,211 @@ async fn do_export_csv(
     }
     .ok_or_else(|| anyhow!("Connection not found"))?;
 
-    for table in &options.tables {
-        let file_path = if options.tables.len() > 1 {
-            format!("{}_{}.csv", options.output_path, table)
-        } else {
-            options.output_path.clone()
-        };
+    let concurrency = export_concurrency(manager, connection_id, db_type, options).await;
+    let files = futures::stream::iter(&options.tables)
+        .map(|table| {
+            export_table_csv(
+                &app_handle,
+                manager,
+                connection_id,
+                export_id,
+                options,
+                db_type,
+                table,
+                token,
+            )
+        })
+        .buffer_unordered(concurrency)
+        .try_collect::<Vec<ExportedFile>>()
+        .await?;
 
-        let file = File::create(&file_path)?;
-        let mut wtr = csv::Writer::from_writer(file);
+    Ok(files)
+}
 
-        let quoted_table = match db_type {
-            "mysql" => format!("`{}`", table.replace("`", "``")),
-            _ => format!("\"{}\"", table.replace("\"", "\"\"")),
-        };
+async fn export_table_csv<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    manager: &crate::core::connection_manager::ConnectionManager,
+    connection_id: &Uuid,
+    export_id: &str,
+    options: &ExportOptions,
+    db_type: &str,
+    table: &str,
+    token: &CancellationToken,
+) -> Result<ExportedFile> {
+    let file_path = if options.tables.len() > 1 {
+        format!("{}_{}.csv", options.output_path, table)
+    } else {
+        options.output_path.clone()
+    };
 
-        let sql = format!("SELECT * FROM {}", quoted_table);
-
-        match db_type {
-            "postgres" => {
-                let pool = manager
-                    .get_postgres_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                let mut rows_exported = 0u64;
-                let mut columns_written = false;
-                while let Some(row) = stream.try_next().await? {
-                    if !columns_written {
-                        let cols: Vec<String> =
-                            row.columns().iter().map(|c| c.name().to_string()).collect();
-                        wtr.write_record(&cols)?;
-                        columns_written = true;
-                    }
-                    let record: Vec<String> = (0..row.columns().len())
-                        .map(|i| postgres_row_to_string(&row, i))
-                        .collect();
-                    wtr.write_record(&record)?;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+    let file = File::create(&file_path)?;
+    let mut wtr = csv_writer(
+        file,
+        options.delimiter,
+        options.excel_bom,
+        &options.csv_quoting,
+    )?;
+
+    let quoted_table = quote_ident(table, db_type);
+
+    let select_list = QueryEngine::export_select_list(
+        manager,
+        connection_id,
+        table,
+        options.columns.as_deref(),
+        db_type,
+    )
+    .await?;
+    let (where_clause, params) = table_where_clause(options, table, db_type);
+    let sql = format!(
+        "SELECT {} FROM {} {}",
+        select_list, quoted_table, where_clause
+    );
+    let total_rows = table_row_total(manager, connection_id, table, options).await?;
+    let mut rows_exported = 0u64;
+
+    match db_type {
+        "postgres" => {
+            let pool = manager
+                .get_postgres_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            let mut columns_written = false;
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !columns_written {
+                    let cols: Vec<String> =
+                        row.columns().iter().map(|c| c.name().to_string()).collect();
+                    wtr.write_record(&cols)?;
+                    columns_written = true;
+                }
+                let record: Vec<String> = (0..row.columns().len())
+                    .map(|i| postgres_row_to_string(&row, i))
+                    .collect();
+                wtr.write_record(&record)?;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            "mysql" => {
-                let pool = manager
-                    .get_mysql_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                let mut rows_exported = 0u64;
-                let mut columns_written = false;
-                while let Some(row) = stream.try_next().await? {
-                    if !columns_written {
-                        let cols: Vec<String> =
-                            row.columns().iter().map(|c| c.name().to_string()).collect();
-                        wtr.write_record(&cols)?;
-                        columns_written = true;
-                    }
-                    let record: Vec<String> = (0..row.columns().len())
-                        .map(|i| mysql_row_to_string(&row, i))
-                        .collect();
-                    wtr.write_record(&record)?;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+        }
+        "mysql" => {
+            let pool = manager
+                .get_mysql_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            let mut columns_written = false;
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !columns_written {
+                    let cols: Vec<String> =
+                        row.columns().iter().map(|c| c.name().to_string()).collect();
+                    wtr.write_record(&cols)?;
+                    columns_written = true;
+                }
+                let record: Vec<String> = (0..row.columns().len())
+                    .map(|i| mysql_row_to_string(&row, i))
+                    .collect();
+                wtr.write_record(&record)?;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            "sqlite" => {
-                let pool = manager
-                    .get_sqlite_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                let mut rows_exported = 0u64;
-                let mut columns_written = false;
-                while let Some(row) = stream.try_next().await? {
-                    if !columns_written {
-                        let cols: Vec<String> =
-                            row.columns().iter().map(|c| c.name().to_string()).collect();
-                        wtr.write_record(&cols)?;
-                        columns_written = true;
-                    }
-                    let record: Vec<String> = (0..row.columns().len())
-                        .map(|i| sqlite_row_to_string(&row, i))
-                        .collect();
-                    wtr.write_record(&record)?;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+        }
+        "sqlite" => {
+            let pool = manager
+                .get_sqlite_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            let mut columns_written = false;
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !columns_written {
+                    let cols: Vec<String> =
+                        row.columns().iter().map(|c| c.name().to_string()).collect();
+                    wtr.write_record(&cols)?;
+                    columns_written = true;
+                }
+                let record: Vec<String> = (0..row.columns().len())
+                    .map(|i| sqlite_row_to_string(&row, i))
+                    .collect();
+                wtr.write_record(&record)?;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            _ => return Err(anyhow!("Unsupported database type")),
         }
-        wtr.flush()?;
+        _ => return Err(anyhow!("Unsupported database type")),
     }
-
-    let _ = app_handle.emit(
-        "export-progress",
-        ExportProgress {
-            export_id: export_id.to_string(),
-            current_table: "".to_string(),
-            rows_exported: 0,
-            status: "complete".to_string(),
-            error: None,
-        },
-    );
-    Ok(())
+    wtr.flush()?;
+    emit_table_complete(app_handle, export_id, table, rows_exported, total_rows);
+    Ok(ExportedFile {
+        path: file_path,
+        rows: rows_exported,
+    })
 }
 
-async fn do_export_json(
-    app_handle: AppHandle,
+async fn do_export_json<R: Runtime>(
+    app_handle: AppHandle<R>,
     manager: &crate::core::connection_manager::ConnectionManager,
     connection_id: &Uuid,
     export_id: &str,
     options: &ExportOptions,
-) -> Result<()> {
+    token: &CancellationToken,
+) -> Result<Vec<ExportedFile>> {
     let db_type = {
         if manager
             .get_postgres_pools()
@@ -281,156 +579,209 @@ async fn do_export_json(
     }
     .ok_or_else(|| anyhow!("Connection not found"))?;
 
-    for table in &options.tables {
-        let file_path = if options.tables.len() > 1 {
-            format!("{}_{}.json", options.output_path, table)
-        } else {
-            options.output_path.clone()
-        };
+    let concurrency = export_concurrency(manager, connection_id, db_type, options).await;
+    let files = futures::stream::iter(&options.tables)
+        .map(|table| {
+            export_table_json(
+                &app_handle,
+                manager,
+                connection_id,
+                export_id,
+                options,
+                db_type,
+                table,
+                token,
+            )
+        })
+        .buffer_unordered(concurrency)
+        .try_collect::<Vec<ExportedFile>>()
+        .await?;
 
-        let file = File::create(&file_path)?;
-        let mut writer = BufWriter::new(file);
-        writer.write_all(b"[\n")?;
+    Ok(files)
+}
 
-        let quoted_table = match db_type {
-            "mysql" => format!("`{}`", table.replace("`", "``")),
-            _ => format!("\"{}\"", table.replace("\"", "\"\"")),
-        };
+async fn export_table_json<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    manager: &crate::core::connection_manager::ConnectionManager,
+    connection_id: &Uuid,
+    export_id: &str,
+    options: &ExportOptions,
+    db_type: &str,
+    table: &str,
+    token: &CancellationToken,
+) -> Result<ExportedFile> {
+    let file_path = if options.tables.len() > 1 {
+        format!("{}_{}.json", options.output_path, table)
+    } else {
+        options.output_path.clone()
+    };
 
-        let sql = format!("SELECT * FROM {}", quoted_table);
-        let mut rows_exported = 0u64;
-        let mut first_row = true;
-
-        match db_type {
-            "postgres" => {
-                let pool = manager
-                    .get_postgres_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                while let Some(row) = stream.try_next().await? {
-                    if !first_row {
-                        writer.write_all(b",\n")?;
-                    }
-                    let mut obj = serde_json::Map::new();
-                    for col in row.columns() {
-                        let i = col.ordinal();
-                        obj.insert(col.name().to_string(), postgres_row_to_json(&row, i));
-                    }
-                    serde_json::to_writer(&mut writer, &Value::Object(obj))?;
-                    first_row = false;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+    let file = File::create(&file_path)?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(b"[\n")?;
+
+    let quoted_table = quote_ident(table, db_type);
+
+    let select_list = QueryEngine::export_select_list(
+        manager,
+        connection_id,
+        table,
+        options.columns.as_deref(),
+        db_type,
+    )
+    .await?;
+    let (where_clause, params) = table_where_clause(options, table, db_type);
+    let sql = format!(
+        "SELECT {} FROM {} {}",
+        select_list, quoted_table, where_clause
+    );
+    let total_rows = table_row_total(manager, connection_id, table, options).await?;
+    let mut rows_exported = 0u64;
+    let mut first_row = true;
+    let mut keys: Option<Vec<String>> = None;
+    let null_mode = JsonNullMode::parse(options.json_null_mode.as_deref())?;
+
+    match db_type {
+        "postgres" => {
+            let pool = manager
+                .get_postgres_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !first_row {
+                    writer.write_all(b",\n")?;
+                }
+                let keys = keys.get_or_insert_with(|| json_keys(row.columns()));
+                let mut obj = serde_json::Map::new();
+                for (i, key) in keys.iter().enumerate() {
+                    null_mode.insert(&mut obj, key.clone(), postgres_row_to_json(&row, i));
+                }
+                serde_json::to_writer(&mut writer, &Value::Object(obj))?;
+                first_row = false;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            "mysql" => {
-                let pool = manager
-                    .get_mysql_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                while let Some(row) = stream.try_next().await? {
-                    if !first_row {
-                        writer.write_all(b",\n")?;
-                    }
-                    let mut obj = serde_json::Map::new();
-                    for col in row.columns() {
-                        let i = col.ordinal();
-                        obj.insert(col.name().to_string(), mysql_row_to_json(&row, i));
-                    }
-                    serde_json::to_writer(&mut writer, &Value::Object(obj))?;
-                    first_row = false;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+        }
+        "mysql" => {
+            let pool = manager
+                .get_mysql_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !first_row {
+                    writer.write_all(b",\n")?;
+                }
+                let keys = keys.get_or_insert_with(|| json_keys(row.columns()));
+                let mut obj = serde_json::Map::new();
+                for (i, key) in keys.iter().enumerate() {
+                    null_mode.insert(&mut obj, key.clone(), mysql_row_to_json(&row, i));
+                }
+                serde_json::to_writer(&mut writer, &Value::Object(obj))?;
+                first_row = false;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            "sqlite" => {
-                let pool = manager
-                    .get_sqlite_pools()
-                    .await
-                    .get(connection_id)
-                    .cloned()
-                    .unwrap();
-                let mut stream = sqlx::query(&sql).fetch(&pool);
-                while let Some(row) = stream.try_next().await? {
-                    if !first_row {
-                        writer.write_all(b",\n")?;
-                    }
-                    let mut obj = serde_json::Map::new();
-                    for col in row.columns() {
-                        let i = col.ordinal();
-                        obj.insert(col.name().to_string(), sqlite_row_to_json(&row, i));
-                    }
-                    serde_json::to_writer(&mut writer, &Value::Object(obj))?;
-                    first_row = false;
-                    rows_exported += 1;
-                    if rows_exported % 1000 == 0 {
-                        let _ = app_handle.emit(
-                            "export-progress",
-                            ExportProgress {
-                                export_id: export_id.to_string(),
-                                current_table: table.to_string(),
-                                rows_exported,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        );
-                    }
+        }
+        "sqlite" => {
+            let pool = manager
+                .get_sqlite_pools()
+                .await
+                .get(connection_id)
+                .cloned()
+                .unwrap();
+            let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
+            while let Some(row) = stream.try_next().await? {
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if !first_row {
+                    writer.write_all(b",\n")?;
+                }
+                let keys = keys.get_or_insert_with(|| json_keys(row.columns()));
+                let mut obj = serde_json::Map::new();
+                for (i, key) in keys.iter().enumerate() {
+                    null_mode.insert(&mut obj, key.clone(), sqlite_row_to_json(&row, i));
+                }
+                serde_json::to_writer(&mut writer, &Value::Object(obj))?;
+                first_row = false;
+                rows_exported += 1;
+                if rows_exported % 1000 == 0 {
+                    let _ = app_handle.emit(
+                        "export-progress",
+                        ExportProgress {
+                            export_id: export_id.to_string(),
+                            current_table: table.to_string(),
+                            rows_exported,
+                            total_rows,
+                            percentage: export_percentage(rows_exported, total_rows),
+                            status: "processing".to_string(),
+                            error: None,
+                            manifest_path: None,
+                        },
+                    );
                 }
             }
-            _ => return Err(anyhow!("Unsupported database type")),
         }
-
-        writer.write_all(b"\n]")?;
-        writer.flush()?;
+        _ => return Err(anyhow!("Unsupported database type")),
     }
 
-    let _ = app_handle.emit(
-        "export-progress",
-        ExportProgress {
-            export_id: export_id.to_string(),
-            current_table: "".to_string(),
-            rows_exported: 0,
-            status: "complete".to_string(),
-            error: None,
-        },
-    );
-    Ok(())
+    writer.write_all(b"\n]")?;
+    writer.flush()?;
+    emit_table_complete(app_handle, export_id, table, rows_exported, total_rows);
+    Ok(ExportedFile {
+        path: file_path,
+        rows: rows_exported,
+    })
 }
 
-async fn do_export_sql(
-    app_handle: AppHandle,
+async fn do_export_sql<R: Runtime>(
+    app_handle: AppHandle<R>,
     manager: &crate::core::connection_manager::ConnectionManager,
     connection_id: &Uuid,
     export_id: &str,
     options: &ExportOptions,
-) -> Result<()> {
+    token: &CancellationToken,
+) -> Result<Vec<ExportedFile>> {
     let db_type = {
         if manager
             .get_postgres_pools()
@@ -450,8 +801,23 @@ async fn do_export_sql(
 
     let file = File::create(&options.output_path)?;
     let mut writer = BufWriter::new(file);
+    let mut total_exported = 0u64;
 
     for table in &options.tables {
+        let _ = app_handle.emit(
+            "export-progress",
+            ExportProgress {
+                export_id: export_id.to_string(),
+                current_table: table.to_string(),
+                rows_exported: 0,
+                total_rows: None,
+                percentage: None,
+                status: "processing".to_string(),
+                error: None,
+                manifest_path: None,
+            },
+        );
+
         if options.include_schema {
             let schema = get_create_table_sql(manager, connection_id, table, db_type).await?;
             writer.write_all(schema.as_bytes())?;
@@ -459,11 +825,21 @@ async fn do_export_sql(
         }
 
         if options.include_data {
-            let quoted_table = match db_type {
-                "mysql" => format!("`{}`", table.replace("`", "``")),
-                _ => format!("\"{}\"", table.replace("\"", "\"\"")),
-            };
-            let sql = format!("SELECT * FROM {}", quoted_table);
+            let quoted_table = quote_ident(table, db_type);
+            let select_list = QueryEngine::export_select_list(
+                manager,
+                connection_id,
+                table,
+                options.columns.as_deref(),
+                db_type,
+            )
+            .await?;
+            let (where_clause, params) = table_where_clause(options, table, db_type);
+            let sql = format!(
+                "SELECT {} FROM {} {}",
+                select_list, quoted_table, where_clause
+            );
+            let total_rows = table_row_total(manager, connection_id, table, options).await?;
             let mut rows_exported = 0u64;
 
             match db_type {
@@ -474,8 +850,11 @@ async fn do_export_sql(
                         .get(connection_id)
                         .cloned()
                         .unwrap();
-                    let mut stream = sqlx::query(&sql).fetch(&pool);
+                    let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
                     while let Some(row) = stream.try_next().await? {
+                        if token.is_cancelled() {
+                            return Err(Cancelled.into());
+                        }
                         writer.write_all(postgres_row_to_sql(&row, table).as_bytes())?;
                         rows_exported += 1;
                         if rows_exported % 1000 == 0 {
@@ -485,8 +864,11 @@ async fn do_export_sql(
                                     export_id: export_id.to_string(),
                                     current_table: table.to_string(),
                                     rows_exported,
+                                    total_rows,
+                                    percentage: export_percentage(rows_exported, total_rows),
                                     status: "processing".to_string(),
                                     error: None,
+                                    manifest_path: None,
                                 },
                             );
                         }
@@ -499,8 +881,11 @@ async fn do_export_sql(
                         .get(connection_id)
                         .cloned()
                         .unwrap();
-                    let mut stream = sqlx::query(&sql).fetch(&pool);
+                    let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
                     while let Some(row) = stream.try_next().await? {
+                        if token.is_cancelled() {
+                            return Err(Cancelled.into());
+                        }
                         writer.write_all(mysql_row_to_sql(&row, table).as_bytes())?;
                         rows_exported += 1;
                         if rows_exported % 1000 == 0 {
@@ -510,8 +895,11 @@ async fn do_export_sql(
                                     export_id: export_id.to_string(),
                                     current_table: table.to_string(),
                                     rows_exported,
+                                    total_rows,
+                                    percentage: export_percentage(rows_exported, total_rows),
                                     status: "processing".to_string(),
                                     error: None,
+                                    manifest_path: None,
                                 },
                             );
                         }
@@ -524,8 +912,11 @@ async fn do_export_sql(
                         .get(connection_id)
                         .cloned()
                         .unwrap();
-                    let mut stream = sqlx::query(&sql).fetch(&pool);
+                    let mut stream = bind_json_params!(sqlx::query(&sql), &params).fetch(&pool);
                     while let Some(row) = stream.try_next().await? {
+                        if token.is_cancelled() {
+                            return Err(Cancelled.into());
+                        }
                         writer.write_all(sqlite_row_to_sql(&row, table).as_bytes())?;
                         rows_exported += 1;
                         if rows_exported % 1000 == 0 {
@@ -535,8 +926,11 @@ async fn do_export_sql(
                                     export_id: export_id.to_string(),
                                     current_table: table.to_string(),
                                     rows_exported,
+                                    total_rows,
+                                    percentage: export_percentage(rows_exported, total_rows),
                                     status: "processing".to_string(),
                                     error: None,
+                                    manifest_path: None,
                                 },
                             );
                         }
@@ -545,21 +939,16 @@ async fn do_export_sql(
                 _ => {}
             }
             writer.write_all(b"\n")?;
+            total_exported += rows_exported;
+            emit_table_complete(&app_handle, export_id, table, rows_exported, total_rows);
         }
     }
 
     writer.flush()?;
-    let _ = app_handle.emit(
-        "export-progress",
-        ExportProgress {
-            export_id: export_id.to_string(),
-            current_table: "".to_string(),
-            rows_exported: 0,
-            status: "complete".to_string(),
-            error: None,
-        },
-    );
-    Ok(())
+    Ok(vec![ExportedFile {
+        path: options.output_path.clone(),
+        rows: total_exported,
+    }])
 }
 
 fn postgres_row_to_json(row: &sqlx::postgres::PgRow, i: usize) -> Value {
@@ -568,9 +957,7 @@ fn postgres_row_to_json(row: &sqlx::postgres::PgRow, i: usize) -> Value {
     } else if let Ok(Some(n)) = row.try_get::<Option<i64>, _>(i) {
         Value::Number(n.into())
     } else if let Ok(Some(f)) = row.try_get::<Option<f64>, _>(i) {
-        serde_json::Number::from_f64(f)
-            .map(Value::Number)
-            .unwrap_or(Value::Null)
+        float_to_json(f)
     } else if let Ok(Some(b)) = row.try_get::<Option<bool>, _>(i) {
         Value::Bool(b)
     } else if let Ok(Some(dt)) = row.try_get::<Option<chrono::NaiveDateTime>, _>(i) {
@@ -594,9 +981,7 @@ fn mysql_row_to_json(row: &sqlx::mysql::MySqlRow, i: usize) -> Value {
     } else if let Ok(Some(n)) = row.try_get::<Option<i64>, _>(i) {
         Value::Number(n.into())
     } else if let Ok(Some(f)) = row.try_get::<Option<f64>, _>(i) {
-        serde_json::Number::from_f64(f)
-            .map(Value::Number)
-            .unwrap_or(Value::Null)
+        float_to_json(f)
     } else if let Ok(Some(b)) = row.try_get::<Option<bool>, _>(i) {
         Value::Bool(b)
     } else if let Ok(Some(dt)) = row.try_get::<Option<chrono::NaiveDateTime>, _>(i) {
@@ -616,9 +1001,7 @@ fn sqlite_row_to_json(row: &sqlx::sqlite::SqliteRow, i: usize) -> Value {
     } else if let Ok(Some(n)) = row.try_get::<Option<i64>, _>(i) {
         Value::Number(n.into())
     } else if let Ok(Some(f)) = row.try_get::<Option<f64>, _>(i) {
-        serde_json::Number::from_f64(f)
-            .map(Value::Number)
-            .unwrap_or(Value::Null)
+        float_to_json(f)
     } else if let Ok(Some(b)) = row.try_get::<Option<bool>, _>(i) {
         Value::Bool(b)
     } else if let Ok(Some(dt)) = row.try_get::<Option<chrono::NaiveDateTime>, _>(i) {
@@ -693,11 +1076,11 @@ fn sqlite_row_to_string(row: &sqlx::sqlite::SqliteRow, i: usize) -> String {
 }
 
 fn postgres_row_to_sql(row: &sqlx::postgres::PgRow, table: &str) -> String {
-    let quoted_table = format!("\"{}\"", table.replace("\"", "\"\""));
+    let quoted_table = quote_ident(table, "postgres");
     let col_names: Vec<String> = row
         .columns()
         .iter()
-        .map(|c| format!("\"{}\"", c.name().replace("\"", "\"\"")))
+        .map(|c| quote_ident(c.name(), "postgres"))
         .collect();
     let values: Vec<String> = (0..row.columns().len())
         .map(|i| {
@@ -734,11 +1117,11 @@ fn postgres_row_to_sql(row: &sqlx::postgres::PgRow, table: &str) -> String {
 }
 
 fn mysql_row_to_sql(row: &sqlx::mysql::MySqlRow, table: &str) -> String {
-    let quoted_table = format!("`{}`", table.replace("`", "``"));
+    let quoted_table = quote_ident(table, "mysql");
     let col_names: Vec<String> = row
         .columns()
         .iter()
-        .map(|c| format!("`{}`", c.name().replace("`", "``")))
+        .map(|c| quote_ident(c.name(), "mysql"))
         .collect();
     let values: Vec<String> = (0..row.columns().len())
         .map(|i| {
@@ -770,11 +1153,11 @@ fn mysql_row_to_sql(row: &sqlx::mysql::MySqlRow, table: &str) -> String {
 }
 
 fn sqlite_row_to_sql(row: &sqlx::sqlite::SqliteRow, table: &str) -> String {
-    let quoted_table = format!("\"{}\"", table.replace("\"", "\"\""));
+    let quoted_table = quote_ident(table, "sqlite");
     let col_names: Vec<String> = row
         .columns()
         .iter()
-        .map(|c| format!("\"{}\"", c.name().replace("\"", "\"\"")))
+        .map(|c| quote_ident(c.name(), "sqlite"))
         .collect();
     let values: Vec<String> = (0..row.columns().len())
         .map(|i| {
@@ -814,8 +1197,8 @@ async fn get_create_table_sql(
             let pools = manager.get_mysql_pools().await;
             let pool = pools.get(connection_id).cloned().unwrap();
             let row = sqlx::query(&format!(
-                "SHOW CREATE TABLE `{}`",
-                table_name.replace("`", "``")
+                "SHOW CREATE TABLE {}",
+                quote_ident(table_name, "mysql")
             ))
             .fetch_one(&pool)
             .await?;
@@ -837,3 +1220,366 @@ async fn get_create_table_sql(
         _ => Err(anyhow!("Unsupported database type")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::connection_manager::ConnectionManager;
+    use crate::core::test_support;
+    use std::path::{Path, PathBuf};
+
+    fn options(output_path: &Path, format: &str, tables: &[&str]) -> ExportOptions {
+        ExportOptions {
+            tables: tables.iter().map(|t| t.to_string()).collect(),
+            output_path: output_path.to_string_lossy().into_owned(),
+            format: format.to_string(),
+            include_schema: false,
+            include_data: true,
+            delimiter: None,
+            excel_bom: false,
+            skip_row_count: false,
+            dump_all: false,
+            concurrency: None,
+            columns: None,
+            filters: HashMap::new(),
+            json_null_mode: None,
+            csv_quoting: CsvQuoting::default(),
+        }
+    }
+
+    fn output_path() -> PathBuf {
+        std::env::temp_dir().join(format!("sqlmate-export-{}", Uuid::new_v4()))
+    }
+
+    /// Creates `table` holding the integers 1..=rows in column `n`
+    async fn numbered_table(manager: &ConnectionManager, id: &Uuid, table: &str, rows: u32) {
+        test_support::run(
+            manager,
+            id,
+            &format!(
+                "DROP TABLE IF EXISTS {0};
+                 CREATE TABLE {0} AS WITH RECURSIVE c(n) AS
+                 (SELECT 1 UNION ALL SELECT n + 1 FROM c WHERE n < {1}) SELECT n FROM c;",
+                table, rows
+            ),
+        )
+        .await;
+    }
+
+    /// Exports four tables at once and checks each file and its progress events
+    async fn exports_each_table_to_its_own_file(manager: &ConnectionManager, id: &Uuid) {
+        let tables = [
+            ("export_a", 1500),
+            ("export_b", 10),
+            ("export_c", 2500),
+            ("export_d", 1),
+        ];
+        for (table, rows) in tables {
+            numbered_table(manager, id, table, rows).await;
+        }
+        let output = output_path();
+        let app = test_support::app();
+        let events = test_support::events(&app, "export-progress");
+        let names: Vec<&str> = tables.iter().map(|(t, _)| *t).collect();
+        let options = ExportOptions {
+            concurrency: Some(4),
+            ..options(&output, "csv", &names)
+        };
+
+        let token = CancellationToken::new();
+        let files = do_export_csv(
+            app.handle().clone(),
+            manager,
+            id,
+            "export",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(files.len(), tables.len());
+        let events = events.lock().unwrap();
+        for (table, rows) in tables {
+            let path = format!("{}_{}.csv", output.display(), table);
+            let file = files.iter().find(|f| f.path == path).unwrap();
+            assert_eq!(file.rows, rows as u64);
+            let contents = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(contents.lines().next(), Some("n"));
+            assert_eq!(contents.lines().count(), rows as usize + 1);
+            std::fs::remove_file(&path).unwrap();
+
+            // Every event names the table whose rows it counts
+            let table_events: Vec<_> = events
+                .iter()
+                .filter(|e| e["current_table"] == table)
+                .collect();
+            assert!(table_events.iter().all(|e| e["total_rows"] == rows));
+            assert_eq!(table_events.last().unwrap()["rows_exported"], rows);
+        }
+    }
+
+    #[tokio::test]
+    async fn exports_tables_to_their_own_files() {
+        for (manager, id) in test_support::backends().await {
+            exports_each_table_to_its_own_file(&manager, &id).await;
+            test_support::run(
+                &manager,
+                &id,
+                "DROP TABLE export_a; DROP TABLE export_b; DROP TABLE export_c; DROP TABLE export_d;",
+            )
+            .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn dump_all_exports_schema_and_data_of_every_table() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER, title TEXT);
+             CREATE TABLE reviews (book_id INTEGER, stars INTEGER);
+             INSERT INTO authors VALUES (1, 'Le Guin');
+             INSERT INTO books VALUES (1, 1, 'The Dispossessed');
+             INSERT INTO reviews VALUES (1, 5);",
+        )
+        .await;
+        let output = output_path();
+        let mut options = ExportOptions {
+            include_schema: true,
+            dump_all: true,
+            ..options(&output, "sql", &[])
+        };
+
+        expand_dump_all(&manager, &id, &mut options).await.unwrap();
+        let app = test_support::app();
+        let token = CancellationToken::new();
+        do_export_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let dump = std::fs::read_to_string(&output).unwrap();
+        for (table, values) in [
+            ("authors", "(1, 'Le Guin')"),
+            ("books", "(1, 1, 'The Dispossessed')"),
+            ("reviews", "(1, 5)"),
+        ] {
+            assert!(
+                dump.contains(&format!("CREATE TABLE {} (", table)),
+                "{}",
+                dump
+            );
+            assert!(
+                dump.contains(&format!("INSERT INTO \"{}\"", table)) && dump.contains(values),
+                "{}",
+                dump
+            );
+        }
+        std::fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn export_percentage_needs_a_nonzero_total() {
+        assert_eq!(export_percentage(250, Some(1000)), Some(25.0));
+        assert_eq!(export_percentage(1200, Some(1000)), Some(100.0));
+        assert_eq!(export_percentage(10, Some(0)), None);
+        assert_eq!(export_percentage(10, None), None);
+    }
+
+    #[tokio::test]
+    async fn table_progress_follows_the_row_count_up_to_100_percent() {
+        let (manager, id) = test_support::sqlite().await;
+        numbered_table(&manager, &id, "events", 2500).await;
+        let output = output_path();
+        let app = test_support::app();
+        let events = test_support::events(&app, "export-progress");
+
+        let options = options(&output, "json", &["events"]);
+        let token = CancellationToken::new();
+        do_export_json(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "export",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let events = events.lock().unwrap();
+        let progress: Vec<(u64, f64)> = events
+            .iter()
+            .map(|e| {
+                assert_eq!(e["total_rows"], 2500);
+                (
+                    e["rows_exported"].as_u64().unwrap(),
+                    e["percentage"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(progress, [(1000, 40.0), (2000, 80.0), (2500, 100.0)]);
+        std::fs::remove_file(output).unwrap();
+    }
+
+    #[tokio::test]
+    async fn exports_only_the_chosen_columns_in_order() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE people (id INTEGER, name TEXT, email TEXT, age INTEGER);
+             INSERT INTO people VALUES (1, 'Ada', 'ada@example.com', 36), (2, 'Bob', 'bob@example.com', 41);",
+        )
+        .await;
+        let output = output_path();
+        let app = test_support::app();
+        let token = CancellationToken::new();
+
+        let options = ExportOptions {
+            columns: Some(vec!["email".to_string(), "id".to_string()]),
+            ..options(&output, "csv", &["people"])
+        };
+        do_export_csv(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "export",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+        let path = format!("{}_people.csv", output.display());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "email,id\nada@example.com,1\nbob@example.com,2\n"
+        );
+
+        let typo = ExportOptions {
+            columns: Some(vec!["emial".to_string()]),
+            ..options
+        };
+        let err = do_export_csv(app.handle().clone(), &manager, &id, "export", &typo, &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unknown column 'emial' in table 'people'");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn filters_limit_the_rows_of_their_table_only() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE orders (id INTEGER, status TEXT);
+             INSERT INTO orders VALUES (1, 'open'), (2, 'shipped'), (3, 'open'), (4, 'it''s open');
+             CREATE TABLE notes (id INTEGER);
+             INSERT INTO notes VALUES (1), (2);",
+        )
+        .await;
+        let output = output_path();
+        let app = test_support::app();
+        let token = CancellationToken::new();
+        let open = FilterConfig {
+            id: "status".to_string(),
+            column: "status".to_string(),
+            operator: "=".to_string(),
+            value: "open".to_string(),
+            enabled: true,
+            case_insensitive: false,
+            json_path: None,
+        };
+
+        let options = ExportOptions {
+            filters: HashMap::from([("orders".to_string(), vec![open])]),
+            ..options(&output, "csv", &["orders", "notes"])
+        };
+        let files = do_export_csv(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "export",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let rows = |table: &str| {
+            files
+                .iter()
+                .find(|f| f.path.ends_with(&format!("_{}.csv", table)))
+                .unwrap()
+                .rows
+        };
+        assert_eq!(rows("orders"), 2);
+        assert_eq!(rows("notes"), 2);
+        let orders_path = format!("{}_orders.csv", output.display());
+        assert_eq!(
+            std::fs::read_to_string(&orders_path).unwrap(),
+            "id,status\n1,open\n3,open\n"
+        );
+        for file in &files {
+            std::fs::remove_file(&file.path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn the_manifest_lists_every_file_with_its_checksum() {
+        let (manager, id) = test_support::sqlite().await;
+        numbered_table(&manager, &id, "single", 1).await;
+        numbered_table(&manager, &id, "many", 1200).await;
+        let output = output_path();
+        let app = test_support::app();
+        let token = CancellationToken::new();
+
+        let options = options(&output, "csv", &["single", "many"]);
+        let files = do_export_csv(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "export",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+        let manifest_path = write_export_manifest("export", &options, &files).unwrap();
+
+        assert_eq!(manifest_path, format!("{}.manifest.json", output.display()));
+        let manifest: Value =
+            serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        assert_eq!(manifest["export_id"], "export");
+        let listed = manifest["files"].as_array().unwrap();
+        assert_eq!(listed.len(), 2);
+        for file in &files {
+            let entry = listed
+                .iter()
+                .find(|e| e["path"] == file.path.as_str())
+                .unwrap();
+            let contents = std::fs::read(&file.path).unwrap();
+            assert_eq!(entry["rows"], file.rows);
+            assert_eq!(entry["bytes"], contents.len() as u64);
+            assert_eq!(entry["sha256"], format!("{:x}", Sha256::digest(&contents)));
+            std::fs::remove_file(&file.path).unwrap();
+        }
+        // "n\n1\n"
+        let single = listed.iter().find(|e| e["rows"] == 1).unwrap();
+        assert_eq!(
+            single["sha256"],
+            "6ff2d84956e507dade83da732a88aa6fa02712d03af8c515633f674de9fd42bd"
+        );
+        std::fs::remove_file(manifest_path).unwrap();
+    }
+}
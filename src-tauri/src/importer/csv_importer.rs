@@ -1,13 +1,21 @@
-use crate::core::AppState;
+use crate::core::{AppState, Cancelled};
 use anyhow::{anyhow, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use csv::ReaderBuilder;
 use serde::Deserialize;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgCopyIn, PgDatabaseError, PgPoolCopyExt};
+use sqlx::Postgres;
 use std::collections::HashMap;
-use std::fs::File;
-use tauri::{AppHandle, Emitter, State};
+use std::io::Read;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::importer::{ImportProgress, InsertTarget};
+use crate::importer::{
+    open_input, open_input_tracked, ImportProgress, ImportTransaction, InsertTarget,
+};
+use crate::utils::{quote_ident, validate_identifier, CsvQuoting};
 
 #[derive(Deserialize, Debug)]
 pub struct CsvImportOptions {
@@ -19,6 +27,81 @@ pub struct CsvImportOptions {
     pub delimiter: char,
     pub skip_rows: u32,
     pub batch_size: usize,
+    #[serde(default)]
+    pub allow_ragged_rows: bool, // lenient mode: pad short rows instead of rejecting them
+    #[serde(default)]
+    pub dry_run: bool, // insert everything in one transaction, then roll it back
+    #[serde(default)]
+    pub binary_columns: HashMap<String, BinaryEncoding>, // db_column -> encoding of its CSV text
+    #[serde(default)]
+    pub quoting: CsvQuoting,
+    #[serde(default)]
+    pub load_data_local: bool, // MySQL: prefer LOAD DATA LOCAL INFILE where the server allows it
+}
+
+/// How a binary (bytea/blob) column is written in the CSV
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryEncoding {
+    Hex,
+    Base64,
+}
+
+impl BinaryEncoding {
+    fn decode(&self, value: &str) -> Result<Vec<u8>> {
+        let value = value.trim();
+        match self {
+            Self::Hex => {
+                // Accept the `0x` prefix our exports use and Postgres' `\x` form
+                let digits = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("\\x"))
+                    .unwrap_or(value);
+                digits
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|pair| {
+                        let high = (pair[0] as char).to_digit(16);
+                        let low = pair.get(1).and_then(|&b| (b as char).to_digit(16));
+                        match (high, low) {
+                            (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+                            _ => Err(anyhow!("invalid hex value '{}'", value)),
+                        }
+                    })
+                    .collect()
+            }
+            Self::Base64 => BASE64_STANDARD
+                .decode(value)
+                .map_err(|e| anyhow!("invalid base64 value: {}", e)),
+        }
+    }
+}
+
+/// The encoding of each resolved column, None for columns bound as text
+fn column_encodings(options: &CsvImportOptions, columns: &[String]) -> Vec<Option<BinaryEncoding>> {
+    columns
+        .iter()
+        .map(|c| options.binary_columns.get(c).copied())
+        .collect()
+}
+
+/// Binds a record's mapped fields, decoding the ones marked as binary into bytes
+macro_rules! bind_record {
+    ($query:expr, $record:expr, $csv_indices:expr, $encodings:expr, $columns:expr, $row_number:expr) => {{
+        let mut query = $query;
+        for ((&idx, encoding), column) in $csv_indices.iter().zip($encodings).zip($columns) {
+            let val = $record.get(idx).unwrap_or("");
+            query = match encoding {
+                Some(encoding) => query.bind(
+                    encoding
+                        .decode(val)
+                        .map_err(|e| anyhow!("Row {}, column {}: {}", $row_number, column, e))?,
+                ),
+                None => query.bind(val),
+            };
+        }
+        query
+    }};
 }
 
 #[tauri::command]
@@ -27,11 +110,16 @@ pub async fn preview_csv(
     delimiter: char,
     has_header: bool,
     skip_rows: u32,
+    quoting: Option<CsvQuoting>,
 ) -> Result<Vec<Vec<String>>, String> {
-    let file = File::open(&file_path).map_err(|e| e.to_string())?;
-    let mut reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .has_headers(has_header)
+    let file = open_input(&file_path).map_err(|e| e.to_string())?;
+    let mut reader = quoting
+        .unwrap_or_default()
+        .configure_reader(
+            ReaderBuilder::new()
+                .delimiter(delimiter as u8)
+                .has_headers(has_header),
+        )
         .from_reader(file);
 
     let mut preview = Vec::new();
@@ -62,6 +150,13 @@ pub async fn import_csv(
     options: CsvImportOptions,
 ) -> Result<(), String> {
     let manager = state.connection_manager.clone();
+    let token = CancellationToken::new();
+    state
+        .active_imports
+        .lock()
+        .await
+        .insert(import_id.clone(), token.clone());
+    let active_imports = state.active_imports.clone();
 
     tokio::spawn(async move {
         let result = do_import_csv(
@@ -70,10 +165,12 @@ pub async fn import_csv(
             &connection_id,
             &import_id,
             &options,
+            &token,
         )
         .await;
 
         if let Err(e) = result {
+            let cancelled = e.downcast_ref::<Cancelled>().is_some();
             let _ = app_handle.emit(
                 "import-progress",
                 ImportProgress {
@@ -81,28 +178,40 @@ pub async fn import_csv(
                     rows_processed: 0,
                     total_rows: None,
                     percentage: None,
-                    status: "error".to_string(),
-                    error: Some(e.to_string()),
+                    status: if cancelled { "cancelled" } else { "error" }.to_string(),
+                    error: if cancelled { None } else { Some(e.to_string()) },
+                    bytes_processed: None,
+                    total_bytes: None,
+                    notice: None,
                 },
             );
         }
+
+        active_imports.lock().await.remove(&import_id);
     });
 
     Ok(())
 }
 
-async fn do_import_csv(
-    app_handle: AppHandle,
+async fn do_import_csv<R: Runtime>(
+    app_handle: AppHandle<R>,
     manager: &crate::core::connection_manager::ConnectionManager,
     connection_id: &Uuid,
     import_id: &str,
     options: &CsvImportOptions,
+    token: &CancellationToken,
 ) -> Result<()> {
     // 1. Open CSV file
-    let file = File::open(&options.file_path)?;
-    let mut reader = ReaderBuilder::new()
-        .delimiter(options.delimiter as u8)
-        .has_headers(options.has_header)
+    let (file, byte_progress) = open_input_tracked(&options.file_path)?;
+    // Arity is validated below so the error can name the offending line
+    let mut reader = options
+        .quoting
+        .configure_reader(
+            ReaderBuilder::new()
+                .delimiter(options.delimiter as u8)
+                .has_headers(options.has_header)
+                .flexible(true),
+        )
         .from_reader(file);
 
     // 2. Detect DB type for proper quoting
@@ -123,24 +232,7 @@ async fn do_import_csv(
     }
     .ok_or_else(|| anyhow!("Connection not found"))?;
 
-    // 3. Create table if missing
-    if options.create_table_if_missing {
-        create_table_if_not_exists(
-            manager,
-            connection_id,
-            &options.table_name,
-            &mut reader,
-            options.has_header,
-            &options.column_mapping,
-        )
-        .await?;
-    }
-
-    // 4. Prepare batch insert logic
-    let mut batch = Vec::new();
-    let mut rows_processed = 0u64;
-
-    // Get connection pool
+    // 3. Get connection pool
     let pool_guard = match db_type {
         "postgres" => {
             let pools = manager.get_postgres_pools().await;
@@ -166,26 +258,105 @@ async fn do_import_csv(
         _ => return Err(anyhow!("Unsupported database type")),
     };
 
+    // A dry run applies everything in one transaction and rolls it back at the end
+    if options.dry_run && options.create_table_if_missing && db_type == "mysql" {
+        return Err(anyhow!(
+            "Dry runs cannot create tables on MySQL because DDL commits immediately"
+        ));
+    }
+    let mut dry_run_tx = if options.dry_run {
+        Some(ImportTransaction::begin(&pool_guard).await?)
+    } else {
+        None
+    };
+
+    // 4. Create table if missing
+    if options.create_table_if_missing {
+        match dry_run_tx.as_mut() {
+            Some(tx) => create_table_if_not_exists(tx, options, &mut reader).await?,
+            None => {
+                let mut tx = ImportTransaction::begin(&pool_guard).await?;
+                create_table_if_not_exists(&mut tx, options, &mut reader).await?;
+                tx.commit().await?;
+            }
+        }
+    }
+
+    // 5. Prepare batch insert logic
+    let mut batch = Vec::new();
+    let mut rows_processed = 0u64;
+
     let headers = if options.has_header {
         reader.headers()?.clone()
     } else {
         csv::StringRecord::new()
     };
+    let mut expected_fields = if options.has_header {
+        Some(headers.len())
+    } else {
+        None
+    };
+
+    // LOAD DATA LOCAL INFILE also needs a client that answers the server's file request,
+    // which the sqlx MySQL driver doesn't, so MySQL always imports with multi-row INSERTs
+    // and the progress events say why
+    let notice = match &pool_guard {
+        InsertTarget::MySql(pool) if options.load_data_local => {
+            Some(load_data_fallback_notice(pool).await?)
+        }
+        _ => None,
+    };
+
+    // Postgres streams rows through COPY, which is far faster than batched INSERTs.
+    // Dry runs stay on INSERTs so errors surface per row inside the rolled-back transaction.
+    let mut copy_sink = match &pool_guard {
+        InsertTarget::Postgres(pool) if !options.dry_run => {
+            Some(CopySink::open(pool, options, &headers).await?)
+        }
+        _ => None,
+    };
 
     for result in reader.records() {
+        if token.is_cancelled() {
+            // Aborting discards every row sent so far
+            if let Some(sink) = copy_sink.take() {
+                let _ = sink.copy_in.abort("Import cancelled").await;
+            }
+            return Err(Cancelled.into());
+        }
         let record = result?;
+
+        let expected = *expected_fields.get_or_insert(record.len());
+        if !options.allow_ragged_rows && record.len() != expected {
+            let line = record.position().map(|p| p.line()).unwrap_or(0);
+            return Err(anyhow!(
+                "Line {}: expected {} fields but found {}",
+                line,
+                expected,
+                record.len()
+            ));
+        }
         batch.push(record);
 
         if batch.len() >= options.batch_size {
-            insert_batch(
-                &pool_guard,
-                &options.table_name,
-                &batch,
-                &options.column_mapping,
-                &headers,
-                db_type,
-            )
-            .await?;
+            match copy_sink.as_mut() {
+                Some(sink) => sink
+                    .send(&batch, rows_processed)
+                    .await
+                    .map_err(|e| copy_error(options, e))?,
+                None => {
+                    write_batch(
+                        &pool_guard,
+                        dry_run_tx.as_mut(),
+                        options,
+                        &batch,
+                        &headers,
+                        rows_processed,
+                        token,
+                    )
+                    .await?
+                }
+            }
             rows_processed += batch.len() as u64;
 
             app_handle.emit(
@@ -194,9 +365,13 @@ async fn do_import_csv(
                     import_id: import_id.to_string(),
                     rows_processed,
                     total_rows: None,
-                    percentage: None,
+                    // The row total is unknown, so progress follows the bytes read
+                    percentage: byte_progress.percentage(),
                     status: "processing".to_string(),
                     error: None,
+                    bytes_processed: Some(byte_progress.bytes_processed()),
+                    total_bytes: Some(byte_progress.total_bytes()),
+                    notice: notice.clone(),
                 },
             )?;
 
@@ -205,18 +380,39 @@ async fn do_import_csv(
     }
 
     if !batch.is_empty() {
-        insert_batch(
-            &pool_guard,
-            &options.table_name,
-            &batch,
-            &options.column_mapping,
-            &headers,
-            db_type,
-        )
-        .await?;
+        match copy_sink.as_mut() {
+            Some(sink) => sink
+                .send(&batch, rows_processed)
+                .await
+                .map_err(|e| copy_error(options, e))?,
+            None => {
+                write_batch(
+                    &pool_guard,
+                    dry_run_tx.as_mut(),
+                    options,
+                    &batch,
+                    &headers,
+                    rows_processed,
+                    token,
+                )
+                .await?
+            }
+        }
         rows_processed += batch.len() as u64;
     }
 
+    // Nothing is visible until COPY completes; a bad row fails the whole import
+    if let Some(sink) = copy_sink {
+        sink.copy_in
+            .finish()
+            .await
+            .map_err(|e| copy_error(options, e.into()))?;
+    }
+
+    if let Some(tx) = dry_run_tx {
+        tx.rollback().await?;
+    }
+
     app_handle.emit(
         "import-progress",
         ImportProgress {
@@ -224,8 +420,16 @@ async fn do_import_csv(
             rows_processed,
             total_rows: Some(rows_processed),
             percentage: Some(100.0),
-            status: "complete".to_string(),
+            status: if options.dry_run {
+                "validated"
+            } else {
+                "complete"
+            }
+            .to_string(),
             error: None,
+            bytes_processed: Some(byte_progress.total_bytes()),
+            total_bytes: Some(byte_progress.total_bytes()),
+            notice,
         },
     )?;
 
@@ -234,19 +438,105 @@ async fn do_import_csv(
 
 // InsertTarget moved to importer/mod.rs
 
-async fn insert_batch(
-    target: &InsertTarget,
-    table_name: &str,
-    batch: &[csv::StringRecord],
-    mapping: &HashMap<String, String>,
-    headers: &csv::StringRecord,
-    db_type: &str,
-) -> Result<()> {
-    if batch.is_empty() {
-        return Ok(());
+/// MySQL rejects prepared statements with more than 65535 placeholders
+const MYSQL_MAX_PLACEHOLDERS: usize = 65_535;
+/// Keeps a single multi-row INSERT well below the default max_allowed_packet
+const MYSQL_ROWS_PER_INSERT: usize = 1_000;
+
+/// Explains why an import that asked for LOAD DATA LOCAL INFILE uses multi-row INSERTs
+async fn load_data_fallback_notice(pool: &sqlx::MySqlPool) -> Result<String> {
+    let local_infile: i64 = sqlx::query_scalar("SELECT CAST(@@GLOBAL.local_infile AS SIGNED)")
+        .fetch_one(pool)
+        .await?;
+    Ok(if local_infile == 0 {
+        "LOAD DATA LOCAL INFILE is unavailable because the server has local_infile disabled; \
+         imported with multi-row INSERTs instead. Enable it with SET GLOBAL local_infile = 1 \
+         (or start mysqld with --local-infile=1)."
+            .to_string()
+    } else {
+        "The server allows LOAD DATA LOCAL INFILE, but the MySQL driver cannot send local \
+         files; imported with multi-row INSERTs instead."
+            .to_string()
+    })
+}
+
+/// Open `COPY ... FROM STDIN` stream fed with the mapped CSV columns
+struct CopySink {
+    copy_in: PgCopyIn<PoolConnection<Postgres>>,
+    columns: Vec<String>,
+    csv_indices: Vec<usize>,
+    encodings: Vec<Option<BinaryEncoding>>,
+}
+
+impl CopySink {
+    async fn open(
+        pool: &sqlx::PgPool,
+        options: &CsvImportOptions,
+        headers: &csv::StringRecord,
+    ) -> Result<Self> {
+        let table_name = options.table_name.as_str();
+        let (columns, csv_indices) = resolve_columns(&options.column_mapping, headers)?;
+        let encodings = column_encodings(options, &columns);
+        let quoted_columns: Vec<String> =
+            columns.iter().map(|c| quote_ident(c, "postgres")).collect();
+        let sql = format!(
+            "COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+            quote_ident(table_name, "postgres"),
+            quoted_columns.join(", ")
+        );
+        let copy_in = pool.copy_in_raw(&sql).await?;
+        Ok(Self {
+            copy_in,
+            columns,
+            csv_indices,
+            encodings,
+        })
     }
 
-    // Identify columns to insert
+    async fn send(&mut self, batch: &[csv::StringRecord], row_offset: u64) -> Result<()> {
+        // Quote every field so empty values stay empty strings, as with the INSERT path
+        let mut writer = csv::WriterBuilder::new()
+            .quote_style(csv::QuoteStyle::Always)
+            .from_writer(Vec::new());
+        for (row, record) in batch.iter().enumerate() {
+            let mut fields = Vec::with_capacity(self.csv_indices.len());
+            let columns = self
+                .csv_indices
+                .iter()
+                .zip(&self.encodings)
+                .zip(&self.columns);
+            for ((&i, encoding), column) in columns {
+                let val = record.get(i).unwrap_or("");
+                fields.push(match encoding {
+                    // COPY reads bytea from its `\x` hex text form
+                    Some(encoding) => {
+                        let bytes = encoding.decode(val).map_err(|e| {
+                            anyhow!(
+                                "Row {}, column {}: {}",
+                                row_offset + row as u64 + 1,
+                                column,
+                                e
+                            )
+                        })?;
+                        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                        format!("\\x{}", hex)
+                    }
+                    None => val.to_string(),
+                });
+            }
+            writer.write_record(&fields)?;
+        }
+        let data = writer.into_inner().map_err(|e| anyhow!(e.to_string()))?;
+        self.copy_in.send(data).await?;
+        Ok(())
+    }
+}
+
+/// Resolves target columns and their CSV field positions from the mapping or headers
+fn resolve_columns(
+    mapping: &HashMap<String, String>,
+    headers: &csv::StringRecord,
+) -> Result<(Vec<String>, Vec<usize>)> {
     let mut columns = Vec::new();
     let mut csv_indices = Vec::new();
 
@@ -274,19 +564,113 @@ async fn insert_batch(
     if columns.is_empty() {
         return Err(anyhow!("No valid columns found for mapping"));
     }
+    Ok((columns, csv_indices))
+}
+
+/// Describes a failed insert with its 1-based data row, source line and raw values
+fn row_error(
+    row_number: u64,
+    record: &csv::StringRecord,
+    err: impl std::fmt::Display,
+) -> anyhow::Error {
+    let line = record.position().map(|p| p.line()).unwrap_or(0);
+    anyhow!(
+        "Row {} (line {}) failed to import: {}. Values: [{}]",
+        row_number,
+        line,
+        err,
+        record.iter().collect::<Vec<_>>().join(", ")
+    )
+}
 
-    let quoted_table = match db_type {
-        "mysql" => format!("`{}`", table_name.replace("`", "``")),
-        _ => format!("\"{}\"", table_name.replace("\"", "\"\"")),
+/// Turns a failed COPY into the row-level error the INSERT path reports. Postgres names the
+/// failing data line in the error context (`COPY t, line 42, column c: ...`); COPY gets one
+/// line per record, so that is the row number, and its values are read back from the file.
+fn copy_error(options: &CsvImportOptions, err: anyhow::Error) -> anyhow::Error {
+    let Some(db_err) = err.downcast_ref::<sqlx::Error>() else {
+        return err;
     };
+    let row_number = db_err
+        .as_database_error()
+        .and_then(|e| e.try_downcast_ref::<PgDatabaseError>())
+        .and_then(|e| e.r#where())
+        .and_then(copy_error_line);
+    let record = row_number.and_then(|n| read_record(options, n).ok().flatten());
+    match (row_number, record) {
+        (Some(row_number), Some(record)) => row_error(row_number, &record, db_err),
+        _ => anyhow!("COPY into {} failed: {}", options.table_name, db_err),
+    }
+}
 
-    let quoted_columns: Vec<String> = columns
-        .iter()
-        .map(|c| match db_type {
-            "mysql" => format!("`{}`", c.replace("`", "``")),
-            _ => format!("\"{}\"", c.replace("\"", "\"\"")),
-        })
-        .collect();
+/// Parses the line number out of a COPY error context such as `COPY t, line 42, column c`
+fn copy_error_line(context: &str) -> Option<u64> {
+    let rest = context.strip_prefix("COPY ")?;
+    rest.split(", line ")
+        .nth(1)?
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Re-reads the CSV to fetch the `row_number`th (1-based) data record, as the import saw it
+fn read_record(options: &CsvImportOptions, row_number: u64) -> Result<Option<csv::StringRecord>> {
+    let file = open_input(&options.file_path)?;
+    let mut reader = options
+        .quoting
+        .configure_reader(
+            ReaderBuilder::new()
+                .delimiter(options.delimiter as u8)
+                .has_headers(options.has_header)
+                .flexible(true),
+        )
+        .from_reader(file);
+    let index = row_number.checked_sub(1).unwrap_or_default() as usize;
+    Ok(reader.records().nth(index).transpose()?)
+}
+
+/// Inserts a batch inside the dry run's transaction, or in its own committed one
+async fn write_batch(
+    target: &InsertTarget,
+    dry_run_tx: Option<&mut ImportTransaction>,
+    options: &CsvImportOptions,
+    batch: &[csv::StringRecord],
+    headers: &csv::StringRecord,
+    row_offset: u64,
+    token: &CancellationToken,
+) -> Result<()> {
+    match dry_run_tx {
+        Some(tx) => insert_batch(tx, options, batch, headers, row_offset, token).await,
+        None => {
+            let mut tx = ImportTransaction::begin(target).await?;
+            insert_batch(&mut tx, options, batch, headers, row_offset, token).await?;
+            tx.commit().await?;
+            Ok(())
+        }
+    }
+}
+
+/// Inserts one batch through `tx`; the caller decides whether to commit or roll back
+async fn insert_batch(
+    tx: &mut ImportTransaction,
+    options: &CsvImportOptions,
+    batch: &[csv::StringRecord],
+    headers: &csv::StringRecord,
+    row_offset: u64,
+    token: &CancellationToken,
+) -> Result<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    // Identify columns to insert
+    let (columns, csv_indices) = resolve_columns(&options.column_mapping, headers)?;
+    let encodings = column_encodings(options, &columns);
+
+    let db_type = tx.db_type();
+    let quoted_table = quote_ident(&options.table_name, db_type);
+
+    let quoted_columns: Vec<String> = columns.iter().map(|c| quote_ident(c, db_type)).collect();
 
     let placeholders: Vec<String> = (0..columns.len())
         .map(|i| match db_type {
@@ -302,42 +686,98 @@ async fn insert_batch(
         placeholders.join(", ")
     );
 
-    match target {
-        InsertTarget::Postgres(pool) => {
-            let mut tx = pool.begin().await?;
-            for record in batch {
-                let mut query = sqlx::query(&sql);
-                for &idx in &csv_indices {
-                    let val = record.get(idx).unwrap_or("");
-                    query = query.bind(val);
+    match tx {
+        ImportTransaction::Postgres(tx) => {
+            for (i, record) in batch.iter().enumerate() {
+                // The caller drops the transaction on error, rolling back this batch
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
                 }
-                query.execute(&mut *tx).await?;
+                let row_number = row_offset + i as u64 + 1;
+                let query = bind_record!(
+                    sqlx::query(&sql),
+                    record,
+                    &csv_indices,
+                    &encodings,
+                    &columns,
+                    row_number
+                );
+                query
+                    .execute(&mut **tx)
+                    .await
+                    .map_err(|e| row_error(row_number, record, e))?;
             }
-            tx.commit().await?;
         }
-        InsertTarget::MySql(pool) => {
-            let mut tx = pool.begin().await?;
-            for record in batch {
-                let mut query = sqlx::query(&sql);
-                for &idx in &csv_indices {
-                    let val = record.get(idx).unwrap_or("");
-                    query = query.bind(val);
+        ImportTransaction::MySql(tx) => {
+            // Multi-row INSERTs cut round trips; chunks stay under the placeholder limit
+            let rows_per_insert =
+                (MYSQL_MAX_PLACEHOLDERS / columns.len()).clamp(1, MYSQL_ROWS_PER_INSERT);
+            let row_placeholders = format!("({})", vec!["?"; columns.len()].join(", "));
+
+            for (chunk_index, chunk) in batch.chunks(rows_per_insert).enumerate() {
+                // The caller drops the transaction on error, rolling back this batch
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                let chunk_sql = format!(
+                    "INSERT INTO {} ({}) VALUES {}",
+                    quoted_table,
+                    quoted_columns.join(", "),
+                    vec![row_placeholders.as_str(); chunk.len()].join(", ")
+                );
+                let chunk_offset = row_offset + (chunk_index * rows_per_insert) as u64;
+                let mut query = sqlx::query(&chunk_sql);
+                for (i, record) in chunk.iter().enumerate() {
+                    query = bind_record!(
+                        query,
+                        record,
+                        &csv_indices,
+                        &encodings,
+                        &columns,
+                        chunk_offset + i as u64 + 1
+                    );
+                }
+                if let Err(err) = query.execute(&mut **tx).await {
+                    // Replay the chunk row by row so the error names the offending row
+                    for (i, record) in chunk.iter().enumerate() {
+                        let row_number = chunk_offset + i as u64 + 1;
+                        let query = bind_record!(
+                            sqlx::query(&sql),
+                            record,
+                            &csv_indices,
+                            &encodings,
+                            &columns,
+                            row_number
+                        );
+                        query
+                            .execute(&mut **tx)
+                            .await
+                            .map_err(|e| row_error(row_number, record, e))?;
+                    }
+                    return Err(err.into());
                 }
-                query.execute(&mut *tx).await?;
             }
-            tx.commit().await?;
         }
-        InsertTarget::Sqlite(pool) => {
-            let mut tx = pool.begin().await?;
-            for record in batch {
-                let mut query = sqlx::query(&sql);
-                for &idx in &csv_indices {
-                    let val = record.get(idx).unwrap_or("");
-                    query = query.bind(val);
+        ImportTransaction::Sqlite(tx) => {
+            for (i, record) in batch.iter().enumerate() {
+                // The caller drops the transaction on error, rolling back this batch
+                if token.is_cancelled() {
+                    return Err(Cancelled.into());
                 }
-                query.execute(&mut *tx).await?;
+                let row_number = row_offset + i as u64 + 1;
+                let query = bind_record!(
+                    sqlx::query(&sql),
+                    record,
+                    &csv_indices,
+                    &encodings,
+                    &columns,
+                    row_number
+                );
+                query
+                    .execute(&mut **tx)
+                    .await
+                    .map_err(|e| row_error(row_number, record, e))?;
             }
-            tx.commit().await?;
         }
     }
 
@@ -345,20 +785,20 @@ async fn insert_batch(
 }
 
 async fn create_table_if_not_exists(
-    manager: &crate::core::connection_manager::ConnectionManager,
-    connection_id: &Uuid,
-    table_name: &str,
-    reader: &mut csv::Reader<File>,
-    has_header: bool,
-    mapping: &HashMap<String, String>,
+    tx: &mut ImportTransaction,
+    options: &CsvImportOptions,
+    reader: &mut csv::Reader<Box<dyn Read + Send>>,
 ) -> Result<()> {
+    let table_name = options.table_name.as_str();
+    let mapping = &options.column_mapping;
+
     // 1. Determine columns
     let mut columns = Vec::new();
     if !mapping.is_empty() {
         for db_col in mapping.values() {
             columns.push(db_col.clone());
         }
-    } else if has_header {
+    } else if options.has_header {
         let headers = reader.headers()?;
         for h in headers.iter() {
             columns.push(h.to_string());
@@ -374,39 +814,26 @@ async fn create_table_if_not_exists(
     if columns.is_empty() {
         return Err(anyhow!("Could not determine columns for table creation"));
     }
-
-    // 2. Identify DB type
-    let db_type = {
-        if manager
-            .get_postgres_pools()
-            .await
-            .contains_key(connection_id)
-        {
-            Some("postgres")
-        } else if manager.get_mysql_pools().await.contains_key(connection_id) {
-            Some("mysql")
-        } else if manager.get_sqlite_pools().await.contains_key(connection_id) {
-            Some("sqlite")
-        } else {
-            None
-        }
+    validate_identifier(table_name)?;
+    for column in &columns {
+        validate_identifier(column)?;
     }
-    .ok_or_else(|| anyhow!("Connection not found"))?;
 
-    // 3. Build CREATE TABLE statement
-    let quoted_table = match db_type {
-        "mysql" => format!("`{}`", table_name.replace("`", "``")),
-        _ => format!("\"{}\"", table_name.replace("\"", "\"\"")),
-    };
+    let db_type = tx.db_type();
+
+    // 2. Build CREATE TABLE statement
+    let quoted_table = quote_ident(table_name, db_type);
 
     let col_defs: Vec<String> = columns
         .iter()
         .map(|c| {
-            let quoted_col = match db_type {
-                "mysql" => format!("`{}`", c.replace("`", "``")),
-                _ => format!("\"{}\"", c.replace("\"", "\"\"")),
+            let quoted_col = quote_ident(c, db_type);
+            let col_type = match (options.binary_columns.contains_key(c), db_type) {
+                (true, "postgres") => "BYTEA",
+                (true, _) => "BLOB",
+                (false, _) => "TEXT",
             };
-            format!("{} TEXT", quoted_col)
+            format!("{} {}", quoted_col, col_type)
         })
         .collect();
 
@@ -416,31 +843,359 @@ async fn create_table_if_not_exists(
         col_defs.join(", ")
     );
 
-    // 4. Execute
-    match db_type {
-        "postgres" => {
-            let pools = manager.get_postgres_pools().await;
-            let pool = pools
-                .get(connection_id)
-                .ok_or_else(|| anyhow!("Pool not found"))?;
-            sqlx::query(&sql).execute(pool).await?;
+    // 3. Execute
+    tx.execute(&sql).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support;
+
+    fn options(path: &std::path::Path, table_name: &str) -> CsvImportOptions {
+        CsvImportOptions {
+            file_path: path.to_string_lossy().into_owned(),
+            table_name: table_name.to_string(),
+            create_table_if_missing: true,
+            column_mapping: HashMap::new(),
+            has_header: true,
+            delimiter: ',',
+            skip_rows: 0,
+            batch_size: 100,
+            allow_ragged_rows: false,
+            dry_run: false,
+            binary_columns: HashMap::new(),
+            quoting: CsvQuoting::default(),
+            load_data_local: false,
         }
-        "mysql" => {
-            let pools = manager.get_mysql_pools().await;
-            let pool = pools
-                .get(connection_id)
-                .ok_or_else(|| anyhow!("Pool not found"))?;
-            sqlx::query(&sql).execute(pool).await?;
+    }
+
+    /// A `n,label` CSV with rows numbered from 1
+    fn numbered_csv(rows: u32) -> String {
+        let mut csv = String::from("n,label\n");
+        for n in 1..=rows {
+            csv.push_str(&format!("{},row {}\n", n, n));
         }
-        "sqlite" => {
-            let pools = manager.get_sqlite_pools().await;
-            let pool = pools
-                .get(connection_id)
-                .ok_or_else(|| anyhow!("Pool not found"))?;
-            sqlx::query(&sql).execute(pool).await?;
+        csv
+    }
+
+    #[tokio::test]
+    async fn cancelling_keeps_only_the_committed_batches() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file("csv", numbered_csv(1000).as_bytes());
+        let app = test_support::app();
+        let token = CancellationToken::new();
+        // Progress is emitted once the first batch of 100 rows has committed
+        let cancel = token.clone();
+        tauri::Listener::listen_any(&app, "import-progress", move |_| cancel.cancel());
+
+        let options = options(&path, "numbers");
+        let err = do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap_err();
+
+        assert!(err.downcast_ref::<Cancelled>().is_some());
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM numbers").await;
+        assert_eq!(count.rows[0][0], 100);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn short_rows_are_rejected_with_their_line_number() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file("csv", b"n,label\n1,one\n2,two\n3\n4,four\n");
+        let app = test_support::app();
+
+        let options = options(&path, "numbers");
+        let token = CancellationToken::new();
+        let err = do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Line 4: expected 2 fields but found 1");
+
+        // Lenient mode pads the short row instead
+        let lenient = CsvImportOptions {
+            allow_ragged_rows: true,
+            ..options
+        };
+        do_import_csv(app.handle().clone(), &manager, &id, "csv", &lenient, &token)
+            .await
+            .unwrap();
+        let padded =
+            test_support::query(&manager, &id, "SELECT label FROM numbers WHERE n = '3'").await;
+        assert_eq!(padded.rows, vec![vec![serde_json::json!("")]]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_failing_row_is_reported_with_its_row_number_and_values() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER PRIMARY KEY, label TEXT)",
+        )
+        .await;
+        // Row 150 repeats the key of row 1
+        let csv = numbered_csv(300).replace("\n150,row 150\n", "\n1,row 150\n");
+        let path = test_support::temp_file("csv", csv.as_bytes());
+        let app = test_support::app();
+
+        let options = CsvImportOptions {
+            create_table_if_missing: false,
+            ..options(&path, "numbers")
+        };
+        let token = CancellationToken::new();
+        let err = do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            err.starts_with("Row 150 (line 151) failed to import"),
+            "{}",
+            err
+        );
+        assert!(err.ends_with("Values: [1, row 150]"), "{}", err);
+        // The failing batch is rolled back, the one before it stays
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM numbers").await;
+        assert_eq!(count.rows[0][0], 100);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn gzipped_csv_imports_the_same_rows_as_plain_csv() {
+        let (manager, id) = test_support::sqlite().await;
+        let csv = numbered_csv(250);
+        let plain = test_support::temp_file("csv", csv.as_bytes());
+        let gzipped = test_support::temp_file("csv.gz", &test_support::gzip(csv.as_bytes()));
+        let app = test_support::app();
+        let token = CancellationToken::new();
+
+        for (path, table) in [(&plain, "plain"), (&gzipped, "gzipped")] {
+            let options = options(path, table);
+            do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+                .await
+                .unwrap();
         }
-        _ => return Err(anyhow!("Unsupported database type")),
+
+        let plain_rows =
+            test_support::query(&manager, &id, "SELECT * FROM plain ORDER BY rowid").await;
+        let gzipped_rows =
+            test_support::query(&manager, &id, "SELECT * FROM gzipped ORDER BY rowid").await;
+        assert_eq!(plain_rows.rows.len(), 250);
+        assert_eq!(plain_rows.rows, gzipped_rows.rows);
+        std::fs::remove_file(plain).unwrap();
+        std::fs::remove_file(gzipped).unwrap();
     }
 
-    Ok(())
+    #[tokio::test]
+    #[ignore = "needs a Postgres server, see core::test_support"]
+    async fn postgres_copy_imports_the_same_rows_as_inserts() {
+        let (pg_manager, pg_id) = test_support::postgres().await;
+        let (sqlite_manager, sqlite_id) = test_support::sqlite().await;
+        // Quotes, embedded delimiters and newlines, and empty fields
+        let csv = format!(
+            "{}2501,\"comma, \"\"quote\"\"\"\n2502,\"two\nlines\"\n2503,\n",
+            numbered_csv(2500)
+        );
+        let path = test_support::temp_file("csv", csv.as_bytes());
+        let app = test_support::app();
+        let token = CancellationToken::new();
+
+        // Postgres takes the COPY path, SQLite the INSERT one
+        let options = options(&path, "copy_numbers");
+        do_import_csv(
+            app.handle().clone(),
+            &pg_manager,
+            &pg_id,
+            "csv",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+        do_import_csv(
+            app.handle().clone(),
+            &sqlite_manager,
+            &sqlite_id,
+            "csv",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let sql = "SELECT n, label FROM copy_numbers ORDER BY CAST(n AS INTEGER)";
+        let copied = test_support::query(&pg_manager, &pg_id, sql).await;
+        let inserted = test_support::query(&sqlite_manager, &sqlite_id, sql).await;
+        assert_eq!(copied.rows.len(), 2503);
+        assert_eq!(copied.rows, inserted.rows);
+        assert_eq!(copied.rows[2502][1], "");
+        test_support::run(&pg_manager, &pg_id, "DROP TABLE copy_numbers").await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_falls_back_to_multi_row_inserts_without_load_data() {
+        let (manager, id) = test_support::mysql().await;
+        // More rows than a single multi-row INSERT holds
+        let path = test_support::temp_file("csv", numbered_csv(2500).as_bytes());
+        let app = test_support::app();
+        let complete = test_support::events(&app, "import-progress");
+        let token = CancellationToken::new();
+
+        let options = CsvImportOptions {
+            batch_size: 2500,
+            load_data_local: true,
+            ..options(&path, "multi_row_numbers")
+        };
+        do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap();
+
+        let rows = test_support::query(
+            &manager,
+            &id,
+            "SELECT n, label FROM multi_row_numbers ORDER BY CAST(n AS UNSIGNED)",
+        )
+        .await;
+        assert_eq!(rows.rows.len(), 2500);
+        for (i, row) in rows.rows.iter().enumerate() {
+            let n = i + 1;
+            assert_eq!(row[0], n.to_string());
+            assert_eq!(row[1], format!("row {}", n));
+        }
+        let events = complete.lock().unwrap();
+        let last = events.last().unwrap();
+        assert_eq!(last["status"], "complete");
+        assert!(last["notice"]
+            .as_str()
+            .unwrap()
+            .contains("multi-row INSERTs"));
+        test_support::run(&manager, &id, "DROP TABLE multi_row_numbers").await;
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dry_runs_report_errors_and_leave_the_table_empty() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(
+            &manager,
+            &id,
+            "CREATE TABLE numbers (n INTEGER PRIMARY KEY, label TEXT)",
+        )
+        .await;
+        let app = test_support::app();
+        let progress = test_support::events(&app, "import-progress");
+        let token = CancellationToken::new();
+
+        let clean = test_support::temp_file("csv", numbered_csv(250).as_bytes());
+        let options = CsvImportOptions {
+            create_table_if_missing: false,
+            dry_run: true,
+            ..options(&clean, "numbers")
+        };
+        do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap();
+        {
+            let events = progress.lock().unwrap();
+            let last = events.last().unwrap();
+            assert_eq!(last["status"], "validated");
+            assert_eq!(last["rows_processed"], 250);
+        }
+
+        let csv = numbered_csv(250).replace("\n150,row 150\n", "\n1,row 150\n");
+        let bad = test_support::temp_file("csv", csv.as_bytes());
+        let options = CsvImportOptions {
+            file_path: bad.to_string_lossy().into_owned(),
+            ..options
+        };
+        let err = do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Row 150 (line 151) failed to import"));
+
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM numbers").await;
+        assert_eq!(count.rows[0][0], 0);
+        std::fs::remove_file(clean).unwrap();
+        std::fs::remove_file(bad).unwrap();
+    }
+
+    #[test]
+    fn binary_encodings_decode_their_text_forms() {
+        let bytes = vec![0x00, 0xff, 0x10];
+        assert_eq!(BinaryEncoding::Hex.decode("00ff10").unwrap(), bytes);
+        assert_eq!(BinaryEncoding::Hex.decode("0x00FF10").unwrap(), bytes);
+        assert_eq!(BinaryEncoding::Hex.decode("\\x00ff10").unwrap(), bytes);
+        assert_eq!(BinaryEncoding::Base64.decode("AP8Q").unwrap(), bytes);
+        assert!(BinaryEncoding::Hex.decode("0x0g").is_err());
+        assert!(BinaryEncoding::Hex.decode("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn hex_blob_columns_import_their_original_bytes() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(&manager, &id, "CREATE TABLE files (name TEXT, data BLOB)").await;
+        let path = test_support::temp_file("csv", b"name,data\na,0x00ff10\nb,\\xdeadbeef\nc,\n");
+        let app = test_support::app();
+
+        let options = CsvImportOptions {
+            create_table_if_missing: false,
+            binary_columns: HashMap::from([("data".to_string(), BinaryEncoding::Hex)]),
+            ..options(&path, "files")
+        };
+        let token = CancellationToken::new();
+        do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap();
+
+        let rows = test_support::query(
+            &manager,
+            &id,
+            "SELECT name, typeof(data), hex(data) FROM files ORDER BY name",
+        )
+        .await;
+        assert_eq!(
+            rows.rows,
+            vec![
+                vec!["a", "blob", "00FF10"],
+                vec!["b", "blob", "DEADBEEF"],
+                vec!["c", "blob", ""],
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn backslash_escaped_quotes_import_as_written() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file(
+            "csv",
+            b"id,note\n1,\"a, b\"\n2,\"say \\\"hi\\\"\"\n3,\"two\nlines\"\n",
+        );
+        let app = test_support::app();
+
+        let options = CsvImportOptions {
+            quoting: CsvQuoting {
+                escape_char: Some('\\'),
+                ..Default::default()
+            },
+            ..options(&path, "notes")
+        };
+        let token = CancellationToken::new();
+        do_import_csv(app.handle().clone(), &manager, &id, "csv", &options, &token)
+            .await
+            .unwrap();
+
+        let rows = test_support::query(&manager, &id, "SELECT note FROM notes ORDER BY id").await;
+        assert_eq!(rows.rows, [["a, b"], ["say \"hi\""], ["two\nlines"]]);
+        std::fs::remove_file(path).unwrap();
+    }
 }
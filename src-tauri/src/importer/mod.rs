@@ -1,7 +1,15 @@
 pub mod csv_importer;
 pub mod sql_importer;
 
+use crate::core::AppState;
+use crate::utils::sql_splitter::leading_keyword;
+use flate2::read::GzDecoder;
 use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::State;
 
 #[derive(Serialize, Clone)]
 pub struct ImportProgress {
@@ -9,8 +17,47 @@ pub struct ImportProgress {
     pub rows_processed: u64,
     pub total_rows: Option<u64>,
     pub percentage: Option<f32>,
-    pub status: String, // "processing" | "complete" | "error"
+    pub status: String, // "processing" | "complete" | "validated" | "error" | "cancelled"
     pub error: Option<String>,
+    pub bytes_processed: Option<u64>, // of the file on disk, i.e. compressed for .gz input
+    pub total_bytes: Option<u64>,
+    pub notice: Option<String>, // why the import took a slower path than requested
+}
+
+/// Tracks how much of an input file has been read, for progress when no row total is known
+#[derive(Clone)]
+pub struct ByteProgress {
+    bytes_read: Arc<AtomicU64>,
+    total_bytes: u64,
+}
+
+impl ByteProgress {
+    pub fn bytes_processed(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    pub fn percentage(&self) -> Option<f32> {
+        (self.total_bytes > 0)
+            .then(|| (self.bytes_processed() as f32 / self.total_bytes as f32 * 100.0).min(100.0))
+    }
+}
+
+/// Counts bytes as they are read from the wrapped file
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
 }
 
 pub enum InsertTarget {
@@ -18,3 +65,105 @@ pub enum InsertTarget {
     MySql(sqlx::MySqlPool),
     Sqlite(sqlx::SqlitePool),
 }
+
+/// A transaction on the import target, used for batches, atomic imports and dry runs
+pub enum ImportTransaction {
+    Postgres(sqlx::Transaction<'static, sqlx::Postgres>),
+    MySql(sqlx::Transaction<'static, sqlx::MySql>),
+    Sqlite(sqlx::Transaction<'static, sqlx::Sqlite>),
+}
+
+impl ImportTransaction {
+    pub async fn begin(target: &InsertTarget) -> sqlx::Result<Self> {
+        Ok(match target {
+            InsertTarget::Postgres(pool) => Self::Postgres(pool.begin().await?),
+            InsertTarget::MySql(pool) => Self::MySql(pool.begin().await?),
+            InsertTarget::Sqlite(pool) => Self::Sqlite(pool.begin().await?),
+        })
+    }
+
+    pub fn db_type(&self) -> &'static str {
+        match self {
+            Self::Postgres(_) => "postgres",
+            Self::MySql(_) => "mysql",
+            Self::Sqlite(_) => "sqlite",
+        }
+    }
+
+    /// Runs one statement unprepared; MySQL rejects triggers, routines and some DDL over
+    /// the prepared-statement protocol.
+    pub async fn execute(&mut self, sql: &str) -> sqlx::Result<u64> {
+        Ok(match self {
+            Self::Postgres(tx) => sqlx::raw_sql(sql).execute(&mut **tx).await?.rows_affected(),
+            Self::MySql(tx) => sqlx::raw_sql(sql).execute(&mut **tx).await?.rows_affected(),
+            Self::Sqlite(tx) => sqlx::raw_sql(sql).execute(&mut **tx).await?.rows_affected(),
+        })
+    }
+
+    pub async fn commit(self) -> sqlx::Result<()> {
+        match self {
+            Self::Postgres(tx) => tx.commit().await,
+            Self::MySql(tx) => tx.commit().await,
+            Self::Sqlite(tx) => tx.commit().await,
+        }
+    }
+
+    pub async fn rollback(self) -> sqlx::Result<()> {
+        match self {
+            Self::Postgres(tx) => tx.rollback().await,
+            Self::MySql(tx) => tx.rollback().await,
+            Self::Sqlite(tx) => tx.rollback().await,
+        }
+    }
+}
+
+/// MySQL commits DDL implicitly, so it cannot be undone by rolling back a dry run
+pub fn commits_implicitly(sql: &str, db_type: &str) -> bool {
+    db_type == "mysql"
+        && matches!(
+            leading_keyword(sql).as_str(),
+            "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME"
+        )
+}
+
+/// Opens an import source, transparently decompressing gzip files.
+///
+/// Compression is detected from the gzip magic bytes rather than the extension.
+pub fn open_input(file_path: &str) -> std::io::Result<Box<dyn Read + Send>> {
+    open_input_tracked(file_path).map(|(reader, _)| reader)
+}
+
+/// Like `open_input`, also reporting how far into the file the reader has got.
+/// Bytes are counted before decompression so they compare against the file size.
+pub fn open_input_tracked(
+    file_path: &str,
+) -> std::io::Result<(Box<dyn Read + Send>, ByteProgress)> {
+    let mut file = File::open(file_path)?;
+    let mut magic = [0u8; 2];
+    let is_gzip = file.read_exact(&mut magic).is_ok() && magic == [0x1f, 0x8b];
+    file.seek(SeekFrom::Start(0))?;
+
+    let progress = ByteProgress {
+        bytes_read: Arc::new(AtomicU64::new(0)),
+        total_bytes: file.metadata()?.len(),
+    };
+    let counted = CountingReader {
+        inner: file,
+        bytes_read: progress.bytes_read.clone(),
+    };
+
+    if is_gzip {
+        Ok((Box::new(GzDecoder::new(counted)), progress))
+    } else {
+        Ok((Box::new(counted), progress))
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_import(state: State<'_, AppState>, import_id: String) -> Result<(), String> {
+    let mut active = state.active_imports.lock().await;
+    if let Some(token) = active.remove(&import_id) {
+        token.cancel();
+    }
+    Ok(())
+}
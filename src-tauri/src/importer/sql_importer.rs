@@ -1,17 +1,70 @@
-use crate::core::AppState;
+use crate::core::{AppState, Cancelled};
 use anyhow::{anyhow, Result};
-use serde::Deserialize;
-use tauri::{AppHandle, Emitter, State};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::importer::{ImportProgress, InsertTarget};
-use std::fs::File;
+use crate::importer::{
+    commits_implicitly, open_input, open_input_tracked, ImportProgress, ImportTransaction,
+    InsertTarget,
+};
+use crate::utils::sql_splitter::{leading_keyword, SqlSplitter};
 use std::io::{BufRead, BufReader};
 
 #[derive(Deserialize, Debug)]
 pub struct SqlImportOptions {
     pub file_path: String,
     pub execute_in_transaction: bool,
+    #[serde(default)]
+    pub estimate_total: bool, // pre-scan the file to count statements for percentage progress
+    #[serde(default)]
+    pub dry_run: bool, // execute everything in one transaction, then roll it back
+}
+
+#[derive(Serialize, Debug)]
+pub struct SqlDumpPreview {
+    /// The first `max_statements` statements, in file order
+    pub statements: Vec<String>,
+    /// Leading keyword (CREATE, INSERT, ...) -> number of statements in the whole file
+    pub statement_counts: BTreeMap<String, u64>,
+    pub total_statements: u64,
+}
+
+#[tauri::command]
+pub async fn preview_sql_dump(
+    file_path: String,
+    max_statements: usize,
+    mysql_syntax: Option<bool>,
+) -> Result<SqlDumpPreview, String> {
+    let reader = BufReader::new(open_input(&file_path).map_err(|e| e.to_string())?);
+    let mut splitter = new_splitter(mysql_syntax.unwrap_or(false));
+    let mut preview = SqlDumpPreview {
+        statements: Vec::new(),
+        statement_counts: BTreeMap::new(),
+        total_statements: 0,
+    };
+
+    let mut tally = |stmt: String| {
+        let keyword = match leading_keyword(&stmt) {
+            k if k.is_empty() => "OTHER".to_string(),
+            k => k,
+        };
+        *preview.statement_counts.entry(keyword).or_insert(0) += 1;
+        preview.total_statements += 1;
+        if preview.statements.len() < max_statements {
+            preview.statements.push(stmt);
+        }
+    };
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        splitter.push_line(&line).into_iter().for_each(&mut tally);
+    }
+    splitter.finish().into_iter().for_each(&mut tally);
+
+    Ok(preview)
 }
 
 #[tauri::command]
@@ -23,6 +76,13 @@ pub async fn import_sql_dump(
     options: SqlImportOptions,
 ) -> Result<(), String> {
     let manager = state.connection_manager.clone();
+    let token = CancellationToken::new();
+    state
+        .active_imports
+        .lock()
+        .await
+        .insert(import_id.clone(), token.clone());
+    let active_imports = state.active_imports.clone();
 
     tokio::spawn(async move {
         let result = do_import_sql(
@@ -31,10 +91,12 @@ pub async fn import_sql_dump(
             &connection_id,
             &import_id,
             &options,
+            &token,
         )
         .await;
 
         if let Err(e) = result {
+            let cancelled = e.downcast_ref::<Cancelled>().is_some();
             let _ = app_handle.emit(
                 "import-progress",
                 ImportProgress {
@@ -42,22 +104,28 @@ pub async fn import_sql_dump(
                     rows_processed: 0,
                     total_rows: None,
                     percentage: None,
-                    status: "error".to_string(),
-                    error: Some(e.to_string()),
+                    status: if cancelled { "cancelled" } else { "error" }.to_string(),
+                    error: if cancelled { None } else { Some(e.to_string()) },
+                    bytes_processed: None,
+                    total_bytes: None,
+                    notice: None,
                 },
             );
         }
+
+        active_imports.lock().await.remove(&import_id);
     });
 
     Ok(())
 }
 
-async fn do_import_sql(
-    app_handle: AppHandle,
+async fn do_import_sql<R: Runtime>(
+    app_handle: AppHandle<R>,
     manager: &crate::core::connection_manager::ConnectionManager,
     connection_id: &Uuid,
     import_id: &str,
     options: &SqlImportOptions,
+    token: &CancellationToken,
 ) -> Result<()> {
     // 1. Detect DB type
     let db_type = {
@@ -77,15 +145,20 @@ async fn do_import_sql(
     }
     .ok_or_else(|| anyhow!("Connection not found"))?;
 
-    // 2. Open file
-    let file = File::open(&options.file_path)?;
+    // 2. Optionally count statements up front so progress can report a percentage
+    let total_statements = if options.estimate_total {
+        Some(count_statements(&options.file_path, db_type == "mysql")?)
+    } else {
+        None
+    };
+
+    // 3. Open file
+    let (file, byte_progress) = open_input_tracked(&options.file_path)?;
     let reader = BufReader::new(file);
 
-    // 3. Process statements
-    let mut current_statement = String::new();
+    // 4. Process statements
+    let mut splitter = new_splitter(db_type == "mysql");
     let mut statements_executed = 0u64;
-    let mut in_string = false;
-    let mut quote_char = ' ';
 
     // Get pool
     let pool_guard = match db_type {
@@ -116,59 +189,77 @@ async fn do_import_sql(
         _ => return Err(anyhow!("Unsupported database type")),
     };
 
+    // Dropping the transaction on an early return rolls back everything applied so far
+    let mut tx = if options.execute_in_transaction || options.dry_run {
+        Some(ImportTransaction::begin(&pool_guard).await?)
+    } else {
+        None
+    };
+
     for line in reader.lines() {
         let line = line?;
-        if line.trim().starts_with("--") || line.trim().starts_with("/*") {
-            continue; // Basic comment skip
-        }
-
-        for c in line.chars() {
-            if (c == '\'' || c == '"' || c == '`') && (db_type == "mysql" || c != '`') {
-                if in_string {
-                    if c == quote_char {
-                        in_string = false;
-                    }
-                } else {
-                    in_string = true;
-                    quote_char = c;
-                }
+        for stmt in splitter.push_line(&line) {
+            if token.is_cancelled() {
+                return Err(Cancelled.into());
             }
+            run_statement(
+                &pool_guard,
+                tx.as_mut(),
+                &stmt,
+                db_type,
+                options.dry_run,
+                statements_executed + 1,
+            )
+            .await?;
+            statements_executed += 1;
 
-            current_statement.push(c);
-
-            if c == ';' && !in_string {
-                let stmt = current_statement.trim();
-                if !stmt.is_empty() {
-                    execute_statement(&pool_guard, stmt).await?;
-                    statements_executed += 1;
-
-                    if statements_executed % 100 == 0 {
-                        app_handle.emit(
-                            "import-progress",
-                            ImportProgress {
-                                import_id: import_id.to_string(),
-                                rows_processed: statements_executed,
-                                total_rows: None,
-                                percentage: None,
-                                status: "processing".to_string(),
-                                error: None,
-                            },
-                        )?;
-                    }
-                }
-                current_statement.clear();
+            if statements_executed % 100 == 0 {
+                app_handle.emit(
+                    "import-progress",
+                    ImportProgress {
+                        import_id: import_id.to_string(),
+                        rows_processed: statements_executed,
+                        total_rows: total_statements,
+                        // Without a pre-scanned statement count, progress follows the bytes read
+                        percentage: match total_statements {
+                            Some(total) if total > 0 => {
+                                Some((statements_executed as f32 / total as f32 * 100.0).min(100.0))
+                            }
+                            _ => byte_progress.percentage(),
+                        },
+                        status: "processing".to_string(),
+                        error: None,
+                        bytes_processed: Some(byte_progress.bytes_processed()),
+                        total_bytes: Some(byte_progress.total_bytes()),
+                        notice: None,
+                    },
+                )?;
             }
         }
-        current_statement.push('\n');
     }
 
     // Execute remaining
-    let stmt = current_statement.trim();
-    if !stmt.is_empty() {
-        execute_statement(&pool_guard, stmt).await?;
+    if let Some(stmt) = splitter.finish() {
+        run_statement(
+            &pool_guard,
+            tx.as_mut(),
+            &stmt,
+            db_type,
+            options.dry_run,
+            statements_executed + 1,
+        )
+        .await?;
         statements_executed += 1;
     }
 
+    if let Some(tx) = tx {
+        if options.dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+    }
+
     app_handle.emit(
         "import-progress",
         ImportProgress {
@@ -176,25 +267,345 @@ async fn do_import_sql(
             rows_processed: statements_executed,
             total_rows: Some(statements_executed),
             percentage: Some(100.0),
-            status: "complete".to_string(),
+            status: if options.dry_run {
+                "validated"
+            } else {
+                "complete"
+            }
+            .to_string(),
             error: None,
+            bytes_processed: Some(byte_progress.total_bytes()),
+            total_bytes: Some(byte_progress.total_bytes()),
+            notice: None,
         },
     )?;
 
     Ok(())
 }
 
+/// MySQL dumps use backslash escapes and `DELIMITER` lines around routines and triggers
+fn new_splitter(is_mysql: bool) -> SqlSplitter {
+    let splitter = SqlSplitter::new(is_mysql);
+    if is_mysql {
+        splitter.with_delimiter_directives()
+    } else {
+        splitter
+    }
+}
+
+fn count_statements(file_path: &str, is_mysql: bool) -> Result<u64> {
+    let reader = BufReader::new(open_input(file_path)?);
+    let mut splitter = new_splitter(is_mysql);
+    let mut count = 0u64;
+
+    for line in reader.lines() {
+        count += splitter.push_line(&line?).len() as u64;
+    }
+    if splitter.finish().is_some() {
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Executes one dump statement, inside `tx` when the import is transactional.
+/// Errors name the 1-based statement number so dry runs can point at the failure.
+async fn run_statement(
+    target: &InsertTarget,
+    tx: Option<&mut ImportTransaction>,
+    sql: &str,
+    db_type: &str,
+    dry_run: bool,
+    statement_number: u64,
+) -> Result<()> {
+    if dry_run && commits_implicitly(sql, db_type) {
+        return Err(anyhow!(
+            "Statement {}: dry runs cannot validate DDL on MySQL because it commits immediately",
+            statement_number
+        ));
+    }
+    let result = match tx {
+        Some(tx) => tx
+            .execute(sql)
+            .await
+            .map(|_| ())
+            .map_err(anyhow::Error::from),
+        None => execute_statement(target, sql).await,
+    };
+    result.map_err(|e| anyhow!("Statement {} failed: {}", statement_number, e))
+}
+
 async fn execute_statement(target: &InsertTarget, sql: &str) -> Result<()> {
     match target {
         InsertTarget::Postgres(pool) => {
-            sqlx::query(sql).execute(pool).await?;
+            sqlx::raw_sql(sql).execute(pool).await?;
         }
         InsertTarget::MySql(pool) => {
-            sqlx::query(sql).execute(pool).await?;
+            sqlx::raw_sql(sql).execute(pool).await?;
         }
         InsertTarget::Sqlite(pool) => {
-            sqlx::query(sql).execute(pool).await?;
+            sqlx::raw_sql(sql).execute(pool).await?;
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support;
+
+    fn options(path: &std::path::Path) -> SqlImportOptions {
+        SqlImportOptions {
+            file_path: path.to_string_lossy().into_owned(),
+            execute_in_transaction: true,
+            estimate_total: false,
+            dry_run: false,
+        }
+    }
+
+    /// A dump creating `items` and inserting `rows` rows into it
+    fn items_dump(rows: u32) -> String {
+        let mut dump = String::from("CREATE TABLE items (n INTEGER);\n");
+        for n in 0..rows {
+            dump.push_str(&format!("INSERT INTO items VALUES ({});\n", n));
+        }
+        dump
+    }
+
+    #[tokio::test]
+    async fn completion_reports_100_percent_of_the_executed_statements() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file("sql", items_dump(250).as_bytes());
+        let app = test_support::app();
+        let events = test_support::events(&app, "import-progress");
+        let options = SqlImportOptions {
+            estimate_total: true,
+            ..options(&path)
+        };
+
+        let token = CancellationToken::new();
+        do_import_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let events = events.lock().unwrap();
+        let done = events.last().unwrap();
+        assert_eq!(done["status"], "complete");
+        assert_eq!(done["percentage"], 100.0);
+        assert_eq!(done["rows_processed"], 251);
+        assert_eq!(done["total_rows"], 251);
+        // The pre-scan gives the intermediate events the same total
+        assert_eq!(events[0]["total_rows"], 251);
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM items").await;
+        assert_eq!(count.rows[0][0], 250);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_transactional_import_rolls_it_back() {
+        let (manager, id) = test_support::sqlite().await;
+        test_support::run(&manager, &id, "CREATE TABLE items (n INTEGER)").await;
+        let dump = items_dump(1000).replacen("CREATE TABLE items (n INTEGER);\n", "", 1);
+        let path = test_support::temp_file("sql", dump.as_bytes());
+        let app = test_support::app();
+        let token = CancellationToken::new();
+        // The first progress event comes after 100 statements, mid-stream
+        let cancel = token.clone();
+        tauri::Listener::listen_any(&app, "import-progress", move |_| cancel.cancel());
+
+        let err = do_import_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options(&path),
+            &token,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.downcast_ref::<Cancelled>().is_some());
+        let count = test_support::query(&manager, &id, "SELECT COUNT(*) FROM items").await;
+        assert_eq!(count.rows[0][0], 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn gzipped_dump_imports_the_same_rows_as_plain_sql() {
+        let dump = items_dump(150);
+        let plain = test_support::temp_file("sql", dump.as_bytes());
+        let gzipped = test_support::temp_file("sql.gz", &test_support::gzip(dump.as_bytes()));
+        let token = CancellationToken::new();
+
+        let mut results = Vec::new();
+        for path in [&plain, &gzipped] {
+            let (manager, id) = test_support::sqlite().await;
+            let app = test_support::app();
+            do_import_sql(
+                app.handle().clone(),
+                &manager,
+                &id,
+                "dump",
+                &options(path),
+                &token,
+            )
+            .await
+            .unwrap();
+            results.push(
+                test_support::query(&manager, &id, "SELECT n FROM items ORDER BY n")
+                    .await
+                    .rows,
+            );
+        }
+
+        assert_eq!(results[0].len(), 150);
+        assert_eq!(results[0], results[1]);
+        std::fs::remove_file(plain).unwrap();
+        std::fs::remove_file(gzipped).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL server, see core::test_support"]
+    async fn mysql_dump_with_a_delimiter_wrapped_trigger_imports() {
+        let (manager, id) = test_support::mysql().await;
+        let dump = "DROP TABLE IF EXISTS sqlmate_audited;
+CREATE TABLE sqlmate_audited (n INT, doubled INT);
+DELIMITER ;;
+CREATE TRIGGER sqlmate_audited_bi BEFORE INSERT ON sqlmate_audited FOR EACH ROW
+BEGIN
+  SET NEW.doubled = NEW.n * 2;
+END ;;
+DELIMITER ;
+INSERT INTO sqlmate_audited (n) VALUES (21);
+";
+        let path = test_support::temp_file("sql", dump.as_bytes());
+        let app = test_support::app();
+        let options = SqlImportOptions {
+            execute_in_transaction: false,
+            ..options(&path)
+        };
+
+        let token = CancellationToken::new();
+        do_import_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let result =
+            test_support::query(&manager, &id, "SELECT doubled FROM sqlmate_audited").await;
+        test_support::run(&manager, &id, "DROP TABLE sqlmate_audited").await;
+        assert_eq!(result.rows, vec![vec![serde_json::json!(42)]]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dry_runs_report_the_failing_statement_and_apply_nothing() {
+        let (manager, id) = test_support::sqlite().await;
+        let dump = format!("{}INSERT INTO missing VALUES (1);\n", items_dump(10));
+        let path = test_support::temp_file("sql", dump.as_bytes());
+        let app = test_support::app();
+        let options = SqlImportOptions {
+            dry_run: true,
+            ..options(&path)
+        };
+
+        let token = CancellationToken::new();
+        let err = do_import_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options,
+            &token,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("missing"), "{}", err);
+
+        let tables = test_support::query(
+            &manager,
+            &id,
+            "SELECT COUNT(*) FROM sqlite_schema WHERE name = 'items'",
+        )
+        .await;
+        assert_eq!(tables.rows[0][0], 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dump_preview_tallies_statements_by_keyword() {
+        let mut dump =
+            String::from("-- two tables\nCREATE TABLE a (n INTEGER);\nCREATE TABLE b (s TEXT);\n");
+        for n in 0..4 {
+            dump.push_str(&format!("INSERT INTO a VALUES ({});\n", n));
+        }
+        dump.push_str("INSERT INTO b VALUES ('semi; colon');\n");
+        let path = test_support::temp_file("sql", dump.as_bytes());
+
+        let preview = preview_sql_dump(path.to_string_lossy().into_owned(), 3, None)
+            .await
+            .unwrap();
+
+        assert_eq!(preview.total_statements, 7);
+        assert_eq!(
+            preview.statement_counts,
+            BTreeMap::from([("CREATE".to_string(), 2), ("INSERT".to_string(), 5)])
+        );
+        assert_eq!(preview.statements.len(), 3);
+        assert!(preview.statements[2].starts_with("INSERT INTO a VALUES (0)"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn byte_progress_climbs_to_100_without_a_statement_total() {
+        let (manager, id) = test_support::sqlite().await;
+        let path = test_support::temp_file("sql", items_dump(20_000).as_bytes());
+        let app = test_support::app();
+        let events = test_support::events(&app, "import-progress");
+
+        let token = CancellationToken::new();
+        do_import_sql(
+            app.handle().clone(),
+            &manager,
+            &id,
+            "dump",
+            &options(&path),
+            &token,
+        )
+        .await
+        .unwrap();
+
+        let events = events.lock().unwrap();
+        let percentages: Vec<f64> = events
+            .iter()
+            .map(|e| e["percentage"].as_f64().unwrap())
+            .collect();
+        assert!(percentages.len() > 2);
+        assert!(percentages[0] < 100.0);
+        assert!(
+            percentages.windows(2).all(|w| w[0] <= w[1]),
+            "{:?}",
+            percentages
+        );
+        assert_eq!(*percentages.last().unwrap(), 100.0);
+
+        assert!(events[0]["total_rows"].is_null());
+        let done = events.last().unwrap();
+        assert_eq!(done["bytes_processed"], done["total_bytes"]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
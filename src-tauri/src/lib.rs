@@ -6,16 +6,28 @@ pub mod security;
 pub mod utils;
 
 use crate::core::ai_service;
-use crate::core::query_engine::QueryEngine;
+use crate::core::query_engine::{
+    contains_destructive_statement, is_destructive_statement, streaming_error, QueryEngine,
+};
 use crate::core::{
-    connection_manager::ConnectionManager, AiSchemaCacheEntry, AiSchemaTable, AppState,
-    ConnectionConfig, FilterConfig, QueryResult, SidebarItem, SidebarItemType, TableMetadata,
+    connection_manager::ConnectionManager, AiResult, AiSchemaCacheEntry, AiSchemaTable, AppState,
+    CatalogFilter, ColumnProfile, ConnectionConfig, ConnectionTestResult, DatabaseSize, DbSession,
+    FilterConfig, MutationResult, QueryResult, ServerInfo, SidebarItem, SidebarItemType,
+    SqlValidation, TableForeignKeys, TableMetadata, TableTrigger, TunnelInfo,
 };
+use crate::db::connection_configs::ConnectionConfigStore;
+use crate::db::history::{QueryHistoryEntry, QueryHistoryStore};
+use crate::db::saved_queries::{SavedQuery, SavedQueryStore};
+use crate::db::sidebar_preferences::SidebarPreferenceStore;
+use crate::exporter::exporter::{export_percentage, ExportProgress};
+use crate::security::SecureStore;
+use crate::utils::{CsvQuoting, JsonNullMode};
 use std::sync::Arc;
 use tauri::State;
 use uuid::Uuid;
 
 use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::time::{Duration, Instant};
@@ -24,7 +36,7 @@ use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
 const AI_SCHEMA_CACHE_TTL: Duration = Duration::from_secs(300);
-const MAX_AI_SCHEMA_TABLES: usize = 12;
+const DEFAULT_AI_SCHEMA_TABLES: usize = 12;
 const MAX_AI_TABLE_NAMES: usize = 200;
 
 fn tokenize_search_terms(input: &str) -> Vec<String> {
@@ -113,9 +125,12 @@ fn table_relevance_score(table: &AiSchemaTable, prompt: &str, terms: &HashSet<St
     score
 }
 
+/// Ranks tables by how strongly the prompt refers to them and keeps the top `max_tables`,
+/// so a table named in the prompt is described even on very large schemas
 fn select_relevant_ai_tables<'a>(
     tables: &'a [AiSchemaTable],
     prompt: &str,
+    max_tables: usize,
 ) -> Vec<&'a AiSchemaTable> {
     if tables.is_empty() {
         return Vec::new();
@@ -133,23 +148,35 @@ fn select_relevant_ai_tables<'a>(
     let mut selected = scored
         .iter()
         .filter(|(score, _)| *score > 0)
-        .take(MAX_AI_SCHEMA_TABLES)
+        .take(max_tables)
         .map(|(_, table)| *table)
         .collect::<Vec<_>>();
 
     if selected.is_empty() {
-        selected = tables.iter().take(MAX_AI_SCHEMA_TABLES).collect();
+        selected = tables.iter().take(max_tables).collect();
     }
 
     selected
 }
 
-fn build_schema_context(tables: &[AiSchemaTable], prompt: &str) -> String {
+/// The schema section of the AI prompt, plus the names of the tables it describes in detail
+fn build_schema_context(
+    tables: &[AiSchemaTable],
+    prompt: &str,
+    max_tables: usize,
+) -> (String, Vec<String>) {
     if tables.is_empty() {
-        return "No schema metadata was available for this connection.".to_string();
+        return (
+            "No schema metadata was available for this connection.".to_string(),
+            Vec::new(),
+        );
     }
 
-    let selected_tables = select_relevant_ai_tables(tables, prompt);
+    let selected_tables = select_relevant_ai_tables(tables, prompt, max_tables);
+    let selected_names = selected_tables
+        .iter()
+        .map(|table| format_ai_table_name(table))
+        .collect();
     let total_tables = tables.len();
     let listed_names = tables
         .iter()
@@ -212,7 +239,7 @@ fn build_schema_context(tables: &[AiSchemaTable], prompt: &str) -> String {
         ));
     }
 
-    sections.join("\n\n")
+    (sections.join("\n\n"), selected_names)
 }
 
 async fn get_cached_ai_schema(
@@ -249,6 +276,23 @@ async fn invalidate_ai_schema_cache(state: &AppState, connection_id: &Uuid) {
     cache.remove(connection_id);
 }
 
+/// Applies the production gate used by execute_mutations to free-form SQL
+async fn ensure_sql_confirmed(
+    state: &AppState,
+    connection_id: &Uuid,
+    sql: &str,
+    confirm_production: Option<bool>,
+) -> Result<(), String> {
+    if contains_destructive_statement(sql) {
+        state
+            .connection_manager
+            .ensure_production_confirmed(connection_id, confirm_production.unwrap_or(false))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn cancel_query(state: State<'_, AppState>, query_id: Uuid) -> Result<(), String> {
     let mut active = state.active_queries.lock().await;
@@ -259,13 +303,19 @@ async fn cancel_query(state: State<'_, AppState>, query_id: Uuid) -> Result<(),
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn execute_query_streaming(
     state: State<'_, AppState>,
     window: Window,
     connection_id: Uuid,
     query_id: Uuid,
     sql: String,
+    display_timezone: Option<String>,
+    format: Option<String>,  // "json" (default) or "arrow"
+    fetch_size: Option<u32>, // Postgres cursor batch size; 0 reads without a cursor
+    confirm_production: Option<bool>,
 ) -> Result<(), String> {
+    ensure_sql_confirmed(&state, &connection_id, &sql, confirm_production).await?;
     let token = CancellationToken::new();
 
     {
@@ -285,17 +335,14 @@ async fn execute_query_streaming(
             query_id,
             &window,
             token,
+            display_timezone.as_deref(),
+            format.as_deref(),
+            fetch_size,
         )
         .await;
 
         if let Err(e) = result {
-            let _ = window.emit(
-                "query-error",
-                serde_json::json!({
-                    "query_id": query_id,
-                    "error": e.to_string()
-                }),
-            );
+            let _ = window.emit("query-error", streaming_error(query_id, &e));
         }
 
         // Cleanup
@@ -309,10 +356,15 @@ async fn execute_query_streaming(
 #[tauri::command]
 async fn connect(
     state: State<'_, AppState>,
-    config: ConnectionConfig,
+    mut config: ConnectionConfig,
     password: Option<String>,
 ) -> Result<(), String> {
     invalidate_ai_schema_cache(&state, &config.id).await;
+    // Saved connections keep their secrets in the keyring rather than the config file
+    let password = password.or_else(|| SecureStore::get_password(&config.id).ok());
+    if config.ssh_enabled {
+        load_ssh_passwords(&mut config);
+    }
     state
         .connection_manager
         .connect(config, password)
@@ -320,12 +372,115 @@ async fn connect(
         .map_err(|e| e.to_string())
 }
 
+/// Fills in SSH passwords the caller left out from the keyring, for the final SSH host and
+/// every jump host
+fn load_ssh_passwords(config: &mut ConnectionConfig) {
+    if config.ssh_password.is_none() {
+        config.ssh_password = SecureStore::get_ssh_password(&config.id).ok();
+    }
+    for (i, hop) in config.ssh_jump_hosts.iter_mut().enumerate() {
+        if hop.password.is_none() {
+            hop.password = SecureStore::get_ssh_hop_password(&config.id, i).ok();
+        }
+    }
+}
+
 #[tauri::command]
-async fn test_connection(
+async fn open_tunnel(
+    state: State<'_, AppState>,
+    mut config: ConnectionConfig,
+) -> Result<TunnelInfo, String> {
+    load_ssh_passwords(&mut config);
+    state
+        .connection_manager
+        .open_tunnel(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_tunnels(state: State<'_, AppState>) -> Result<Vec<TunnelInfo>, String> {
+    Ok(state.connection_manager.list_tunnels().await)
+}
+
+#[tauri::command]
+async fn close_tunnel(state: State<'_, AppState>, tunnel_id: Uuid) -> Result<(), String> {
+    state
+        .connection_manager
+        .close_tunnel(&tunnel_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn save_connection_config(
     state: State<'_, AppState>,
     config: ConnectionConfig,
     password: Option<String>,
 ) -> Result<(), String> {
+    if let Some(password) = password.as_deref() {
+        SecureStore::save_password(&config.id, password).map_err(|e| e.to_string())?;
+    }
+    if let Some(ssh_password) = config.ssh_password.as_deref() {
+        SecureStore::save_ssh_password(&config.id, ssh_password).map_err(|e| e.to_string())?;
+    }
+    for (i, hop) in config.ssh_jump_hosts.iter().enumerate() {
+        if let Some(hop_password) = hop.password.as_deref() {
+            SecureStore::save_ssh_hop_password(&config.id, i, hop_password)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    state
+        .connection_configs
+        .save(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_connection_configs(
+    state: State<'_, AppState>,
+) -> Result<Vec<ConnectionConfig>, String> {
+    state
+        .connection_configs
+        .list()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_connection_config(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+) -> Result<(), String> {
+    let jump_hosts = state
+        .connection_configs
+        .list()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| c.id == connection_id)
+        .map_or(0, |c| c.ssh_jump_hosts.len());
+    state
+        .connection_configs
+        .delete(&connection_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Missing keyring entries are fine here
+    let _ = SecureStore::delete_password(&connection_id);
+    let _ = SecureStore::delete_ssh_password(&connection_id);
+    for i in 0..jump_hosts {
+        let _ = SecureStore::delete_ssh_hop_password(&connection_id, i);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn test_connection(
+    state: State<'_, AppState>,
+    config: ConnectionConfig,
+    password: Option<String>,
+) -> Result<ConnectionTestResult, String> {
     state
         .connection_manager
         .test_connection(config, password)
@@ -334,194 +489,1121 @@ async fn test_connection(
 }
 
 #[tauri::command]
+async fn ping_connection(state: State<'_, AppState>, connection_id: Uuid) -> Result<u64, String> {
+    state
+        .connection_manager
+        .ping(&connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_server_info(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+) -> Result<ServerInfo, String> {
+    state
+        .connection_manager
+        .get_server_info(&connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn execute_query(
     state: State<'_, AppState>,
     connection_id: Uuid,
     sql: String,
     page: Option<u32>,
     page_size: Option<u32>,
+    display_timezone: Option<String>,
+    auto_limit: Option<u32>,
+    server_timing: Option<bool>,
+    confirm_production: Option<bool>,
 ) -> Result<QueryResult, String> {
-    let result = QueryEngine::execute_query(
+    ensure_sql_confirmed(&state, &connection_id, &sql, confirm_production).await?;
+    let start = Instant::now();
+    let mut result = QueryEngine::execute_query(
         &state.connection_manager,
         &connection_id,
         &sql,
         page,
         page_size,
+        display_timezone.as_deref(),
+        auto_limit,
     )
     .await;
+
+    // Detailed timing re-runs the query, so it stays opt-in
+    if let (Ok(res), Some(true)) = (&mut result, server_timing) {
+        match QueryEngine::server_timing(&state.connection_manager, &connection_id, &sql).await {
+            Ok(timing) => res.server_timing = timing,
+            Err(e) => eprintln!("Failed to collect server timing: {}", e),
+        }
+    }
+
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let recorded = match &result {
+        Ok(res) => {
+            let row_count = if res.columns.is_empty() {
+                res.affected_rows
+            } else {
+                res.rows.len() as u64
+            };
+            state
+                .query_history
+                .record(&connection_id, &sql, duration_ms, Some(row_count), None)
+                .await
+        }
+        Err(e) => {
+            state
+                .query_history
+                .record(
+                    &connection_id,
+                    &sql,
+                    duration_ms,
+                    None,
+                    Some(&e.to_string()),
+                )
+                .await
+        }
+    };
+    if let Err(e) = recorded {
+        eprintln!("Failed to record query history: {}", e);
+    }
+
     result.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn create_database(
+async fn record_query_history(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    db_name: String,
+    sql: String,
+    duration_ms: u64,
+    row_count: Option<u64>,
+    error: Option<String>,
 ) -> Result<(), String> {
-    QueryEngine::create_database(&state.connection_manager, &connection_id, &db_name)
+    state
+        .query_history
+        .record(
+            &connection_id,
+            &sql,
+            duration_ms,
+            row_count,
+            error.as_deref(),
+        )
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_databases(
+async fn get_query_history(
     state: State<'_, AppState>,
     connection_id: Uuid,
-) -> Result<Vec<String>, String> {
-    QueryEngine::get_databases(&state.connection_manager, &connection_id)
+    limit: Option<u32>,
+) -> Result<Vec<QueryHistoryEntry>, String> {
+    state
+        .query_history
+        .list(&connection_id, limit.unwrap_or(100))
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn switch_database(
+async fn execute_script(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    db_name: String,
-) -> Result<(), String> {
-    state
-        .connection_manager
-        .switch_database(&connection_id, &db_name)
+    sql: String,
+    confirm_production: Option<bool>,
+) -> Result<Vec<QueryResult>, String> {
+    ensure_sql_confirmed(&state, &connection_id, &sql, confirm_production).await?;
+    let results = QueryEngine::execute_script(&state.connection_manager, &connection_id, &sql)
         .await
         .map_err(|e| e.to_string())?;
     invalidate_ai_schema_cache(&state, &connection_id).await;
-    Ok(())
+    Ok(results)
 }
 
 #[tauri::command]
-async fn get_tables(
+async fn execute_parameterized(
     state: State<'_, AppState>,
     connection_id: Uuid,
-) -> Result<Vec<String>, String> {
-    QueryEngine::get_tables(&state.connection_manager, &connection_id)
+    sql: String,
+    params: Vec<serde_json::Value>,
+    confirm_production: Option<bool>,
+) -> Result<QueryResult, String> {
+    ensure_sql_confirmed(&state, &connection_id, &sql, confirm_production).await?;
+    QueryEngine::execute_parameterized(&state.connection_manager, &connection_id, &sql, params)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_sidebar_items(
+async fn begin_transaction(
     state: State<'_, AppState>,
     connection_id: Uuid,
-) -> Result<Vec<SidebarItem>, String> {
-    let items = QueryEngine::get_sidebar_items(&state.connection_manager, &connection_id)
+) -> Result<Uuid, String> {
+    QueryEngine::begin_transaction(&state.connection_manager, &connection_id)
         .await
-        .map_err(|e| e.to_string())?;
-    invalidate_ai_schema_cache(&state, &connection_id).await;
-    Ok(items)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_table_data(
+async fn execute_in_transaction(
+    state: State<'_, AppState>,
+    tx_id: Uuid,
+    sql: String,
+    confirm_production: Option<bool>,
+) -> Result<QueryResult, String> {
+    let connection_id = state
+        .connection_manager
+        .get_transaction_connection(&tx_id)
+        .await
+        .ok_or_else(|| "Transaction not found".to_string())?;
+    ensure_sql_confirmed(&state, &connection_id, &sql, confirm_production).await?;
+    QueryEngine::execute_in_transaction(&state.connection_manager, &tx_id, &sql)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn commit_transaction(state: State<'_, AppState>, tx_id: Uuid) -> Result<(), String> {
+    QueryEngine::commit_transaction(&state.connection_manager, &tx_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn rollback_transaction(state: State<'_, AppState>, tx_id: Uuid) -> Result<(), String> {
+    QueryEngine::rollback_transaction(&state.connection_manager, &tx_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn open_result_cursor(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    table_name: String,
-    limit: u32,
-    offset: u32,
-    filters: Option<Vec<FilterConfig>>,
-    sort_column: Option<String>,
-    sort_direction: Option<String>,
+    sql: String,
+) -> Result<Uuid, String> {
+    QueryEngine::open_result_cursor(&state.connection_manager, &connection_id, &sql)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn fetch_cursor(
+    state: State<'_, AppState>,
+    cursor_id: Uuid,
+    count: u32,
 ) -> Result<QueryResult, String> {
-    let filters = filters.unwrap_or_default();
-    QueryEngine::get_table_data(
-        &state.connection_manager,
-        &connection_id,
-        &table_name,
-        limit,
-        offset,
-        filters,
-        sort_column,
-        sort_direction,
-    )
-    .await
-    .map_err(|e| e.to_string())
+    QueryEngine::fetch_cursor(&state.connection_manager, &cursor_id, count)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_table_count(
+async fn close_cursor(state: State<'_, AppState>, cursor_id: Uuid) -> Result<(), String> {
+    QueryEngine::close_cursor(&state.connection_manager, &cursor_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn export_cell_bytes(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    table_name: String,
-    filters: Option<Vec<FilterConfig>>,
+    sql: String,
+    row_index: usize,
+    column: String,
+    file_path: String,
 ) -> Result<u64, String> {
-    let filters = filters.unwrap_or_default();
-    QueryEngine::get_table_count(
+    QueryEngine::export_cell_bytes(
         &state.connection_manager,
         &connection_id,
-        &table_name,
-        filters,
+        &sql,
+        row_index,
+        &column,
+        &file_path,
     )
     .await
     .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_table_metadata(
+async fn save_query(
     state: State<'_, AppState>,
-    connection_id: Uuid,
-    table_name: String,
-) -> Result<TableMetadata, String> {
-    QueryEngine::get_table_metadata(&state.connection_manager, &connection_id, &table_name)
+    name: String,
+    sql: String,
+    description: Option<String>,
+    connection_id: Option<Uuid>,
+) -> Result<SavedQuery, String> {
+    state
+        .saved_queries
+        .save(&name, &sql, description.as_deref(), connection_id)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn get_table_structure(
+async fn list_saved_queries(
     state: State<'_, AppState>,
-    connection_id: Uuid,
-    table_name: String,
-) -> Result<crate::core::TableStructure, String> {
-    QueryEngine::get_table_structure(&state.connection_manager, &connection_id, &table_name)
+    connection_id: Option<Uuid>,
+) -> Result<Vec<SavedQuery>, String> {
+    state
+        .saved_queries
+        .list(connection_id)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn execute_mutations(
+async fn update_saved_query(
     state: State<'_, AppState>,
-    connection_id: Uuid,
-    statements: Vec<String>,
-) -> Result<u64, String> {
-    let affected_rows =
-        QueryEngine::execute_mutations(&state.connection_manager, &connection_id, statements)
-            .await
-            .map_err(|e| e.to_string())?;
-    invalidate_ai_schema_cache(&state, &connection_id).await;
-    Ok(affected_rows)
+    id: Uuid,
+    name: String,
+    sql: String,
+    description: Option<String>,
+    connection_id: Option<Uuid>,
+) -> Result<SavedQuery, String> {
+    state
+        .saved_queries
+        .update(&id, &name, &sql, description.as_deref(), connection_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn export_table_data(
+async fn delete_saved_query(state: State<'_, AppState>, id: Uuid) -> Result<(), String> {
+    state
+        .saved_queries
+        .delete(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn create_database(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    table_name: String,
-    filters: Option<Vec<FilterConfig>>,
-    sort_column: Option<String>,
-    sort_direction: Option<String>,
-    format: String,
-    file_path: String,
-) -> Result<u64, String> {
-    let filters = filters.unwrap_or_default();
-    QueryEngine::export_table_data(
-        &state.connection_manager,
-        &connection_id,
-        &table_name,
-        filters,
-        sort_column,
-        sort_direction,
-        &format,
-        &file_path,
-    )
-    .await
-    .map_err(|e| e.to_string())
+    db_name: String,
+) -> Result<(), String> {
+    QueryEngine::create_database(&state.connection_manager, &connection_id, &db_name)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn text_to_sql(
+async fn get_databases(
     state: State<'_, AppState>,
     connection_id: Uuid,
-    prompt: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    name_filter: Option<String>,
+) -> Result<Vec<String>, String> {
+    let filter = CatalogFilter {
+        limit,
+        offset,
+        name_filter,
+    };
+    QueryEngine::get_databases(&state.connection_manager, &connection_id, &filter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn attach_database(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    file_path: String,
+    alias: String,
+) -> Result<(), String> {
+    QueryEngine::attach_database(
+        &state.connection_manager,
+        &connection_id,
+        &file_path,
+        &alias,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn detach_database(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    alias: String,
+) -> Result<(), String> {
+    QueryEngine::detach_database(&state.connection_manager, &connection_id, &alias)
+        .await
+        .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_views(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    schema: Option<String>,
+) -> Result<Vec<String>, String> {
+    QueryEngine::get_views(&state.connection_manager, &connection_id, schema)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_view_definition(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    view_name: String,
+) -> Result<String, String> {
+    QueryEngine::get_view_definition(&state.connection_manager, &connection_id, &view_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_foreign_keys(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+) -> Result<TableForeignKeys, String> {
+    QueryEngine::get_foreign_keys(&state.connection_manager, &connection_id, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn validate_sql(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    sql: String,
+) -> Result<SqlValidation, String> {
+    QueryEngine::validate_sql(&state.connection_manager, &connection_id, &sql)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_primary_keys(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+) -> Result<Vec<String>, String> {
+    QueryEngine::get_primary_keys(&state.connection_manager, &connection_id, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_triggers(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+) -> Result<Vec<TableTrigger>, String> {
+    QueryEngine::get_triggers(&state.connection_manager, &connection_id, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn switch_database(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    db_name: String,
+) -> Result<(), String> {
+    state
+        .connection_manager
+        .switch_database(&connection_id, &db_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_tables(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    name_filter: Option<String>,
+) -> Result<Vec<String>, String> {
+    let filter = CatalogFilter {
+        limit,
+        offset,
+        name_filter,
+    };
+    QueryEngine::get_tables(&state.connection_manager, &connection_id, &filter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Streams a query result into a file; cancellable through `cancel_export`
+#[tauri::command]
+async fn export_query_to_file(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    export_id: String,
+    sql: String,
+    format: String,
+    file_path: String,
+    json_null_mode: Option<String>,
+) -> Result<u64, String> {
+    let json_null_mode =
+        JsonNullMode::parse(json_null_mode.as_deref()).map_err(|e| e.to_string())?;
+    let token = CancellationToken::new();
+    state
+        .active_exports
+        .lock()
+        .await
+        .insert(export_id.clone(), token.clone());
+
+    let result = QueryEngine::stream_query_to_file(
+        &state.connection_manager,
+        &connection_id,
+        &sql,
+        &format,
+        &file_path,
+        &json_null_mode,
+        &token,
+    )
+    .await;
+
+    state.active_exports.lock().await.remove(&export_id);
+    result.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_sidebar_items(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    sort_by_usage: Option<bool>,
+) -> Result<Vec<SidebarItem>, String> {
+    let mut items = QueryEngine::get_sidebar_items(&state.connection_manager, &connection_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = state
+        .sidebar_preferences
+        .apply(&connection_id, &mut items, sort_by_usage.unwrap_or(false))
+        .await
+    {
+        eprintln!("Failed to load sidebar preferences: {}", e);
+    }
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(items)
+}
+
+#[tauri::command]
+async fn set_sidebar_item_pinned(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    schema: Option<String>,
+    table_name: String,
+    pinned: bool,
+) -> Result<(), String> {
+    state
+        .sidebar_preferences
+        .set_pinned(&connection_id, schema.as_deref(), &table_name, pinned)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn record_sidebar_item_access(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    schema: Option<String>,
+    table_name: String,
+) -> Result<(), String> {
+    state
+        .sidebar_preferences
+        .record_access(&connection_id, schema.as_deref(), &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_table_data(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    limit: u32,
+    offset: u32,
+    filters: Option<Vec<FilterConfig>>,
+    sort_column: Option<String>,
+    sort_direction: Option<String>,
+) -> Result<QueryResult, String> {
+    let filters = filters.unwrap_or_default();
+    QueryEngine::get_table_data(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        limit,
+        offset,
+        filters,
+        sort_column,
+        sort_direction,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn get_table_page(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    limit: u32,
+    offset: u32,
+    filters: Option<Vec<FilterConfig>>,
+    sort_column: Option<String>,
+    sort_direction: Option<String>,
+) -> Result<QueryResult, String> {
+    let filters = filters.unwrap_or_default();
+    QueryEngine::get_table_page(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        limit,
+        offset,
+        filters,
+        sort_column,
+        sort_direction,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_table_count(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    filters: Option<Vec<FilterConfig>>,
+) -> Result<u64, String> {
+    let filters = filters.unwrap_or_default();
+    QueryEngine::get_table_count(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        filters,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn profile_column(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    column_name: String,
+) -> Result<ColumnProfile, String> {
+    QueryEngine::profile_column(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        &column_name,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_table_sample(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    count: u32,
+) -> Result<QueryResult, String> {
+    QueryEngine::get_table_sample(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        count,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_database_size(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+) -> Result<DatabaseSize, String> {
+    QueryEngine::get_database_size(&state.connection_manager, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_distinct_values(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    column_name: String,
+    limit: Option<u32>,
+) -> Result<Vec<serde_json::Value>, String> {
+    QueryEngine::get_distinct_values(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        &column_name,
+        limit,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_enum_values(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    enum_type_name: String,
+    table_name: Option<String>,
+) -> Result<Vec<String>, String> {
+    QueryEngine::get_enum_values(
+        &state.connection_manager,
+        &connection_id,
+        &enum_type_name,
+        table_name.as_deref(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_sessions(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+) -> Result<Vec<DbSession>, String> {
+    QueryEngine::list_sessions(&state.connection_manager, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn kill_session(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    pid: i64,
+) -> Result<bool, String> {
+    QueryEngine::kill_session(&state.connection_manager, &connection_id, pid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_table_metadata(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+) -> Result<TableMetadata, String> {
+    QueryEngine::get_table_metadata(&state.connection_manager, &connection_id, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_completion_schema(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+) -> Result<BTreeMap<String, Vec<String>>, String> {
+    QueryEngine::get_completion_schema(&state.connection_manager, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_table_structure(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+) -> Result<crate::core::TableStructure, String> {
+    QueryEngine::get_table_structure(&state.connection_manager, &connection_id, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn diff_tables(
+    state: State<'_, AppState>,
+    connection_a: Uuid,
+    table_a: String,
+    connection_b: Uuid,
+    table_b: String,
+) -> Result<crate::core::StructureDiff, String> {
+    let structure_a =
+        QueryEngine::get_table_structure(&state.connection_manager, &connection_a, &table_a)
+            .await
+            .map_err(|e| e.to_string())?;
+    let structure_b =
+        QueryEngine::get_table_structure(&state.connection_manager, &connection_b, &table_b)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(QueryEngine::diff_table_structure(
+        &structure_a,
+        &structure_b,
+    ))
+}
+
+#[tauri::command]
+async fn generate_migration_sql(
+    diff: crate::core::StructureDiff,
+    table_name: String,
+    db_type: String,
+    allow_destructive: Option<bool>,
+) -> Result<String, String> {
+    Ok(QueryEngine::generate_migration_sql(
+        &diff,
+        &table_name,
+        &db_type,
+        allow_destructive.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+async fn execute_mutations(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    statements: Vec<String>,
+    confirm_production: Option<bool>,
+) -> Result<u64, String> {
+    if statements.iter().any(|sql| is_destructive_statement(sql)) {
+        state
+            .connection_manager
+            .ensure_production_confirmed(&connection_id, confirm_production.unwrap_or(false))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let affected_rows =
+        QueryEngine::execute_mutations(&state.connection_manager, &connection_id, statements)
+            .await
+            .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(affected_rows)
+}
+
+#[tauri::command]
+async fn execute_mutations_detailed(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    statements: Vec<String>,
+    confirm_production: Option<bool>,
+) -> Result<Vec<MutationResult>, String> {
+    if statements.iter().any(|sql| is_destructive_statement(sql)) {
+        state
+            .connection_manager
+            .ensure_production_confirmed(&connection_id, confirm_production.unwrap_or(false))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let results = QueryEngine::execute_mutations_detailed(
+        &state.connection_manager,
+        &connection_id,
+        statements,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(results)
+}
+
+#[tauri::command]
+async fn truncate_table(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    confirm: bool,
+    confirm_production: Option<bool>,
+) -> Result<u64, String> {
+    QueryEngine::truncate_table(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        confirm,
+        confirm_production.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn maintain_table(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    op: String,
+) -> Result<Vec<String>, String> {
+    QueryEngine::maintain_table(&state.connection_manager, &connection_id, &table_name, &op)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn drop_table(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    cascade: bool,
+    confirm_production: Option<bool>,
+) -> Result<(), String> {
+    QueryEngine::drop_table(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        cascade,
+        confirm_production.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn duplicate_row(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    pk_columns: Vec<String>,
+    pk_values: Vec<serde_json::Value>,
+    overrides: Option<BTreeMap<String, serde_json::Value>>,
+) -> Result<Option<i64>, String> {
+    QueryEngine::duplicate_row(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        pk_columns,
+        pk_values,
+        overrides.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn rename_table(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    old_name: String,
+    new_name: String,
+) -> Result<(), String> {
+    QueryEngine::rename_table(
+        &state.connection_manager,
+        &connection_id,
+        &old_name,
+        &new_name,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn alter_column(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    column_name: String,
+    new_name: Option<String>,
+    new_type: Option<String>,
+    nullable: Option<bool>,
+    confirm_production: Option<bool>,
+) -> Result<(), String> {
+    QueryEngine::alter_column(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        &column_name,
+        new_name,
+        new_type,
+        nullable,
+        confirm_production.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn add_column(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    column_name: String,
+    data_type: String,
+    nullable: Option<bool>,
+    default: Option<serde_json::Value>,
+) -> Result<(), String> {
+    QueryEngine::add_column(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        &column_name,
+        &data_type,
+        nullable.unwrap_or(true),
+        default,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn drop_column(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    column_name: String,
+    confirm_production: Option<bool>,
+) -> Result<(), String> {
+    QueryEngine::drop_column(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        &column_name,
+        confirm_production.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn create_index(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    columns: Vec<String>,
+    unique: bool,
+    index_name: Option<String>,
+    method: Option<String>,
+) -> Result<String, String> {
+    let name = QueryEngine::create_index(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        columns,
+        unique,
+        index_name,
+        method,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    invalidate_ai_schema_cache(&state, &connection_id).await;
+    Ok(name)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn export_table_data(
+    window: Window,
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    table_name: String,
+    columns: Option<Vec<String>>,
+    filters: Option<Vec<FilterConfig>>,
+    sort_column: Option<String>,
+    sort_direction: Option<String>,
+    format: String,
+    file_path: String,
+    delimiter: Option<char>,
+    excel_bom: Option<bool>,
+    export_id: Option<String>,
+    json_null_mode: Option<String>,
+    csv_quoting: Option<CsvQuoting>,
+) -> Result<u64, String> {
+    let filters = filters.unwrap_or_default();
+    let json_null_mode =
+        JsonNullMode::parse(json_null_mode.as_deref()).map_err(|e| e.to_string())?;
+    // Progress is only reported when the caller gives the export an id to listen for
+    let emit_progress = |rows_exported: u64, total_rows: u64, status: &str| {
+        if let Some(export_id) = &export_id {
+            let _ = window.emit(
+                "export-progress",
+                ExportProgress {
+                    export_id: export_id.clone(),
+                    current_table: table_name.clone(),
+                    rows_exported,
+                    total_rows: Some(total_rows),
+                    percentage: export_percentage(rows_exported, Some(total_rows)),
+                    status: status.to_string(),
+                    error: None,
+                    manifest_path: None,
+                },
+            );
+        }
+    };
+    let on_progress = |rows_exported: u64, total_rows: u64| {
+        emit_progress(rows_exported, total_rows, "processing")
+    };
+
+    let rows = QueryEngine::export_table_data(
+        &state.connection_manager,
+        &connection_id,
+        &table_name,
+        columns.as_deref(),
+        filters,
+        sort_column,
+        sort_direction,
+        &format,
+        &file_path,
+        delimiter,
+        excel_bom.unwrap_or(false),
+        &csv_quoting.unwrap_or_default(),
+        &json_null_mode,
+        Some(&on_progress),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    emit_progress(rows, rows, "complete");
+    Ok(rows)
+}
+
+#[tauri::command]
+async fn text_to_sql(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    prompt: String,
+    max_schema_tables: Option<usize>,
 ) -> Result<String, String> {
+    generate_ai_sql(&state, connection_id, &prompt, max_schema_tables)
+        .await
+        .map(|result| result.sql)
+}
+
+/// Like `text_to_sql`, but also reports the model and schema context that were used
+#[tauri::command]
+async fn text_to_sql_detailed(
+    state: State<'_, AppState>,
+    connection_id: Uuid,
+    prompt: String,
+    max_schema_tables: Option<usize>,
+) -> Result<AiResult, String> {
+    generate_ai_sql(&state, connection_id, &prompt, max_schema_tables).await
+}
+
+async fn generate_ai_sql(
+    state: &AppState,
+    connection_id: Uuid,
+    prompt: &str,
+    max_schema_tables: Option<usize>,
+) -> Result<AiResult, String> {
     // Read API key from environment
     let api_key = std::env::var("YOUR_GROQ_API_KEY")
         .map_err(|_| "Groq API key not found. Set YOUR_GROQ_API_KEY in .env file".to_string())?;
@@ -555,11 +1637,31 @@ async fn text_to_sql(
     }
     .to_string();
 
-    let schema_tables = get_cached_ai_schema(&state, &connection_id).await?;
-    let schema_context = build_schema_context(&schema_tables, &prompt);
+    let schema_tables = get_cached_ai_schema(state, &connection_id).await?;
+    let max_tables = max_schema_tables.unwrap_or(DEFAULT_AI_SCHEMA_TABLES);
+    let (schema_context, schema_tables_used) =
+        build_schema_context(&schema_tables, prompt, max_tables);
 
     // Call Gemini API
-    ai_service::generate_sql(&api_key, &prompt, &schema_context, &db_type).await
+    let sql = ai_service::generate_sql(
+        &api_key,
+        prompt,
+        &schema_context,
+        &db_type,
+        &ai_service::RetryPolicy::default(),
+    )
+    .await?;
+
+    Ok(AiResult {
+        sql,
+        model: ai_service::GROQ_MODEL.to_string(),
+        prompt_tokens_estimate: ai_service::estimate_prompt_tokens(
+            prompt,
+            &schema_context,
+            &db_type,
+        ),
+        schema_tables_used,
+    })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -567,10 +1669,28 @@ pub fn run() {
     dotenvy::dotenv().ok();
 
     let connection_manager = Arc::new(ConnectionManager::new());
+    let (query_history, saved_queries, sidebar_preferences) =
+        tauri::async_runtime::block_on(async {
+            let pool = db::open_app_db().await?;
+            let history = QueryHistoryStore::new(pool.clone()).await?;
+            let saved = SavedQueryStore::new(pool.clone()).await?;
+            let sidebar = SidebarPreferenceStore::new(pool).await?;
+            anyhow::Ok((history, saved, sidebar))
+        })
+        .expect("failed to open the SqlMate app database");
+    let connection_configs =
+        ConnectionConfigStore::new().expect("failed to open the SqlMate connection store");
+
     let state = AppState {
         connection_manager: connection_manager.clone(),
         active_queries: Arc::new(Mutex::new(HashMap::new())),
         ai_schema_cache: Arc::new(Mutex::new(HashMap::new())),
+        query_history: Arc::new(query_history),
+        saved_queries: Arc::new(saved_queries),
+        sidebar_preferences: Arc::new(sidebar_preferences),
+        connection_configs: Arc::new(connection_configs),
+        active_imports: Arc::new(Mutex::new(HashMap::new())),
+        active_exports: Arc::new(Mutex::new(HashMap::new())),
     };
 
     tauri::Builder::default()
@@ -579,27 +1699,149 @@ pub fn run() {
         .manage(state)
         .invoke_handler(tauri::generate_handler![
             connect,
+            save_connection_config,
+            list_connection_configs,
+            delete_connection_config,
             test_connection,
+            open_tunnel,
+            list_tunnels,
+            close_tunnel,
+            ping_connection,
+            get_server_info,
             execute_query,
+            execute_script,
+            execute_parameterized,
+            begin_transaction,
+            execute_in_transaction,
+            commit_transaction,
+            rollback_transaction,
+            open_result_cursor,
+            fetch_cursor,
+            close_cursor,
+            export_cell_bytes,
+            record_query_history,
+            get_query_history,
+            save_query,
+            list_saved_queries,
+            update_saved_query,
+            delete_saved_query,
             execute_query_streaming,
             cancel_query,
             create_database,
             switch_database,
             get_databases,
+            attach_database,
+            detach_database,
+            get_views,
+            get_view_definition,
+            get_triggers,
+            get_foreign_keys,
+            get_primary_keys,
+            validate_sql,
             get_tables,
             get_table_data,
+            get_table_page,
             get_table_count,
+            get_table_sample,
+            profile_column,
             get_table_metadata,
+            get_database_size,
+            get_distinct_values,
+            get_enum_values,
+            list_sessions,
+            kill_session,
             get_table_structure,
+            get_completion_schema,
+            diff_tables,
+            generate_migration_sql,
             get_sidebar_items,
+            set_sidebar_item_pinned,
+            record_sidebar_item_access,
             execute_mutations,
+            execute_mutations_detailed,
+            truncate_table,
+            drop_table,
+            maintain_table,
+            duplicate_row,
+            rename_table,
+            alter_column,
+            add_column,
+            drop_column,
+            create_index,
             export_table_data,
+            export_query_to_file,
             text_to_sql,
+            text_to_sql_detailed,
             importer::csv_importer::preview_csv,
             importer::csv_importer::import_csv,
+            importer::sql_importer::preview_sql_dump,
             importer::sql_importer::import_sql_dump,
-            exporter::exporter::export_data
+            importer::cancel_import,
+            exporter::exporter::export_data,
+            exporter::exporter::cancel_export
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TableColumnStructure;
+
+    /// A table with an `id` primary key plus the given text columns
+    fn ai_table(name: &str, columns: &[&str]) -> AiSchemaTable {
+        let column = |name: &str, is_primary_key| TableColumnStructure {
+            name: name.to_string(),
+            data_type: if is_primary_key { "integer" } else { "text" }.to_string(),
+            is_nullable: !is_primary_key,
+            default_value: None,
+            is_primary_key,
+            comment: None,
+        };
+        AiSchemaTable {
+            name: name.to_string(),
+            schema: Some("public".to_string()),
+            item_type: SidebarItemType::Table,
+            columns: std::iter::once(column("id", true))
+                .chain(columns.iter().map(|c| column(c, false)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn schema_context_lists_the_tables_it_describes() {
+        let tables = [
+            ai_table("customers", &["name"]),
+            ai_table("orders", &["customer_id", "total"]),
+            ai_table("products", &["sku"]),
+        ];
+
+        let (context, used) = build_schema_context(&tables, "total orders per customer", 2);
+
+        assert_eq!(used, ["orders", "customers"]);
+        assert!(context.contains("TABLE orders:\n  id integer PRIMARY KEY NOT NULL"));
+        assert!(context.contains("TABLE customers:"));
+        assert!(!context.contains("TABLE products:"));
+    }
+
+    #[test]
+    fn a_table_named_in_the_prompt_survives_the_table_limit() {
+        let mut tables: Vec<AiSchemaTable> = (1..60)
+            .map(|n| ai_table(&format!("archive_{:02}", n), &["payload"]))
+            .collect();
+        tables.push(ai_table("warehouses", &["city"]));
+        assert_eq!(tables[59].name, "warehouses");
+
+        let (context, used) = build_schema_context(
+            &tables,
+            "How many warehouses are in each city?",
+            DEFAULT_AI_SCHEMA_TABLES,
+        );
+
+        assert_eq!(used, ["warehouses"]);
+        assert!(context
+            .contains("TABLE warehouses:\n  id integer PRIMARY KEY NOT NULL\n  city text NULL"));
+        assert!(context.starts_with("AVAILABLE TABLES/VIEWS (60 total):"));
+    }
+}
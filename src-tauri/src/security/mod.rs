@@ -25,4 +25,56 @@ impl SecureStore {
             .delete_password()
             .map_err(|e| anyhow!("Failed to delete password: {}", e))
     }
+
+    pub fn save_ssh_password(connection_id: &Uuid, password: &str) -> Result<()> {
+        let entry = Entry::new("com.sqlmate.db", &format!("{}-ssh", connection_id))?;
+        entry
+            .set_password(password)
+            .map_err(|e| anyhow!("Failed to save SSH password: {}", e))
+    }
+
+    pub fn get_ssh_password(connection_id: &Uuid) -> Result<String> {
+        let entry = Entry::new("com.sqlmate.db", &format!("{}-ssh", connection_id))?;
+        entry
+            .get_password()
+            .map_err(|e| anyhow!("Failed to get SSH password: {}", e))
+    }
+
+    pub fn delete_ssh_password(connection_id: &Uuid) -> Result<()> {
+        let entry = Entry::new("com.sqlmate.db", &format!("{}-ssh", connection_id))?;
+        entry
+            .delete_password()
+            .map_err(|e| anyhow!("Failed to delete SSH password: {}", e))
+    }
+
+    /// Jump hosts are keyed by their position in `ssh_jump_hosts`
+    pub fn save_ssh_hop_password(connection_id: &Uuid, hop: usize, password: &str) -> Result<()> {
+        let entry = Entry::new(
+            "com.sqlmate.db",
+            &format!("{}-ssh-hop-{}", connection_id, hop),
+        )?;
+        entry
+            .set_password(password)
+            .map_err(|e| anyhow!("Failed to save SSH jump host password: {}", e))
+    }
+
+    pub fn get_ssh_hop_password(connection_id: &Uuid, hop: usize) -> Result<String> {
+        let entry = Entry::new(
+            "com.sqlmate.db",
+            &format!("{}-ssh-hop-{}", connection_id, hop),
+        )?;
+        entry
+            .get_password()
+            .map_err(|e| anyhow!("Failed to get SSH jump host password: {}", e))
+    }
+
+    pub fn delete_ssh_hop_password(connection_id: &Uuid, hop: usize) -> Result<()> {
+        let entry = Entry::new(
+            "com.sqlmate.db",
+            &format!("{}-ssh-hop-{}", connection_id, hop),
+        )?;
+        entry
+            .delete_password()
+            .map_err(|e| anyhow!("Failed to delete SSH jump host password: {}", e))
+    }
 }
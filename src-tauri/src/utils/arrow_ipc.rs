@@ -0,0 +1,256 @@
+use arrow::array::{ArrayRef, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Maps a driver type name (as reported in `column_types`) to the Arrow type used for it.
+/// Anything without an exact numeric or boolean equivalent, such as DECIMAL or
+/// BIGINT UNSIGNED, is sent as text so no precision is lost.
+pub fn arrow_type(type_name: &str) -> DataType {
+    match type_name.to_uppercase().as_str() {
+        "INT2" | "INT4" | "INT8" | "SMALLINT" | "INT" | "INTEGER" | "BIGINT" | "TINYINT"
+        | "MEDIUMINT" | "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED"
+        | "INT UNSIGNED" => DataType::Int64,
+        "FLOAT4" | "FLOAT8" | "REAL" | "FLOAT" | "DOUBLE" => DataType::Float64,
+        "BOOL" | "BOOLEAN" => DataType::Boolean,
+        _ => DataType::Utf8,
+    }
+}
+
+/// Writes one Arrow IPC stream across many batches: the schema message comes out with
+/// the first batch, every later chunk holds only a record batch, and `finish` returns the
+/// end-of-stream marker. Concatenating the chunks in order gives a complete stream.
+pub struct ArrowStreamEncoder {
+    schema: SchemaRef,
+    writer: StreamWriter<Vec<u8>>,
+}
+
+impl ArrowStreamEncoder {
+    pub fn new(columns: &[String], column_types: &[String]) -> anyhow::Result<Self> {
+        let fields: Vec<Field> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let data_type = column_types
+                    .get(i)
+                    .map_or(DataType::Utf8, |t| arrow_type(t));
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let writer = StreamWriter::try_new(Vec::new(), &schema)?;
+        Ok(Self { schema, writer })
+    }
+
+    /// An empty batch with one builder per column of the schema
+    pub fn batch_builder(&self, capacity: usize) -> ArrowBatchBuilder {
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| match field.data_type() {
+                DataType::Int64 => ColumnBuilder::Int64(Int64Builder::with_capacity(capacity)),
+                DataType::Float64 => {
+                    ColumnBuilder::Float64(Float64Builder::with_capacity(capacity))
+                }
+                DataType::Boolean => {
+                    ColumnBuilder::Boolean(BooleanBuilder::with_capacity(capacity))
+                }
+                _ => ColumnBuilder::Utf8(StringBuilder::new()),
+            })
+            .collect();
+        ArrowBatchBuilder { columns, rows: 0 }
+    }
+
+    /// Encodes a batch, returning the bytes written since the previous call
+    pub fn encode(&mut self, mut batch: ArrowBatchBuilder) -> anyhow::Result<Vec<u8>> {
+        let arrays: Vec<ArrayRef> = batch
+            .columns
+            .iter_mut()
+            .map(ColumnBuilder::finish)
+            .collect();
+        // The explicit row count keeps zero-column results (`SELECT FROM t`) valid
+        let batch = RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            arrays,
+            &RecordBatchOptions::new().with_row_count(Some(batch.rows)),
+        )?;
+        self.writer.write(&batch)?;
+        Ok(std::mem::take(self.writer.get_mut()))
+    }
+
+    /// The end-of-stream marker, plus the schema if no batch was ever encoded
+    pub fn finish(mut self) -> anyhow::Result<Vec<u8>> {
+        self.writer.finish()?;
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+enum ColumnBuilder {
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Boolean(BooleanBuilder),
+    Utf8(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Int64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Float64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Boolean(b) => Arc::new(b.finish()),
+            ColumnBuilder::Utf8(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// Rows of one record batch, filled cell by cell straight from the driver's rows.
+///
+/// The typed appends are used when a cell decodes to the column's Arrow type; anything
+/// else goes through `append_value`. Values that cannot be represented in their column's
+/// type are written as null, which only happens when SQLite stores a value that does not
+/// match the declared type.
+pub struct ArrowBatchBuilder {
+    columns: Vec<ColumnBuilder>,
+    rows: usize,
+}
+
+impl ArrowBatchBuilder {
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    pub fn data_type(&self, column: usize) -> DataType {
+        match self.columns.get(column) {
+            Some(ColumnBuilder::Int64(_)) => DataType::Int64,
+            Some(ColumnBuilder::Float64(_)) => DataType::Float64,
+            Some(ColumnBuilder::Boolean(_)) => DataType::Boolean,
+            _ => DataType::Utf8,
+        }
+    }
+
+    pub fn append_i64(&mut self, column: usize, value: i64) {
+        match self.columns.get_mut(column) {
+            Some(ColumnBuilder::Int64(b)) => b.append_value(value),
+            _ => self.append_value(column, Value::from(value)),
+        }
+    }
+
+    pub fn append_f64(&mut self, column: usize, value: f64) {
+        match self.columns.get_mut(column) {
+            Some(ColumnBuilder::Float64(b)) => b.append_value(value),
+            _ => self.append_value(column, Value::from(value)),
+        }
+    }
+
+    pub fn append_bool(&mut self, column: usize, value: bool) {
+        match self.columns.get_mut(column) {
+            Some(ColumnBuilder::Boolean(b)) => b.append_value(value),
+            _ => self.append_value(column, Value::from(value)),
+        }
+    }
+
+    /// Appends a cell already converted to the JSON representation used for result grids
+    pub fn append_value(&mut self, column: usize, value: Value) {
+        match self.columns.get_mut(column) {
+            Some(ColumnBuilder::Int64(b)) => b.append_option(
+                value
+                    .as_i64()
+                    .or_else(|| value.as_bool().map(i64::from))
+                    .or_else(|| value.as_str().and_then(|s| s.parse().ok())),
+            ),
+            Some(ColumnBuilder::Float64(b)) => b.append_option(
+                value
+                    .as_f64()
+                    .or_else(|| value.as_str().and_then(|s| s.parse().ok())),
+            ),
+            Some(ColumnBuilder::Boolean(b)) => {
+                b.append_option(value.as_bool().or_else(|| value.as_i64().map(|n| n != 0)))
+            }
+            Some(ColumnBuilder::Utf8(b)) => match value {
+                Value::Null => b.append_null(),
+                Value::String(s) => b.append_value(s),
+                other => b.append_value(other.to_string()),
+            },
+            None => {}
+        }
+    }
+
+    /// Marks the current row as complete; every column must have been appended to
+    pub fn end_row(&mut self) {
+        self.rows += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::{Array, BooleanArray, Float64Array, Int64Array, StringArray};
+    use arrow::ipc::reader::StreamReader;
+
+    #[test]
+    fn emitted_batches_decode_back_into_typed_columns() {
+        let columns = ["id", "score", "active", "name"].map(String::from);
+        let types = ["INT8", "FLOAT8", "BOOL", "TEXT"].map(String::from);
+        let mut encoder = ArrowStreamEncoder::new(&columns, &types).unwrap();
+
+        let mut stream = Vec::new();
+        for (id, name) in [(1, Some("one")), (2, None)] {
+            let mut batch = encoder.batch_builder(1);
+            batch.append_i64(0, id);
+            batch.append_f64(1, id as f64 / 2.0);
+            batch.append_bool(2, id % 2 == 1);
+            batch.append_value(3, name.map_or(Value::Null, Value::from));
+            batch.end_row();
+            stream.extend(encoder.encode(batch).unwrap());
+        }
+        stream.extend(encoder.finish().unwrap());
+
+        let reader = StreamReader::try_new(stream.as_slice(), None).unwrap();
+        let schema = reader.schema();
+        let types: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|f| f.data_type().clone())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                DataType::Int64,
+                DataType::Float64,
+                DataType::Boolean,
+                DataType::Utf8
+            ]
+        );
+        let (mut ids, mut scores, mut active, mut names) = (vec![], vec![], vec![], vec![]);
+        for batch in reader {
+            let batch = batch.unwrap();
+            assert_eq!(batch.num_rows(), 1);
+            let column = |i: usize| batch.column(i).as_any();
+            ids.push(column(0).downcast_ref::<Int64Array>().unwrap().value(0));
+            scores.push(column(1).downcast_ref::<Float64Array>().unwrap().value(0));
+            active.push(column(2).downcast_ref::<BooleanArray>().unwrap().value(0));
+            let name = column(3).downcast_ref::<StringArray>().unwrap();
+            names.push((!name.is_null(0)).then(|| name.value(0).to_string()));
+        }
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(scores, [0.5, 1.0]);
+        assert_eq!(active, [true, false]);
+        assert_eq!(names, [Some("one".to_string()), None]);
+    }
+
+    #[test]
+    fn unknown_and_decimal_types_travel_as_text() {
+        assert_eq!(arrow_type("int4"), DataType::Int64);
+        assert_eq!(arrow_type("DOUBLE"), DataType::Float64);
+        assert_eq!(arrow_type("NUMERIC"), DataType::Utf8);
+        assert_eq!(arrow_type("BIGINT UNSIGNED"), DataType::Utf8);
+    }
+}
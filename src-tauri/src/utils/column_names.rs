@@ -0,0 +1,40 @@
+use std::collections::HashSet;
+
+/// Makes result column names usable as JSON object keys by suffixing repeats
+/// (`id`, `id` -> `id`, `id_2`). A suffix never takes a name another column already has.
+pub fn unique_column_names(columns: &[String]) -> Vec<String> {
+    let original: HashSet<&str> = columns.iter().map(String::as_str).collect();
+    let mut used: HashSet<String> = HashSet::with_capacity(columns.len());
+
+    columns
+        .iter()
+        .map(|name| {
+            let mut unique = name.clone();
+            let mut n = 2;
+            while used.contains(&unique) || (unique != *name && original.contains(unique.as_str()))
+            {
+                unique = format!("{}_{}", name, n);
+                n += 1;
+            }
+            used.insert(unique.clone());
+            unique
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(columns: &[&str]) -> Vec<String> {
+        unique_column_names(&columns.iter().map(|c| c.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn repeated_names_get_numbered_suffixes() {
+        assert_eq!(names(&["id", "name"]), ["id", "name"]);
+        assert_eq!(names(&["id", "id", "id"]), ["id", "id_2", "id_3"]);
+        // A suffix skips names that other columns already use
+        assert_eq!(names(&["id", "id", "id_2"]), ["id", "id_3", "id_2"]);
+    }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+/// When fields are wrapped in the quote character
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvQuoteStyle {
+    Always,
+    /// Only fields containing the delimiter, quote or a line break
+    #[default]
+    Necessary,
+    /// Never quote on export; on import quotes are read as plain characters
+    Never,
+}
+
+/// Quoting and escaping shared by CSV import and export.
+///
+/// Without `escape_char` an embedded quote is doubled (`""`); with one set
+/// it is escaped instead, e.g. `\"` for `'\\'`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvQuoting {
+    #[serde(default)]
+    pub quote_char: Option<char>, // defaults to '"'
+    #[serde(default)]
+    pub quote_style: CsvQuoteStyle,
+    #[serde(default)]
+    pub escape_char: Option<char>,
+}
+
+impl CsvQuoting {
+    fn quote(&self) -> u8 {
+        self.quote_char.unwrap_or('"') as u8
+    }
+
+    pub fn configure_reader<'a>(
+        &self,
+        builder: &'a mut csv::ReaderBuilder,
+    ) -> &'a mut csv::ReaderBuilder {
+        builder
+            .quote(self.quote())
+            .quoting(self.quote_style != CsvQuoteStyle::Never)
+            .double_quote(self.escape_char.is_none())
+            .escape(self.escape_char.map(|c| c as u8))
+    }
+
+    pub fn configure_writer<'a>(
+        &self,
+        builder: &'a mut csv::WriterBuilder,
+    ) -> &'a mut csv::WriterBuilder {
+        builder
+            .quote(self.quote())
+            .quote_style(match self.quote_style {
+                CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+                CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+                CsvQuoteStyle::Never => csv::QuoteStyle::Never,
+            })
+            .double_quote(self.escape_char.is_none());
+        if let Some(escape) = self.escape_char {
+            builder.escape(escape as u8);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRICKY: [[&str; 3]; 2] = [["plain", "a, b", "say \"hi\""], ["multi\nline", "it's", ""]];
+
+    fn write(quoting: &CsvQuoting, records: &[[&str; 3]]) -> String {
+        let mut writer = quoting
+            .configure_writer(&mut csv::WriterBuilder::new())
+            .from_writer(Vec::new());
+        for record in records {
+            writer.write_record(record).unwrap();
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    fn read(quoting: &CsvQuoting, text: &str) -> Vec<Vec<String>> {
+        quoting
+            .configure_reader(csv::ReaderBuilder::new().has_headers(false))
+            .from_reader(text.as_bytes())
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn quoted_styles_round_trip_commas_quotes_and_newlines() {
+        let styles = [
+            CsvQuoting::default(),
+            CsvQuoting {
+                quote_style: CsvQuoteStyle::Always,
+                ..Default::default()
+            },
+            CsvQuoting {
+                escape_char: Some('\\'),
+                ..Default::default()
+            },
+            CsvQuoting {
+                quote_char: Some('\''),
+                quote_style: CsvQuoteStyle::Always,
+                ..Default::default()
+            },
+        ];
+        for quoting in styles {
+            let text = write(&quoting, &TRICKY);
+            assert_eq!(read(&quoting, &text), TRICKY, "{:?}: {}", quoting, text);
+        }
+    }
+
+    #[test]
+    fn each_style_writes_its_own_shape() {
+        let row = [["1", "a, b", "say \"hi\""]];
+        let write_with = |quote_style, escape_char| {
+            let quoting = CsvQuoting {
+                quote_style,
+                escape_char,
+                ..Default::default()
+            };
+            write(&quoting, &row)
+        };
+        assert_eq!(
+            write_with(CsvQuoteStyle::Necessary, None),
+            "1,\"a, b\",\"say \"\"hi\"\"\"\n"
+        );
+        assert_eq!(
+            write_with(CsvQuoteStyle::Always, None),
+            "\"1\",\"a, b\",\"say \"\"hi\"\"\"\n"
+        );
+        assert_eq!(
+            write_with(CsvQuoteStyle::Necessary, Some('\\')),
+            "1,\"a, b\",\"say \\\"hi\\\"\"\n"
+        );
+        assert_eq!(
+            write_with(CsvQuoteStyle::Never, None),
+            "1,a, b,say \"hi\"\n"
+        );
+    }
+
+    #[test]
+    fn unquoted_imports_keep_quote_characters() {
+        let quoting = CsvQuoting {
+            quote_style: CsvQuoteStyle::Never,
+            ..Default::default()
+        };
+        assert_eq!(read(&quoting, "5\" pipe,\"x\"\n"), [["5\" pipe", "\"x\""]]);
+    }
+}
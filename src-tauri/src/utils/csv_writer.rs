@@ -0,0 +1,47 @@
+use crate::utils::CsvQuoting;
+use std::io::Write;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Builds a CSV writer with an optional custom delimiter and the given quoting.
+///
+/// When `excel_bom` is set a UTF-8 byte order mark is written first so Excel
+/// detects the encoding instead of assuming the system code page.
+pub fn csv_writer<W: Write>(
+    mut inner: W,
+    delimiter: Option<char>,
+    excel_bom: bool,
+    quoting: &CsvQuoting,
+) -> std::io::Result<csv::Writer<W>> {
+    if excel_bom {
+        inner.write_all(UTF8_BOM)?;
+    }
+    Ok(quoting
+        .configure_writer(csv::WriterBuilder::new().delimiter(delimiter.unwrap_or(',') as u8))
+        .from_writer(inner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(delimiter: Option<char>, excel_bom: bool) -> Vec<u8> {
+        let mut wtr = csv_writer(Vec::new(), delimiter, excel_bom, &CsvQuoting::default()).unwrap();
+        wtr.write_record(["id", "price"]).unwrap();
+        wtr.write_record(["1", "3,50"]).unwrap();
+        wtr.into_inner().unwrap()
+    }
+
+    #[test]
+    fn semicolon_delimiter_leaves_decimal_commas_unquoted() {
+        assert_eq!(write(Some(';'), false), b"id;price\n1;3,50\n");
+        assert_eq!(write(None, false), b"id,price\n1,\"3,50\"\n");
+    }
+
+    #[test]
+    fn excel_bom_comes_before_the_header() {
+        let output = write(Some(';'), true);
+        assert!(output.starts_with(UTF8_BOM));
+        assert_eq!(&output[UTF8_BOM.len()..], b"id;price\n1;3,50\n");
+    }
+}
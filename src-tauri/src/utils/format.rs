@@ -0,0 +1,54 @@
+const BYTE_UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+/// Formats a byte count for display, e.g. "512 B", "1.5 MB".
+///
+/// Uses 1024-based units with one decimal place, dropping a trailing ".0".
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    // Promote values that would round up to "1024.0" of the current unit
+    while value >= 1023.95 && unit < BYTE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    let formatted = format!("{:.1}", value);
+    let formatted = formatted.strip_suffix(".0").unwrap_or(&formatted);
+    format!("{} {}", formatted, BYTE_UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_below_one_kilobyte_stay_in_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+    }
+
+    #[test]
+    fn larger_sizes_use_one_decimal_in_the_largest_whole_unit() {
+        assert_eq!(format_bytes(1024), "1 KB");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(1024 * 1024 - 1), "1 MB");
+        assert_eq!(format_bytes(1024 * 1024), "1 MB");
+        assert_eq!(format_bytes(1024 * 1024 * 3 / 2), "1.5 MB");
+        assert_eq!(format_bytes(1024 * 1024 * 1024 - 1), "1 GB");
+        assert_eq!(format_bytes(1024 * 1024 * 1024), "1 GB");
+        assert_eq!(
+            format_bytes(10 * 1024 * 1024 * 1024 + 1024 * 1024 * 1024 / 2),
+            "10.5 GB"
+        );
+    }
+
+    #[test]
+    fn sizes_past_petabytes_stay_in_petabytes() {
+        assert_eq!(format_bytes(u64::MAX), "16384 PB");
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+/// How SQL NULLs are written into JSON export objects
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum JsonNullMode {
+    /// `"col": null`
+    #[default]
+    Null,
+    /// The key is left out of the object
+    Omit,
+    /// `"col": "<text>"`
+    Sentinel(String),
+}
+
+impl JsonNullMode {
+    /// Parses `"null"`, `"omit"` or `"sentinel:<text>"`; None means `"null"`
+    pub fn parse(mode: Option<&str>) -> Result<Self> {
+        match mode {
+            None | Some("null") => Ok(Self::Null),
+            Some("omit") => Ok(Self::Omit),
+            Some(mode) => mode
+                .strip_prefix("sentinel:")
+                .map(|text| Self::Sentinel(text.to_string()))
+                .ok_or_else(|| anyhow!("Unknown JSON null mode '{}'", mode)),
+        }
+    }
+
+    /// Adds `value` to `obj` under `key`, applying the mode when it is NULL
+    pub fn insert(&self, obj: &mut Map<String, Value>, key: String, value: Value) {
+        match (value, self) {
+            (Value::Null, Self::Omit) => {}
+            (Value::Null, Self::Sentinel(text)) => {
+                obj.insert(key, Value::String(text.clone()));
+            }
+            (value, _) => {
+                obj.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn row(mode: &JsonNullMode) -> Value {
+        let mut obj = Map::new();
+        mode.insert(&mut obj, "id".into(), json!(1));
+        mode.insert(&mut obj, "email".into(), Value::Null);
+        Value::Object(obj)
+    }
+
+    #[test]
+    fn each_mode_shapes_a_null_column() {
+        let parse = |mode| JsonNullMode::parse(mode).unwrap();
+        assert_eq!(row(&parse(None)), json!({ "id": 1, "email": null }));
+        assert_eq!(row(&parse(Some("null"))), json!({ "id": 1, "email": null }));
+        assert_eq!(row(&parse(Some("omit"))), json!({ "id": 1 }));
+        assert_eq!(
+            row(&parse(Some("sentinel:\\N"))),
+            json!({ "id": 1, "email": "\\N" })
+        );
+        assert!(JsonNullMode::parse(Some("drop")).is_err());
+    }
+}
@@ -1 +1,14 @@
+pub mod arrow_ipc;
+pub mod column_names;
+pub mod csv_dialect;
+pub mod csv_writer;
+pub mod format;
+pub mod json_null;
+pub mod sql_ident;
+pub mod sql_splitter;
 
+pub use column_names::unique_column_names;
+pub use csv_dialect::CsvQuoting;
+pub use format::format_bytes;
+pub use json_null::JsonNullMode;
+pub use sql_ident::{quote_ident, validate_identifier, validate_type_name};
@@ -0,0 +1,121 @@
+/// Quotes an identifier for the given backend, escaping embedded quote characters.
+///
+/// MySQL uses backticks; Postgres and SQLite use standard double quotes.
+pub fn quote_ident(name: &str, db_type: &str) -> String {
+    match db_type {
+        "mysql" => format!("`{}`", name.replace('`', "``")),
+        _ => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Rejects identifiers that cannot be safely quoted into a statement.
+pub fn validate_identifier(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Identifier must not be empty"));
+    }
+    if name.contains(['\0', '\n', '\r']) {
+        return Err(anyhow::anyhow!(
+            "Identifier {:?} contains a null byte or line break",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects column types that could smuggle extra SQL, since types can't be quoted.
+///
+/// Allows names, sizes and array brackets such as `numeric(10, 2)`,
+/// `timestamp with time zone` or `int[]`. Brackets must balance and commas may only
+/// separate arguments inside parentheses, so a type can't end one column and start another.
+pub fn validate_type_name(data_type: &str) -> anyhow::Result<()> {
+    let invalid = || anyhow::anyhow!("Invalid column type {:?}", data_type);
+    let allowed = |c: char| c.is_ascii_alphanumeric() || " _(),.[]".contains(c);
+    if data_type.trim().is_empty() || !data_type.chars().all(allowed) {
+        return Err(invalid());
+    }
+    let mut open = Vec::new();
+    for c in data_type.chars() {
+        match c {
+            '(' | '[' => open.push(c),
+            ')' if open.pop() != Some('(') => return Err(invalid()),
+            ']' if open.pop() != Some('[') => return Err(invalid()),
+            ',' if open.last() != Some(&'(') => return Err(invalid()),
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_names_accept_sizes_and_arrays() {
+        for data_type in [
+            "integer",
+            "numeric(10, 2)",
+            "timestamp with time zone",
+            "int[]",
+            "varchar(255)[]",
+            "decimal(10,2) unsigned",
+        ] {
+            assert!(validate_type_name(data_type).is_ok(), "{}", data_type);
+        }
+    }
+
+    #[test]
+    fn type_names_reject_commas_outside_parentheses() {
+        for data_type in [
+            "int, b int",
+            "int,",
+            "numeric(10, 2), extra text",
+            "int[1,2]",
+        ] {
+            assert!(validate_type_name(data_type).is_err(), "{}", data_type);
+        }
+    }
+
+    #[test]
+    fn type_names_reject_unbalanced_brackets_and_other_characters() {
+        for data_type in [
+            "numeric(10",
+            "numeric)10(",
+            "int]",
+            "int[)",
+            "",
+            "   ",
+            "int; DROP TABLE t",
+            "text DEFAULT 'x'",
+        ] {
+            assert!(validate_type_name(data_type).is_err(), "{:?}", data_type);
+        }
+    }
+
+    #[test]
+    fn quote_ident_escapes_the_quote_character() {
+        assert_eq!(quote_ident("a\"b", "postgres"), "\"a\"\"b\"");
+        assert_eq!(quote_ident("a\"b", "sqlite"), "\"a\"\"b\"");
+        assert_eq!(quote_ident("a`b", "mysql"), "`a``b`");
+    }
+
+    #[test]
+    fn quote_ident_leaves_the_other_backends_quote_alone() {
+        assert_eq!(quote_ident("a`b", "postgres"), "\"a`b\"");
+        assert_eq!(quote_ident("a\"b", "mysql"), "`a\"b`");
+        assert_eq!(quote_ident("order", "sqlite"), "\"order\"");
+        assert_eq!(quote_ident("it's", "mysql"), "`it's`");
+    }
+
+    #[test]
+    fn identifiers_reject_empty_names_null_bytes_and_line_breaks() {
+        assert!(validate_identifier("user accounts").is_ok());
+        assert!(validate_identifier("a\"b`c").is_ok());
+        for name in ["", "a\0b", "a\nb", "a\rb"] {
+            assert!(validate_identifier(name).is_err(), "{:?}", name);
+        }
+    }
+}
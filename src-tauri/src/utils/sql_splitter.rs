@@ -0,0 +1,435 @@
+enum SplitState {
+    Normal,
+    Quoted(char),
+    DollarQuoted(String),
+    BlockComment,
+}
+
+/// Incrementally splits SQL text into individual statements.
+///
+/// Semicolons (or the active `DELIMITER`) inside quoted strings, identifiers,
+/// Postgres dollar-quoted bodies and comments do not terminate a statement. Statements consisting only of
+/// comments are dropped, except MySQL `/*! ... */` executable comments, which count as
+/// statement text (mysqldump wraps triggers and session settings in them).
+pub struct SqlSplitter {
+    backslash_escapes: bool,
+    delimiter_directives: bool,
+    delimiter: String,
+    state: SplitState,
+    current: String,
+    has_content: bool,
+}
+
+impl SqlSplitter {
+    /// `backslash_escapes` enables MySQL-style `\'` escapes inside strings.
+    pub fn new(backslash_escapes: bool) -> Self {
+        Self {
+            backslash_escapes,
+            delimiter_directives: false,
+            delimiter: String::from(";"),
+            state: SplitState::Normal,
+            current: String::new(),
+            has_content: false,
+        }
+    }
+
+    /// Enables MySQL client `DELIMITER <x>` lines, which change the statement terminator.
+    pub fn with_delimiter_directives(mut self) -> Self {
+        self.delimiter_directives = true;
+        self
+    }
+
+    /// Feeds one line (without its terminator) and returns every statement it completed.
+    pub fn push_line(&mut self, line: &str) -> Vec<String> {
+        if let Some(delimiter) = self.delimiter_directive(line) {
+            self.delimiter = delimiter;
+            return Vec::new();
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let delimiter: Vec<char> = self.delimiter.chars().collect();
+        let mut statements = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            match &self.state {
+                SplitState::Normal => {
+                    if c == '-' && chars.get(i + 1) == Some(&'-') {
+                        // Line comment: keep the rest of the line verbatim
+                        self.current.extend(&chars[i..]);
+                        break;
+                    } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+                        if chars.get(i + 2) == Some(&'!') {
+                            self.has_content = true;
+                        }
+                        self.state = SplitState::BlockComment;
+                        self.current.push_str("/*");
+                        i += 2;
+                        continue;
+                    } else if c == '\'' || c == '"' || c == '`' {
+                        self.state = SplitState::Quoted(c);
+                        self.has_content = true;
+                    } else if c == '$' {
+                        if let Some(tag) = dollar_tag(&chars[i..]) {
+                            self.current.push_str(&tag);
+                            i += tag.chars().count();
+                            self.state = SplitState::DollarQuoted(tag);
+                            self.has_content = true;
+                            continue;
+                        }
+                        self.has_content = true;
+                    } else if chars[i..].starts_with(&delimiter) {
+                        if let Some(stmt) = self.take_statement() {
+                            statements.push(stmt);
+                        }
+                        i += delimiter.len();
+                        continue;
+                    } else if !c.is_whitespace() {
+                        self.has_content = true;
+                    }
+                    self.current.push(c);
+                }
+                SplitState::Quoted(quote) => {
+                    let quote = *quote;
+                    self.current.push(c);
+                    if c == '\\' && self.backslash_escapes && quote != '`' {
+                        if let Some(next) = chars.get(i + 1) {
+                            self.current.push(*next);
+                            i += 1;
+                        }
+                    } else if c == quote {
+                        self.state = SplitState::Normal;
+                    }
+                }
+                SplitState::DollarQuoted(tag) => {
+                    if c == '$'
+                        && chars[i..]
+                            .iter()
+                            .collect::<String>()
+                            .starts_with(tag.as_str())
+                    {
+                        let tag = tag.clone();
+                        self.current.push_str(&tag);
+                        i += tag.chars().count();
+                        self.state = SplitState::Normal;
+                        continue;
+                    }
+                    self.current.push(c);
+                }
+                SplitState::BlockComment => {
+                    if c == '*' && chars.get(i + 1) == Some(&'/') {
+                        self.current.push_str("*/");
+                        self.state = SplitState::Normal;
+                        i += 2;
+                        continue;
+                    }
+                    self.current.push(c);
+                }
+            }
+            i += 1;
+        }
+
+        self.current.push('\n');
+        statements
+    }
+
+    /// Returns the trailing statement that was not terminated by a semicolon, if any.
+    pub fn finish(mut self) -> Option<String> {
+        self.take_statement()
+    }
+
+    /// Parses a `DELIMITER <x>` line, which is only recognised between statements.
+    fn delimiter_directive(&self, line: &str) -> Option<String> {
+        if !self.delimiter_directives
+            || self.has_content
+            || !matches!(self.state, SplitState::Normal)
+        {
+            return None;
+        }
+        let mut parts = line.trim().splitn(2, char::is_whitespace);
+        if !parts.next()?.eq_ignore_ascii_case("DELIMITER") {
+            return None;
+        }
+        let delimiter = parts.next()?.trim();
+        if delimiter.is_empty() {
+            None
+        } else {
+            Some(delimiter.to_string())
+        }
+    }
+
+    fn take_statement(&mut self) -> Option<String> {
+        let stmt = std::mem::take(&mut self.current);
+        let has_content = std::mem::replace(&mut self.has_content, false);
+        let stmt = stmt.trim();
+        if has_content && !stmt.is_empty() {
+            Some(stmt.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Matches a Postgres dollar-quote opener such as `$$` or `$body$`.
+fn dollar_tag(chars: &[char]) -> Option<String> {
+    let mut tag = String::from("$");
+    for (idx, &c) in chars.iter().enumerate().skip(1) {
+        if c == '$' {
+            tag.push('$');
+            return Some(tag);
+        }
+        let valid = if idx == 1 {
+            c.is_alphabetic() || c == '_'
+        } else {
+            c.is_alphanumeric() || c == '_'
+        };
+        if !valid {
+            return None;
+        }
+        tag.push(c);
+    }
+    None
+}
+
+/// Splits a complete script into statements without their terminating semicolons.
+pub fn split_statements(sql: &str, backslash_escapes: bool) -> Vec<String> {
+    let mut splitter = SqlSplitter::new(backslash_escapes);
+    let mut statements = Vec::new();
+    for line in sql.lines() {
+        statements.extend(splitter.push_line(line));
+    }
+    statements.extend(splitter.finish());
+    statements
+}
+
+/// Returns the first keyword of a statement, skipping leading whitespace and comments.
+pub fn leading_keyword(sql: &str) -> String {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(stripped) = rest.strip_prefix("/*!") {
+            // Executable comment: the keyword follows the optional version number
+            rest = stripped
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .trim_start();
+        } else if let Some(stripped) = rest.strip_prefix("--") {
+            rest = stripped
+                .split_once('\n')
+                .map(|(_, r)| r)
+                .unwrap_or("")
+                .trim_start();
+        } else if let Some(stripped) = rest.strip_prefix("/*") {
+            rest = stripped
+                .split_once("*/")
+                .map(|(_, r)| r)
+                .unwrap_or("")
+                .trim_start();
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Returns the uppercased words of a statement that sit outside strings, quoted
+/// identifiers and comments, so keyword checks don't match `'returning'` or `trigger_count`.
+pub fn code_words(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    scan_words(&chars).into_iter().map(|w| w.text).collect()
+}
+
+/// Returns the text after the first `keyword` outside strings, comments and parentheses,
+/// e.g. the SELECT of `CREATE VIEW v (a, b) AS SELECT ...`.
+pub fn after_top_level_keyword<'a>(sql: &'a str, keyword: &str) -> Option<&'a str> {
+    let chars: Vec<char> = sql.chars().collect();
+    let word = scan_words(&chars)
+        .into_iter()
+        .find(|w| w.depth == 0 && w.text.eq_ignore_ascii_case(keyword))?;
+    let byte_end = sql
+        .char_indices()
+        .nth(word.end)
+        .map_or(sql.len(), |(pos, _)| pos);
+    Some(sql[byte_end..].trim())
+}
+
+struct CodeWord {
+    text: String, // uppercased
+    end: usize,   // char index just past the word
+    depth: usize, // parentheses open around the word
+}
+
+fn scan_words(chars: &[char]) -> Vec<CodeWord> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_alphanumeric() || c == '_' {
+            word.extend(c.to_uppercase());
+            i += 1;
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(CodeWord {
+                text: std::mem::take(&mut word),
+                end: i,
+                depth,
+            });
+        }
+
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            i = skip_past(chars, i + 2, &['\n']);
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i = skip_past(chars, i + 2, &['*', '/']);
+        } else if c == '\'' || c == '"' || c == '`' {
+            i = skip_past(chars, i + 1, &[c]);
+        } else if let Some(tag) = (c == '$').then(|| dollar_tag(&chars[i..])).flatten() {
+            let tag: Vec<char> = tag.chars().collect();
+            i = skip_past(chars, i + tag.len(), &tag);
+        } else {
+            if c == '(' {
+                depth += 1;
+            } else if c == ')' {
+                depth = depth.saturating_sub(1);
+            }
+            i += 1;
+        }
+    }
+    if !word.is_empty() {
+        words.push(CodeWord {
+            text: word,
+            end: chars.len(),
+            depth,
+        });
+    }
+    words
+}
+
+/// Drops trailing terminators, whitespace and comments, so text appended to the statement
+/// can't land inside a `--` comment. MySQL `/*! ... */` executable comments are kept.
+pub fn trim_statement_end(sql: &str) -> &str {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut end = 0; // chars up to the end of the last piece of code
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            i = skip_past(&chars, i + 2, &['\n']);
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let executable = chars.get(i + 2) == Some(&'!');
+            i = skip_past(&chars, i + 2, &['*', '/']);
+            if executable {
+                end = i;
+            }
+            continue;
+        } else if c == '\'' || c == '"' || c == '`' {
+            i = skip_past(&chars, i + 1, &[c]);
+            end = i;
+            continue;
+        } else if let Some(tag) = (c == '$').then(|| dollar_tag(&chars[i..])).flatten() {
+            let tag: Vec<char> = tag.chars().collect();
+            i = skip_past(&chars, i + tag.len(), &tag);
+            end = i;
+            continue;
+        } else if !c.is_whitespace() && c != ';' {
+            end = i + 1;
+        }
+        i += 1;
+    }
+
+    let byte_end = sql
+        .char_indices()
+        .nth(end)
+        .map_or(sql.len(), |(pos, _)| pos);
+    sql[..byte_end].trim_start()
+}
+
+/// Index just after the next occurrence of `end` at or after `from`, or the end of input.
+fn skip_past(chars: &[char], from: usize, end: &[char]) -> usize {
+    (from..chars.len())
+        .find(|&i| chars[i..].starts_with(end))
+        .map_or(chars.len(), |i| i + end.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_semicolons_outside_quotes_and_comments() {
+        let sql = "SELECT 'a;b', \"c;d\"; -- note; here\nSELECT 2 /* x; y */;\nSELECT 3";
+        assert_eq!(
+            split_statements(sql, false),
+            [
+                "SELECT 'a;b', \"c;d\"",
+                "-- note; here\nSELECT 2 /* x; y */",
+                "SELECT 3"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_dollar_quoted_bodies_whole() {
+        let sql = "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN RETURN 1; END;\n$body$ LANGUAGE plpgsql;\nSELECT f();";
+        let statements = split_statements(sql, false);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("$body$ LANGUAGE plpgsql"));
+        assert_eq!(statements[1], "SELECT f()");
+    }
+
+    #[test]
+    fn backslash_escapes_only_when_enabled() {
+        let sql = r"SELECT 'it\'s; fine'; SELECT 2;";
+        assert_eq!(
+            split_statements(sql, true),
+            [r"SELECT 'it\'s; fine'", "SELECT 2"]
+        );
+        // Without escapes the backslash doesn't protect the quote, so the string ends early
+        assert_eq!(split_statements(sql, false)[0], r"SELECT 'it\'s");
+    }
+
+    #[test]
+    fn drops_empty_and_comment_only_statements() {
+        assert_eq!(
+            split_statements(";;\n-- just a comment\n;SELECT 1;\n/* trailing */", false),
+            ["SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn push_line_returns_statements_as_they_complete() {
+        let mut splitter = SqlSplitter::new(false);
+        assert!(splitter.push_line("INSERT INTO t").is_empty());
+        assert_eq!(
+            splitter.push_line("VALUES (1); SELECT"),
+            ["INSERT INTO t\nVALUES (1)"]
+        );
+        assert_eq!(splitter.finish().as_deref(), Some("SELECT"));
+    }
+
+    #[test]
+    fn delimiter_directives_wrap_trigger_bodies() {
+        let mut splitter = SqlSplitter::new(true).with_delimiter_directives();
+        let dump = "CREATE TABLE t (n INT);\nDELIMITER ;;\nCREATE TRIGGER t_bi BEFORE INSERT ON t FOR EACH ROW\nBEGIN\n  SET NEW.n = NEW.n + 1;\nEND ;;\nDELIMITER ;\nINSERT INTO t VALUES (1);";
+        let statements: Vec<String> = dump
+            .lines()
+            .flat_map(|line| splitter.push_line(line))
+            .collect();
+        assert_eq!(
+            statements,
+            [
+                "CREATE TABLE t (n INT)",
+                "CREATE TRIGGER t_bi BEFORE INSERT ON t FOR EACH ROW\nBEGIN\n  SET NEW.n = NEW.n + 1;\nEND",
+                "INSERT INTO t VALUES (1)",
+            ]
+        );
+        assert!(splitter.finish().is_none());
+    }
+}
@@ -157,8 +157,16 @@ export const ConnectionModal = ({ open, onOpenChange }: { open: boolean, onOpenC
     setTestStatus(null);
     setError(null);
     try {
-      await invoke('test_connection', { config: getConfig(), password: password || null });
-      setTestStatus('success');
+      const result = await invoke<{ success: boolean; error: string | null }>('test_connection', {
+        config: getConfig(),
+        password: password || null,
+      });
+      if (result.success) {
+        setTestStatus('success');
+      } else {
+        setTestStatus('error');
+        setError(result.error);
+      }
     } catch (err: any) {
       console.error(err);
       setTestStatus('error');
@@ -4,6 +4,7 @@ import * as ContextMenu from '@radix-ui/react-context-menu';
 import { cn } from '../utils/cn';
 import { useDatabaseStore } from '../store/databaseStore';
 import { invoke } from '@tauri-apps/api/core';
+import { DECLINED, invokeWithProductionConfirm } from '../utils/productionGuard';
 
 interface TreeItemProps {
   label: string;
@@ -88,9 +89,9 @@ const TableItem = ({ table, active, onClick, connectionId }: {
 
   const handleTruncate = async () => {
     if (!window.confirm(`Are you sure you want to truncate "${table}"?\n\nThis will permanently delete ALL rows.`)) return;
-    const sql = dbType === 'Sqlite' ? `DELETE FROM ${quoteId(table)}` : `TRUNCATE TABLE ${quoteId(table)}`;
     try {
-      await invoke('execute_query', { connectionId, sql });
+      const truncated = await invokeWithProductionConfirm('truncate_table', { connectionId, tableName: table, confirm: true }, 'Truncating deletes every row');
+      if (truncated === DECLINED) return;
       triggerRefresh();
     } catch (err: any) {
       alert(`Truncate failed: ${err}`);
@@ -99,9 +100,9 @@ const TableItem = ({ table, active, onClick, connectionId }: {
 
   const handleDelete = async () => {
     if (!window.confirm(`Are you sure you want to delete "${table}"?\n\nThis action CANNOT be undone.`)) return;
-    const sql = dbType === 'Postgres' ? `DROP TABLE ${quoteId(table)} CASCADE` : `DROP TABLE ${quoteId(table)}`;
     try {
-      await invoke('execute_query', { connectionId, sql });
+      const dropped = await invokeWithProductionConfirm('drop_table', { connectionId, tableName: table, cascade: dbType === 'Postgres' }, 'Dropping the table cannot be undone');
+      if (dropped === DECLINED) return;
       tabs.filter(t => t.type === 'table' && t.tableName === table && t.connectionId === connectionId).forEach(t => closeTab(t.id));
       triggerRefresh();
     } catch (err: any) {
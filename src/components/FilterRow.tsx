@@ -22,7 +22,9 @@ const OPERATORS = [
   { value: 'ILIKE', label: 'contains (case-insensitive)' },
   { value: 'IN', label: 'in list' },
   { value: 'IS NULL', label: 'is null' },
-  { value: 'IS NOT NULL', label: 'is not null' }
+  { value: 'IS NOT NULL', label: 'is not null' },
+  { value: 'json_path_eq', label: 'JSON path equals' },
+  { value: 'json_contains', label: 'JSON contains' }
 ];
 
 // Custom styled select component
@@ -72,6 +74,7 @@ export const FilterRow = ({
   // Convert columns to options format
   const columnOptions = columns.map(col => ({ value: col, label: col }));
   const needsValue = !['IS NULL', 'IS NOT NULL'].includes(filter.operator);
+  const isJsonOperator = filter.operator.startsWith('json_');
 
   return (
     <div className="flex items-center gap-2 h-8 mb-1.5 group">
@@ -108,6 +111,21 @@ export const FilterRow = ({
         minWidth={100}
       />
 
+      {/* JSON path, e.g. address.city */}
+      {isJsonOperator && (
+        <div className="w-40 h-full">
+          <input
+            type="text"
+            value={filter.json_path ?? ''}
+            onChange={(e) => onUpdate({ json_path: e.target.value })}
+            placeholder="JSON path (a.b.0)"
+            className="w-full h-full px-3 bg-[#1e1e1e] text-white text-xs border border-[#3a3a3a] rounded-md 
+                       outline-none focus:border-[#007acc] focus:ring-1 focus:ring-[#007acc]/30
+                       hover:border-[#555] transition-colors placeholder:text-[#555]"
+          />
+        </div>
+      )}
+
       {/* Value Input */}
       <div className="flex-1 h-full">
         <input 
@@ -9,6 +9,8 @@ interface TableMetadata {
   data_size?: string;
   index_size?: string;
   comment?: string;
+  page_count?: number;
+  avg_row_size?: string;
 }
 
 export const ObjectDetails = () => {
@@ -67,6 +69,12 @@ export const ObjectDetails = () => {
         { name: 'data_size', value: metadata?.data_size || '...', originalIndex: 1 },
         { name: 'index_size', value: metadata?.index_size || '...', originalIndex: 2 },
         { name: 'comment', value: metadata?.comment || 'NULL', originalIndex: 3 },
+        ...(metadata?.page_count != null
+          ? [{ name: 'page_count', value: String(metadata.page_count), originalIndex: 4 }]
+          : []),
+        ...(metadata?.avg_row_size
+          ? [{ name: 'avg_row_size', value: metadata.avg_row_size, originalIndex: 5 }]
+          : []),
       ];
     } else {
       return [];
@@ -9,6 +9,7 @@ import { FilterBar } from './FilterBar';
 import { ColumnVisibilityPopover } from './ColumnVisibilityPopover';
 import { TabContentStructure } from './TabContentStructure';
 import { ExportModal } from './ExportModal';
+import { DECLINED, invokeWithProductionConfirm } from '../utils/productionGuard';
 
 interface TabContentTableProps {
   id: string;
@@ -138,7 +139,8 @@ export const TabContentTable = ({ id, tableName, connectionId }: TabContentTable
     setCommitError(null);
     const startTime = Date.now();
     try {
-      await invoke('execute_mutations', { connectionId, statements });
+      const applied = await invokeWithProductionConfirm('execute_mutations', { connectionId, statements }, 'These changes delete data');
+      if (applied === DECLINED) return;
       
       // Log each statement to history
       const duration = Math.round((Date.now() - startTime) / statements.length);
@@ -2,17 +2,20 @@ import { useState, useCallback, useRef, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { v4 as uuidv4 } from 'uuid';
+import { DECLINED, invokeWithProductionConfirm } from '../utils/productionGuard';
 
 export interface QueryStats {
   time: number;
   rows: number;
   totalRows?: number;
   affectedRows?: number;
+  rowsPerSec?: number;
 }
 
 export interface StreamingMetadata {
   query_id: string;
   columns: string[];
+  column_types: string[];
 }
 
 export interface StreamingBatch {
@@ -27,6 +30,13 @@ export interface StreamingComplete {
   affected_rows: number;
 }
 
+export interface StreamingStats {
+  query_id: string;
+  elapsed_ms: number;
+  total_rows: number;
+  rows_per_sec: number;
+}
+
 export const useStreamingQuery = (connectionId: string, initialData?: { rows?: any[][], columns?: string[], stats?: QueryStats | null }) => {
   const [rows, setRows] = useState<any[][]>(initialData?.rows || []);
   const [columns, setColumns] = useState<string[]>(initialData?.columns || []);
@@ -56,6 +66,7 @@ export const useStreamingQuery = (connectionId: string, initialData?: { rows?: a
     let unlistenMetadata: UnlistenFn | null = null;
     let unlistenBatch: UnlistenFn | null = null;
     let unlistenComplete: UnlistenFn | null = null;
+    let unlistenStats: UnlistenFn | null = null;
     let unlistenError: UnlistenFn | null = null;
     let isMounted = true;
 
@@ -78,6 +89,12 @@ export const useStreamingQuery = (connectionId: string, initialData?: { rows?: a
       });
       if (isMounted) unlistenBatch = uBatch; else uBatch();
 
+      const uStats = await listen<StreamingStats>('query-stats', (event) => {
+        if (event.payload.query_id !== queryIdRef.current) return;
+        setStats((prev) => prev ? { ...prev, time: event.payload.elapsed_ms, rowsPerSec: event.payload.rows_per_sec } : null);
+      });
+      if (isMounted) unlistenStats = uStats; else uStats();
+
       const uComplete = await listen<StreamingComplete>('query-complete', (event) => {
         if (event.payload.query_id !== queryIdRef.current) return;
         
@@ -94,11 +111,11 @@ export const useStreamingQuery = (connectionId: string, initialData?: { rows?: a
       });
       if (isMounted) unlistenComplete = uComplete; else uComplete();
 
-      const uError = await listen<{ query_id: string, error: string }>('query-error', (event) => {
+      const uError = await listen<{ query_id: string, code: string, message: string }>('query-error', (event) => {
         if (event.payload.query_id !== queryIdRef.current) return;
         
         flushBuffer();
-        setError(event.payload.error);
+        setError(event.payload.message);
         setIsLoading(false);
         queryIdRef.current = null;
       });
@@ -112,6 +129,7 @@ export const useStreamingQuery = (connectionId: string, initialData?: { rows?: a
       if (unlistenMetadata) unlistenMetadata();
       if (unlistenBatch) unlistenBatch();
       if (unlistenComplete) unlistenComplete();
+      if (unlistenStats) unlistenStats();
       if (unlistenError) unlistenError();
     };
   }, [flushBuffer]);
@@ -154,11 +172,15 @@ export const useStreamingQuery = (connectionId: string, initialData?: { rows?: a
       const newQueryId = uuidv4();
       queryIdRef.current = newQueryId;
       
-      await invoke('execute_query_streaming', {
+      const started = await invokeWithProductionConfirm('execute_query_streaming', {
         connectionId,
         queryId: newQueryId,
         sql
       });
+      if (started === DECLINED) {
+        setIsLoading(false);
+        queryIdRef.current = null;
+      }
     } catch (err: any) {
       setError(err.toString());
       setIsLoading(false);
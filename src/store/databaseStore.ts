@@ -10,6 +10,8 @@ export interface FilterConfig {
   operator: string;
   value: string;
   enabled: boolean;
+  case_insensitive?: boolean;
+  json_path?: string;
 }
 
 export interface SortConfig {
@@ -32,6 +34,8 @@ export interface SidebarItem {
   name: string;
   item_type: SidebarItemType;
   schema?: string;
+  pinned?: boolean;
+  usage_count?: number;
 }
 
 export interface SidebarSettings {
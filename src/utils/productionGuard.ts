@@ -0,0 +1,29 @@
+import { invoke } from '@tauri-apps/api/core';
+
+// Substring of the error the backend returns when a production connection needs confirmation
+const PRODUCTION_GATE_ERROR = 'require confirm_production';
+
+// Returned instead of the command's result when the user declines to run it
+export const DECLINED = Symbol('declined');
+
+/**
+ * Invokes a command that may be destructive. When the backend refuses it because the
+ * connection is marked as production, the user is asked once and the call is retried
+ * with `confirmProduction`. Resolves to DECLINED if the user says no.
+ */
+export async function invokeWithProductionConfirm<T>(
+  command: string,
+  args: Record<string, unknown>,
+  action = 'This statement deletes data'
+): Promise<T | typeof DECLINED> {
+  try {
+    return await invoke<T>(command, { ...args, confirmProduction: false });
+  } catch (err: any) {
+    const message = String(err);
+    if (!message.includes(PRODUCTION_GATE_ERROR)) throw err;
+    const name = message.match(/Connection '(.*)' is marked as production/)?.[1];
+    const subject = name ? `"${name}"` : 'This';
+    if (!window.confirm(`${subject} is a production connection.\n\n${action}. Run it anyway?`)) return DECLINED;
+    return await invoke<T>(command, { ...args, confirmProduction: true });
+  }
+}